@@ -0,0 +1,24 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Si ya se avisó que no se pudo fijar un hilo, para no repetirlo en cada uno
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Fija el hilo actual a un núcleo, repartiendo los hilos en orden
+/// (`worker` módulo la cantidad de núcleos). Sirve para que las mediciones
+/// de los modos paralelos no dependan de dónde el sistema operativo ponga
+/// cada hilo. Devuelve `false` si el sistema no permite fijar hilos.
+pub fn pin_current_thread(worker: usize) -> bool {
+    match core_affinity::get_core_ids() {
+        Some(cores) if !cores.is_empty() => core_affinity::set_for_current(cores[worker % cores.len()]),
+        _ => false,
+    }
+}
+
+/// Como `pin_current_thread`, pero si no se puede fijar el hilo lo avisa
+/// por la consola (una sola vez por ejecución) en lugar de seguir como si
+/// nada: las mediciones de `--pin-threads` no serían estables.
+pub fn pin_or_warn(worker: usize) {
+    if !pin_current_thread(worker) && !WARNED.swap(true, Ordering::Relaxed) {
+        eprintln!("No se pudo fijar el hilo {} a un núcleo: --pin-threads no tiene efecto en este sistema", worker);
+    }
+}
@@ -0,0 +1,248 @@
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+
+use crate::{CellState, GameOfLife};
+
+/// Radio (en celdas) en el que un boid ve a los demás
+const BOID_RADIUS: i64 = 6;
+/// Probabilidad de que un boid sin vecinos cambie de rumbo al azar
+const BOID_WANDER: f64 = 0.1;
+/// Probabilidad de que un boid gire hacia el centro de sus vecinos
+const BOID_COHESION: f64 = 0.25;
+
+/// Hacia dónde mira un agente
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Heading {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Heading {
+    pub const ALL: [Heading; 4] = [Heading::North, Heading::East, Heading::South, Heading::West];
+
+    /// Desplazamiento de un paso hacia adelante (y crece hacia abajo)
+    pub fn delta(self) -> (i64, i64) {
+        match self {
+            Heading::North => (0, -1),
+            Heading::East => (1, 0),
+            Heading::South => (0, 1),
+            Heading::West => (-1, 0),
+        }
+    }
+
+    /// Gira `quarters` cuartos de vuelta en sentido horario (negativo:
+    /// antihorario)
+    pub fn turned(self, quarters: i32) -> Self {
+        let index = Self::ALL.iter().position(|&h| h == self).unwrap_or(0) as i32;
+        Self::ALL[(index + quarters).rem_euclid(4) as usize]
+    }
+
+    pub fn right(self) -> Self {
+        self.turned(1)
+    }
+
+    pub fn left(self) -> Self {
+        self.turned(-1)
+    }
+}
+
+/// Un agente sobre el grid: posición, rumbo y un estado propio que usa su
+/// comportamiento (por ejemplo el estado de un turmite)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Agent {
+    pub x: usize,
+    pub y: usize,
+    pub heading: Heading,
+    pub state: u32,
+}
+
+impl Agent {
+    pub fn new(x: usize, y: usize, heading: Heading) -> Self {
+        Self { x, y, heading, state: 0 }
+    }
+
+    /// Celda de adelante; los bordes se unen con los opuestos
+    pub fn ahead(&self, width: usize, height: usize) -> (usize, usize) {
+        let (dx, dy) = self.heading.delta();
+        (
+            (self.x as i64 + dx).rem_euclid(width as i64) as usize,
+            (self.y as i64 + dy).rem_euclid(height as i64) as usize,
+        )
+    }
+
+    /// Avanza una celda en la dirección en la que mira
+    pub fn forward(&mut self, width: usize, height: usize) {
+        (self.x, self.y) = self.ahead(width, height);
+    }
+}
+
+/// Grid que los agentes pueden leer y escribir: cada celda es un color, 0
+/// el fondo. En el Juego de la Vida, 0 es una célula muerta y 1 una viva.
+pub trait Cells {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn get(&self, x: usize, y: usize) -> u8;
+    fn set(&mut self, x: usize, y: usize, color: u8);
+}
+
+impl Cells for GameOfLife {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, x: usize, y: usize) -> u8 {
+        self.is_alive(x, y) as u8
+    }
+
+    /// Cualquier color distinto de 0 es una célula viva
+    fn set(&mut self, x: usize, y: usize, color: u8) {
+        self.grid.set(x, y, if color == 0 { CellState::Dead } else { CellState::Alive });
+    }
+}
+
+/// Lo que hace un agente en cada paso: mira su celda, a los demás agentes
+/// (como estaban al empezar el paso) y al grid, escribe en él y se mueve
+pub trait Behavior {
+    fn act(&self, agent: &mut Agent, others: &[Agent], cells: &mut dyn Cells, rng: &mut dyn RngCore);
+}
+
+/// Hormiga de Langton: en una celda de fondo gira a la derecha, en una de
+/// color a la izquierda; invierte la celda y avanza
+pub struct LangtonAnt;
+
+impl Behavior for LangtonAnt {
+    fn act(&self, agent: &mut Agent, _others: &[Agent], cells: &mut dyn Cells, _rng: &mut dyn RngCore) {
+        let color = cells.get(agent.x, agent.y);
+        agent.heading = if color == 0 { agent.heading.right() } else { agent.heading.left() };
+        cells.set(agent.x, agent.y, (color == 0) as u8);
+        agent.forward(cells.width(), cells.height());
+    }
+}
+
+/// Boids sobre el grid: se apartan si tienen a otro justo adelante, copian
+/// el rumbo más común entre los que ven, a veces giran hacia el centro del
+/// grupo y sin compañía deambulan. Dejan una célula viva donde estaban.
+pub struct Boids;
+
+impl Behavior for Boids {
+    fn act(&self, agent: &mut Agent, others: &[Agent], cells: &mut dyn Cells, rng: &mut dyn RngCore) {
+        let (width, height) = (cells.width(), cells.height());
+        // Distancia más corta en cada eje, con los bordes unidos
+        let offset = |from: usize, to: usize, size: usize| {
+            let d = (to as i64 - from as i64).rem_euclid(size as i64);
+            if d > size as i64 / 2 { d - size as i64 } else { d }
+        };
+        let neighbors: Vec<(&Agent, i64, i64)> = others
+            .iter()
+            .map(|other| (other, offset(agent.x, other.x, width), offset(agent.y, other.y, height)))
+            .filter(|&(_, dx, dy)| (dx, dy) != (0, 0) && dx.abs() <= BOID_RADIUS && dy.abs() <= BOID_RADIUS)
+            .collect();
+
+        if neighbors.iter().any(|(other, ..)| (other.x, other.y) == agent.ahead(width, height)) {
+            // Separación
+            agent.heading = if rng.gen_bool(0.5) { agent.heading.left() } else { agent.heading.right() };
+        } else if neighbors.is_empty() {
+            if rng.gen_bool(BOID_WANDER) {
+                agent.heading = if rng.gen_bool(0.5) { agent.heading.left() } else { agent.heading.right() };
+            }
+        } else if rng.gen_bool(BOID_COHESION) {
+            // Cohesión: el eje en el que el grupo está más lejos
+            let count = neighbors.len() as i64;
+            let (cx, cy) = neighbors.iter().fold((0, 0), |(sx, sy), &(_, dx, dy)| (sx + dx, sy + dy));
+            let (cx, cy) = (cx / count, cy / count);
+            if (cx, cy) != (0, 0) {
+                agent.heading = match (cx.abs() >= cy.abs(), cx > 0, cy > 0) {
+                    (true, true, _) => Heading::East,
+                    (true, false, _) => Heading::West,
+                    (false, _, true) => Heading::South,
+                    (false, _, false) => Heading::North,
+                };
+            }
+        } else {
+            // Alineación: el rumbo más común entre los vecinos
+            let votes = Heading::ALL.map(|h| neighbors.iter().filter(|(other, ..)| other.heading == h).count());
+            let best = (0..4).max_by_key(|&i| votes[i]).unwrap_or(0);
+            agent.heading = Heading::ALL[best];
+        }
+
+        cells.set(agent.x, agent.y, 1);
+        agent.forward(width, height);
+    }
+}
+
+/// Agentes que se mueven sobre el grid, un paso por generación, todos con
+/// el mismo comportamiento
+pub struct AgentLayer {
+    agents: Vec<Agent>,
+    behavior: Box<dyn Behavior>,
+    rng: StdRng,
+}
+
+impl AgentLayer {
+    /// Los números al azar de los comportamientos salen de `seed`
+    pub fn new(agents: Vec<Agent>, behavior: Box<dyn Behavior>, seed: u64) -> Self {
+        Self { agents, behavior, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    pub fn agents(&self) -> &[Agent] {
+        &self.agents
+    }
+
+    pub fn agents_mut(&mut self) -> &mut Vec<Agent> {
+        &mut self.agents
+    }
+
+    /// Un paso de todos los agentes, en orden. Los que salen por un borde
+    /// entran por el opuesto; si el grid cambió de tamaño, los que quedaron
+    /// fuera vuelven a entrar de la misma forma.
+    pub fn step(&mut self, cells: &mut dyn Cells) {
+        let (width, height) = (cells.width(), cells.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+        for agent in &mut self.agents {
+            (agent.x, agent.y) = (agent.x % width, agent.y % height);
+        }
+        let others = self.agents.clone();
+        for agent in &mut self.agents {
+            self.behavior.act(agent, &others, cells, &mut self.rng);
+        }
+    }
+}
+
+// ===== AGENTES INCLUIDOS =====
+/// Comportamientos que se pueden elegir desde la línea de comandos
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum AgentPreset {
+    /// Hormigas de Langton: la primera en el centro y las demás al azar
+    LangtonAnt,
+    /// Bandada que se alinea y se agrupa, dejando células vivas detrás
+    Boids,
+}
+
+impl AgentPreset {
+    /// `count` agentes en un grid de `width`×`height`
+    pub fn layer(self, count: usize, width: usize, height: usize, seed: u64) -> AgentLayer {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut agents: Vec<Agent> = (0..count)
+            .map(|_| Agent::new(rng.gen_range(0..width), rng.gen_range(0..height), Heading::ALL[rng.gen_range(0..4)]))
+            .collect();
+        let behavior: Box<dyn Behavior> = match self {
+            AgentPreset::LangtonAnt => {
+                if let Some(first) = agents.first_mut() {
+                    *first = Agent::new(width / 2, height / 2, Heading::North);
+                }
+                Box::new(LangtonAnt)
+            }
+            AgentPreset::Boids => Box::new(Boids),
+        };
+        AgentLayer::new(agents, behavior, rng.next_u64())
+    }
+}
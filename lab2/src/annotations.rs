@@ -0,0 +1,85 @@
+use serde::Deserialize;
+
+use crate::font;
+
+/// Color de las anotaciones en los GIF (en la ventana se usa el mismo)
+pub const ANNOTATION_RGB: [u8; 3] = [0xFF, 0xD0, 0x40];
+/// Índice de ese color en la paleta de los GIF (después de célula muerta y viva)
+pub const ANNOTATION_INDEX: u8 = 2;
+/// Generaciones que se ve una anotación si no se indica otra cosa
+const DEFAULT_DURATION: u64 = 30;
+/// Distancia entre la célula señalada y la etiqueta, en células
+const LABEL_GAP: usize = 3;
+
+/// Texto que aparece durante unas generaciones junto a una célula, con una
+/// flecha que la señala
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Annotation {
+    /// Primera generación en la que se ve
+    pub generation: u64,
+    /// Generaciones que se ve
+    #[serde(default = "default_duration")]
+    pub duration: u64,
+    /// Célula señalada
+    pub x: usize,
+    pub y: usize,
+    pub text: String,
+}
+
+fn default_duration() -> u64 {
+    DEFAULT_DURATION
+}
+
+impl Annotation {
+    pub fn visible_at(&self, generation: u64) -> bool {
+        (self.generation..self.generation + self.duration).contains(&generation)
+    }
+
+    /// Dibuja la etiqueta y la flecha en un buffer de `buffer_width` de
+    /// ancho donde cada célula ocupa `scale`×`scale` píxeles (1 en los
+    /// frames del GIF). La etiqueta va arriba a la derecha de la célula, o
+    /// del otro lado si no cabe.
+    pub fn draw<T: Copy>(&self, buffer: &mut [T], buffer_width: usize, scale: usize, text_scale: usize, color: T) {
+        let buffer_height = buffer.len() / buffer_width.max(1);
+        let target = (self.x * scale + scale / 2, self.y * scale + scale / 2);
+        if target.0 >= buffer_width || target.1 >= buffer_height {
+            return;
+        }
+        let text_width = (self.text.chars().count() * (font::GLYPH_WIDTH + 1)).saturating_sub(1) * text_scale;
+        let text_height = font::GLYPH_HEIGHT * text_scale;
+        let gap = LABEL_GAP * scale;
+
+        let (left, corner_x) = if target.0 + gap + text_width <= buffer_width || target.0 < gap + text_width {
+            (target.0 + gap, target.0 + gap)
+        } else {
+            (target.0 - gap - text_width, target.0 - gap)
+        };
+        let (top, corner_y) = if target.1 >= gap + text_height {
+            (target.1 - gap - text_height, target.1 - gap)
+        } else {
+            (target.1 + gap, target.1 + gap)
+        };
+        font::draw_text_scaled(buffer, buffer_width, (left, top), &self.text, color, text_scale);
+
+        // Flecha: una línea desde la esquina de la etiqueta hasta la célula
+        let (dx, dy) = (target.0 as f32 - corner_x as f32, target.1 as f32 - corner_y as f32);
+        let steps = dx.abs().max(dy.abs()).max(1.0) as usize;
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let (x, y) = ((corner_x as f32 + dx * t).round() as usize, (corner_y as f32 + dy * t).round() as usize);
+            if x < buffer_width && y < buffer_height {
+                buffer[y * buffer_width + x] = color;
+            }
+        }
+    }
+}
+
+/// Anotaciones visibles en `generation`, movidas `offset` células (las
+/// coordenadas de las células cambian al agrandar o recortar el universo)
+pub fn visible(annotations: &[Annotation], generation: u64, offset: (i64, i64)) -> impl Iterator<Item = Annotation> + '_ {
+    annotations.iter().filter(move |a| a.visible_at(generation)).filter_map(move |a| {
+        let (x, y) = (a.x as i64 + offset.0, a.y as i64 + offset.1);
+        (x >= 0 && y >= 0).then(|| Annotation { x: x as usize, y: y as usize, ..a.clone() })
+    })
+}
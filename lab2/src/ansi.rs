@@ -0,0 +1,41 @@
+use crate::GameOfLife;
+use crate::gradient::Gradient;
+use crate::overlay;
+
+/// Dibujo del universo para la terminal con colores ANSI de 24 bits. Cada
+/// carácter `▀` muestra dos bloques de células, uno arriba del otro (el de
+/// arriba con el color del texto y el de abajo con el del fondo), así que
+/// con bloques de una célula se ven cuadradas. Si el universo no entra en
+/// `columns`×`rows` caracteres, cada bloque junta varias células y es más
+/// claro cuantas más vivas tiene.
+pub fn preview(game: &GameOfLife, columns: usize, rows: usize) -> String {
+    let block = game
+        .width()
+        .div_ceil(columns.max(1))
+        .max(game.height().div_ceil(rows.max(1) * 2))
+        .max(1);
+    let (blocks_x, blocks_y, densities) = overlay::block_densities(game, block);
+    let palette = Gradient::linear(crate::DEAD_RGB, crate::ALIVE_RGB).palette(256);
+    let color = |x: usize, y: usize| {
+        let density = if y < blocks_y { densities[y * blocks_x + x] } else { 0.0 };
+        palette[(density * 255.0).round() as usize]
+    };
+
+    let mut text = String::new();
+    for y in (0..blocks_y).step_by(2) {
+        for x in 0..blocks_x {
+            let ([r, g, b], [br, bg, bb]) = (color(x, y), color(x, y + 1));
+            text += &format!("\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m▀", r, g, b, br, bg, bb);
+        }
+        text += "\x1b[0m\n";
+    }
+    text
+}
+
+/// Columnas y filas de la terminal, o 80×24 si la salida no es una terminal
+pub fn terminal_size() -> (usize, usize) {
+    match terminal_size::terminal_size() {
+        Some((terminal_size::Width(columns), terminal_size::Height(rows))) => (columns as usize, rows as usize),
+        None => (80, 24),
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use png::chunk;
+
+/// PNG animado con frames en color verdadero (sin límite de 256 colores
+/// como el GIF). El formato necesita saber cuántos frames hay antes del
+/// primero, así que cada frame se comprime apenas llega, se guarda en
+/// memoria ya comprimido y el archivo se escribe en `finish`.
+pub struct ApngEncoder {
+    file: File,
+    width: usize,
+    height: usize,
+    /// Datos comprimidos (el contenido de los IDAT) y demora en centésimas
+    frames: Vec<(Vec<u8>, u16)>,
+}
+
+impl ApngEncoder {
+    pub fn new(path: &Path, width: usize, height: usize) -> std::io::Result<Self> {
+        Ok(Self { file: File::create(path)?, width, height, frames: Vec::new() })
+    }
+
+    /// Agrega un frame de `width`×`height` píxeles (3 bytes por píxel, fila
+    /// por fila) que dura `delay` centésimas de segundo
+    pub fn add_frame(&mut self, rgb: &[u8], delay: u16) -> Result<(), Box<dyn std::error::Error>> {
+        // Se comprime como un PNG suelto y se queda con sus IDAT
+        let mut encoded = Vec::new();
+        let mut encoder = png::Encoder::new(&mut encoded, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(rgb)?;
+        self.frames.push((image_data(&encoded), delay));
+        Ok(())
+    }
+
+    /// Escribe el archivo: la cabecera, la cantidad de frames y cada frame
+    /// con su demora. El primero es también la imagen que muestran los
+    /// visores sin soporte de animación.
+    pub fn finish(self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.frames.is_empty() {
+            return Err("el PNG animado no tiene frames".into());
+        }
+        let mut encoder = png::Encoder::new(BufWriter::new(self.file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        // Los frames se escriben como chunks sueltos, sin pasar por el encoder
+        encoder.validate_sequence(false);
+        let mut writer = encoder.write_header()?;
+
+        // acTL: cantidad de frames y de repeticiones (0 = para siempre)
+        let mut actl = (self.frames.len() as u32).to_be_bytes().to_vec();
+        actl.extend(0u32.to_be_bytes());
+        writer.write_chunk(chunk::acTL, &actl)?;
+
+        let mut sequence = 0u32;
+        for (i, (data, delay)) in self.frames.iter().enumerate() {
+            // fcTL: número de secuencia, tamaño, posición, demora en
+            // centésimas, sin borrar ni mezclar con el frame anterior
+            let mut fctl = Vec::with_capacity(26);
+            for value in [sequence, self.width as u32, self.height as u32, 0, 0] {
+                fctl.extend(value.to_be_bytes());
+            }
+            fctl.extend(delay.to_be_bytes());
+            fctl.extend(100u16.to_be_bytes());
+            fctl.extend([0, 0]);
+            writer.write_chunk(chunk::fcTL, &fctl)?;
+            sequence += 1;
+
+            if i == 0 {
+                writer.write_chunk(chunk::IDAT, data)?;
+            } else {
+                let mut fdat = sequence.to_be_bytes().to_vec();
+                fdat.extend_from_slice(data);
+                writer.write_chunk(chunk::fdAT, &fdat)?;
+                sequence += 1;
+            }
+        }
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+/// Contenido de todos los chunks IDAT de un PNG, en orden
+fn image_data(png: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut offset = 8; // Firma del PNG
+    while offset + 8 <= png.len() {
+        let length = u32::from_be_bytes(png[offset..offset + 4].try_into().expect("4 bytes")) as usize;
+        let kind = &png[offset + 4..offset + 8];
+        if kind == b"IDAT" {
+            data.extend_from_slice(&png[offset + 8..offset + 8 + length]);
+        }
+        offset += 12 + length; // Largo, tipo, datos y CRC
+    }
+    data
+}
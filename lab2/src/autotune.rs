@@ -0,0 +1,254 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::engine::Engine;
+use crate::grid;
+use crate::simulation::Simulation;
+use crate::sparse::{SparseLife, Viewport};
+use crate::{CellState, GameOfLife, Topology};
+
+/// Generaciones que se miden con cada motor al elegir
+const TRIAL_GENERATIONS: u64 = 3;
+/// Generaciones entre cada nueva medición, aunque la densidad no cambie
+const RETUNE_EVERY: u64 = 2000;
+/// Factor en que tiene que cambiar la densidad para volver a medir
+const DENSITY_CHANGE: f64 = 2.0;
+/// Densidad mínima con la que se comparan los cambios, para que un
+/// universo casi vacío no vuelva a medir por cada célula
+const DENSITY_FLOOR: f64 = 0.001;
+/// Parte del tiempo de cada frame que se dedica a avanzar; el resto queda
+/// para dibujar y exportar
+const FRAME_FILL: f64 = 0.6;
+const MAX_GENERATIONS_PER_STEP: u64 = 1024;
+
+/// Forma de calcular las generaciones entre las que elige `AutoEngine`.
+/// Todas dan el mismo resultado; cambia cuánto tardan.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Backend {
+    /// Célula por célula, contando las vecinas de cada una
+    Naive,
+    /// De a 64 células con operaciones de bits, en un hilo
+    Bitpacked,
+    /// De a 64 células, repartiendo las filas entre los hilos de rayon
+    Parallel,
+    /// Solo las células vivas (`SparseLife` con bordes): gana cuando el
+    /// universo está casi vacío
+    Sparse,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 4] = [Backend::Naive, Backend::Bitpacked, Backend::Parallel, Backend::Sparse];
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Backend::Naive => "célula por célula",
+            Backend::Bitpacked => "bits en un hilo",
+            Backend::Parallel => "bits en paralelo",
+            Backend::Sparse => "disperso",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Dónde están las células según el motor actual
+enum Cells {
+    Grid(GameOfLife),
+    Sparse(SparseLife),
+}
+
+/// Motor que se ajusta solo: mide cuánto tarda una generación con cada
+/// `Backend`, se queda con el más rápido y avanza tantas generaciones por
+/// `step` como entren en el tiempo de un frame. Vuelve a medir cada tanto y
+/// cuando la densidad cambia mucho (una sopa que se calma pasa del grid al
+/// disperso). El universo tiene bordes como el de `GameOfLife`, o es un
+/// toro si `game` lo es (entonces no usa el disperso).
+pub struct AutoEngine {
+    cells: Cells,
+    backend: Backend,
+    /// El grid con la regla y la topología, vacío mientras las células
+    /// están en el disperso
+    template: GameOfLife,
+    frame_budget: Duration,
+    generations_per_step: u64,
+    /// Densidad al medir por última vez; `None` obliga a medir
+    tuned_density: Option<f64>,
+    since_tuning: u64,
+    single_thread: rayon::ThreadPool,
+}
+
+impl AutoEngine {
+    /// Empieza con las células, la regla y la topología de `game`, que debe
+    /// actualizarse en forma síncrona. `frame_budget` es el tiempo de cada
+    /// frame (la inversa de los FPS buscados).
+    pub fn new(game: GameOfLife, frame_budget: Duration) -> Result<Self, String> {
+        let single_thread = rayon::ThreadPoolBuilder::new().num_threads(1).build().map_err(|e| e.to_string())?;
+        let mut template = GameOfLife::new(game.width(), game.height());
+        template.set_rule(game.rule());
+        template.set_topology(game.topology());
+        Ok(Self {
+            template,
+            cells: Cells::Grid(game),
+            backend: Backend::Parallel,
+            frame_budget,
+            generations_per_step: 1,
+            tuned_density: None,
+            since_tuning: 0,
+            single_thread,
+        })
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub fn generations_per_step(&self) -> u64 {
+        self.generations_per_step
+    }
+
+    /// Motores que dan el resultado correcto con esta regla y topología
+    fn candidates(&self) -> Vec<Backend> {
+        let sparse = !self.template.rule().is_birth(0) && self.template.topology() == Topology::Bounded;
+        Backend::ALL.into_iter().filter(|&b| b != Backend::Sparse || sparse).collect()
+    }
+
+    fn density(&self) -> f64 {
+        let area = (self.template.width() * self.template.height()).max(1);
+        self.population() as f64 / area as f64
+    }
+
+    /// Mueve las células a la forma que usa `backend`
+    fn switch_to(&mut self, backend: Backend) {
+        let (width, height) = (self.template.width(), self.template.height());
+        let cells = std::mem::replace(&mut self.cells, Cells::Grid(GameOfLife::new(0, 0)));
+        self.cells = match (cells, backend) {
+            (Cells::Grid(game), Backend::Sparse) => match SparseLife::from_game(&game) {
+                Ok(sparse) => Cells::Sparse(sparse.with_viewport(Viewport::new(width, height)).with_bounds(width, height)),
+                Err(_) => Cells::Grid(game),
+            },
+            (Cells::Sparse(sparse), Backend::Naive | Backend::Bitpacked | Backend::Parallel) => {
+                let mut game = self.template.clone();
+                for &(x, y) in sparse.cells() {
+                    GameOfLife::set_cell(&mut game, x as usize, y as usize, CellState::Alive);
+                }
+                Cells::Grid(game)
+            }
+            (cells, _) => cells,
+        };
+        self.backend = backend;
+    }
+
+    /// Avanza `generations` generaciones con el motor actual
+    fn run(&mut self, generations: u64) {
+        let torus = self.template.topology() == Topology::Torus;
+        match (&mut self.cells, self.backend) {
+            (Cells::Sparse(sparse), _) => sparse.advance(generations),
+            (Cells::Grid(game), Backend::Naive) => {
+                let rule = game.rule();
+                for _ in 0..generations {
+                    game.grid = grid::step_cells(&game.grid, &rule, torus);
+                }
+            }
+            (Cells::Grid(game), Backend::Bitpacked) => self.single_thread.install(|| game.advance(generations)),
+            (Cells::Grid(game), _) => game.advance(generations),
+        }
+    }
+
+    /// Prueba cada motor unas generaciones (que cuentan: la simulación
+    /// avanza mientras tanto) y se queda con el más rápido
+    fn tune(&mut self) {
+        let previous = (self.backend, self.generations_per_step);
+        let mut best: Option<(Backend, Duration)> = None;
+        for backend in self.candidates() {
+            self.switch_to(backend);
+            let started = Instant::now();
+            self.run(TRIAL_GENERATIONS);
+            let time = started.elapsed() / TRIAL_GENERATIONS as u32;
+            if best.is_none_or(|(_, fastest)| time < fastest) {
+                best = Some((backend, time));
+            }
+        }
+        let Some((backend, time)) = best else { return };
+        self.switch_to(backend);
+        let fitting = self.frame_budget.as_secs_f64() * FRAME_FILL / time.as_secs_f64().max(1e-9);
+        self.generations_per_step = (fitting as u64).clamp(1, MAX_GENERATIONS_PER_STEP);
+        self.tuned_density = Some(self.density());
+        self.since_tuning = 0;
+        if (backend, self.generations_per_step) != previous {
+            println!(
+                "Motor automático: {} ({:.3} ms por generación), {} generaciones por frame",
+                backend, time.as_secs_f64() * 1000.0, self.generations_per_step
+            );
+        }
+    }
+
+    /// Si la densidad cambió tanto desde la última medición que otro motor
+    /// podría ser más rápido
+    fn needs_tuning(&self) -> bool {
+        let Some(tuned) = self.tuned_density else { return true };
+        let (now, tuned) = (self.density().max(DENSITY_FLOOR), tuned.max(DENSITY_FLOOR));
+        self.since_tuning >= RETUNE_EVERY || now / tuned >= DENSITY_CHANGE || tuned / now >= DENSITY_CHANGE
+    }
+}
+
+impl Engine for AutoEngine {
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        match &self.cells {
+            Cells::Grid(game) => game.is_alive(x, y),
+            Cells::Sparse(sparse) => sparse.is_alive_at((x as i64, y as i64)),
+        }
+    }
+
+    fn set_cell(&mut self, x: usize, y: usize, state: CellState) {
+        if x >= self.template.width() || y >= self.template.height() {
+            return;
+        }
+        match &mut self.cells {
+            Cells::Grid(game) => GameOfLife::set_cell(game, x, y, state),
+            Cells::Sparse(sparse) => sparse.set_at((x as i64, y as i64), state),
+        }
+    }
+
+    fn advance(&mut self, generations: u64) {
+        self.run(generations);
+        self.since_tuning += generations;
+    }
+
+    fn population(&self) -> u64 {
+        match &self.cells {
+            Cells::Grid(game) => game.population(),
+            Cells::Sparse(sparse) => sparse.population(),
+        }
+    }
+}
+
+impl Simulation for AutoEngine {
+    fn width(&self) -> usize {
+        self.template.width()
+    }
+
+    fn height(&self) -> usize {
+        self.template.height()
+    }
+
+    fn step(&mut self) {
+        if self.needs_tuning() {
+            self.tune();
+        }
+        self.advance(self.generations_per_step);
+    }
+
+    fn palette(&self) -> Vec<[u8; 3]> {
+        vec![crate::DEAD_RGB, crate::ALIVE_RGB]
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        Engine::is_alive(self, x, y) as u8
+    }
+
+    /// Revive la célula bajo el mouse
+    fn paint(&mut self, x: usize, y: usize) {
+        self.set_cell(x, y, CellState::Alive);
+    }
+}
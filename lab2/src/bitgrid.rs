@@ -0,0 +1,252 @@
+use rayon::prelude::*;
+
+use crate::CellState;
+use crate::grid::Grid;
+use crate::rule::Rule;
+
+/// Filas que calcula cada tarea de rayon en `BitGrid::step`
+const BAND_ROWS: usize = 16;
+
+/// Células de un universo en bits, fila por fila: el bit `b` de la palabra
+/// `i` de una fila es la célula `64 * i + b`. Los bits que sobran al final
+/// de cada fila siempre están en 0, así que dos grids con las mismas
+/// células son iguales.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    /// Grid de `width`×`height` células muertas
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self { width, height, words_per_row, words: vec![0; words_per_row * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.words[y * self.words_per_row + x / 64] >> (x % 64) & 1 == 1
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> CellState {
+        if self.is_alive(x, y) { CellState::Alive } else { CellState::Dead }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, state: CellState) {
+        let word = &mut self.words[y * self.words_per_row + x / 64];
+        match state {
+            CellState::Alive => *word |= 1 << (x % 64),
+            CellState::Dead => *word &= !(1 << (x % 64)),
+        }
+    }
+
+    /// Mata todas las células
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Células vivas
+    pub fn population(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    /// La generación siguiente con `rule`, para todas las células a la vez.
+    /// Los vecinos se cuentan de a 64 células: cada una de las 8 vecinas es
+    /// la fila desplazada un bit, y las sumas se hacen bit a bit en cuatro
+    /// palabras (el bit `k` de la cuenta de cada célula en `sum[k]`). En un
+    /// toro las filas y columnas de los bordes se unen con las opuestas; si
+    /// no, fuera del grid todo está muerto. Las filas se reparten en franjas
+    /// entre los hilos de rayon; el resultado no depende de cuántos haya.
+    pub fn step(&self, rule: &Rule, torus: bool) -> BitGrid {
+        let mut next = BitGrid::new(self.width, self.height);
+        if self.width == 0 || self.height == 0 {
+            return next;
+        }
+        let zero = vec![0; self.words_per_row];
+        let last_mask = match self.width % 64 {
+            0 => !0,
+            bits => (1 << bits) - 1,
+        };
+        // Solo las cantidades de vecinos con las que una célula queda viva
+        let counts: Vec<(usize, bool, bool)> = (0..=8)
+            .map(|n| (n, rule.is_birth(n), rule.is_survival(n)))
+            .filter(|&(_, birth, survival)| birth || survival)
+            .collect();
+
+        let band = Band { counts: &counts, zero: &zero, last_mask, torus };
+        let words_per_row = self.words_per_row;
+        next.words.par_chunks_mut(words_per_row * BAND_ROWS).enumerate().for_each(|(index, rows)| {
+            for (offset, out) in rows.chunks_mut(words_per_row).enumerate() {
+                self.step_row(index * BAND_ROWS + offset, out, &band);
+            }
+        });
+        next
+    }
+
+    /// Escribe en `out` la fila `y` de la generación siguiente
+    fn step_row(&self, y: usize, out: &mut [u64], band: &Band) {
+        let torus = band.torus;
+        let row = self.row(y);
+        let above = match (y, torus) {
+            (0, true) => self.row(self.height - 1),
+            (0, false) => band.zero,
+            _ => self.row(y - 1),
+        };
+        let below = match (y + 1 == self.height, torus) {
+            (true, true) => self.row(0),
+            (true, false) => band.zero,
+            _ => self.row(y + 1),
+        };
+        for (i, cell) in out.iter_mut().enumerate() {
+            let mut sum = [0; 4];
+            for neighbors in [above, row, below] {
+                let (west, east) = self.shifted(neighbors, i, torus);
+                add(&mut sum, west);
+                add(&mut sum, east);
+            }
+            add(&mut sum, above[i]);
+            add(&mut sum, below[i]);
+
+            let alive = row[i];
+            let mut word = 0;
+            for &(n, birth, survival) in band.counts {
+                let equal = (0..4).fold(!0, |mask, bit| mask & if n >> bit & 1 == 1 { sum[bit] } else { !sum[bit] });
+                if birth {
+                    word |= equal & !alive;
+                }
+                if survival {
+                    word |= equal & alive;
+                }
+            }
+            if i + 1 == self.words_per_row {
+                word &= band.last_mask;
+            }
+            *cell = word;
+        }
+    }
+
+    /// Palabra `i` de la fila con cada célula reemplazada por su vecina de
+    /// la izquierda (oeste) y por la de la derecha (este)
+    fn shifted(&self, row: &[u64], i: usize, torus: bool) -> (u64, u64) {
+        let last = row.len() - 1;
+        let edge = (self.width - 1) % 64;
+        let mut west = row[i] << 1;
+        if i > 0 {
+            west |= row[i - 1] >> 63;
+        } else if torus {
+            west |= row[last] >> edge & 1;
+        }
+        let mut east = row[i] >> 1;
+        if i < last {
+            east |= row[i + 1] << 63;
+        } else if torus {
+            east |= (row[0] & 1) << edge;
+        }
+        (west, east)
+    }
+}
+
+impl Grid for BitGrid {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        BitGrid::is_alive(self, x, y)
+    }
+
+    fn set(&mut self, x: usize, y: usize, state: CellState) {
+        BitGrid::set(self, x, y, state);
+    }
+
+    fn clear(&mut self) {
+        BitGrid::clear(self);
+    }
+
+    fn population(&self) -> usize {
+        BitGrid::population(self)
+    }
+
+    fn step(&self, rule: &Rule, torus: bool) -> Self {
+        BitGrid::step(self, rule, torus)
+    }
+}
+
+/// Lo que comparten todas las filas de un `BitGrid::step`
+struct Band<'a> {
+    /// Cantidades de vecinos que hacen nacer o sobrevivir
+    counts: &'a [(usize, bool, bool)],
+    /// Fila muerta para los bordes de un universo que no es toro
+    zero: &'a [u64],
+    /// Bits válidos de la última palabra de cada fila
+    last_mask: u64,
+    torus: bool,
+}
+
+/// Suma un bit a cada una de las 64 cuentas de `sum`
+fn add(sum: &mut [u64; 4], bits: u64) {
+    let mut carry = bits;
+    for digit in sum.iter_mut() {
+        let next = *digit & carry;
+        *digit ^= carry;
+        carry = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// `generations` pasos de `grid` en un grupo de `threads` hilos
+    fn run(grid: &BitGrid, torus: bool, generations: usize, threads: usize) -> BitGrid {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        pool.install(|| {
+            let mut grid = grid.clone();
+            for _ in 0..generations {
+                grid = grid.step(&Rule::conway(), torus);
+            }
+            grid
+        })
+    }
+
+    #[test]
+    fn step_is_the_same_with_one_or_many_threads() {
+        // Un ancho que no es múltiplo de 64 y varias franjas de filas, la
+        // última incompleta
+        let mut grid = BitGrid::new(150, 5 * BAND_ROWS + 3);
+        let mut rng = StdRng::seed_from_u64(7);
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if rng.gen_bool(0.35) {
+                    grid.set(x, y, CellState::Alive);
+                }
+            }
+        }
+        for torus in [false, true] {
+            let serial = run(&grid, torus, 12, 1);
+            assert_ne!(serial, grid);
+            assert_eq!(run(&grid, torus, 12, 4), serial);
+            assert_eq!(run(&grid, torus, 12, 7), serial);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+
+use crate::GameOfLife;
+use crate::stats::{self, Rect, Tracker};
+
+/// Qué tiene que pasar para que la simulación se detenga
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    /// La población cruza el umbral, subiendo o bajando
+    Population(usize),
+    /// Empieza a haber cambios dentro del rectángulo
+    Activity(Rect),
+    /// Se llega a esa generación
+    Generation(u64),
+    /// El objeto que está en `from` al empezar llega al rectángulo
+    Reach { from: (usize, usize), target: Rect },
+}
+
+/// Condición que pausa la simulación la primera generación en que se
+/// cumple (si deja de cumplirse, puede volver a pausarla después)
+#[derive(Clone)]
+pub struct Breakpoint {
+    condition: Condition,
+    was_met: bool,
+    population: Option<usize>,
+    watched: Option<Vec<bool>>,
+    tracker: Option<Tracker>,
+}
+
+impl Breakpoint {
+    pub fn new(condition: Condition) -> Self {
+        let tracker = match condition {
+            Condition::Reach { from, .. } => Some(Tracker::new(from)),
+            _ => None,
+        };
+        Self { condition, was_met: false, population: None, watched: None, tracker }
+    }
+
+    /// Rectángulo que vigila la condición, para dibujarlo en la ventana
+    pub fn area(&self) -> Option<Rect> {
+        match self.condition {
+            Condition::Activity(rect) | Condition::Reach { target: rect, .. } => Some(rect),
+            Condition::Population(_) | Condition::Generation(_) => None,
+        }
+    }
+
+    /// Interpreta `population:500`, `generation:150`, `activity:X,Y,ANCHO,ALTO`
+    /// o `reach:X,Y:X,Y,ANCHO,ALTO`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let error = || format!("punto de interrupción inválido: {}", text);
+        let number = |value: &str| value.trim().parse::<usize>().map_err(|_| error());
+        let rect = |value: &str| Rect::parse(value).map_err(|_| error());
+
+        let (kind, value) = text.split_once(':').ok_or_else(error)?;
+        let condition = match kind.trim() {
+            "population" => Condition::Population(number(value)?),
+            "generation" => Condition::Generation(number(value)? as u64),
+            "activity" => Condition::Activity(rect(value)?),
+            "reach" => {
+                let (from, target) = value.split_once(':').ok_or_else(error)?;
+                let (x, y) = from.split_once(',').ok_or_else(error)?;
+                Condition::Reach { from: (number(x)?, number(y)?), target: rect(target)? }
+            }
+            _ => return Err(error()),
+        };
+        Ok(Self::new(condition))
+    }
+
+    /// Revisa la condición con la generación recién calculada. Devuelve
+    /// `true` si la simulación se tiene que pausar.
+    pub fn check(&mut self, game: &GameOfLife, generation: u64) -> bool {
+        let met = match &self.condition {
+            Condition::Population(threshold) => {
+                let population = stats::population(game);
+                let crossed = self.population.is_some_and(|before| (before < *threshold) != (population < *threshold));
+                self.population = Some(population);
+                crossed
+            }
+            Condition::Activity(rect) => {
+                let cells = rect.cells(game);
+                let changed = self.watched.as_ref().is_some_and(|before| *before != cells);
+                self.watched = Some(cells);
+                changed
+            }
+            Condition::Generation(target) => generation == *target,
+            Condition::Reach { target, .. } => {
+                let tracker = self.tracker.as_mut().expect("las condiciones de llegada siguen un objeto");
+                tracker.update(game).is_some_and(|(x, y)| target.contains(x, y))
+            }
+        };
+        let fire = met && !self.was_met;
+        self.was_met = met;
+        fire
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.condition {
+            Condition::Population(threshold) => write!(f, "la población cruzó {}", threshold),
+            Condition::Activity(rect) => write!(f, "hubo actividad en {}", rect),
+            Condition::Generation(generation) => write!(f, "generación {}", generation),
+            Condition::Reach { from: (x, y), target } => write!(f, "el objeto de {},{} llegó a {}", x, y, target),
+        }
+    }
+}
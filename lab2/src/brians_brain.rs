@@ -0,0 +1,77 @@
+use rand::Rng;
+
+use crate::simulation::Simulation;
+
+/// Estados de Brian's Brain: una célula apagada se enciende si tiene
+/// exactamente dos vecinas encendidas, una encendida pasa a moribunda y una
+/// moribunda se apaga
+const OFF: u8 = 0;
+const ON: u8 = 1;
+const DYING: u8 = 2;
+
+/// Brian's Brain, de Brian Silverman: casi todo lo que nace se mueve, así
+/// que el universo se llena de naves que chocan sin estabilizarse nunca
+#[derive(Clone)]
+pub struct BriansBrain {
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
+}
+
+impl BriansBrain {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, cells: vec![OFF; width * height] }
+    }
+
+    /// Cada celda queda encendida con la probabilidad dada
+    pub fn randomize(&mut self, density: f64, rng: &mut impl Rng) {
+        for cell in &mut self.cells {
+            *cell = if rng.gen_bool(density) { ON } else { OFF };
+        }
+    }
+
+    /// Vecinas encendidas de (x, y); fuera de los bordes todo está apagado
+    fn live_neighbors(&self, x: usize, y: usize) -> usize {
+        let mut count = 0;
+        for dy in -1..=1i32 {
+            for dx in -1..=1i32 {
+                if dx == 0 && dy == 0 { continue; }
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if nx >= 0 && ny >= 0 && (nx as usize) < self.width && (ny as usize) < self.height {
+                    count += (self.cells[ny as usize * self.width + nx as usize] == ON) as usize;
+                }
+            }
+        }
+        count
+    }
+}
+
+impl Simulation for BriansBrain {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn step(&mut self) {
+        let next = (0..self.cells.len())
+            .map(|i| match self.cells[i] {
+                OFF if self.live_neighbors(i % self.width, i / self.width) == 2 => ON,
+                ON => DYING,
+                _ => OFF,
+            })
+            .collect();
+        self.cells = next;
+    }
+
+    /// Apagada, encendida (blanco) y moribunda (azul)
+    fn palette(&self) -> Vec<[u8; 3]> {
+        vec![crate::DEAD_RGB, [0xFF, 0xFF, 0xFF], [0x30, 0x60, 0xE0]]
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        self.cells[y * self.width + x]
+    }
+}
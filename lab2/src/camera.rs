@@ -0,0 +1,427 @@
+use serde::Deserialize;
+
+use crate::geometry::{CellPos, GridSize, PixelPos};
+use crate::{CellState, GameOfLife};
+
+/// Fracción de la distancia al objetivo que la cámara recorre en cada frame
+const CAMERA_SMOOTHING: f32 = 0.08;
+/// Zoom más lejano y más cercano de `ZoomView`, en veces la escala normal
+const MIN_ZOOM: f32 = 0.125;
+const MAX_ZOOM: f32 = 32.0;
+
+/// Vista de una región del grid. El centro se mueve suavemente hacia su
+/// objetivo y siempre se mantiene dentro de los bordes del universo.
+pub struct Camera {
+    center_x: f32,
+    center_y: f32,
+    target_x: f32,
+    target_y: f32,
+    view_width: usize,
+    view_height: usize,
+    grid_width: usize,
+    grid_height: usize,
+}
+
+impl Camera {
+    /// Cámara centrada que muestra `view_width`×`view_height` células
+    pub fn new(game: &GameOfLife, view_width: usize, view_height: usize) -> Self {
+        // Un universo vacío se trata como de 1×1, para que la vista (de al
+        // menos una célula) siempre quepa; fuera del universo no hay células
+        let (grid_width, grid_height) = (game.width().max(1), game.height().max(1));
+        let center_x = grid_width as f32 / 2.0;
+        let center_y = grid_height as f32 / 2.0;
+        Self {
+            center_x,
+            center_y,
+            target_x: center_x,
+            target_y: center_y,
+            view_width: view_width.clamp(1, grid_width),
+            view_height: view_height.clamp(1, grid_height),
+            grid_width,
+            grid_height,
+        }
+    }
+
+    /// Células que muestra la vista
+    pub fn view_size(&self) -> GridSize {
+        GridSize::new(self.view_width, self.view_height)
+    }
+
+    /// Fija el punto (en células) hacia el que se moverá la cámara
+    pub fn look_at(&mut self, x: f32, y: f32) {
+        self.target_x = x;
+        self.target_y = y;
+    }
+
+    /// Cuántas células le faltan al objetivo para que la vista pueda
+    /// centrarse en él sin salirse del grid (0 si ya puede)
+    pub fn out_of_reach(&self) -> (i64, i64) {
+        let deficit = |target: f32, view: usize, grid: usize| {
+            let half = view as f32 / 2.0;
+            (target - target.clamp(half, grid as f32 - half)).round() as i64
+        };
+        (
+            deficit(self.target_x, self.view_width, self.grid_width),
+            deficit(self.target_y, self.view_height, self.grid_height),
+        )
+    }
+
+    /// Desplaza la cámara y su objetivo junto con las células, después de
+    /// trasladar el universo
+    pub fn shift(&mut self, dx: f32, dy: f32) {
+        self.center_x += dx;
+        self.center_y += dy;
+        self.target_x += dx;
+        self.target_y += dy;
+    }
+
+    /// Acerca la cámara un paso hacia su objetivo
+    pub fn update(&mut self) {
+        self.center_x += (self.target_x - self.center_x) * CAMERA_SMOOTHING;
+        self.center_y += (self.target_y - self.center_y) * CAMERA_SMOOTHING;
+    }
+
+    /// Esquina superior izquierda de la vista, limitada al grid
+    fn origin(&self) -> (f32, f32) {
+        let max_x = (self.grid_width - self.view_width) as f32;
+        let max_y = (self.grid_height - self.view_height) as f32;
+        (
+            (self.center_x - self.view_width as f32 / 2.0).clamp(0.0, max_x),
+            (self.center_y - self.view_height as f32 / 2.0).clamp(0.0, max_y),
+        )
+    }
+
+    /// Renderiza la vista estirándola al tamaño de la ventana
+    pub fn render(&self, game: &GameOfLife, buffer: &mut [u32], window_width: usize, window_height: usize) {
+        let (origin_x, origin_y) = self.origin();
+        let cells_per_px_x = self.view_width as f32 / window_width as f32;
+        let cells_per_px_y = self.view_height as f32 / window_height as f32;
+
+        for py in 0..window_height {
+            let y = (origin_y + py as f32 * cells_per_px_y) as usize;
+            for px in 0..window_width {
+                let x = (origin_x + px as f32 * cells_per_px_x) as usize;
+                buffer[py * window_width + px] = game.get_color(x, y);
+            }
+        }
+    }
+
+    /// Célula que `render` dibuja en el píxel `pixel` de una ventana de
+    /// `window_width`×`window_height`, por ejemplo la que está bajo el mouse
+    pub fn cell_at(&self, pixel: PixelPos, window_width: usize, window_height: usize) -> CellPos {
+        let (origin_x, origin_y) = self.origin();
+        let x = origin_x + pixel.x.max(0.0) * self.view_width as f32 / window_width.max(1) as f32;
+        let y = origin_y + pixel.y.max(0.0) * self.view_height as f32 / window_height.max(1) as f32;
+        CellPos::new((x as usize).min(self.grid_width - 1), (y as usize).min(self.grid_height - 1))
+    }
+
+    /// Datos del frame del GIF con solo las células visibles
+    pub fn to_gif_frame_data(&self, game: &GameOfLife) -> Vec<u8> {
+        let (origin_x, origin_y) = self.origin();
+        let (origin_x, origin_y) = (origin_x.round() as usize, origin_y.round() as usize);
+        let mut frame_data = Vec::with_capacity(self.view_width * self.view_height);
+        for y in origin_y..origin_y + self.view_height {
+            for x in origin_x..origin_x + self.view_width {
+                frame_data.push((x < game.width() && y < game.height() && game.is_alive(x, y)) as u8);
+            }
+        }
+        frame_data
+    }
+}
+
+/// Zoom y desplazamiento de la ventana sobre el universo, independientes
+/// de la escala: con zoom 1 y sin desplazar, cada célula ocupa `scale`
+/// píxeles como siempre; con zoom 4 ocupa `4 * scale` y la ventana muestra
+/// una parte del universo; con zoom 1/4 un universo grande entra en una
+/// ventana chica. Lo que queda fuera del universo se ve negro.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ZoomView {
+    zoom: f32,
+    /// Célula (con fracción) en la esquina superior izquierda de la ventana
+    x: f32,
+    y: f32,
+    window: GridSize,
+    scale: usize,
+}
+
+impl ZoomView {
+    /// Vista sin zoom de una ventana de `window` píxeles con `scale`
+    /// píxeles por célula
+    pub fn new(window: GridSize, scale: usize) -> Self {
+        Self { zoom: 1.0, x: 0.0, y: 0.0, window, scale: scale.max(1) }
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Sin zoom ni desplazamiento: la ventana muestra el universo como
+    /// siempre
+    pub fn is_identity(&self) -> bool {
+        self.zoom == 1.0 && self.x == 0.0 && self.y == 0.0
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.window, self.scale);
+    }
+
+    /// Sigue mostrando la misma esquina con el mismo zoom en una ventana de
+    /// otro tamaño o con otra escala
+    pub fn resize(&mut self, window: GridSize, scale: usize) {
+        (self.window, self.scale) = (window, scale.max(1));
+    }
+
+    /// Píxeles de la ventana por célula
+    fn pixels_per_cell(&self) -> f32 {
+        self.scale as f32 * self.zoom
+    }
+
+    /// Multiplica el zoom por `factor` sin mover la célula que está bajo
+    /// `pixel` (el mouse, o el centro de la ventana)
+    pub fn zoom_by(&mut self, factor: f32, pixel: PixelPos) {
+        let (x, y) = self.cell_coords(pixel);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.x = x - pixel.x / self.pixels_per_cell();
+        self.y = y - pixel.y / self.pixels_per_cell();
+    }
+
+    /// Centro de la ventana, para hacer zoom con el teclado
+    pub fn center(&self) -> PixelPos {
+        PixelPos::new(self.window.width as f32 / 2.0, self.window.height as f32 / 2.0)
+    }
+
+    /// Mueve la vista `dx`×`dy` píxeles de la ventana (positivo: el
+    /// universo se corre hacia la izquierda y hacia arriba)
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.x += dx / self.pixels_per_cell();
+        self.y += dy / self.pixels_per_cell();
+    }
+
+    /// Coordenadas (con fracción) de la célula bajo `pixel`
+    fn cell_coords(&self, pixel: PixelPos) -> (f32, f32) {
+        (self.x + pixel.x / self.pixels_per_cell(), self.y + pixel.y / self.pixels_per_cell())
+    }
+
+    /// Célula bajo `pixel`, si cae dentro de un universo de `size`
+    pub fn to_cell(&self, pixel: PixelPos, size: GridSize) -> Option<CellPos> {
+        let (x, y) = self.cell_coords(pixel);
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let cell = CellPos::new(x as usize, y as usize);
+        size.contains(cell).then_some(cell)
+    }
+
+    /// Columna o fila del universo que se ve en el píxel `pixel` contando
+    /// desde `origin` (`usize::MAX` si queda antes del universo)
+    fn cell_at(&self, origin: f32, pixel: usize) -> usize {
+        let at = origin + (pixel as f32 + 0.5) / self.pixels_per_cell();
+        if at < 0.0 { usize::MAX } else { at as usize }
+    }
+
+    /// Dibuja la parte visible de `game` en un buffer del tamaño de la ventana
+    pub fn render(&self, game: &GameOfLife, buffer: &mut [u32]) {
+        let columns: Vec<usize> = (0..self.window.width).map(|px| self.cell_at(self.x, px)).collect();
+        for (py, row) in buffer.chunks_mut(self.window.width).take(self.window.height).enumerate() {
+            let y = self.cell_at(self.y, py);
+            for (pixel, &x) in row.iter_mut().zip(&columns) {
+                *pixel = game.get_color(x, y);
+            }
+        }
+    }
+
+    /// Líneas de un píxel entre las células visibles de un universo de
+    /// `size`, si con el zoom actual cada célula ocupa al menos
+    /// `overlay::GRID_MIN_SCALE` píxeles
+    pub fn draw_grid_lines(&self, buffer: &mut [u32], size: GridSize, color: u32) {
+        if self.pixels_per_cell() < crate::overlay::GRID_MIN_SCALE as f32 {
+            return;
+        }
+        // Píxeles donde empieza una célula que no es la primera de su fila
+        // o columna
+        let starts = |origin: f32, pixels: usize, cells: usize| -> Vec<bool> {
+            (0..pixels)
+                .map(|p| {
+                    let cell = self.cell_at(origin, p);
+                    p > 0 && cell < cells && cell > 0 && self.cell_at(origin, p - 1) != cell
+                })
+                .collect()
+        };
+        let columns = starts(self.x, self.window.width, size.width);
+        let rows = starts(self.y, self.window.height, size.height);
+        let inside = |origin: f32, p: usize, cells: usize| self.cell_at(origin, p) < cells;
+        for (py, row) in buffer.chunks_mut(self.window.width).take(self.window.height).enumerate() {
+            if !inside(self.y, py, size.height) {
+                continue;
+            }
+            for (px, pixel) in row.iter_mut().enumerate() {
+                if inside(self.x, px, size.width) && (rows[py] || columns[px]) {
+                    *pixel = color;
+                }
+            }
+        }
+    }
+}
+
+/// Mide la actividad (nacimientos + muertes) por bloques y cada cierto
+/// número de generaciones elige el bloque más activo como objetivo
+pub struct AutoDirector {
+    block: usize,
+    interval: usize,
+    blocks_x: usize,
+    activity: Vec<u32>,
+    previous: Vec<CellState>,
+    generations: usize,
+}
+
+impl AutoDirector {
+    pub fn new(game: &GameOfLife, block: usize, interval: usize) -> Self {
+        let blocks_x = game.width().div_ceil(block);
+        let blocks_y = game.height().div_ceil(block);
+        Self {
+            block,
+            interval: interval.max(1),
+            blocks_x,
+            activity: vec![0; blocks_x * blocks_y],
+            previous: game.cells(),
+            generations: 0,
+        }
+    }
+
+    /// Toma el estado actual como punto de partida, después de trasladar el
+    /// universo, para que el movimiento no cuente como actividad
+    pub fn rebase(&mut self, game: &GameOfLife) {
+        self.previous = game.cells();
+    }
+
+    /// Registra la generación recién calculada. Devuelve el centro (en
+    /// células) de la región más activa cuando toca elegir un nuevo objetivo.
+    pub fn observe(&mut self, game: &GameOfLife) -> Option<(f32, f32)> {
+        let current = game.cells();
+        for (i, (before, now)) in self.previous.iter().zip(&current).enumerate() {
+            if before != now {
+                let (x, y) = (i % game.width(), i / game.width());
+                self.activity[(y / self.block) * self.blocks_x + x / self.block] += 1;
+            }
+        }
+        self.previous = current;
+        self.generations += 1;
+
+        if !self.generations.is_multiple_of(self.interval) {
+            return None;
+        }
+
+        let (best, &score) = self.activity.iter().enumerate().max_by_key(|&(_, a)| *a)?;
+        self.activity.iter_mut().for_each(|a| *a = 0);
+        if score == 0 {
+            return None; // Universo estático: la cámara se queda donde está
+        }
+
+        let half = self.block as f32 / 2.0;
+        Some((
+            (best % self.blocks_x * self.block) as f32 + half,
+            (best / self.blocks_x * self.block) as f32 + half,
+        ))
+    }
+}
+
+/// Cómo se reparte el movimiento entre dos keyframes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Easing {
+    /// Velocidad constante
+    #[default]
+    Linear,
+    /// Arranca despacio
+    EaseIn,
+    /// Frena al llegar
+    EaseOut,
+    /// Arranca despacio y frena al llegar
+    EaseInOut,
+}
+
+impl Easing {
+    /// Fracción del recorrido hecha cuando pasó la fracción `t` del tiempo
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Posición de la cámara en una generación: centro de la vista (en
+/// células) y acercamiento (1 muestra una célula por píxel del GIF)
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Keyframe {
+    pub generation: u64,
+    pub x: f32,
+    pub y: f32,
+    #[serde(default = "default_zoom")]
+    pub zoom: f32,
+    /// Cómo se llega a este keyframe desde el anterior
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+fn default_zoom() -> f32 {
+    1.0
+}
+
+/// Recorrido de la cámara definido por keyframes. Antes del primero y
+/// después del último la cámara se queda quieta.
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    pub fn new(mut keyframes: Vec<Keyframe>) -> Result<Self, String> {
+        if keyframes.is_empty() {
+            return Err("el recorrido de la cámara necesita al menos un keyframe".to_string());
+        }
+        if let Some(keyframe) = keyframes.iter().find(|k| k.zoom <= 0.0) {
+            return Err(format!("acercamiento inválido en la generación {}: {}", keyframe.generation, keyframe.zoom));
+        }
+        keyframes.sort_by_key(|k| k.generation);
+        Ok(Self { keyframes })
+    }
+
+    /// Centro y acercamiento de la cámara en `generation`. El acercamiento
+    /// se interpola en escala logarítmica, así pasar de 1 a 4 se ve parejo.
+    pub fn at(&self, generation: u64) -> (f32, f32, f32) {
+        let next = self.keyframes.partition_point(|k| k.generation <= generation);
+        let to = self.keyframes[next.min(self.keyframes.len() - 1)];
+        let Some(from) = next.checked_sub(1).map(|i| self.keyframes[i]) else {
+            return (to.x, to.y, to.zoom);
+        };
+        if next == self.keyframes.len() {
+            return (from.x, from.y, from.zoom);
+        }
+
+        let t = (generation - from.generation) as f32 / (to.generation - from.generation) as f32;
+        let t = to.easing.apply(t);
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        (lerp(from.x, to.x), lerp(from.y, to.y), lerp(from.zoom.ln(), to.zoom.ln()).exp())
+    }
+
+    /// Datos de un frame del GIF de `width`×`height` con lo que ve la
+    /// cámara en `generation`. Lo que queda fuera del universo se ve muerto.
+    pub fn frame_data(&self, game: &GameOfLife, generation: u64, width: usize, height: usize) -> Vec<u8> {
+        let (center_x, center_y, zoom) = self.at(generation);
+        let origin_x = center_x - width as f32 / zoom / 2.0;
+        let origin_y = center_y - height as f32 / zoom / 2.0;
+        let mut frame_data = Vec::with_capacity(width * height);
+        for py in 0..height {
+            let y = (origin_y + (py as f32 + 0.5) / zoom).floor();
+            for px in 0..width {
+                let x = (origin_x + (px as f32 + 0.5) / zoom).floor();
+                let inside = x >= 0.0 && y >= 0.0 && (x as usize) < game.width() && (y as usize) < game.height();
+                frame_data.push((inside && game.is_alive(x as usize, y as usize)) as u8);
+            }
+        }
+        frame_data
+    }
+}
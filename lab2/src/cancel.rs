@@ -0,0 +1,46 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Señal para detener una operación larga (avanzar muchas generaciones,
+/// una búsqueda, una exportación) desde otro hilo, por ejemplo el de una
+/// interfaz o un servidor. Las copias comparten la señal: se le pasa una a
+/// la operación y con otra se llama a `cancel`. La operación la revisa
+/// entre pasos, así que termina pronto y sin matar hilos.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pide que se detengan las operaciones que usan esta señal
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// `Err(Cancelled)` si se pidió detener, para cortar con `?`
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() { Err(Cancelled) } else { Ok(()) }
+    }
+}
+
+/// Error de una operación que se detuvo con `CancelToken::cancel` antes de
+/// tener un resultado
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "operación cancelada")
+    }
+}
+
+impl std::error::Error for Cancelled {}
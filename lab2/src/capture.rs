@@ -0,0 +1,161 @@
+use std::io::Cursor;
+use std::ops::Range;
+use std::path::Path;
+
+use image::{ImageFormat, Rgb, RgbImage};
+
+use crate::GameOfLife;
+use crate::simulation::Simulation;
+
+/// Imagen RGB en memoria (una `image::RgbImage`). Es la misma para las
+/// capturas de los notebooks, las miniaturas de `state list` y las
+/// imágenes de `--snapshot-images`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ImageBuffer {
+    image: RgbImage,
+}
+
+impl ImageBuffer {
+    /// Imagen de `width`×`height` con el color de cada píxel según `color`
+    pub fn from_fn(width: usize, height: usize, mut color: impl FnMut(usize, usize) -> [u8; 3]) -> Self {
+        let image = RgbImage::from_fn(width as u32, height as u32, |x, y| Rgb(color(x as usize, y as usize)));
+        Self { image }
+    }
+
+    /// Dibuja el estado actual de `simulation`, `scale` píxeles por celda
+    pub fn render(simulation: &dyn Simulation, scale: usize) -> Self {
+        let scale = scale.max(1);
+        Self::from_fn(simulation.width() * scale, simulation.height() * scale, |x, y| {
+            simulation.color(x / scale, y / scale)
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.image.width() as usize
+    }
+
+    pub fn height(&self) -> usize {
+        self.image.height() as usize
+    }
+
+    /// Los píxeles como RGB, listos para numpy (`reshape(alto, ancho, 3)`)
+    pub fn pixels(&self) -> &[u8] {
+        self.image.as_raw()
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        self.image.get_pixel(x as u32, y as u32).0
+    }
+
+    /// La imagen de `image`, para seguir trabajándola con ese crate
+    pub fn as_image(&self) -> &RgbImage {
+        &self.image
+    }
+
+    /// La imagen codificada como PNG
+    pub fn to_png(&self) -> Result<Vec<u8>, image::ImageError> {
+        let mut bytes = Cursor::new(Vec::new());
+        self.image.write_to(&mut bytes, ImageFormat::Png)?;
+        Ok(bytes.into_inner())
+    }
+
+    pub fn save_png(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.image.save_with_format(path, ImageFormat::Png)?;
+        Ok(())
+    }
+
+    /// Muestra la imagen en un notebook de Jupyter con el kernel de Rust
+    /// (evcxr), que llama a este método con el valor de la última línea
+    pub fn evcxr_display(&self) {
+        match self.to_png() {
+            Ok(png) => println!("EVCXR_BEGIN_CONTENT image/png\n{}\nEVCXR_END_CONTENT", base64(&png)),
+            Err(e) => println!("no se pudo codificar la imagen: {}", e),
+        }
+    }
+}
+
+/// Imágenes de las generaciones `range` de `simulation`, contando desde su
+/// estado actual (0 es el estado actual), sin modificarla ni escribir
+/// archivos. Pensado para notebooks, que pueden mostrar la evolución en
+/// línea.
+pub fn capture_frames<S: Simulation + Clone>(simulation: &S, range: Range<usize>, scale: usize) -> Vec<ImageBuffer> {
+    let mut simulation = simulation.clone();
+    let mut frames = Vec::with_capacity(range.len());
+    for generation in 0..range.end {
+        if generation >= range.start {
+            frames.push(ImageBuffer::render(&simulation, scale));
+        }
+        if generation + 1 < range.end {
+            simulation.step();
+        }
+    }
+    frames
+}
+
+impl GameOfLife {
+    /// Imágenes de las generaciones `range` a partir de la actual, con los
+    /// colores del tema y `scale` píxeles por célula. El juego no cambia.
+    pub fn capture_frames(&self, range: Range<usize>, scale: usize) -> Vec<ImageBuffer> {
+        capture_frames(self, range, scale)
+    }
+}
+
+/// Codificación base64 estándar, con relleno
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &b)| group | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(ALPHABET[(group >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KnownPattern;
+
+    #[test]
+    fn render_scales_each_cell() {
+        let mut game = GameOfLife::new(6, 4);
+        game.set_cell(2, 1, crate::CellState::Alive);
+        let image = ImageBuffer::render(&game, 3);
+        assert_eq!((image.width(), image.height()), (18, 12));
+        assert_eq!(image.pixels().len(), 18 * 12 * 3);
+        for (x, y) in [(6, 3), (8, 5)] {
+            assert_eq!(image.pixel(x, y), game.color(2, 1));
+        }
+        assert_eq!(image.pixel(9, 3), game.color(3, 1));
+        assert_ne!(game.color(2, 1), game.color(3, 1));
+    }
+
+    #[test]
+    fn png_decodes_to_the_same_pixels() {
+        let mut game = GameOfLife::new(10, 10);
+        KnownPattern::Glider.stamp(&mut game, 2, 2);
+        let image = ImageBuffer::render(&game, 2);
+        let decoded = image::load_from_memory_with_format(&image.to_png().unwrap(), ImageFormat::Png).unwrap();
+        assert_eq!(&decoded.to_rgb8(), image.as_image());
+    }
+
+    #[test]
+    fn capture_frames_leaves_the_game_alone() {
+        let mut game = GameOfLife::new(10, 10);
+        KnownPattern::Blinker.stamp(&mut game, 4, 4);
+        let before = game.clone();
+        let frames = game.capture_frames(1..4, 1);
+        assert_eq!(frames.len(), 3);
+        // El parpadeador tiene período 2
+        assert_ne!(frames[0], frames[1]);
+        assert_eq!(frames[0], frames[2]);
+        assert_eq!(frames[1], ImageBuffer::render(&before, 1));
+        assert_eq!(game.cells(), before.cells());
+    }
+}
@@ -0,0 +1,322 @@
+use std::fmt;
+
+use crate::{CellState, GameOfLife, Topology};
+
+/// Tipo de los valores del canal auxiliar
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AuxKind {
+    /// Enteros de 0 a 255 (contadores, niveles discretos)
+    U8,
+    /// Números reales (concentraciones que se difunden)
+    F32,
+}
+
+/// Valores del canal, fila por fila
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuxData {
+    U8(Vec<u8>),
+    F32(Vec<f32>),
+}
+
+/// Función que actualiza el canal después de cada generación: lee el
+/// universo y el canal y escribe en el canal
+pub type AuxStep = fn(&GameOfLife, &mut AuxChannel);
+
+/// Dato extra por célula que el motor no usa (nutrientes, feromonas,
+/// visitas, ...), para extensiones de tipo ecológico. Lo actualiza la
+/// función de `with_step` después de cada generación.
+#[derive(Clone, Debug)]
+pub struct AuxChannel {
+    width: usize,
+    height: usize,
+    data: AuxData,
+    diffusion: Option<Diffusion>,
+    step: Option<AuxStep>,
+}
+
+impl AuxChannel {
+    /// Canal de `width`×`height` con todos los valores en 0
+    pub fn new(width: usize, height: usize, kind: AuxKind) -> Self {
+        let data = match kind {
+            AuxKind::U8 => AuxData::U8(vec![0; width * height]),
+            AuxKind::F32 => AuxData::F32(vec![0.0; width * height]),
+        };
+        Self { width, height, data, diffusion: None, step: None }
+    }
+
+    /// El mismo canal, actualizado con `step` en cada generación
+    pub fn with_step(mut self, step: AuxStep) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    /// El mismo canal, que en cada generación recibe lo que depositan las
+    /// células vivas, se difunde y se desvanece (antes de la función de
+    /// `with_step`, si hay una)
+    pub fn with_diffusion(mut self, diffusion: Diffusion) -> Self {
+        self.diffusion = Some(diffusion);
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn kind(&self) -> AuxKind {
+        match self.data {
+            AuxData::U8(_) => AuxKind::U8,
+            AuxData::F32(_) => AuxKind::F32,
+        }
+    }
+
+    pub fn data(&self) -> &AuxData {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut AuxData {
+        &mut self.data
+    }
+
+    /// Valor de la celda (x, y); 0 fuera del canal
+    pub fn get(&self, x: usize, y: usize) -> f32 {
+        if x >= self.width || y >= self.height {
+            return 0.0;
+        }
+        let i = y * self.width + x;
+        match &self.data {
+            AuxData::U8(values) => values[i] as f32,
+            AuxData::F32(values) => values[i],
+        }
+    }
+
+    /// Cambia el valor de la celda (x, y). En un canal `u8` se redondea y se
+    /// limita a 0..=255; fuera del canal no hace nada.
+    pub fn set(&mut self, x: usize, y: usize, value: f32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let i = y * self.width + x;
+        match &mut self.data {
+            AuxData::U8(values) => values[i] = value.round().clamp(0.0, 255.0) as u8,
+            AuxData::F32(values) => values[i] = value,
+        }
+    }
+
+    /// Valores entre 0.0 y 1.0 fila por fila, para dibujarlos: los canales
+    /// `u8` se dividen por 255 y los `f32` por el mayor valor del canal
+    pub fn levels(&self) -> Vec<f32> {
+        match &self.data {
+            AuxData::U8(values) => values.iter().map(|&v| v as f32 / 255.0).collect(),
+            AuxData::F32(values) => {
+                let max = values.iter().copied().fold(0.0, f32::max);
+                values.iter().map(|&v| if max > 0.0 { (v / max).clamp(0.0, 1.0) } else { 0.0 }).collect()
+            }
+        }
+    }
+
+    /// Aplica la función de actualización después de una generación de
+    /// `game`. Si el universo cambió de tamaño (Ctrl+E, Ctrl+R), el canal
+    /// vuelve a empezar en 0 con el tamaño nuevo.
+    pub fn step(&mut self, game: &GameOfLife) {
+        if (self.width, self.height) != (game.width(), game.height()) {
+            self.data = Self::new(game.width(), game.height(), self.kind()).data;
+            (self.width, self.height) = (game.width(), game.height());
+        }
+        if let Some(diffusion) = self.diffusion {
+            self.diffuse(game, diffusion);
+        }
+        if let Some(step) = self.step {
+            step(game, self);
+        }
+    }
+
+    /// Un paso de difusión: cada célula viva deposita `deposit` en su
+    /// celda, cada celda se acerca al promedio de sus 8 vecinas en la
+    /// proporción `rate` y después pierde la fracción `decay`. Con bordes
+    /// muertos el promedio usa solo las vecinas que existen; en un toro, las
+    /// del borde opuesto.
+    pub fn diffuse(&mut self, game: &GameOfLife, diffusion: Diffusion) {
+        let (width, height) = (self.width, self.height);
+        let torus = game.topology() == Topology::Torus;
+        let values: Vec<f32> = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                let deposit = if game.is_alive(x, y) { diffusion.deposit } else { 0.0 };
+                self.get(x, y) + deposit
+            })
+            .collect();
+
+        let mut next = Vec::with_capacity(values.len());
+        for y in 0..height {
+            for x in 0..width {
+                let (mut sum, mut count) = (0.0, 0);
+                for dy in -1..=1i64 {
+                    for dx in -1..=1i64 {
+                        if dx == 0 && dy == 0 { continue; }
+                        let (mut nx, mut ny) = (x as i64 + dx, y as i64 + dy);
+                        if torus {
+                            nx = nx.rem_euclid(width as i64);
+                            ny = ny.rem_euclid(height as i64);
+                        } else if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                            continue;
+                        }
+                        sum += values[ny as usize * width + nx as usize];
+                        count += 1;
+                    }
+                }
+                let value = values[y * width + x];
+                let mixed = if count > 0 { value + diffusion.rate * (sum / count as f32 - value) } else { value };
+                next.push(mixed * (1.0 - diffusion.decay));
+            }
+        }
+        for (i, value) in next.into_iter().enumerate() {
+            self.set(i % width, i / width, value);
+        }
+    }
+}
+
+/// Parámetros de `AuxChannel::with_diffusion`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Diffusion {
+    /// Cuánto suma cada célula viva a su celda por generación
+    pub deposit: f32,
+    /// Fracción (0 a 1) con la que cada celda se mezcla con sus vecinas
+    pub rate: f32,
+    /// Fracción (0 a 1) que se pierde en cada generación
+    pub decay: f32,
+}
+
+impl Default for Diffusion {
+    fn default() -> Self {
+        Self { deposit: 1.0, rate: 0.2, decay: 0.05 }
+    }
+}
+
+// ===== REGLAS QUE MIRAN EL CAMPO =====
+/// Intervalo de valores del campo, con los extremos incluidos
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FieldRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl FieldRange {
+    /// Cualquier valor
+    pub const ANY: FieldRange = FieldRange { min: f32::NEG_INFINITY, max: f32::INFINITY };
+
+    /// Lee `MIN..MAX`; cualquiera de los dos extremos puede faltar
+    /// (`0.5..` es 0.5 o más, `..2` es 2 o menos)
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (min, max) = text.split_once("..").ok_or_else(|| format!("se esperaba MIN..MAX: {}", text))?;
+        let bound = |value: &str, missing: f32| match value.trim() {
+            "" => Ok(missing),
+            value => value.parse::<f32>().map_err(|_| format!("número inválido: {}", value)),
+        };
+        let range = Self { min: bound(min, f32::NEG_INFINITY)?, max: bound(max, f32::INFINITY)? };
+        if range.min > range.max {
+            return Err(format!("el mínimo es mayor que el máximo: {}", text));
+        }
+        Ok(range)
+    }
+
+    pub fn contains(&self, value: f32) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+impl Default for FieldRange {
+    fn default() -> Self {
+        Self::ANY
+    }
+}
+
+impl fmt::Display for FieldRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.min.is_finite() {
+            write!(f, "{}", self.min)?;
+        }
+        write!(f, "..")?;
+        if self.max.is_finite() {
+            write!(f, "{}", self.max)?;
+        }
+        Ok(())
+    }
+}
+
+/// Condiciones sobre el valor del campo en la celda, además de la regla B/S:
+/// una célula solo nace si el campo está en `birth` y solo sobrevive si
+/// está en `survival`. Con un campo de difusión alcanza para modelos de
+/// moho mucilaginoso o de feromonas de hormigas.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FieldRule {
+    pub birth: FieldRange,
+    pub survival: FieldRange,
+}
+
+impl FieldRule {
+    /// Sin condiciones: el universo evoluciona igual que sin campo
+    pub fn is_unconditional(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl GameOfLife {
+    /// Avanza una generación con la regla del universo y después deshace los
+    /// nacimientos y las supervivencias que `rule` no permite con el valor
+    /// de `field` en cada celda. Las células congeladas no cambian.
+    pub fn next_generation_in_field(&mut self, field: &AuxChannel, rule: &FieldRule) {
+        let before = self.grid.clone();
+        self.next_generation();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.is_frozen(x, y) || !self.grid.is_alive(x, y) {
+                    continue;
+                }
+                let allowed = match before.get(x, y) {
+                    CellState::Dead => &rule.birth,
+                    CellState::Alive => &rule.survival,
+                };
+                if !allowed.contains(field.get(x, y)) {
+                    self.grid.set(x, y, CellState::Dead);
+                }
+            }
+        }
+    }
+}
+
+// ===== CANALES INCLUIDOS =====
+/// Canales auxiliares que se pueden elegir desde la línea de comandos
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum AuxPreset {
+    /// Generaciones que cada célula pasó viva (hasta 255): un mapa de las
+    /// zonas más transitadas
+    Visits,
+    /// Rastro que dejan las células vivas, se difunde y se desvanece
+    Diffusion,
+}
+
+impl AuxPreset {
+    /// Canal de `width`×`height`; `diffusion` solo se usa en `Diffusion`
+    pub fn channel(self, width: usize, height: usize, diffusion: Diffusion) -> AuxChannel {
+        match self {
+            AuxPreset::Visits => AuxChannel::new(width, height, AuxKind::U8).with_step(count_visits),
+            AuxPreset::Diffusion => AuxChannel::new(width, height, AuxKind::F32).with_diffusion(diffusion),
+        }
+    }
+}
+
+/// Suma 1 a las celdas de las células vivas
+pub fn count_visits(game: &GameOfLife, channel: &mut AuxChannel) {
+    if let AuxData::U8(values) = channel.data_mut() {
+        for (i, value) in values.iter_mut().enumerate() {
+            if game.is_alive(i % game.width(), i / game.width()) {
+                *value = value.saturating_add(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,509 @@
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::geometry::{CellPos, PixelPos};
+use crate::results::ResultsDb;
+use crate::rle::{RlePattern, Transform};
+use crate::rule_search::SearchConfig;
+use crate::scenario::Scenario;
+use crate::screen::Screen;
+use crate::snapshot::{self, Snapshot};
+use crate::stats;
+use crate::totalistic::MultiStateLife;
+use crate::simulation::{self, Simulation};
+use crate::watch::Watcher;
+use crate::{GameOfLife, KnownPattern};
+use crate::discovery::Discovery;
+use crate::options::{ExportOptions, RunOptions, UniverseOptions};
+use crate::runner::{run_backend, run_simulation, speed_keys};
+use crate::{ansi, demo, favorites, gif_import, methuselah, montage, overlay, rule_search, showcase, totalistic, tutorial};
+
+// ===== CONFIGURACIÓN =====
+const THUMBNAIL_SIZE: usize = 64; // Lado máximo en píxeles de las miniaturas de `state list`
+const PREVIEW_COLUMNS: usize = 48; // Ancho máximo en caracteres de las vistas previas de `state list`
+
+// ===== EXPLORADOR DE REGLAS =====
+/// Miniaturas por fila en la galería del explorador
+const GALLERY_COLUMNS: usize = 4;
+/// Píxeles por célula en las miniaturas
+const GALLERY_SCALE: usize = 4;
+/// Separación en píxeles entre miniaturas
+const GALLERY_GAP: usize = 4;
+
+pub fn run_explorer(
+    states: u8,
+    samples: usize,
+    lambda: f64,
+    density: f64,
+    universe: &UniverseOptions,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Probando {} reglas de {} estados...", samples, states);
+    let mut trials = totalistic::explore(states, samples, GALLERY_COLUMNS * GALLERY_COLUMNS, lambda, density);
+    for (i, trial) in trials.iter().enumerate() {
+        println!("{:2}. puntaje {:.3}  {}", i + 1, trial.score, trial.simulation.rule());
+    }
+
+    let cell = totalistic::TRIAL_SIZE * GALLERY_SCALE + GALLERY_GAP;
+    let rows = trials.len().div_ceil(GALLERY_COLUMNS);
+    let (window_width, window_height) = (GALLERY_COLUMNS * cell + GALLERY_GAP, rows * cell + GALLERY_GAP);
+
+    if universe.headless {
+        return Err("explore necesita la ventana para elegir una regla".into());
+    }
+    let mut window = Window::new(
+        "Explorador de reglas - clic para abrir una regla, ESC para salir",
+        window_width,
+        window_height,
+        WindowOptions::default(),
+    )?;
+    window.limit_update_rate(Some(export_options.speed().generation_interval()));
+    let mut buffer: Vec<u32> = vec![0; window_width * window_height];
+
+    let mut selected = None;
+    while window.is_open() && !window.is_key_down(Key::Escape) && selected.is_none() {
+        for (i, trial) in trials.iter_mut().enumerate() {
+            trial.simulation.step();
+            let origin = (GALLERY_GAP + i % GALLERY_COLUMNS * cell, GALLERY_GAP + i / GALLERY_COLUMNS * cell);
+            simulation::render_at(&trial.simulation, &mut buffer, window_width, origin, GALLERY_SCALE);
+        }
+        window.update_with_buffer(&buffer, window_width, window_height)?;
+
+        if window.get_mouse_down(MouseButton::Left)
+            && let Some(CellPos { x: column, y: row }) = window.get_mouse_pos(MouseMode::Discard).and_then(|p| PixelPos::from(p).to_cell(cell))
+        {
+            let index = row * GALLERY_COLUMNS + column;
+            if column < GALLERY_COLUMNS && index < trials.len() {
+                selected = Some(index);
+            }
+        }
+    }
+
+    let Some(index) = selected else { return Ok(()) };
+    let rule = trials[index].simulation.rule().clone();
+    println!("Regla elegida: {}", rule);
+    let mut totalistic = MultiStateLife::new(universe.width, universe.height, rule);
+    totalistic.randomize(density, &mut rand::thread_rng());
+    drop(window);
+    run_backend(&mut totalistic, "Totalística", universe, export_options)
+}
+
+/// Corre el algoritmo genético y guarda las mejores reglas en
+/// `out/best_rules.txt`, con un GIF de ejemplo de cada una
+pub fn run_rule_search(
+    config: &SearchConfig,
+    out: &Path,
+    keep: usize,
+    results: Option<&Path>,
+    universe: &UniverseOptions,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = results.map(ResultsDb::open).transpose()?;
+    println!(
+        "Evolucionando {} reglas de {} estados durante {} generaciones con {} hilos...",
+        config.population, config.states, config.generations, config.workers
+    );
+    let best = rule_search::evolve(config, |generation, candidates| {
+        let mean = candidates.iter().map(|c| c.fitness).sum::<f64>() / candidates.len() as f64;
+        println!("Generación {:3}: mejor {:.3}, promedio {:.3}", generation, candidates[0].fitness, mean);
+    });
+
+    std::fs::create_dir_all(out)?;
+    let mut summary = String::new();
+    for (i, candidate) in best.iter().take(keep).enumerate() {
+        summary.push_str(&format!("{:.3} {}\n", candidate.fitness, candidate.rule));
+        if let Some(db) = &db {
+            db.record_rule(config.states, candidate.fitness, &candidate.rule.to_string())?;
+        }
+
+        let mut example = MultiStateLife::new(totalistic::TRIAL_SIZE, totalistic::TRIAL_SIZE, candidate.rule.clone());
+        example.randomize(config.density, &mut rand::thread_rng());
+        let path = out.join(format!("best_{}.gif", i + 1));
+        let mut gif_generator = export_options
+            .create_gif(&path, example.width(), example.height(), GALLERY_SCALE, universe.frames as usize)?
+            .with_palette(example.palette());
+        for _ in 0..universe.frames {
+            gif_generator.add_frame(&simulation::to_gif_frame_data(&example))?;
+            example.step();
+        }
+        gif_generator.finish()?;
+    }
+    std::fs::write(out.join("best_rules.txt"), &summary)?;
+
+    print!("Mejores reglas (guardadas en {}):\n{}", out.display(), summary);
+    if let Some(db) = &db {
+        println!("Reglas agregadas a {}", db.path().display());
+    }
+    Ok(())
+}
+
+/// Busca matusalenes e imprime el mejor patrón de cada cadena en RLE
+pub fn run_methuselah_search(config: &methuselah::SearchConfig, out: Option<&Path>, results: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let db = results.map(ResultsDb::open).transpose()?;
+    println!(
+        "Buscando patrones de hasta {} células en una caja de {}x{} ({} cadenas de {} pasos)...",
+        config.max_cells, methuselah::BOX_SIZE, methuselah::BOX_SIZE, config.workers, config.iterations
+    );
+    let results = methuselah::search(config);
+    for result in &results {
+        println!(
+            "\n{} generaciones hasta entrar en un ciclo de período {}, {} células:\n{}",
+            result.lifespan,
+            result.period,
+            result.pattern.population(),
+            result.pattern.to_rle()
+        );
+        if let Some(db) = &db {
+            db.record_methuselah(result.pattern.population(), result.lifespan, result.period, &result.pattern.to_rle())?;
+        }
+    }
+    if let Some(db) = &db {
+        println!("Patrones agregados a {}", db.path().display());
+    }
+
+    if let Some(path) = out {
+        std::fs::write(path, results[0].pattern.to_rle())?;
+        println!("Mejor patrón guardado en {}", path.display());
+    }
+    Ok(())
+}
+
+// ===== MOSAICO DE EJECUCIONES =====
+pub fn run_montage(
+    inputs: &[PathBuf],
+    output: &Path,
+    columns: Option<usize>,
+    scale: usize,
+    input_scale: usize,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let runs = inputs
+        .iter()
+        .map(|path| montage::Run::load(path, input_scale).map_err(|e| format!("{}: {}", path.display(), e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let montage = montage::Montage::new(runs, columns);
+    let (width, height) = montage.size();
+    println!("Mosaico de {} ejecuciones: {}x{}, {} frames", inputs.len(), width, height, montage.frame_count());
+
+    let mut gif_generator = export_options
+        .create_gif(output, width, height, scale, montage.frame_count())?
+        .with_palette(montage::Montage::palette());
+    for i in 0..montage.frame_count() {
+        gif_generator.add_frame(&montage.frame(i))?;
+    }
+    gif_generator.finish()?;
+    println!("{} exportado en {}", export_options.format().extension().to_uppercase(), export_options.format().output_path(output).display());
+    Ok(())
+}
+
+// ===== VITRINA =====
+pub fn run_showcase(
+    pattern: &RlePattern,
+    name: &str,
+    periods: usize,
+    scale: usize,
+    output: &Path,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let showcase = showcase::Showcase::from_pattern(pattern, name, periods.max(1), scale.max(1))?;
+    let (width, height) = showcase.size();
+    let frames = showcase.frame_count();
+    println!("{}: período {}, {}x{} píxeles, {} frames", name, showcase.period(), width, height, frames);
+
+    let mut gif_generator = export_options
+        .create_gif(output, width, height, 1, frames)?
+        .with_palette(showcase::Showcase::palette());
+    for i in 0..frames {
+        gif_generator.add_frame(&showcase.frame(i))?;
+    }
+    gif_generator.finish()?;
+    println!("{} exportado en {}", export_options.format().extension().to_uppercase(), export_options.format().output_path(output).display());
+    Ok(())
+}
+
+// ===== DEMOSTRACIÓN =====
+/// Muestra las escenas de `demo::SCENES` una tras otra, sin fin, con su
+/// título en la barra de estado
+pub fn run_demo(universe: &UniverseOptions, export_options: &ExportOptions) -> Result<(), Box<dyn std::error::Error>> {
+    if universe.headless {
+        return Err("demo necesita la ventana; no funciona con --headless".into());
+    }
+    let (width, height, scale) = (universe.width, universe.height, universe.scale);
+    let (window_width, window_height) = (width * scale, height * scale);
+    let mut window = Screen::open("Demostración - Espacio: siguiente escena, ESC: salir", window_width, window_height, false)?
+        .with_renderer(universe.renderer);
+    let mut speed = export_options.speed();
+    window.limit_update_rate(Some(speed.generation_interval()));
+    let mut buffer: Vec<u32> = vec![0; window_width * window_height];
+
+    for (index, scene) in demo::SCENES.iter().enumerate().cycle() {
+        let mut simulation = scene.build(width, height);
+        let status = format!("{}/{} {}: {}", index + 1, demo::SCENES.len(), scene.title, scene.caption);
+        println!("{}", status);
+        let started = Instant::now();
+        while started.elapsed() < demo::SCENE_DURATION && !window.is_key_pressed(Key::Space, KeyRepeat::No) {
+            if !window.is_open() || window.is_key_down(Key::Escape) {
+                return Ok(());
+            }
+            if let Some(new_speed) = speed_keys(&window, speed) {
+                speed = new_speed;
+                window.limit_update_rate(Some(speed.generation_interval()));
+            }
+            simulation.step();
+            simulation::render(simulation.as_ref(), &mut buffer, scale);
+            overlay::draw_status(&mut buffer, window_width, &status);
+            window.update_with_buffer(&buffer, window_width, window_height)?;
+        }
+    }
+    Ok(())
+}
+
+// ===== TUTORIAL =====
+/// Recorre los pasos de `tutorial::STEPS` en un universo vacío, con las
+/// mismas teclas que una ejecución normal. Cada paso se completa cuando el
+/// usuario hace lo que pide.
+pub fn run_tutorial(universe: &UniverseOptions, options: &RunOptions, export_options: &ExportOptions) -> Result<(), Box<dyn std::error::Error>> {
+    if universe.headless {
+        return Err("tutorial necesita la ventana; no funciona con --headless".into());
+    }
+    let (width, height, scale) = (universe.width, universe.height, universe.scale);
+    let (window_width, window_height) = (width * scale, height * scale);
+    let mut window = Screen::open("Tutorial - ESC para salir", window_width, window_height, false)?.with_renderer(universe.renderer);
+    window.limit_update_rate(Some(export_options.speed().generation_interval()));
+    let mut buffer: Vec<u32> = vec![0; window_width * window_height];
+
+    let mut game = GameOfLife::new(width, height);
+    game.set_theme(universe.theme.theme());
+    let mut discovery = Discovery::new();
+    let mut tutorial = tutorial::Tutorial::new();
+    let mut paused = true;
+    let mut generation = 0u64;
+    if let Some(step) = tutorial.current() {
+        println!("Paso 1/{}: {}: {}", tutorial::STEPS.len(), step.title, step.prompt);
+    }
+
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        let mut actions = Vec::new();
+        if window.is_key_pressed(Key::Space, KeyRepeat::No) {
+            paused = !paused;
+            actions.push(if paused { tutorial::Action::Paused } else { tutorial::Action::Resumed });
+        }
+        if window.is_key_pressed(Key::P, KeyRepeat::No)
+            && let Some(cell) = window.mouse_pixel().and_then(|p| p.to_cell(scale))
+            && !KnownPattern::Glider.stamp(&mut game, cell.x, cell.y).is_empty()
+        {
+            actions.push(tutorial::Action::Stamped(KnownPattern::Glider));
+        }
+        let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+        if shift && window.is_key_pressed(Key::Slash, KeyRepeat::No) {
+            let found = discovery.reroll(&mut game, universe.density);
+            println!("Regla {} con la semilla {}", found.rule, found.seed);
+            actions.push(tutorial::Action::RuleChanged);
+        }
+        if window.is_key_pressed(Key::S, KeyRepeat::No) {
+            std::fs::create_dir_all(&options.rle_dir)?;
+            let format = options.pattern_format;
+            let path = options.rle_dir.join(format!("generacion_{}.{}", generation, format.extension()));
+            std::fs::write(&path, format.write(&RlePattern::from_game(&game)))?;
+            println!("Generación guardada en {}", path.display());
+            actions.push(tutorial::Action::Exported);
+        }
+        for action in actions {
+            if tutorial.observe(action) {
+                match tutorial.current() {
+                    Some(step) => println!("Paso {}/{}: {}: {}", tutorial.position(), tutorial::STEPS.len(), step.title, step.prompt),
+                    None => println!("Tutorial completo. Las ejecuciones normales además graban todo en un GIF (-o)"),
+                }
+            }
+        }
+
+        if !paused {
+            game.next_generation();
+            generation += 1;
+        }
+        simulation::render(&game, &mut buffer, scale);
+        let (title, prompt) = match tutorial.current() {
+            Some(step) => (format!("paso {}/{}: {}", tutorial.position(), tutorial::STEPS.len(), step.title), step.prompt),
+            None => ("tutorial completo".to_string(), "sigue probando o presiona ESC para salir"),
+        };
+        overlay::draw_hud(&mut buffer, window_width, &[title, format!("generación {}", generation)]);
+        overlay::draw_status(&mut buffer, window_width, prompt);
+        window.update_with_buffer(&buffer, window_width, window_height)?;
+    }
+    Ok(())
+}
+
+
+// ===== EXPORTAR GUIONES =====
+/// Exporta el guion y, con `watch`, vuelve a exportarlo cada vez que cambia
+/// alguno de sus archivos. Mientras se vigila, un error (por ejemplo, un
+/// guion a medio escribir) se muestra y se espera al siguiente cambio.
+pub fn run_export(
+    path: &Path,
+    watch: bool,
+    universe: &mut UniverseOptions,
+    options: &RunOptions,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    universe.headless = true;
+    if !watch {
+        return export_scenario(&Scenario::validate(path)?, universe, options, export_options);
+    }
+
+    // Sin patrones, cada exportación parte del mismo universo aleatorio
+    universe.seed = Some(universe.seed());
+    let mut watcher = Watcher::new(vec![path.to_path_buf()]);
+    loop {
+        match Scenario::validate(path) {
+            Ok(scenario) => {
+                // Lo que cambie durante la exportación cuenta para la siguiente
+                watcher.set_files(scenario.inputs(path));
+                if let Err(e) = export_scenario(&scenario, universe, options, export_options) {
+                    eprintln!("Error: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Error: {}", e),
+        }
+        println!("Esperando cambios en {} (Ctrl+C para salir)", path.display());
+        let changed = watcher.wait();
+        let names: Vec<String> = changed.iter().map(|file| file.display().to_string()).collect();
+        println!("Cambió {}: exportando de nuevo", names.join(", "));
+    }
+}
+
+/// Estampa los patrones del guion en un universo vacío (o lo llena al azar
+/// si no tiene ninguno) y lo simula sin ventana
+fn export_scenario(
+    scenario: &Scenario,
+    universe: &UniverseOptions,
+    options: &RunOptions,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut game = GameOfLife::new(universe.width, universe.height);
+    let seed = universe.seed();
+    if scenario.patterns.is_empty() {
+        game.initialize_with_seed(universe.density, seed);
+    }
+    game.set_seed(seed);
+    game.set_coordinates(options.coordinates());
+    for placement in &scenario.patterns {
+        let pattern = RlePattern::load(&placement.file)?;
+        game.place_pattern_at(&pattern, (placement.x, placement.y), Transform::default(), options.placement)
+            .map_err(|e| format!("{}: {}", placement.file.display(), e))?;
+    }
+    run_simulation(game, 0, universe, options, scenario, export_options)
+}
+
+// ===== FAVORITOS =====
+pub fn run_favorites_list(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let favorites = favorites::load(path)?;
+    if favorites.is_empty() {
+        println!("{}: no hay favoritos guardados (K los guarda en el modo descubrimiento)", path.display());
+    }
+    for favorite in &favorites {
+        print!("{}: {}, semilla {}, densidad {}, {}x{}", favorite.name, favorite.rule, favorite.seed, favorite.density, favorite.width, favorite.height);
+        match &favorite.scenario {
+            Some(scenario) => println!(", guion {}", scenario.display()),
+            None => println!(),
+        }
+    }
+    Ok(())
+}
+
+// ===== ESTADOS GUARDADOS =====
+/// Las miniaturas y vistas previas necesitan las células, así que solo con
+/// ellas se cargan los estados (y la población se muestra)
+pub fn run_state_list(dir: &Path, thumbnails: bool, preview: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let (states, failed) = snapshot::list(dir)?;
+    if states.is_empty() && failed.is_empty() {
+        println!("{}: no hay estados guardados", dir.display());
+    }
+    for info in &states {
+        let name = info.path.file_name().unwrap_or_default().to_string_lossy();
+        let kind = match info.base_generation {
+            Some(base) => format!("diferencia con la generación {}", base),
+            None => "completo".to_string(),
+        };
+        print!("{}: generación {}, {}x{}, {}, {} bytes", name, info.generation, info.width, info.height, kind, info.bytes);
+        if !thumbnails && !preview {
+            println!();
+            continue;
+        }
+        let game = match Snapshot::load(&info.path) {
+            Ok(snapshot) => snapshot.game,
+            Err(e) => {
+                println!(", no se pudo cargar: {}", e);
+                continue;
+            }
+        };
+        println!(", {} células vivas", stats::population(&game));
+        if thumbnails {
+            let thumbnail = info.path.with_extension("thumb.png");
+            snapshot::write_thumbnail(&game, &thumbnail, THUMBNAIL_SIZE)?;
+            println!("  miniatura: {}", thumbnail.display());
+        }
+        if preview {
+            print!("{}", ansi::preview(&game, PREVIEW_COLUMNS, PREVIEW_COLUMNS / 2));
+        }
+    }
+    for (path, e) in &failed {
+        eprintln!("{}: {}", path.display(), e);
+    }
+    Ok(())
+}
+
+/// Deja dos filas de la terminal para el título y el prompt
+pub fn run_show(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let is_state = path.extension().is_some_and(|ext| ext == "snap" || ext == "snapd");
+    let (game, title) = if is_state {
+        let snapshot = Snapshot::load(path)?;
+        (snapshot.game, format!("generación {}", snapshot.generation))
+    } else {
+        let pattern = RlePattern::load(path)?;
+        let mut game = GameOfLife::new(pattern.width, pattern.height);
+        pattern.stamp(&mut game, 0, 0);
+        let rule = pattern.rule.map_or_else(|| "regla sin indicar".to_string(), |rule| format!("regla {}", rule));
+        let title = match &pattern.name {
+            Some(name) => format!("{}, {}", name, rule),
+            None => rule,
+        };
+        (game, title)
+    };
+    println!(
+        "{}: {}x{}, {}, {} células vivas",
+        path.display(),
+        game.width(),
+        game.height(),
+        title,
+        stats::population(&game)
+    );
+    let (columns, rows) = ansi::terminal_size();
+    print!("{}", ansi::preview(&game, columns, rows.saturating_sub(2)));
+    Ok(())
+}
+
+// ===== IMPORTACIÓN DE GIF =====
+pub fn run_import(
+    path: &Path,
+    scale: usize,
+    verify: bool,
+    universe: &UniverseOptions,
+    options: &RunOptions,
+    scenario: &Scenario,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut states = gif_import::import_gif(path, scale)?;
+    println!("GIF importado: {} frames de {}x{}", states.len(), states[0].width(), states[0].height());
+
+    if verify {
+        match gif_import::first_mismatch(&states) {
+            None => println!("El GIF coincide con la evolución del motor actual"),
+            Some(frame) => return Err(format!("el frame {} no coincide con la evolución del motor actual", frame).into()),
+        }
+        return Ok(());
+    }
+
+    let last = states.pop().expect("import_gif devuelve al menos un frame");
+    run_simulation(last, 0, universe, options, scenario, export_options)
+}
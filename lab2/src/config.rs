@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Command};
+use toml::de::DeTable;
+
+use crate::scenario::line_column;
+
+/// Archivo de configuración que se usa si existe en la carpeta actual
+pub const DEFAULT_CONFIG_FILE: &str = "lab2.toml";
+/// Prefijo de las variables de entorno (`LAB2_SPEED` equivale a `--speed`)
+pub const ENV_PREFIX: &str = "LAB2_";
+/// Opción de la línea de comandos (y variable `LAB2_CONFIG`) que elige el archivo
+const CONFIG_ARG: &str = "config";
+
+/// De dónde salió el valor de una opción, de menor a mayor prioridad
+#[derive(Clone, Debug, PartialEq)]
+pub enum Origin {
+    Default,
+    File(PathBuf),
+    Env(String),
+    CommandLine,
+}
+
+impl Origin {
+    fn rank(&self) -> u8 {
+        match self {
+            Origin::Default => 0,
+            Origin::File(_) => 1,
+            Origin::Env(_) => 2,
+            Origin::CommandLine => 3,
+        }
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "predeterminado"),
+            Origin::File(path) => write!(f, "archivo {}", path.display()),
+            Origin::Env(name) => write!(f, "entorno {}", name),
+            Origin::CommandLine => write!(f, "línea de comandos"),
+        }
+    }
+}
+
+/// Opciones de la línea de comandos completadas con el archivo de
+/// configuración y las variables de entorno. Las capas, de menor a mayor
+/// prioridad: valores predeterminados < archivo < entorno < línea de comandos.
+pub struct Layers {
+    /// Argumentos finales: los del archivo y el entorno van antes que los
+    /// originales
+    pub args: Vec<OsString>,
+    /// Opciones completadas por el archivo o el entorno, por nombre largo
+    injected: BTreeMap<String, Origin>,
+    /// Archivo de configuración en uso, si hay uno
+    path: Option<PathBuf>,
+    /// Opciones del archivo o el entorno que se ignoraron por desconocidas o
+    /// inválidas, con dónde están
+    problems: Vec<String>,
+}
+
+impl Layers {
+    /// Completa `args` (con el nombre del programa primero) para `command`.
+    /// Solo se completan las opciones del comando principal que no se
+    /// dieron en la línea de comandos. Las opciones desconocidas o inválidas
+    /// del archivo o el entorno se saltan y se anotan en `problems`, para
+    /// que `config validate` pueda correr igual.
+    pub fn resolve(command: &Command, args: Vec<OsString>) -> Self {
+        // Si la línea de comandos es inválida, el error lo muestra el análisis final
+        let Ok(given) = command.clone().ignore_errors(true).try_get_matches_from(&args) else {
+            return Self { args, injected: BTreeMap::new(), path: None, problems: Vec::new() };
+        };
+        let find = |long: &str| command.get_arguments().find(|arg| arg.get_long() == Some(long));
+        let given_arg = |arg: &clap::Arg| given.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine);
+        // Opciones que no se pueden usar juntas (por ejemplo --fps y --speed)
+        let conflicting = |a: &clap::Arg, b: &clap::Arg| {
+            command.get_arg_conflicts_with(a).iter().any(|c| c.get_id() == b.get_id())
+                || command.get_arg_conflicts_with(b).iter().any(|c| c.get_id() == a.get_id())
+        };
+        // Una opción de la línea de comandos también tapa a las incompatibles con ella
+        let on_command_line = |long: &str| {
+            find(long).is_some_and(|arg| given_arg(arg) || command.get_arguments().any(|b| given_arg(b) && conflicting(arg, b)))
+        };
+
+        let options = options(command);
+
+        // Cada capa reemplaza los valores de la anterior
+        let mut values: BTreeMap<String, (Vec<String>, Origin)> = BTreeMap::new();
+        let mut problems = Vec::new();
+        let path = config_path(&given);
+        if let Some(path) = &path {
+            match read_file(path) {
+                Ok(file) => {
+                    for option in file {
+                        match check_option(command, &option.key, &option.values) {
+                            Ok(long) => {
+                                values.insert(long, (option.values, Origin::File(path.clone())));
+                            }
+                            Err(e) => problems.push(format!("{}:{}:{}: {}", path.display(), option.line, option.column, e)),
+                        }
+                    }
+                }
+                Err(e) => problems.push(e),
+            }
+        }
+        for (long, _) in &options {
+            let name = env_name(long);
+            if let Ok(value) = std::env::var(&name) {
+                match check_option(command, long, std::slice::from_ref(&value)) {
+                    Ok(_) => {
+                        values.insert(long.clone(), (vec![value], Origin::Env(name)));
+                    }
+                    Err(e) => problems.push(format!("{}: {}", name, e)),
+                }
+            }
+        }
+
+        let mut injected = BTreeMap::new();
+        let mut extra: Vec<OsString> = Vec::new();
+        // Entre dos opciones incompatibles de distintas capas queda la de la capa más alta
+        let overridden = |long: &str, origin: &Origin| {
+            values.iter().any(|(other, (_, other_origin))| {
+                other_origin.rank() > origin.rank()
+                    && find(long).zip(find(other)).is_some_and(|(a, b)| conflicting(a, b))
+            })
+        };
+        for (long, (value, origin)) in &values {
+            if on_command_line(long) || overridden(long, origin) {
+                continue;
+            }
+            let takes_values = options.iter().any(|(name, takes)| name == long && *takes);
+            if takes_values {
+                for v in value {
+                    extra.push(format!("--{}={}", long, v).into());
+                }
+            } else if value.iter().any(|v| is_true(v)) {
+                extra.push(format!("--{}", long).into());
+            }
+            injected.insert(long.clone(), origin.clone());
+        }
+
+        let mut args = args;
+        let tail = args.split_off(1.min(args.len()));
+        args.extend(extra);
+        args.extend(tail);
+        Self { args, injected, path, problems }
+    }
+
+    /// Archivo de configuración en uso (`--config`, `LAB2_CONFIG` o
+    /// `lab2.toml`), si hay uno
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Opciones desconocidas o inválidas del archivo o el entorno, que no se
+    /// aplicaron. Los subcomandos `config` corren igual, así se puede
+    /// revisar un archivo que no funciona; los demás deberían fallar.
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+
+    /// Origen de la opción `long` en la configuración final
+    pub fn origin(&self, matches: &ArgMatches, id: &str, long: &str) -> Origin {
+        match (self.injected.get(long), matches.value_source(id)) {
+            (Some(origin), _) => origin.clone(),
+            (None, Some(ValueSource::CommandLine)) => Origin::CommandLine,
+            (None, Some(ValueSource::EnvVariable)) => Origin::Env(env_name(long)),
+            _ => Origin::Default,
+        }
+    }
+
+    /// Una línea `opción = valor  # origen` por cada opción del comando
+    /// principal; sin `all`, solo las que no tienen el valor predeterminado
+    pub fn describe(&self, command: &Command, matches: &ArgMatches, all: bool) -> Vec<String> {
+        command
+            .get_arguments()
+            .filter_map(|arg| Some((arg.get_id().as_str(), arg.get_long()?)))
+            .filter(|&(_, long)| long != "help" && long != "version")
+            .filter_map(|(id, long)| {
+                let origin = self.origin(matches, id, long);
+                if !all && origin == Origin::Default {
+                    return None;
+                }
+                let value = matches
+                    .get_raw(id)
+                    .map(|raw| raw.map(|v| v.to_string_lossy().into_owned()).collect::<Vec<_>>().join(" "))
+                    .unwrap_or_else(|| "-".to_string());
+                Some(format!("{} = {}  # {}", long, value, origin))
+            })
+            .collect()
+    }
+}
+
+/// `--config`, `LAB2_CONFIG` o `lab2.toml` si existe
+fn config_path(given: &ArgMatches) -> Option<PathBuf> {
+    if let Ok(Some(path)) = given.try_get_one::<PathBuf>(CONFIG_ARG) {
+        return Some(path.clone());
+    }
+    if let Ok(path) = std::env::var(env_name(CONFIG_ARG)) {
+        return Some(PathBuf::from(path));
+    }
+    Path::new(DEFAULT_CONFIG_FILE).exists().then(|| PathBuf::from(DEFAULT_CONFIG_FILE))
+}
+
+/// Variable de entorno de una opción: `--max-output-size` es `LAB2_MAX_OUTPUT_SIZE`
+pub fn env_name(long: &str) -> String {
+    format!("{}{}", ENV_PREFIX, long.to_uppercase().replace('-', "_"))
+}
+
+/// Comprueba un archivo de configuración sin ejecutar nada: que sea TOML
+/// válido, que cada clave sea una opción del comando principal y que cada
+/// valor sea uno que la opción acepta. Devuelve cuántas opciones define o
+/// todos los errores, con `archivo:línea:columna`.
+pub fn validate_file(command: &Command, path: &Path) -> Result<usize, Vec<String>> {
+    let text = std::fs::read_to_string(path).map_err(|e| vec![format!("{}: {}", path.display(), e)])?;
+    validate_text(command, path, &text)
+}
+
+/// Como `validate_file`, con el contenido que tendría `path`
+pub fn validate_text(command: &Command, path: &Path, text: &str) -> Result<usize, Vec<String>> {
+    let file = parse_file(path, text).map_err(|e| vec![e])?;
+    let problems: Vec<String> = file
+        .iter()
+        .filter_map(|option| {
+            let e = check_option(command, &option.key, &option.values).err()?;
+            Some(format!("{}:{}:{}: {}", path.display(), option.line, option.column, e))
+        })
+        .collect();
+    if problems.is_empty() { Ok(file.len()) } else { Err(problems) }
+}
+
+/// Opciones del comando principal que se pueden fijar en el archivo o el
+/// entorno, con si llevan valor
+fn options(command: &Command) -> Vec<(String, bool)> {
+    command
+        .get_arguments()
+        .filter_map(|arg| Some((arg.get_long()?.to_string(), arg.get_action().takes_values())))
+        .filter(|(long, _)| long != CONFIG_ARG && long != "help" && long != "version")
+        .collect()
+}
+
+/// Comprueba que `key` (con `-` o `_`) sea una opción del comando principal
+/// y que acepte `values`, como si se hubieran dado en la línea de comandos.
+/// Devuelve el nombre largo de la opción.
+fn check_option(command: &Command, key: &str, values: &[String]) -> Result<String, String> {
+    let long = key.replace('_', "-");
+    let Some(&(_, takes_values)) = options(command).iter().find(|(name, _)| *name == long) else {
+        return Err(format!("opción desconocida '{}'", key));
+    };
+    if !takes_values {
+        return match values.iter().find(|v| !is_true(v) && !is_false(v)) {
+            Some(value) => Err(format!("se esperaba true o false para {}, no '{}'", key, value)),
+            None => Ok(long),
+        };
+    }
+    for value in values {
+        let args = [command.get_name().to_string(), format!("--{}={}", long, value)];
+        if let Err(e) = command.clone().try_get_matches_from(args)
+            && matches!(e.kind(), ErrorKind::InvalidValue | ErrorKind::ValueValidation)
+        {
+            let message = e.to_string();
+            let first = message.lines().next().unwrap_or_default();
+            return Err(first.trim_start_matches("error: ").to_string());
+        }
+    }
+    Ok(long)
+}
+
+/// Una opción del archivo de configuración
+struct FileOption {
+    key: String,
+    /// Valores como texto, igual que en la línea de comandos. Las listas
+    /// repiten la opción.
+    values: Vec<String>,
+    /// Dónde está la clave, desde 1
+    line: usize,
+    column: usize,
+}
+
+/// Lee las opciones del archivo TOML. Los errores de formato indican
+/// `archivo:línea:columna`.
+fn read_file(path: &Path) -> Result<Vec<FileOption>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse_file(path, &text)
+}
+
+fn parse_file(path: &Path, text: &str) -> Result<Vec<FileOption>, String> {
+    let at = |offset: usize| {
+        let (line, column) = line_column(text, offset);
+        format!("{}:{}:{}", path.display(), line, column)
+    };
+    let spans = DeTable::parse(text).map_err(|e| format!("{}: {}", at(e.span().map_or(0, |span| span.start)), e.message().trim()))?;
+    let table: toml::Table = toml::from_str(text).map_err(|e| format!("{}: {}", path.display(), e.message().trim()))?;
+    table
+        .iter()
+        .map(|(key, value)| {
+            let offset = spans.get_ref().iter().find(|(k, _)| k.get_ref() == key).map_or(0, |(k, _)| k.span().start);
+            let scalar = |value: &toml::Value| match value {
+                toml::Value::String(s) => Ok(s.clone()),
+                toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => Ok(value.to_string()),
+                _ => Err(format!("{}: valor inválido para '{}'", at(offset), key)),
+            };
+            let values = match value {
+                toml::Value::Array(items) => items.iter().map(scalar).collect::<Result<_, _>>()?,
+                value => vec![scalar(value)?],
+            };
+            let (line, column) = line_column(text, offset);
+            Ok(FileOption { key: key.clone(), values, line, column })
+        })
+        .collect()
+}
+
+fn is_true(value: &str) -> bool {
+    matches!(value.to_ascii_lowercase().as_str(), "true" | "1" | "yes" | "on")
+}
+
+fn is_false(value: &str) -> bool {
+    matches!(value.to_ascii_lowercase().as_str(), "false" | "0" | "no" | "off" | "")
+}
@@ -0,0 +1,142 @@
+use crate::simulation::Simulation;
+use crate::{CellState, GameOfLife};
+
+/// Reglas de dos capas acopladas. El siguiente estado de una célula depende de
+/// sus vecinos vivos en su propia capa y de la célula correspondiente en la
+/// otra capa.
+#[derive(Clone, Debug)]
+pub struct CoupledRule {
+    /// `table[capa][viva][otra viva]` contiene los números de vecinos con los
+    /// que la célula queda viva en la siguiente generación
+    table: [[[[bool; 9]; 2]; 2]; 2],
+}
+
+impl CoupledRule {
+    /// Construye la regla a partir de las cantidades de vecinos que dejan
+    /// viva a la célula, en el orden `[capa][viva][otra viva]`
+    pub fn from_counts(counts: [[[&[usize]; 2]; 2]; 2]) -> Self {
+        let mut table = [[[[false; 9]; 2]; 2]; 2];
+        for layer in 0..2 {
+            for alive in 0..2 {
+                for other in 0..2 {
+                    for &n in counts[layer][alive][other] {
+                        table[layer][alive][other][n] = true;
+                    }
+                }
+            }
+        }
+        Self { table }
+    }
+
+    /// Dos universos de Conway que no se afectan entre sí
+    pub fn independent() -> Self {
+        let conway = [[&[3][..], &[3]], [&[2, 3], &[2, 3]]];
+        Self::from_counts([conway, conway])
+    }
+
+    /// Capa 0 = presas, capa 1 = depredadores. Las presas siguen a Conway,
+    /// pero no nacen donde hay un depredador. Los depredadores solo viven
+    /// sobre presas: ahí nacen con 1 vecino y sobreviven con 0 a 3; sin
+    /// presa debajo mueren de hambre.
+    pub fn predator_prey() -> Self {
+        Self::from_counts([
+            [[&[3], &[]], [&[2, 3], &[2, 3]]],
+            [[&[], &[1]], [&[], &[0, 1, 2, 3]]],
+        ])
+    }
+
+    pub fn next_state(&self, layer: usize, current: CellState, other: CellState, neighbors: usize) -> CellState {
+        let alive = (current == CellState::Alive) as usize;
+        let other_alive = (other == CellState::Alive) as usize;
+        if self.table[layer][alive][other_alive][neighbors] {
+            CellState::Alive
+        } else {
+            CellState::Dead
+        }
+    }
+}
+
+/// Cómo se muestran las dos capas en la ventana y en el GIF
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum LayerView {
+    /// Ambas capas superpuestas: verde, rojo o amarillo si coinciden
+    Blend,
+    /// Capa 0 a la izquierda y capa 1 a la derecha
+    SideBySide,
+}
+
+/// Dos grids del mismo tamaño que evolucionan juntos
+pub struct CoupledLife {
+    layers: [GameOfLife; 2],
+    rule: CoupledRule,
+    view: LayerView,
+}
+
+impl CoupledLife {
+    pub fn new(width: usize, height: usize, rule: CoupledRule, view: LayerView) -> Self {
+        Self {
+            layers: [GameOfLife::new(width, height), GameOfLife::new(width, height)],
+            rule,
+            view,
+        }
+    }
+
+    /// Llena cada capa con células aleatorias con la probabilidad indicada
+    pub fn randomize(&mut self, densities: [f64; 2]) {
+        for (layer, density) in self.layers.iter_mut().zip(densities) {
+            layer.clear_grid();
+            layer.add_random_cells(density);
+        }
+    }
+}
+
+impl Simulation for CoupledLife {
+    fn width(&self) -> usize {
+        match self.view {
+            LayerView::Blend => self.layers[0].width(),
+            LayerView::SideBySide => self.layers[0].width() * 2 + 1, // Columna separadora
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.layers[0].height()
+    }
+
+    fn step(&mut self) {
+        let mut next = self.layers.clone();
+        for (layer, next_layer) in next.iter_mut().enumerate() {
+            let (own, other) = (&self.layers[layer], &self.layers[1 - layer]);
+            for y in 0..own.height() {
+                for x in 0..own.width() {
+                    let neighbors = own.count_live_neighbors(x, y);
+                    let state = self.rule.next_state(layer, own.grid.get(x, y), other.grid.get(x, y), neighbors);
+                    next_layer.set_cell(x, y, state);
+                }
+            }
+        }
+        self.layers = next;
+    }
+
+    fn palette(&self) -> Vec<[u8; 3]> {
+        vec![
+            crate::DEAD_RGB,
+            [0x30, 0xD0, 0x50], // Capa 0
+            [0xE0, 0x30, 0x30], // Capa 1
+            [0xF0, 0xE0, 0x40], // Ambas capas
+            [0x80, 0x80, 0x80], // Separador
+        ]
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        let width = self.layers[0].width();
+        match self.view {
+            LayerView::Blend => {
+                self.layers[0].is_alive(x, y) as u8 | (self.layers[1].is_alive(x, y) as u8) << 1
+            }
+            LayerView::SideBySide if x < width => self.layers[0].is_alive(x, y) as u8,
+            LayerView::SideBySide if x == width => 4,
+            LayerView::SideBySide => (self.layers[1].is_alive(x - width - 1, y) as u8) * 2,
+        }
+    }
+}
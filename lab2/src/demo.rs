@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use crate::brians_brain::BriansBrain;
+use crate::reaction_diffusion::{GrayScottPreset, ReactionDiffusion};
+use crate::simulation::Simulation;
+use crate::{GameOfLife, KnownPattern};
+
+/// Tiempo que se muestra cada escena antes de pasar a la siguiente
+pub const SCENE_DURATION: Duration = Duration::from_secs(10);
+
+/// Cañón de Gosper, el primer patrón conocido que crece sin límite
+const GOSPER_GUN: &str = "\
+x = 36, y = 9, rule = B3/S23
+24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bo
+bo$10bo5bo7bo$11bo3bo$12b2o!";
+
+/// Replicador de HighLife: cada 12 generaciones aparecen dos copias de él
+const REPLICATOR: &str = "\
+x = 5, y = 5, rule = B36/S23
+2b3o$bo2bo$o3bo$o2bo$3o!";
+
+/// Separación entre las esquinas de los púlsares (cada uno ocupa 13×13)
+const PULSAR_SPACING: usize = 18;
+
+/// Una escena de `lab2 demo`: un autómata ya preparado y el texto que se
+/// muestra mientras corre
+pub struct Scene {
+    pub title: &'static str,
+    pub caption: &'static str,
+    build: fn(usize, usize) -> Box<dyn Simulation>,
+}
+
+impl Scene {
+    /// Prepara la escena en un universo de `width`×`height`
+    pub fn build(&self, width: usize, height: usize) -> Box<dyn Simulation> {
+        (self.build)(width, height)
+    }
+}
+
+/// Las escenas de la demostración, en el orden en que se muestran
+pub const SCENES: [Scene; 5] = [
+    Scene {
+        title: "Cañón de Gosper",
+        caption: "dispara un planeador cada 30 generaciones",
+        build: gosper_gun,
+    },
+    Scene {
+        title: "Púlsares",
+        caption: "osciladores de período 3",
+        build: pulsars,
+    },
+    Scene {
+        title: "HighLife B36/S23",
+        caption: "el replicador se copia a sí mismo",
+        build: replicator,
+    },
+    Scene {
+        title: "Brian's Brain",
+        caption: "encendida, moribunda, apagada: nada se queda quieto",
+        build: brians_brain,
+    },
+    Scene {
+        title: "Gray-Scott",
+        caption: "reacción-difusión de dos sustancias",
+        build: gray_scott,
+    },
+];
+
+/// Estampa un patrón RLE con su centro en (`x`, `y`)
+fn place_centered(game: &mut GameOfLife, rle: &str, x: usize, y: usize) {
+    let pattern = crate::rle::RlePattern::parse(rle).expect("los patrones de la demostración son válidos");
+    if let Some(rule) = pattern.rule {
+        game.set_rule(rule);
+    }
+    pattern.stamp(game, x.saturating_sub(pattern.width / 2), y.saturating_sub(pattern.height / 2));
+}
+
+fn gosper_gun(width: usize, height: usize) -> Box<dyn Simulation> {
+    let mut game = GameOfLife::new(width, height);
+    // Arriba a la izquierda, para que los planeadores crucen todo el universo
+    game.place_rle(2, 2, GOSPER_GUN).expect("el cañón de Gosper es un RLE válido");
+    Box::new(game)
+}
+
+fn pulsars(width: usize, height: usize) -> Box<dyn Simulation> {
+    let mut game = GameOfLife::new(width, height);
+    let (columns, rows) = ((width / PULSAR_SPACING).max(1), (height / PULSAR_SPACING).max(1));
+    // Centra la cuadrícula de púlsares en el universo
+    let left = (width - (columns - 1) * PULSAR_SPACING).saturating_sub(13) / 2;
+    let top = (height - (rows - 1) * PULSAR_SPACING).saturating_sub(13) / 2;
+    for row in 0..rows {
+        for column in 0..columns {
+            KnownPattern::Pulsar.stamp(&mut game, left + column * PULSAR_SPACING, top + row * PULSAR_SPACING);
+        }
+    }
+    Box::new(game)
+}
+
+fn replicator(width: usize, height: usize) -> Box<dyn Simulation> {
+    let mut game = GameOfLife::new(width, height);
+    // La regla B36/S23 sale de la cabecera del RLE
+    place_centered(&mut game, REPLICATOR, width / 2, height / 2);
+    Box::new(game)
+}
+
+fn brians_brain(width: usize, height: usize) -> Box<dyn Simulation> {
+    let mut brain = BriansBrain::new(width, height);
+    brain.randomize(0.1, &mut rand::thread_rng());
+    Box::new(brain)
+}
+
+fn gray_scott(width: usize, height: usize) -> Box<dyn Simulation> {
+    let mut reaction = ReactionDiffusion::new(width, height, GrayScottPreset::Coral.params(), 10);
+    reaction.seed_squares(12, 6);
+    Box::new(reaction)
+}
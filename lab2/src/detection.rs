@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+
+use crate::cancel::{CancelToken, Cancelled};
+use crate::{CellState, GameOfLife};
+
+/// Momento en que una evolución entra en un ciclo
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cycle {
+    /// Generaciones antes de entrar en el ciclo (el transitorio)
+    pub transient: usize,
+    /// Generaciones que dura el ciclo (1 si el estado queda fijo)
+    pub period: usize,
+    /// Cuánto se desplaza el patrón en cada vuelta del ciclo
+    pub displacement: (i64, i64),
+    /// Células vivas al entrar en el ciclo
+    pub population: usize,
+}
+
+impl Cycle {
+    /// Descripción del ciclo, por ejemplo "nave, período 4, velocidad c/2"
+    pub fn describe(&self) -> String {
+        let (dx, dy) = self.displacement;
+        if self.population == 0 {
+            "extinción".to_string()
+        } else if (dx, dy) != (0, 0) {
+            let direction = match (dx, dy) {
+                (_, 0) | (0, _) => "ortogonal",
+                _ if dx.abs() == dy.abs() => "diagonal",
+                _ => "oblicua",
+            };
+            format!("nave, período {}, velocidad {} {}", self.period, speed(dx.abs().max(dy.abs()) as usize, self.period), direction)
+        } else if self.period == 1 {
+            "vida fija".to_string()
+        } else {
+            format!("oscilador, período {}", self.period)
+        }
+    }
+}
+
+/// Velocidad en la notación habitual: `c/2`, `2c/5`, `c`
+fn speed(cells: usize, period: usize) -> String {
+    let gcd = (1..=cells.min(period)).rev().find(|&d| cells.is_multiple_of(d) && period.is_multiple_of(d)).unwrap_or(1);
+    let (cells, period) = (cells / gcd, period / gcd);
+    match (cells, period) {
+        (1, 1) => "c".to_string(),
+        (1, _) => format!("c/{}", period),
+        (_, 1) => format!("{}c", cells),
+        _ => format!("{}c/{}", cells, period),
+    }
+}
+
+/// Forma canónica de un estado: el hash de las células dentro de su caja
+/// envolvente, sin importar dónde esté la caja, y la esquina de la caja.
+/// Así un patrón que solo se trasladó tiene el mismo hash.
+fn canonical(game: &GameOfLife) -> (u64, (i64, i64)) {
+    let alive = |x: usize, y: usize| game.grid.is_alive(x, y);
+    let rows: Vec<usize> = (0..game.height()).filter(|&y| (0..game.width()).any(|x| alive(x, y))).collect();
+    let columns: Vec<usize> = (0..game.width()).filter(|&x| rows.iter().any(|&y| alive(x, y))).collect();
+    let (Some(&min_x), Some(&max_x), Some(&min_y), Some(&max_y)) =
+        (columns.first(), columns.last(), rows.first(), rows.last())
+    else {
+        return (0, (0, 0));
+    };
+
+    // FNV-1a, igual que `state_hash`, más el tamaño de la caja
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for value in [max_x - min_x, max_y - min_y] {
+        hash ^= value as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            hash ^= alive(x, y) as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    (hash, (min_x as i64, min_y as i64))
+}
+
+/// Estado al que llegó una simulación en curso que se quedó quieta o
+/// repite un ciclo corto
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stable {
+    /// Generaciones del ciclo (1 si el estado quedó fijo)
+    pub period: usize,
+    /// Generación en que empezó el ciclo
+    pub since: u64,
+    pub population: usize,
+}
+
+impl Stable {
+    /// Descripción para mostrar, por ejemplo "oscilador de período 2
+    /// desde la generación 140"
+    pub fn describe(&self) -> String {
+        let state = match (self.population, self.period) {
+            (0, _) => "sin células vivas".to_string(),
+            (_, 1) => "fijo".to_string(),
+            (_, period) => format!("en un ciclo de período {}", period),
+        };
+        format!("{} desde la generación {}", state, self.since)
+    }
+}
+
+/// Vigila una simulación generación por generación y avisa cuando el
+/// estado es igual a alguno de los últimos `max_period`: el universo quedó
+/// fijo o repite un ciclo corto, y desde ahí ya no va a cambiar. A
+/// diferencia de `find_cycle`, compara posiciones exactas (una nave que
+/// viaja no cuenta) y no hace avanzar copias del juego.
+#[derive(Clone, Debug)]
+pub struct StabilityDetector {
+    max_period: usize,
+    /// Hashes de las últimas generaciones, del más viejo al más nuevo
+    recent: VecDeque<(u64, u64)>,
+    /// Ya se avisó del ciclo actual
+    reported: bool,
+}
+
+impl StabilityDetector {
+    pub fn new(max_period: usize) -> Self {
+        Self { max_period: max_period.max(1), recent: VecDeque::new(), reported: false }
+    }
+
+    /// Registra `game` como la generación `generation`. Devuelve el ciclo
+    /// la primera vez que el estado se repite; mientras siga en el mismo
+    /// ciclo no vuelve a avisar, y si algo lo saca del ciclo (por ejemplo
+    /// pintar células) vuelve a vigilar.
+    pub fn observe(&mut self, game: &GameOfLife, generation: u64) -> Option<Stable> {
+        let hash = game.state_hash();
+        let repeated = self.recent.iter().rev().position(|&(h, _)| h == hash);
+        if self.recent.len() == self.max_period {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((hash, generation));
+        let Some(back) = repeated else {
+            self.reported = false;
+            return None;
+        };
+        if self.reported {
+            return None;
+        }
+        self.reported = true;
+        let period = back + 1;
+        let population = game.cells().iter().filter(|&&c| c == CellState::Alive).count();
+        Some(Stable { period, since: generation - period as u64, population })
+    }
+}
+
+/// Detecta el ciclo en el que termina la evolución de `game` con el
+/// algoritmo de Brent, comparando hashes de estado. Solo guarda dos copias
+/// del juego, así que sirve para períodos y transitorios muy largos.
+/// Los estados se comparan sin importar su posición, de modo que una nave
+/// también cuenta como ciclo (con desplazamiento).
+/// Devuelve `None` si no encuentra un ciclo cuyo transitorio más período
+/// quepa en `limit` generaciones.
+pub fn find_cycle(game: &GameOfLife, limit: usize) -> Option<Cycle> {
+    find_cycle_cancellable(game, limit, &CancelToken::new()).ok().flatten()
+}
+
+/// Como `find_cycle`, revisando `cancel` en cada generación
+pub fn find_cycle_cancellable(game: &GameOfLife, limit: usize, cancel: &CancelToken) -> Result<Option<Cycle>, Cancelled> {
+    let hash = |game: &GameOfLife| canonical(game).0;
+
+    // Fase 1: la liebre avanza y la tortuga salta a su posición en cada
+    // potencia de dos, hasta que la liebre la alcanza. La distancia es el período.
+    // La liebre da como mucho unas 3 veces (transitorio + período) pasos.
+    let max_steps = limit.saturating_mul(3);
+    let mut tortoise = hash(game);
+    let mut hare = game.clone();
+    hare.next_generation();
+    let (mut power, mut period, mut steps) = (1, 1, 1);
+    while hash(&hare) != tortoise {
+        if power == period {
+            tortoise = hash(&hare);
+            power *= 2;
+            period = 0;
+        }
+        if steps >= max_steps {
+            return Ok(None);
+        }
+        cancel.check()?;
+        hare.next_generation();
+        period += 1;
+        steps += 1;
+    }
+
+    // Fase 2: con una ventaja de `period` generaciones, las dos copias se
+    // encuentran justo donde empieza el ciclo
+    let mut tortoise = game.clone();
+    let mut hare = game.clone();
+    for _ in 0..period {
+        hare.next_generation();
+    }
+    let mut transient = 0;
+    while hash(&tortoise) != hash(&hare) {
+        cancel.check()?;
+        tortoise.next_generation();
+        hare.next_generation();
+        transient += 1;
+    }
+
+    let ((_, start), (_, end)) = (canonical(&tortoise), canonical(&hare));
+    let displacement = (end.0 - start.0, end.1 - start.1);
+    let population = tortoise.cells().iter().filter(|&&c| c == CellState::Alive).count();
+    Ok((transient + period <= limit).then_some(Cycle { transient, period, displacement, population }))
+}
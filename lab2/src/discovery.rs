@@ -0,0 +1,50 @@
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+use crate::GameOfLife;
+use crate::favorites::Favorite;
+use crate::rule::Rule;
+
+/// Modo descubrimiento: salta a reglas al azar, cada una con una sopa
+/// nueva, para recorrer el espacio de reglas a ojo y quedarse con las que
+/// hacen algo interesante
+pub struct Discovery {
+    rng: StdRng,
+    current: Option<Favorite>,
+}
+
+impl Discovery {
+    pub fn new() -> Self {
+        Self { rng: StdRng::from_entropy(), current: None }
+    }
+
+    /// Cambia `game` a una regla al azar y lo llena con una sopa nueva de
+    /// `density`. Devuelve lo necesario para repetirla.
+    pub fn reroll(&mut self, game: &mut GameOfLife, density: f64) -> &Favorite {
+        let rule = Rule::random(&mut self.rng);
+        let seed = self.rng.next_u64();
+        game.set_rule(rule);
+        game.initialize_with_seed(density, seed);
+        game.set_seed(seed);
+        self.current.insert(Favorite {
+            name: String::new(),
+            rule: rule.to_string(),
+            seed,
+            density,
+            width: game.width(),
+            height: game.height(),
+            scenario: None,
+        })
+    }
+
+    /// La última regla elegida al azar, si ya hubo una
+    pub fn current(&self) -> Option<&Favorite> {
+        self.current.as_ref()
+    }
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
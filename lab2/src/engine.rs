@@ -0,0 +1,81 @@
+use crate::cancel::CancelToken;
+use crate::progress::{self, Progress, ProgressSink};
+use crate::simulation::Simulation;
+use crate::{CellState, GameOfLife};
+
+/// Motor que hace evolucionar un universo del Juego de la Vida. Todos se
+/// pueden mostrar y exportar como cualquier `Simulation`; la región que se
+/// ve es la de `width`×`height` células que empieza en (0, 0), salvo en
+/// `SparseLife`, donde la elige su `Viewport`. Las coordenadas de estos
+/// métodos son las de esa región.
+pub trait Engine: Simulation {
+    fn is_alive(&self, x: usize, y: usize) -> bool;
+    fn set_cell(&mut self, x: usize, y: usize, state: CellState);
+    /// Avanza `generations` generaciones de una vez
+    fn advance(&mut self, generations: u64);
+    /// Como `advance`, pero se detiene pronto si se cancela `cancel`.
+    /// Devuelve las generaciones que llegó a avanzar.
+    fn advance_cancellable(&mut self, generations: u64, cancel: &CancelToken) -> u64 {
+        for done in 0..generations {
+            if cancel.is_cancelled() {
+                return done;
+            }
+            self.advance(1);
+        }
+        generations
+    }
+    /// Como `advance`, en tramos de alrededor del 1% que se informan a
+    /// `sink` con la población después de cada uno
+    fn advance_with_progress(&mut self, generations: u64, sink: &mut dyn ProgressSink) {
+        let interval = progress::report_interval(generations);
+        let mut done = 0;
+        while done < generations {
+            let chunk = interval.min(generations - done);
+            self.advance(chunk);
+            done += chunk;
+            sink.report(&Progress::new(done, generations).with_metric("población", self.population() as f64));
+        }
+    }
+    /// Células vivas en todo el universo (no solo en la región que se ve)
+    fn population(&self) -> u64;
+}
+
+impl Engine for GameOfLife {
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y) == CellState::Alive
+    }
+
+    fn set_cell(&mut self, x: usize, y: usize, state: CellState) {
+        GameOfLife::set_cell(self, x, y, state);
+    }
+
+    fn advance(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.next_generation();
+        }
+    }
+
+    fn population(&self) -> u64 {
+        crate::stats::population(self) as u64
+    }
+}
+
+/// Motores que se pueden elegir desde la línea de comandos
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum EngineKind {
+    /// El grid de siempre: recorre todas las células en cada generación
+    #[default]
+    Naive,
+    /// Quadtree con memoización: salta miles de generaciones en universos
+    /// enormes si el patrón se repite en el espacio o en el tiempo
+    Hashlife,
+    /// Solo las coordenadas de las células vivas, en un mundo sin bordes:
+    /// los planeadores viajan para siempre
+    Sparse,
+    /// Mide cuánto tarda cada generación y elige solo entre el grid célula
+    /// por célula, el de bits en un hilo o en todos y el disperso, y cuántas
+    /// generaciones avanzar por frame; vuelve a elegir si la densidad cambia
+    /// mucho. El universo tiene bordes, como con naive.
+    Auto,
+}
@@ -0,0 +1,220 @@
+use rand::Rng;
+use rand::rngs::ThreadRng;
+
+use crate::simulation::Simulation;
+
+/// Radio (en celdas) del pincel con el que se pintan elementos
+const BRUSH_RADIUS: i32 = 2;
+/// Generaciones que dura una llama como máximo
+const FIRE_LIFETIME: u8 = 30;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Element {
+    Empty,
+    Sand,
+    Water,
+    Wall,
+    Fire,
+}
+
+/// Autómata de arena que cae. A diferencia del Juego de la Vida, las celdas
+/// se actualizan en orden (de abajo hacia arriba, alternando la dirección
+/// horizontal en cada paso) y cada partícula se mueve como máximo una vez
+/// por paso, así que el resultado depende del orden de actualización.
+pub struct FallingSand {
+    width: usize,
+    height: usize,
+    cells: Vec<Element>,
+    /// Vida restante de cada llama
+    heat: Vec<u8>,
+    /// Marca las partículas que ya se movieron en el paso actual
+    moved: Vec<bool>,
+    brush: Element,
+    left_to_right: bool,
+    rng: ThreadRng,
+}
+
+impl FallingSand {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Element::Empty; width * height],
+            heat: vec![0; width * height],
+            moved: vec![false; width * height],
+            brush: Element::Sand,
+            left_to_right: true,
+            rng: rand::thread_rng(),
+        }
+    }
+
+    /// Escena inicial: suelo, dos rampas, un montón de arena y un charco
+    pub fn add_demo_scene(&mut self) {
+        let (w, h) = (self.width, self.height);
+        for x in 0..w {
+            self.set(x, h - 1, Element::Wall);
+        }
+        for i in 0..w / 3 {
+            self.set(w / 10 + i, h / 3 + i / 2, Element::Wall);
+            self.set(w - w / 10 - i, h * 2 / 3 - i / 2, Element::Wall);
+        }
+        for y in 2..h / 5 {
+            for x in w / 6..w / 3 {
+                self.set(x, y, Element::Sand);
+            }
+            for x in w * 2 / 3..w * 5 / 6 {
+                self.set(x, y, Element::Water);
+            }
+        }
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<Element> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        Some(self.cells[self.index(x as usize, y as usize)])
+    }
+
+    fn set(&mut self, x: usize, y: usize, element: Element) {
+        let i = self.index(x, y);
+        self.cells[i] = element;
+        self.heat[i] = if element == Element::Fire { FIRE_LIFETIME } else { 0 };
+    }
+
+    /// Intercambia dos celdas y marca la partícula movida
+    fn swap(&mut self, x: usize, y: usize, to_x: i32, to_y: i32) {
+        let from = self.index(x, y);
+        let to = self.index(to_x as usize, to_y as usize);
+        self.cells.swap(from, to);
+        self.heat.swap(from, to);
+        self.moved[to] = true;
+    }
+
+    /// Intenta mover la partícula a la primera posición libre de `targets`
+    /// cuyo contenido pueda desplazar
+    fn try_move(&mut self, x: usize, y: usize, targets: &[(i32, i32)], displaces: &[Element]) -> bool {
+        for &(dx, dy) in targets {
+            let (tx, ty) = (x as i32 + dx, y as i32 + dy);
+            if let Some(target) = self.get(tx, ty)
+                && displaces.contains(&target)
+                && !self.moved[self.index(tx as usize, ty as usize)]
+            {
+                self.swap(x, y, tx, ty);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Orden aleatorio para los dos lados, para que los montones sean simétricos
+    fn sides(&mut self) -> [i32; 2] {
+        if self.rng.gen_bool(0.5) { [-1, 1] } else { [1, -1] }
+    }
+
+    fn update_sand(&mut self, x: usize, y: usize) {
+        let [a, b] = self.sides();
+        self.try_move(x, y, &[(0, 1), (a, 1), (b, 1)], &[Element::Empty, Element::Water]);
+    }
+
+    fn update_water(&mut self, x: usize, y: usize) {
+        let [a, b] = self.sides();
+        self.try_move(x, y, &[(0, 1), (a, 1), (b, 1), (a, 0), (b, 0)], &[Element::Empty]);
+    }
+
+    fn update_fire(&mut self, x: usize, y: usize) {
+        let i = self.index(x, y);
+        let touches_water = [(0, 1), (0, -1), (1, 0), (-1, 0)]
+            .iter()
+            .any(|&(dx, dy)| self.get(x as i32 + dx, y as i32 + dy) == Some(Element::Water));
+        if touches_water || self.heat[i] <= 1 {
+            self.set(x, y, Element::Empty);
+            return;
+        }
+
+        self.heat[i] -= self.rng.gen_range(1..=2).min(self.heat[i] - 1);
+        let drift = self.rng.gen_range(-1..=1);
+        self.try_move(x, y, &[(drift, -1), (0, -1)], &[Element::Empty]);
+    }
+}
+
+impl Simulation for FallingSand {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn step(&mut self) {
+        self.moved.iter_mut().for_each(|m| *m = false);
+
+        for y in (0..self.height).rev() {
+            for i in 0..self.width {
+                let x = if self.left_to_right { i } else { self.width - 1 - i };
+                if self.moved[self.index(x, y)] {
+                    continue;
+                }
+                match self.cells[self.index(x, y)] {
+                    Element::Sand => self.update_sand(x, y),
+                    Element::Water => self.update_water(x, y),
+                    Element::Fire => self.update_fire(x, y),
+                    Element::Empty | Element::Wall => {}
+                }
+            }
+        }
+
+        self.left_to_right = !self.left_to_right;
+    }
+
+    fn palette(&self) -> Vec<[u8; 3]> {
+        vec![
+            crate::DEAD_RGB,    // Vacío
+            [0xE0, 0xC0, 0x70], // Arena
+            [0x30, 0x70, 0xE0], // Agua
+            [0x80, 0x80, 0x80], // Pared
+            [0xFF, 0xD0, 0x40], // Fuego reciente
+            [0xE0, 0x40, 0x10], // Fuego apagándose
+        ]
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        let i = self.index(x, y);
+        match self.cells[i] {
+            Element::Empty => 0,
+            Element::Sand => 1,
+            Element::Water => 2,
+            Element::Wall => 3,
+            Element::Fire if self.heat[i] > FIRE_LIFETIME / 2 => 4,
+            Element::Fire => 5,
+        }
+    }
+
+    /// Pinta el elemento actual en un círculo alrededor de (x, y)
+    fn paint(&mut self, x: usize, y: usize) {
+        for dy in -BRUSH_RADIUS..=BRUSH_RADIUS {
+            for dx in -BRUSH_RADIUS..=BRUSH_RADIUS {
+                let (px, py) = (x as i32 + dx, y as i32 + dy);
+                if dx * dx + dy * dy <= BRUSH_RADIUS * BRUSH_RADIUS && self.get(px, py).is_some() {
+                    self.set(px as usize, py as usize, self.brush);
+                }
+            }
+        }
+    }
+
+    /// 1 arena, 2 agua, 3 pared, 4 fuego, 0 borrar
+    fn select_brush(&mut self, digit: u8) {
+        self.brush = match digit {
+            1 => Element::Sand,
+            2 => Element::Water,
+            3 => Element::Wall,
+            4 => Element::Fire,
+            0 => Element::Empty,
+            _ => self.brush,
+        };
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::rule::Rule;
+
+/// Archivo donde se guardan los favoritos si no se elige otro
+pub const FAVORITES_FILE: &str = "favoritos.json";
+
+/// Regla y sopa inicial que vale la pena volver a ver: con la misma regla,
+/// semilla, densidad y tamaño el universo se repite exacto
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Favorite {
+    /// Nombre con el que se carga con `--favorite`; si está vacío, `append`
+    /// le pone uno
+    #[serde(default)]
+    pub name: String,
+    /// Regla en notación B/S
+    pub rule: String,
+    pub seed: u64,
+    pub density: f64,
+    pub width: usize,
+    pub height: usize,
+    /// Guion (`--scenario`) con el que se vio, si hubo uno
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scenario: Option<PathBuf>,
+}
+
+impl Favorite {
+    pub fn rule(&self) -> Result<Rule, String> {
+        Rule::parse(&self.rule)
+    }
+
+    /// Opciones de la línea de comandos que repiten este universo
+    pub fn command_line(&self) -> String {
+        let mut line = format!(
+            "--rule {} --seed {} --density {} --width {} --height {}",
+            self.rule, self.seed, self.density, self.width, self.height
+        );
+        if let Some(scenario) = &self.scenario {
+            line += &format!(" --scenario {}", scenario.display());
+        }
+        line
+    }
+}
+
+/// Los favoritos guardados en `path`; ninguno si el archivo no existe
+pub fn load(path: &Path) -> Result<Vec<Favorite>, Box<dyn std::error::Error>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(serde_json::from_str(&text).map_err(|e| format!("{} no es válido: {}", path.display(), e))?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// El favorito llamado `name` de los guardados en `path`
+pub fn find(path: &Path, name: &str) -> Result<Favorite, Box<dyn std::error::Error>> {
+    let favorites = load(path)?;
+    let names: Vec<&str> = favorites.iter().map(|f| f.name.as_str()).collect();
+    let error = format!("no existe el favorito '{}' en {} (hay: {})", name, path.display(), names.join(", "));
+    favorites.iter().find(|f| f.name == name).cloned().ok_or_else(|| error.into())
+}
+
+/// Agrega `favorite` al final de los guardados en `path` y devuelve su
+/// nombre. Sin nombre se llama `favorito-N`, con el primer N libre; un
+/// nombre que ya existe es un error.
+pub fn append(path: &Path, favorite: &Favorite) -> Result<String, Box<dyn std::error::Error>> {
+    let mut favorites = load(path)?;
+    let taken = |name: &str| favorites.iter().any(|f| f.name == name);
+    let name = if favorite.name.is_empty() {
+        (favorites.len() + 1..).map(|n| format!("favorito-{}", n)).find(|name| !taken(name)).unwrap_or_default()
+    } else if taken(&favorite.name) {
+        return Err(format!("ya hay un favorito llamado '{}' en {}", favorite.name, path.display()).into());
+    } else {
+        favorite.name.clone()
+    };
+    favorites.push(Favorite { name: name.clone(), ..favorite.clone() });
+    std::fs::write(path, serde_json::to_string_pretty(&favorites)?)?;
+    Ok(name)
+}
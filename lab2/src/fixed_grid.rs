@@ -0,0 +1,64 @@
+use crate::CellState;
+use crate::grid::Grid;
+
+/// Grid de `W`×`H` células con el tamaño fijo en el tipo: es un arreglo en
+/// la pila y no reserva memoria en el heap, ni al avanzar (la generación
+/// siguiente es otra copia del arreglo). Sirve para probar las reglas con
+/// muchos grids pequeños sin el costo de crear un `GameOfLife` para cada
+/// uno.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct FixedGrid<const W: usize, const H: usize> {
+    cells: [[bool; W]; H],
+}
+
+impl<const W: usize, const H: usize> FixedGrid<W, H> {
+    /// Grid de células muertas
+    pub const fn new() -> Self {
+        Self { cells: [[false; W]; H] }
+    }
+
+    /// Grid con vivas las células de `alive` (las que caen fuera se
+    /// ignoran)
+    pub fn from_cells(alive: &[(usize, usize)]) -> Self {
+        let mut grid = Self::new();
+        for &(x, y) in alive {
+            if x < W && y < H {
+                grid.cells[y][x] = true;
+            }
+        }
+        grid
+    }
+
+    /// Las filas, de arriba hacia abajo
+    pub fn rows(&self) -> &[[bool; W]; H] {
+        &self.cells
+    }
+}
+
+impl<const W: usize, const H: usize> Default for FixedGrid<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> Grid for FixedGrid<W, H> {
+    fn width(&self) -> usize {
+        W
+    }
+
+    fn height(&self) -> usize {
+        H
+    }
+
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.cells[y][x]
+    }
+
+    fn set(&mut self, x: usize, y: usize, state: CellState) {
+        self.cells[y][x] = state == CellState::Alive;
+    }
+
+    fn clear(&mut self) {
+        self.cells = [[false; W]; H];
+    }
+}
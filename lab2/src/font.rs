@@ -0,0 +1,92 @@
+/// Ancho en píxeles de cada carácter de la fuente
+pub const GLYPH_WIDTH: usize = 3;
+/// Alto en píxeles de cada carácter de la fuente
+pub const GLYPH_HEIGHT: usize = 5;
+
+/// Fuente de mapa de bits de 3×5: cada carácter son 5 filas de 3 bits, de
+/// arriba hacia abajo y con el bit más alto a la izquierda
+fn glyph(c: char) -> u16 {
+    // Las vocales con tilde y la ñ se dibujan sin tilde
+    let c = match c {
+        'á' | 'Á' => 'A',
+        'é' | 'É' => 'E',
+        'í' | 'Í' => 'I',
+        'ó' | 'Ó' => 'O',
+        'ú' | 'Ú' => 'U',
+        'ñ' | 'Ñ' => 'N',
+        _ => c,
+    };
+    match c.to_ascii_uppercase() {
+        'A' => 0b010_101_111_101_101,
+        'B' => 0b110_101_110_101_110,
+        'C' => 0b011_100_100_100_011,
+        'D' => 0b110_101_101_101_110,
+        'E' => 0b111_100_110_100_111,
+        'F' => 0b111_100_110_100_100,
+        'G' => 0b011_100_101_101_011,
+        'H' => 0b101_101_111_101_101,
+        'I' => 0b111_010_010_010_111,
+        'J' => 0b001_001_001_101_010,
+        'K' => 0b101_101_110_101_101,
+        'L' => 0b100_100_100_100_111,
+        'M' => 0b101_111_111_101_101,
+        'N' => 0b110_101_101_101_101,
+        'O' => 0b010_101_101_101_010,
+        'P' => 0b110_101_110_100_100,
+        'Q' => 0b010_101_101_110_011,
+        'R' => 0b110_101_110_101_101,
+        'S' => 0b011_100_010_001_110,
+        'T' => 0b111_010_010_010_010,
+        'U' => 0b101_101_101_101_111,
+        'V' => 0b101_101_101_101_010,
+        'W' => 0b101_101_111_111_101,
+        'X' => 0b101_101_010_101_101,
+        'Y' => 0b101_101_010_010_010,
+        'Z' => 0b111_001_010_100_111,
+        '0' => 0b111_101_101_101_111,
+        '1' => 0b010_110_010_010_111,
+        '2' => 0b110_001_010_100_111,
+        '3' => 0b110_001_010_001_110,
+        '4' => 0b101_101_111_001_001,
+        '5' => 0b111_100_110_001_110,
+        '6' => 0b011_100_111_101_111,
+        '7' => 0b111_001_010_010_010,
+        '8' => 0b111_101_111_101_111,
+        '9' => 0b111_101_111_001_110,
+        '-' => 0b000_000_111_000_000,
+        '_' => 0b000_000_000_000_111,
+        '.' => 0b000_000_000_000_010,
+        ':' => 0b000_010_000_010_000,
+        '/' => 0b001_001_010_100_100,
+        ',' => 0b000_000_000_010_100,
+        '+' => 0b000_010_111_010_000,
+        '(' => 0b001_010_010_010_001,
+        ')' => 0b100_010_010_010_100,
+        ' ' => 0,
+        _ => 0b110_001_010_000_010, // '?'
+    }
+}
+
+/// Escribe `text` en un buffer de `buffer_width` de ancho a partir de la
+/// esquina `origin`. Lo que queda fuera del buffer no se dibuja.
+pub fn draw_text<T: Copy>(buffer: &mut [T], buffer_width: usize, origin: (usize, usize), text: &str, color: T) {
+    draw_text_scaled(buffer, buffer_width, origin, text, color, 1);
+}
+
+/// Como `draw_text`, con cada píxel de la fuente como un cuadrado de
+/// `scale`×`scale`
+pub fn draw_text_scaled<T: Copy>(buffer: &mut [T], buffer_width: usize, origin: (usize, usize), text: &str, color: T, scale: usize) {
+    let buffer_height = buffer.len() / buffer_width.max(1);
+    for (i, c) in text.chars().enumerate() {
+        let bits = glyph(c);
+        for row in 0..GLYPH_HEIGHT * scale {
+            for column in 0..GLYPH_WIDTH * scale {
+                let bit = (GLYPH_HEIGHT - 1 - row / scale) * GLYPH_WIDTH + (GLYPH_WIDTH - 1 - column / scale);
+                let (x, y) = (origin.0 + i * (GLYPH_WIDTH + 1) * scale + column, origin.1 + row);
+                if bits & (1 << bit) != 0 && x < buffer_width && y < buffer_height {
+                    buffer[y * buffer_width + x] = color;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+
+/// Posición de una célula en el grid: columna `x`, fila `y`. No es lo mismo
+/// que una posición en la ventana (`PixelPos`); para pasar de una a otra
+/// hace falta la escala o la cámara.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct CellPos {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl CellPos {
+    pub const fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// La célula `dx`, `dy` más allá, o `None` si queda a la izquierda o
+    /// arriba del grid
+    pub fn offset(self, dx: i64, dy: i64) -> Option<Self> {
+        let x = usize::try_from(self.x as i64 + dx).ok()?;
+        let y = usize::try_from(self.y as i64 + dy).ok()?;
+        Some(Self { x, y })
+    }
+
+    /// La célula `dx`, `dy` hacia arriba a la izquierda, sin pasar de 0
+    pub fn saturating_sub(self, dx: usize, dy: usize) -> Self {
+        Self { x: self.x.saturating_sub(dx), y: self.y.saturating_sub(dy) }
+    }
+
+    /// Esquina superior izquierda de la célula en un frame de `scale`
+    /// píxeles por célula
+    pub fn to_pixel(self, scale: usize) -> PixelPos {
+        PixelPos::new((self.x * scale) as f32, (self.y * scale) as f32)
+    }
+}
+
+impl From<(usize, usize)> for CellPos {
+    fn from((x, y): (usize, usize)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<CellPos> for (usize, usize) {
+    fn from(pos: CellPos) -> Self {
+        (pos.x, pos.y)
+    }
+}
+
+impl fmt::Display for CellPos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// Tamaño de un grid en células (ancho por alto)
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct GridSize {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl GridSize {
+    pub const fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+
+    /// Cantidad de células
+    pub fn area(self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn contains(self, pos: CellPos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    /// Si un grid de tamaño `other` cabe entero dentro de este
+    pub fn fits(self, other: GridSize) -> bool {
+        other.width <= self.width && other.height <= self.height
+    }
+
+    /// Índice de `pos` en un vector fila por fila
+    pub fn index(self, pos: CellPos) -> usize {
+        pos.y * self.width + pos.x
+    }
+
+    /// Tamaño en píxeles con `scale` píxeles por célula (ancho, alto)
+    pub fn to_pixels(self, scale: usize) -> (usize, usize) {
+        (self.width * scale, self.height * scale)
+    }
+}
+
+impl From<(usize, usize)> for GridSize {
+    fn from((width, height): (usize, usize)) -> Self {
+        Self { width, height }
+    }
+}
+
+impl fmt::Display for GridSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+// ===== CONVENCIONES DE COORDENADAS =====
+/// Dónde está la coordenada (0, 0)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Origin {
+    /// En la esquina de la que salen los ejes: arriba a la izquierda con la
+    /// y hacia abajo, abajo a la izquierda con la y hacia arriba
+    #[default]
+    Corner,
+    /// En la célula del centro del universo; las coordenadas pueden ser
+    /// negativas
+    Center,
+}
+
+/// Hacia dónde crece la coordenada y
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum YAxis {
+    /// Como las filas de la pantalla y de los archivos RLE
+    #[default]
+    Down,
+    /// Como en los gráficos de matemática
+    Up,
+}
+
+/// Convención con la que se dan las coordenadas al colocar células y
+/// patrones. Por dentro el grid siempre usa `CellPos` (esquina superior
+/// izquierda, y hacia abajo); esto solo traduce.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Coordinates {
+    pub origin: Origin,
+    pub y_axis: YAxis,
+}
+
+impl Coordinates {
+    pub const fn new(origin: Origin, y_axis: YAxis) -> Self {
+        Self { origin, y_axis }
+    }
+
+    /// Columna y fila de la coordenada (0, 0) en un grid de `size`
+    fn zero(self, size: GridSize) -> (i64, i64) {
+        match (self.origin, self.y_axis) {
+            (Origin::Center, _) => ((size.width / 2) as i64, (size.height / 2) as i64),
+            (Origin::Corner, YAxis::Down) => (0, 0),
+            (Origin::Corner, YAxis::Up) => (0, size.height as i64 - 1),
+        }
+    }
+
+    /// Columna y fila del punto (`x`, `y`), aunque caiga fuera del grid
+    fn to_grid(self, x: i64, y: i64, size: GridSize) -> (i64, i64) {
+        let (x0, y0) = self.zero(size);
+        match self.y_axis {
+            YAxis::Down => (x0 + x, y0 + y),
+            YAxis::Up => (x0 + x, y0 - y),
+        }
+    }
+
+    /// La célula del punto (`x`, `y`) en un grid de `size`, o `None` si cae
+    /// fuera de él
+    pub fn to_cell(self, x: i64, y: i64, size: GridSize) -> Option<CellPos> {
+        let (column, row) = self.to_grid(x, y, size);
+        let pos = CellPos::default().offset(column, row)?;
+        size.contains(pos).then_some(pos)
+    }
+
+    /// El punto de la célula `pos` de un grid de `size`
+    pub fn from_cell(self, pos: CellPos, size: GridSize) -> (i64, i64) {
+        let (x0, y0) = self.zero(size);
+        let dy = pos.y as i64 - y0;
+        (pos.x as i64 - x0, if self.y_axis == YAxis::Up { -dy } else { dy })
+    }
+
+    /// Esquina superior izquierda (en el grid) de un patrón de `pattern`
+    /// colocado en (`x`, `y`). El punto es la esquina del patrón que está
+    /// del lado del origen de los ejes: la superior izquierda con la y hacia
+    /// abajo y la inferior izquierda con la y hacia arriba, así el patrón
+    /// crece en el sentido de los ejes. La esquina puede quedar a la derecha
+    /// o abajo del grid (ver `Placement::Fit`); `None` si queda a la
+    /// izquierda o arriba.
+    pub fn pattern_corner(self, x: i64, y: i64, pattern: GridSize, size: GridSize) -> Option<CellPos> {
+        let (column, row) = self.to_grid(x, y, size);
+        let top = match self.y_axis {
+            YAxis::Down => row,
+            YAxis::Up => row + 1 - pattern.height.max(1) as i64,
+        };
+        CellPos::default().offset(column, top)
+    }
+
+    /// El punto con el que se coloca un patrón de `pattern` cuya esquina
+    /// superior izquierda está en `corner`; lo inverso de `pattern_corner`
+    pub fn pattern_point(self, corner: CellPos, pattern: GridSize, size: GridSize) -> (i64, i64) {
+        let bottom = CellPos::new(corner.x, corner.y + pattern.height.max(1) - 1);
+        match self.y_axis {
+            YAxis::Down => self.from_cell(corner, size),
+            YAxis::Up => self.from_cell(bottom, size),
+        }
+    }
+}
+
+/// Posición en píxeles dentro de la ventana o de un frame, como la del
+/// mouse. Puede tener decimales y caer fuera del grid.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct PixelPos {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl PixelPos {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// Célula bajo el píxel en un frame de `scale` píxeles por célula
+    /// dibujado desde la esquina de la ventana, o `None` si el píxel está a
+    /// la izquierda o arriba de ella
+    pub fn to_cell(self, scale: usize) -> Option<CellPos> {
+        if self.x < 0.0 || self.y < 0.0 {
+            return None;
+        }
+        let scale = scale.max(1);
+        Some(CellPos::new(self.x as usize / scale, self.y as usize / scale))
+    }
+}
+
+impl From<(f32, f32)> for PixelPos {
+    fn from((x, y): (f32, f32)) -> Self {
+        Self { x, y }
+    }
+}
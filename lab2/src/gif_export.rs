@@ -0,0 +1,1045 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use gif::{Encoder, Frame, Repeat};
+
+use crate::affinity;
+use crate::apng::ApngEncoder;
+use crate::cancel::{CancelToken, Cancelled};
+use crate::progress::{self, Progress, ProgressSink};
+use crate::pacing::{DEFAULT_SPEED, DelayClock, Speed};
+use crate::quantize::{Quantizer, QuantizerKind};
+use crate::png_sequence::PngSequence;
+use crate::video::VideoEncoder;
+use crate::{ALIVE_RGB, DEAD_RGB};
+
+/// Frames que cada hilo comprime por lote cuando la codificación es paralela
+const FRAMES_PER_ENCODER_THREAD: usize = 4;
+/// Cada cuántos frames escritos se fuerzan los datos del GIF al disco
+const SYNC_INTERVAL: usize = 50;
+/// Byte con el que termina todo archivo GIF
+const GIF_TRAILER: u8 = 0x3B;
+/// Bytes de cada frame además de sus datos LZW: la extensión de control,
+/// el descriptor de la imagen y los bytes de los sub-bloques, redondeado
+/// hacia arriba
+const FRAME_OVERHEAD: u64 = 32;
+/// Con `--downsample auto`, lado máximo (en bloques) del GIF exportado
+const AUTO_DOWNSAMPLE_MAX_SIDE: usize = 512;
+/// Con `with_loop`, bytes de frames que se guardan en memoria buscando el
+/// ciclo; pasado eso el GIF se graba completo, sin recortar
+const MAX_LOOP_BUFFER_BYTES: usize = 256 << 20;
+
+/// Qué hacer cuando el GIF va a superar el tamaño máximo permitido
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SizePolicy {
+    /// Guardar solo uno de cada N frames, duplicando N cada vez que haga falta
+    Stride,
+    /// Reducir la escala de exportación antes de empezar y, si no alcanza, detenerse
+    Scale,
+    /// Dejar de grabar frames al llegar al límite
+    Stop,
+}
+
+/// Qué hacer con frames idénticos al anterior, por ejemplo cuando el
+/// universo quedó fijo
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum DedupPolicy {
+    /// Juntarlos en un solo frame que dura lo mismo que todos ellos
+    Merge,
+    /// Dejar de grabar en el primer frame repetido
+    Stop,
+}
+
+/// Formato del archivo exportado
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ExportFormat {
+    #[default]
+    Gif,
+    /// Video H.264 (con ffmpeg)
+    Mp4,
+    /// Video VP9 (con ffmpeg)
+    Webm,
+    /// Una imagen PNG por frame en una carpeta
+    PngSeq,
+    /// PNG animado con frames en color verdadero
+    Apng,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Gif => "gif",
+            ExportFormat::Mp4 => "mp4",
+            ExportFormat::Webm => "webm",
+            ExportFormat::PngSeq | ExportFormat::Apng => "png",
+        }
+    }
+
+    pub fn is_video(self) -> bool {
+        matches!(self, ExportFormat::Mp4 | ExportFormat::Webm)
+    }
+
+    /// `path` con la extensión del formato si todavía tiene la de un GIF
+    /// (por ejemplo, el `-o` predeterminado). Una secuencia de PNG va en
+    /// una carpeta con el nombre del archivo sin extensión.
+    pub fn output_path(self, path: &Path) -> PathBuf {
+        match self {
+            _ if path.extension().is_none_or(|ext| ext != "gif") => path.to_path_buf(),
+            ExportFormat::PngSeq => path.with_extension(""),
+            _ => path.with_extension(self.extension()),
+        }
+    }
+
+    /// Opciones de ffmpeg para el códec del formato
+    pub(crate) fn codec_args(self) -> &'static [&'static str] {
+        match self {
+            ExportFormat::Gif | ExportFormat::PngSeq | ExportFormat::Apng => &[],
+            ExportFormat::Mp4 => &["-c:v", "libx264", "-crf", "18", "-movflags", "+faststart"],
+            ExportFormat::Webm => &["-c:v", "libvpx-vp9", "-crf", "32", "-b:v", "0"],
+        }
+    }
+}
+
+struct SizeBudget {
+    max_bytes: u64,
+    policy: SizePolicy,
+    expected_frames: usize,
+}
+
+/// Escritor que cuenta los bytes que pasan por él. Después de cada frame
+/// deja escrito el byte final del GIF, así el archivo se puede abrir aunque
+/// el proceso muera a mitad de la exportación; la siguiente escritura lo
+/// sobrescribe.
+struct CountingWriter<W> {
+    inner: W,
+    written: u64,
+    trailer_pending: bool,
+}
+
+impl CountingWriter<File> {
+    /// Escribe el byte final provisional. Con `sync`, además pide al sistema
+    /// que lleve los datos al disco.
+    fn checkpoint(&mut self, sync: bool) -> std::io::Result<()> {
+        use std::io::Write;
+        if !self.trailer_pending {
+            self.inner.write_all(&[GIF_TRAILER])?;
+            self.trailer_pending = true;
+        }
+        if sync {
+            self.inner.sync_data()?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write + std::io::Seek> std::io::Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.trailer_pending {
+            self.inner.seek(std::io::SeekFrom::Current(-1))?;
+            self.trailer_pending = false;
+        }
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub struct GifGenerator {
+    // El encoder se crea con el primer frame, cuando ya se conoce la escala final
+    path: PathBuf,
+    encoder: Option<Encoder<CountingWriter<File>>>,
+    /// Con otro formato, los frames van a ffmpeg, a una carpeta de PNG o a
+    /// un PNG animado en lugar del GIF
+    format: ExportFormat,
+    video: Option<VideoEncoder>,
+    png_sequence: Option<PngSequence>,
+    apng: Option<ApngEncoder>,
+    width: usize,
+    height: usize,
+    scale: usize,
+    downsample: usize,
+    palette: Vec<[u8; 3]>,
+    threads: usize,
+    pin_threads: bool,
+    quantizer: Quantizer,
+    /// Con frames RGB, calcular una paleta local para cada frame en lugar de
+    /// una global a partir del primero
+    palette_per_frame: bool,
+    frame_limit: Option<usize>,
+    pending: Vec<Frame<'static>>,
+    budget: Option<SizeBudget>,
+    stride: usize,
+    frames_seen: usize,
+    frames_written: usize,
+    stopped: bool,
+    speed: Speed,
+    clock: DelayClock,
+    dedup: Option<DedupPolicy>,
+    /// Con `dedup`, el último frame queda retenido hasta saber si el
+    /// siguiente es igual
+    held: Option<Frame<'static>>,
+    /// Con `with_loop`, frames guardados en memoria (con el estado que
+    /// muestran) hasta encontrar uno que ya apareció
+    loop_frames: Option<Vec<(LoopState, Frame<'static>)>>,
+    /// Bytes de los frames de `loop_frames`
+    loop_bytes: usize,
+    /// Con `with_cycle`, frames que se saltan y frames que se graban
+    cycle: Option<(usize, usize)>,
+    /// Frames agregados mientras se espera el ciclo de `cycle`
+    cycle_seen: usize,
+    /// Duración de los frames escritos, en centésimas
+    played: u64,
+    cancel: Option<CancelToken>,
+    /// Frames que se esperan y a quién informar el avance
+    progress: Option<(u64, Box<dyn ProgressSink>)>,
+}
+
+impl GifGenerator {
+    /// Crea el GIF para un grid de `width`×`height` células, donde cada
+    /// célula ocupa `scale`×`scale` píxeles
+    pub fn new(
+        filename: impl AsRef<Path>,
+        width: usize,
+        height: usize,
+        scale: usize,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            path: filename.as_ref().to_path_buf(),
+            encoder: None,
+            format: ExportFormat::Gif,
+            video: None,
+            png_sequence: None,
+            apng: None,
+            width,
+            height,
+            scale,
+            downsample: 1,
+            palette: vec![DEAD_RGB, ALIVE_RGB],
+            threads: 1,
+            pin_threads: false,
+            quantizer: Quantizer { kind: QuantizerKind::MedianCut, colors: 256, dither: false },
+            palette_per_frame: false,
+            frame_limit: None,
+            pending: Vec::new(),
+            budget: None,
+            stride: 1,
+            frames_seen: 0,
+            frames_written: 0,
+            stopped: false,
+            speed: Speed::from_generations_per_second(DEFAULT_SPEED),
+            clock: DelayClock::default(),
+            dedup: None,
+            held: None,
+            loop_frames: None,
+            loop_bytes: 0,
+            cycle: None,
+            cycle_seen: 0,
+            played: 0,
+            cancel: None,
+            progress: None,
+        })
+    }
+
+    /// Comprime los frames (LZW) en `threads` hilos. Los frames se agrupan
+    /// en lotes y se escriben en orden, así que el GIF resultante es idéntico
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Escribe un video con ffmpeg, una secuencia de PNG o un PNG animado en
+    /// lugar del GIF. La escala, el stride y el límite de frames funcionan
+    /// igual; el tamaño máximo del archivo no se controla. La secuencia de
+    /// PNG y el PNG animado guardan cada frame apenas llega, sin
+    /// deduplicación ni recorte del ciclo, y el PNG animado recibe los
+    /// frames RGB sin pasar por el cuantizador.
+    pub fn with_format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Fija cada hilo de compresión a un núcleo distinto
+    pub fn with_pinned_threads(mut self, pin: bool) -> Self {
+        self.pin_threads = pin;
+        self
+    }
+
+    /// Cómo reducir los frames RGB (`add_rgb_frame`) a 256 colores
+    pub fn with_quantizer(mut self, quantizer: Quantizer, palette_per_frame: bool) -> Self {
+        self.quantizer = quantizer;
+        self.palette_per_frame = palette_per_frame;
+        self
+    }
+
+    /// Colores para los índices de los frames (por defecto, célula muerta y viva)
+    pub fn with_palette(mut self, palette: Vec<[u8; 3]>) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Reduce cada bloque de `factor`×`factor` células a un solo píxel cuyo
+    /// tono de gris indica cuántas células vivas contiene
+    pub fn with_downsample(mut self, factor: usize) -> Self {
+        self.downsample = factor.clamp(1, 15); // factor² + 1 tonos deben caber en 256 colores
+        self
+    }
+
+    /// Velocidad de reproducción del GIF: cada frame dura lo que tardan en
+    /// pasar sus generaciones a esa velocidad
+    pub fn with_speed(mut self, speed: Speed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Cambia la velocidad de los frames que se agreguen desde ahora
+    pub fn set_speed(&mut self, speed: Speed) {
+        self.speed = speed;
+    }
+
+    /// Junta los frames repetidos o deja de grabar en el primero, según `policy`
+    pub fn with_dedup(mut self, policy: DedupPolicy) -> Self {
+        self.dedup = Some(policy);
+        self
+    }
+
+    /// Deja de grabar cuando se repite un estado y recorta el GIF al ciclo:
+    /// se descartan los frames anteriores a la primera aparición del estado
+    /// repetido, así el GIF vuelve a empezar sin saltos. Los estados se
+    /// comparan recortados a la caja de las células no muertas (las que no
+    /// usan el índice 0), así que una nave que volvió a su forma en otro
+    /// lugar también cuenta; primero por un hash y después célula por
+    /// célula. Los frames se guardan en memoria hasta
+    /// `MAX_LOOP_BUFFER_BYTES`; si el ciclo no aparece antes, el GIF se
+    /// graba completo.
+    pub fn with_loop(mut self) -> Self {
+        if self.cycle.is_none() {
+            self.loop_frames = Some(Vec::new());
+        }
+        self
+    }
+
+    /// Graba una sola vuelta de un ciclo ya conocido: se saltan los
+    /// primeros `skip` frames, se graban los `period` siguientes y después
+    /// se deja de grabar. Reemplaza a `with_loop`, que tiene que encontrar
+    /// el ciclo comparando los frames.
+    pub fn with_cycle(mut self, skip: usize, period: usize) -> Self {
+        self.cycle = Some((skip, period.max(1)));
+        self.loop_frames = None;
+        self
+    }
+
+    /// Guarda solo uno de cada `stride` frames desde el principio
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.stride = stride.max(1);
+        self
+    }
+
+    /// Deja de grabar después de `frames` frames
+    pub fn with_frame_limit(mut self, frames: usize) -> Self {
+        self.frame_limit = Some(frames);
+        self
+    }
+
+    /// Limita el tamaño del archivo. `expected_frames` se usa para proyectar
+    /// el tamaño final a partir de lo que se lleva escrito
+    pub fn with_size_budget(mut self, max_bytes: u64, policy: SizePolicy, expected_frames: usize) -> Self {
+        self.budget = Some(SizeBudget { max_bytes, policy, expected_frames });
+        self
+    }
+
+    /// Después de `cancel.cancel()`, agregar un frame devuelve el error
+    /// `Cancelled`; `finish` (o soltar el generador) escribe el archivo con
+    /// los frames que ya se agregaron
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Informa a `sink`, unas cien veces a lo largo de `total_frames`
+    /// frames agregados, cuántos van, cuántos se escribieron y (en un GIF)
+    /// los bytes que ocupa el archivo
+    pub fn with_progress(mut self, total_frames: usize, sink: Box<dyn ProgressSink>) -> Self {
+        self.progress = Some((total_frames as u64, sink));
+        self
+    }
+
+    pub fn add_frame(&mut self, frame_data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_cancelled()?;
+        if !self.in_cycle() {
+            return Ok(());
+        }
+        self.add_indexed_frame(frame_data, None)?;
+        self.report_progress();
+        Ok(())
+    }
+
+    /// Agrega un frame con un color RGB por célula, que puede tener más de
+    /// 256 colores: se cuantiza con una paleta global (calculada con el
+    /// primer frame) o con una paleta local por frame
+    pub fn add_rgb_frame(&mut self, pixels: &[[u8; 3]]) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_cancelled()?;
+        if !self.in_cycle() {
+            return Ok(());
+        }
+        self.add_rgb_pixels(pixels)?;
+        self.report_progress();
+        Ok(())
+    }
+
+    fn add_rgb_pixels(&mut self, pixels: &[[u8; 3]]) -> Result<(), Box<dyn std::error::Error>> {
+        if self.downsample > 1 {
+            return Err("la reducción por bloques solo funciona con frames de paleta".into());
+        }
+        if self.format == ExportFormat::Apng {
+            if !self.started() {
+                self.start_encoder(&[])?;
+            }
+            if !self.admit_frame() {
+                return Ok(());
+            }
+            let rgb: Vec<u8> = self.scale_frame(pixels, self.scale).into_iter().flatten().collect();
+            return self.write_true_color(&rgb);
+        }
+        if self.palette_per_frame {
+            let palette = self.quantizer.palette(pixels);
+            let indices = self.quantizer.map(pixels, self.width, &palette);
+            return self.add_indexed_frame(&indices, Some(palette.into_iter().flatten().collect()));
+        }
+
+        if !self.started() {
+            self.palette = self.quantizer.palette(pixels);
+        }
+        let indices = self.quantizer.map(pixels, self.width, &self.palette);
+        self.add_indexed_frame(&indices, None)
+    }
+
+    fn add_indexed_frame(&mut self, frame_data: &[u8], local_palette: Option<Vec<u8>>) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.started() {
+            self.start_encoder(frame_data)?;
+        }
+        if !self.admit_frame() {
+            return Ok(());
+        }
+
+        let (width, height) = self.reduced_size();
+        let state = self.loop_frames.is_some().then(|| LoopState::new(frame_data, self.width, local_palette.as_deref()));
+        if self.apng.is_some() {
+            let palette = local_palette.unwrap_or_else(|| self.palette());
+            let rgb: Vec<u8> = self
+                .prepare_frame(frame_data, self.scale)
+                .into_iter()
+                .flat_map(|i| {
+                    let c = i as usize * 3;
+                    [palette[c], palette[c + 1], palette[c + 2]]
+                })
+                .collect();
+            return self.write_true_color(&rgb);
+        }
+        if self.png_sequence.is_some() {
+            let palette = local_palette.unwrap_or_else(|| self.palette());
+            let pixels = self.prepare_frame(frame_data, self.scale);
+            self.played += self.clock.next_delay(self.stride as f64 * self.speed.generation_interval().as_secs_f64()) as u64;
+            if let Some(sequence) = self.png_sequence.as_mut() {
+                sequence.write_frame(self.frames_seen - 1, width * self.scale, height * self.scale, &palette, &pixels)?;
+            }
+            self.frames_written += 1;
+            return Ok(());
+        }
+        let frame = Frame {
+            width: (width * self.scale) as u16,
+            height: (height * self.scale) as u16,
+            buffer: std::borrow::Cow::Owned(self.prepare_frame(frame_data, self.scale)),
+            // Cada frame guardado representa `stride` generaciones
+            delay: self.clock.next_delay(self.stride as f64 * self.speed.generation_interval().as_secs_f64()),
+            palette: local_palette,
+            ..Frame::default()
+        };
+
+        if let (Some(frames), Some(state)) = (self.loop_frames.as_mut(), state) {
+            match frames.iter().position(|(seen, _)| *seen == state) {
+                Some(start) => {
+                    frames.drain(..start);
+                    self.stopped = true;
+                    println!("El frame {} repite el {}: el GIF da vueltas a los últimos {} frames", frames.len() + start + 1, start + 1, frames.len());
+                }
+                None if self.loop_bytes + frame.buffer.len() + state.cells.len() > MAX_LOOP_BUFFER_BYTES => {
+                    println!(
+                        "No se repitió ningún frame en los primeros {}: el GIF se graba completo, sin recortar",
+                        format_size(self.loop_bytes as u64)
+                    );
+                    for (_, buffered) in self.loop_frames.take().unwrap_or_default() {
+                        self.push_frame(buffered)?;
+                    }
+                    return self.push_frame(frame);
+                }
+                None => {
+                    self.loop_bytes += frame.buffer.len() + state.cells.len();
+                    frames.push((state, frame));
+                }
+            }
+            return Ok(());
+        }
+        self.push_frame(frame)
+    }
+
+    fn check_cancelled(&self) -> Result<(), Cancelled> {
+        self.cancel.as_ref().map_or(Ok(()), CancelToken::check)
+    }
+
+    fn report_progress(&mut self) {
+        let seen = self.frames_seen as u64;
+        let written = self.frames_written as f64;
+        let bytes = self.encoder.as_ref().map(|e| e.get_ref().written as f64);
+        let Some((total, sink)) = self.progress.as_mut() else { return };
+        if !seen.is_multiple_of(progress::report_interval(*total)) && seen != *total {
+            return;
+        }
+        let mut report = Progress::new(seen, *total).with_metric("frames escritos", written);
+        if let Some(bytes) = bytes {
+            report = report.with_metric("bytes", bytes);
+        }
+        sink.report(&report);
+    }
+
+    /// Cuenta el frame que llega y dice si hay que guardarlo según el stride
+    /// y el límite de frames
+    /// Cuenta el frame contra `cycle` y dice si cae dentro de la vuelta
+    /// que se graba
+    fn in_cycle(&mut self) -> bool {
+        let Some((skip, period)) = self.cycle else { return true };
+        self.cycle_seen += 1;
+        if self.cycle_seen > skip + period {
+            self.stopped = true;
+        }
+        self.cycle_seen > skip && !self.stopped
+    }
+
+    fn admit_frame(&mut self) -> bool {
+        self.frames_seen += 1;
+        if self.stopped || !(self.frames_seen - 1).is_multiple_of(self.stride) {
+            return false;
+        }
+        let buffered = self.loop_frames.as_ref().map_or(0, Vec::len);
+        let queued = self.frames_written + self.pending.len() + self.held.is_some() as usize + buffered;
+        if self.frame_limit.is_some_and(|limit| queued >= limit) {
+            self.stopped = true;
+            return false;
+        }
+        true
+    }
+
+    /// Agrega al PNG animado un frame ya escalado (3 bytes por píxel)
+    fn write_true_color(&mut self, rgb: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        // Cada frame guardado representa `stride` generaciones
+        let delay = self.clock.next_delay(self.stride as f64 * self.speed.generation_interval().as_secs_f64());
+        self.played += delay as u64;
+        if let Some(apng) = self.apng.as_mut() {
+            apng.add_frame(rgb, delay)?;
+            self.frames_written += 1;
+        }
+        Ok(())
+    }
+
+    /// Pasa el frame por la deduplicación y lo escribe
+    fn push_frame(&mut self, frame: Frame<'static>) -> Result<(), Box<dyn std::error::Error>> {
+        match (self.dedup, self.held.take()) {
+            (None, _) => self.write_frame(frame),
+            (Some(policy), Some(mut held)) if held.buffer == frame.buffer && held.palette == frame.palette => {
+                // El frame retenido pasa a durar también lo que este
+                held.delay = held.delay.saturating_add(frame.delay);
+                self.held = Some(held);
+                self.stopped = policy == DedupPolicy::Stop;
+                Ok(())
+            }
+            (Some(_), held) => {
+                self.held = Some(frame);
+                held.map_or(Ok(()), |held| self.write_frame(held))
+            }
+        }
+    }
+
+    /// Escribe el frame o, con varios hilos, lo agrega al lote actual
+    fn write_frame(&mut self, frame: Frame<'static>) -> Result<(), Box<dyn std::error::Error>> {
+        if self.format.is_video() {
+            self.played += frame.delay as u64;
+            let palette = frame.palette.clone().unwrap_or_else(|| self.palette());
+            let rgb: Vec<u8> = frame.buffer.iter().flat_map(|&i| &palette[i as usize * 3..i as usize * 3 + 3]).copied().collect();
+            if let Some(video) = self.video.as_mut() {
+                video.write_frame(&rgb, frame.delay as f64 / 100.0)?;
+                self.frames_written += 1;
+            }
+            return Ok(());
+        }
+        if self.threads == 1 {
+            let mut frame = frame;
+            frame.make_lzw_pre_encoded();
+            if !self.fits_budget(&frame) {
+                return Ok(());
+            }
+            self.encoder_mut().write_lzw_pre_encoded_frame(&frame)?;
+            self.frames_written += 1;
+            self.played += frame.delay as u64;
+            self.checkpoint()?;
+            self.check_budget();
+        } else {
+            self.pending.push(frame);
+            if self.pending.len() >= self.threads * FRAMES_PER_ENCODER_THREAD {
+                self.flush_pending()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Escribe los frames que quedan en el lote actual y cierra el archivo.
+    /// Si no se llama (por un error o un pánico), `drop` hace lo mismo
+    /// ignorando los errores.
+    pub fn finish(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        // Sin frames, el GIF igual queda válido: cabecera y byte final
+        if !self.started() && self.format == ExportFormat::Gif {
+            self.start_encoder(&[])?;
+        }
+        let started = self.started();
+        if let Some(frames) = self.loop_frames.take() {
+            if !self.stopped {
+                println!("No se repitió ningún frame: el GIF no da vueltas sin saltos");
+            }
+            for (_, frame) in frames {
+                self.push_frame(frame)?;
+            }
+        }
+        if let Some(held) = self.held.take() {
+            self.write_frame(held)?;
+        }
+        self.flush_pending()?;
+        if let Some(encoder) = self.encoder.take() {
+            encoder.into_inner()?;
+        }
+        if let Some(apng) = self.apng.take() {
+            apng.finish()?;
+            println!("APNG: {} frames, {:.2} s de reproducción", self.frames_written, self.played as f64 / 100.0);
+            return Ok(());
+        }
+        if let Some(sequence) = self.png_sequence.take() {
+            println!("PNG: {} imágenes en {}", sequence.written(), sequence.dir().display());
+            return Ok(());
+        }
+        if let Some(video) = self.video.take() {
+            let duration = video.duration();
+            video.finish()?;
+            println!("Video: {} frames, {:.2} s de reproducción", self.frames_written, duration);
+            return Ok(());
+        }
+        if !started {
+            println!("No se grabó ningún frame: no se escribió {}", self.path.display());
+            return Ok(());
+        }
+        println!(
+            "GIF: {} frames, {:.2} s de reproducción",
+            self.frames_written,
+            self.played as f64 / 100.0
+        );
+        Ok(())
+    }
+
+    /// Deja el archivo como un GIF válido con los frames escritos hasta ahora
+    fn checkpoint(&mut self) -> std::io::Result<()> {
+        let sync = self.frames_written.is_multiple_of(SYNC_INTERVAL);
+        self.encoder_mut().get_mut().checkpoint(sync)
+    }
+
+    /// Escribe la cabecera del GIF. Con la política `Scale`, antes se reduce
+    /// la escala hasta que el tamaño estimado a partir del primer frame quepa
+    fn start_encoder(&mut self, first_frame: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(budget) = self.budget.as_ref().filter(|b| b.policy == SizePolicy::Scale) {
+            let original_scale = self.scale;
+            while self.scale > 1 {
+                let mut probe = Frame {
+                    buffer: std::borrow::Cow::Owned(self.prepare_frame(first_frame, self.scale)),
+                    ..Frame::default()
+                };
+                probe.make_lzw_pre_encoded();
+                if (probe.buffer.len() * budget.expected_frames) as u64 <= budget.max_bytes {
+                    break;
+                }
+                self.scale -= 1;
+            }
+            if self.scale != original_scale {
+                println!(
+                    "El GIF superaría el límite de {}: escala reducida de {} a {}",
+                    format_size(budget.max_bytes), original_scale, self.scale
+                );
+            }
+        }
+
+        let (width, height) = self.reduced_size();
+        if self.format == ExportFormat::PngSeq {
+            self.png_sequence = Some(PngSequence::new(&self.path)?);
+            return Ok(());
+        }
+        if self.format == ExportFormat::Apng {
+            self.apng = Some(ApngEncoder::new(&self.path, width * self.scale, height * self.scale)?);
+            return Ok(());
+        }
+        if self.format.is_video() {
+            // Un frame guardado dura `stride` generaciones
+            let frame_interval = self.stride as f64 * self.speed.generation_interval().as_secs_f64();
+            let fps = (1.0 / frame_interval).round() as u32;
+            self.video = Some(VideoEncoder::new(&self.path, width * self.scale, height * self.scale, fps, self.format)?);
+            return Ok(());
+        }
+        let writer = CountingWriter { inner: File::create(&self.path)?, written: 0, trailer_pending: false };
+        let mut encoder = Encoder::new(writer, (width * self.scale) as u16, (height * self.scale) as u16, &self.palette())?;
+        encoder.write_extension(gif::ExtensionData::Repetitions(Repeat::Infinite))?;
+        self.encoder = Some(encoder);
+        self.checkpoint()?;
+        Ok(())
+    }
+
+    /// Si ya se escribió la cabecera del GIF o se abrió el video, la carpeta
+    /// o el PNG animado
+    fn started(&self) -> bool {
+        self.encoder.is_some() || self.video.is_some() || self.png_sequence.is_some() || self.apng.is_some()
+    }
+
+    fn encoder_mut(&mut self) -> &mut Encoder<CountingWriter<File>> {
+        self.encoder.as_mut().expect("el encoder se crea con el primer frame")
+    }
+
+    /// Comprime el lote pendiente en paralelo y lo escribe en orden
+    fn flush_pending(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let chunk_size = self.pending.len().div_ceil(self.threads);
+        let pin_threads = self.pin_threads;
+        std::thread::scope(|scope| {
+            for (worker, chunk) in self.pending.chunks_mut(chunk_size).enumerate() {
+                scope.spawn(move || {
+                    if pin_threads {
+                        affinity::pin_or_warn(worker);
+                    }
+                    chunk.iter_mut().for_each(Frame::make_lzw_pre_encoded)
+                });
+            }
+        });
+
+        let pending = std::mem::take(&mut self.pending);
+        for frame in &pending {
+            if !self.fits_budget(frame) {
+                break;
+            }
+            self.encoder_mut().write_lzw_pre_encoded_frame(frame)?;
+            self.frames_written += 1;
+            self.played += frame.delay as u64;
+            self.checkpoint()?;
+        }
+        self.check_budget();
+        Ok(())
+    }
+
+    /// Si el frame ya comprimido entra en el tamaño máximo junto con lo
+    /// escrito y el byte final. Si no entra, se deja de grabar sin escribirlo.
+    fn fits_budget(&mut self, frame: &Frame) -> bool {
+        let Some(budget) = &self.budget else { return true };
+        let written = self.encoder.as_ref().map_or(0, |e| e.get_ref().written);
+        let data = frame.buffer.len() as u64;
+        let size = data + data.div_ceil(255) + frame.palette.as_ref().map_or(0, |p| p.len() as u64) + FRAME_OVERHEAD;
+        if written + size < budget.max_bytes {
+            return true;
+        }
+        if !self.stopped {
+            println!(
+                "El GIF alcanzaría el límite de {} en el frame {}: se deja de grabar",
+                format_size(budget.max_bytes), self.frames_written + 1
+            );
+        }
+        self.stopped = true;
+        false
+    }
+
+    /// Proyecta el tamaño final con el promedio por frame y aplica la política
+    fn check_budget(&mut self) {
+        let Some(budget) = &self.budget else { return };
+        let written = self.encoder.as_ref().map_or(0, |e| e.get_ref().written);
+
+        if written >= budget.max_bytes {
+            if !self.stopped {
+                println!(
+                    "El GIF alcanzó el límite de {} en el frame {}: se deja de grabar",
+                    format_size(budget.max_bytes), self.frames_seen
+                );
+            }
+            self.stopped = true;
+            return;
+        }
+        if budget.policy != SizePolicy::Stride || self.frames_written == 0 {
+            return;
+        }
+
+        let per_frame = written / self.frames_written as u64;
+        let remaining = budget.expected_frames.saturating_sub(self.frames_seen) as u64;
+        let old_stride = self.stride;
+        while written + per_frame * remaining / self.stride as u64 > budget.max_bytes
+            && (self.stride as u64) < remaining
+        {
+            self.stride *= 2;
+        }
+        if self.stride != old_stride {
+            println!(
+                "El GIF superaría el límite de {}: se guarda 1 de cada {} frames desde el frame {}",
+                format_size(budget.max_bytes), self.stride, self.frames_seen
+            );
+        }
+    }
+
+    /// Sin reducción se usa la paleta configurada. Con reducción, la paleta
+    /// tiene un tono por cada cantidad posible de células vivas en un bloque,
+    /// desde el primer color hasta el último
+    fn palette(&self) -> Vec<u8> {
+        if self.downsample == 1 {
+            return self.palette.iter().flatten().copied().collect();
+        }
+
+        let (first, last) = (self.palette[0], self.palette[self.palette.len() - 1]);
+        let levels = self.downsample * self.downsample;
+        (0..=levels)
+            .flat_map(|level| {
+                (0..3).map(move |c| {
+                    let (dead, alive) = (first[c] as usize, last[c] as usize);
+                    ((dead * (levels - level) + alive * level) / levels) as u8
+                })
+            })
+            .collect()
+    }
+
+    /// Ancho y alto en bloques después de la reducción
+    fn reduced_size(&self) -> (usize, usize) {
+        (self.width.div_ceil(self.downsample), self.height.div_ceil(self.downsample))
+    }
+
+    /// Índices de la paleta listos para escribir: reducidos por bloques y escalados
+    fn prepare_frame(&self, frame_data: &[u8], scale: usize) -> Vec<u8> {
+        if self.downsample == 1 {
+            return self.scale_frame(frame_data, scale);
+        }
+
+        // El índice de cada bloque es el número de células vivas que contiene
+        let (reduced_width, reduced_height) = self.reduced_size();
+        let mut reduced = vec![0u8; reduced_width * reduced_height];
+        for (i, &index) in frame_data.iter().enumerate() {
+            if index != 0 {
+                let (x, y) = (i % self.width, i / self.width);
+                reduced[(y / self.downsample) * reduced_width + x / self.downsample] += 1;
+            }
+        }
+        self.scale_frame(&reduced, scale)
+    }
+
+    /// Repite cada índice (o color) para que una célula ocupe scale×scale píxeles
+    fn scale_frame<T: Copy>(&self, frame_data: &[T], scale: usize) -> Vec<T> {
+        if scale == 1 {
+            return frame_data.to_vec();
+        }
+        let (width, height) = self.reduced_size();
+        let scaled_width = width * scale;
+        let mut scaled = Vec::with_capacity(scaled_width * height * scale);
+        for row in frame_data.chunks(width) {
+            let start = scaled.len();
+            for &index in row {
+                scaled.extend(std::iter::repeat_n(index, scale));
+            }
+            for _ in 1..scale {
+                scaled.extend_from_within(start..start + scaled_width);
+            }
+        }
+        scaled
+    }
+}
+
+/// Si la exportación termina sin `finish`, escribe los frames pendientes; al
+/// soltar el encoder se escribe el final del archivo
+impl Drop for GifGenerator {
+    fn drop(&mut self) {
+        if self.started() {
+            for (_, frame) in self.loop_frames.take().unwrap_or_default() {
+                let _ = self.push_frame(frame);
+            }
+            if let Some(held) = self.held.take() {
+                let _ = self.write_frame(held);
+            }
+            let _ = self.flush_pending();
+            if let Some(apng) = self.apng.take() {
+                let _ = apng.finish();
+            }
+        }
+    }
+}
+
+/// Reducción por bloques para exportar universos grandes
+#[derive(Clone, Copy, Debug)]
+pub enum Downsample {
+    /// Elige el menor factor (1, 2, 4 u 8) que deja el GIF en un tamaño manejable
+    Auto,
+    Factor(usize),
+}
+
+impl Downsample {
+    pub fn factor_for(self, width: usize, height: usize) -> usize {
+        match self {
+            Downsample::Factor(factor) => factor,
+            Downsample::Auto => [1, 2, 4, 8]
+                .into_iter()
+                .find(|&f| width.max(height).div_ceil(f) <= AUTO_DOWNSAMPLE_MAX_SIDE)
+                .unwrap_or(8),
+        }
+    }
+}
+
+pub fn parse_downsample(text: &str) -> Result<Downsample, String> {
+    match text {
+        "auto" => Ok(Downsample::Auto),
+        _ => match text.parse::<usize>() {
+            Ok(factor) if (1..=15).contains(&factor) => Ok(Downsample::Factor(factor)),
+            _ => Err(format!("se esperaba 'auto' o un factor entre 1 y 15: {}", text)),
+        },
+    }
+}
+
+/// Interpreta tamaños como `50MB`, `512KB` o `1048576` (múltiplos de 1024)
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().map_err(|_| format!("tamaño inválido: {}", text))?;
+    let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1u64,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        other => return Err(format!("unidad de tamaño desconocida: {}", other)),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Estado que muestra un frame, sin importar su posición: las células (un
+/// índice por célula, antes de escalarlas) recortadas a la caja de las que
+/// no son el fondo (índice 0), y la paleta local. Con `with_loop`, dos
+/// frames muestran el mismo estado si son iguales; el hash solo adelanta
+/// la comparación.
+#[derive(Debug)]
+struct LoopState {
+    hash: u64,
+    width: usize,
+    cells: Vec<u8>,
+    palette: Option<Vec<u8>>,
+}
+
+impl LoopState {
+    fn new(frame_data: &[u8], width: usize, palette: Option<&[u8]>) -> Self {
+        let rows: Vec<&[u8]> = frame_data.chunks_exact(width.max(1)).collect();
+        let live_rows: Vec<usize> = (0..rows.len()).filter(|&y| rows[y].iter().any(|&c| c != 0)).collect();
+        let live_columns: Vec<usize> = (0..width).filter(|&x| live_rows.iter().any(|&y| rows[y][x] != 0)).collect();
+        let (cells, width) = match (live_rows.first(), live_rows.last(), live_columns.first(), live_columns.last()) {
+            (Some(&top), Some(&bottom), Some(&left), Some(&right)) => {
+                (rows[top..=bottom].iter().flat_map(|row| &row[left..=right]).copied().collect(), right - left + 1)
+            }
+            _ => (Vec::new(), 0),
+        };
+        // FNV-1a de las células, el ancho del recorte y la paleta
+        let hash = cells
+            .iter()
+            .chain(&width.to_le_bytes())
+            .chain(palette.unwrap_or_default())
+            .fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+        Self { hash, width, cells, palette: palette.map(<[u8]>::to_vec) }
+    }
+}
+
+impl PartialEq for LoopState {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.width == other.width && self.cells == other.cells && self.palette == other.palette
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1 << 30 => format!("{:.1} GB", b as f64 / (1u64 << 30) as f64),
+        b if b >= 1 << 20 => format!("{:.1} MB", b as f64 / (1u64 << 20) as f64),
+        b if b >= 1 << 10 => format!("{:.1} KB", b as f64 / (1u64 << 10) as f64),
+        b => format!("{} B", b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CellState, GameOfLife, KnownPattern};
+
+    /// Frames que tiene el GIF de `path`
+    fn frame_count(path: &Path) -> usize {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(std::fs::File::open(path).unwrap()).unwrap();
+        std::iter::from_fn(|| decoder.read_next_frame().unwrap().map(|_| ())).count()
+    }
+
+    #[test]
+    fn loop_state_ignores_position() {
+        let mut a = GameOfLife::new(16, 16);
+        KnownPattern::Glider.stamp(&mut a, 2, 2);
+        let mut b = GameOfLife::new(16, 16);
+        KnownPattern::Glider.stamp(&mut b, 9, 5);
+        let state = |game: &GameOfLife| LoopState::new(&game.to_gif_frame_data(), 16, None);
+        assert_eq!(state(&a), state(&b));
+
+        // Otra fase del planeador tiene otra forma
+        b.next_generation();
+        assert_ne!(state(&a), state(&b));
+        // La misma forma con otra paleta local es otro estado
+        assert_ne!(LoopState::new(&a.to_gif_frame_data(), 16, Some(&[0; 6])), state(&a));
+    }
+
+    #[test]
+    fn loop_cuts_a_glider_to_one_period() {
+        let path = std::env::temp_dir().join(format!("lab2-loop-{}.gif", std::process::id()));
+        let mut game = GameOfLife::new(32, 32);
+        KnownPattern::Glider.stamp(&mut game, 2, 2);
+        let mut generator = GifGenerator::new(&path, 32, 32, 1).unwrap().with_loop();
+        for _ in 0..20 {
+            generator.add_frame(&game.to_gif_frame_data()).unwrap();
+            game.next_generation();
+        }
+        generator.finish().unwrap();
+        // El planeador vuelve a su forma, una célula más allá, cada 4 generaciones
+        assert_eq!(frame_count(&path), 4);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn cycle_keeps_one_detected_period() {
+        let path = std::env::temp_dir().join(format!("lab2-cycle-{}.gif", std::process::id()));
+        // Un parpadeador con un transitorio: una célula suelta que muere
+        let mut game = GameOfLife::new(16, 16);
+        KnownPattern::Blinker.stamp(&mut game, 6, 6);
+        game.set_cell(1, 1, CellState::Alive);
+        let cycle = crate::detection::find_cycle(&game, 20).unwrap();
+        assert_eq!((cycle.transient, cycle.period), (1, 2));
+
+        let mut generator = GifGenerator::new(&path, 16, 16, 1).unwrap().with_loop().with_cycle(cycle.transient, cycle.period);
+        for _ in 0..20 {
+            generator.add_frame(&game.to_gif_frame_data()).unwrap();
+            game.next_generation();
+        }
+        generator.finish().unwrap();
+        assert_eq!(frame_count(&path), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs::File;
+use std::path::Path;
+
+use crate::{CellState, GameOfLife};
+
+/// Lee un GIF exportado anteriormente y devuelve el grid de cada frame.
+/// `scale` indica cuántos píxeles ocupa cada célula en el GIF. Las células
+/// se distinguen por el índice de la paleta: vivas son las del más claro de
+/// los dos primeros colores, y los colores que siguen (rastros de
+/// `--trails`, anotaciones) cuentan como células muertas.
+pub fn import_gif(path: &Path, scale: usize) -> Result<Vec<GameOfLife>, Box<dyn std::error::Error>> {
+    let scale = scale.max(1);
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+    let mut decoder = options.read_info(File::open(path)?)?;
+    let global_palette = decoder.global_palette().map(<[u8]>::to_vec).unwrap_or_default();
+
+    let canvas_width = decoder.width() as usize;
+    let canvas_height = decoder.height() as usize;
+    if !canvas_width.is_multiple_of(scale) || !canvas_height.is_multiple_of(scale) {
+        return Err(format!(
+            "el GIF mide {}x{} píxeles, que no es múltiplo de la escala {}",
+            canvas_width, canvas_height, scale
+        )
+        .into());
+    }
+    let width = canvas_width / scale;
+    let height = canvas_height / scale;
+
+    // Los frames pueden cubrir solo una parte del lienzo, así que se
+    // componen sobre el frame anterior antes de leer las células
+    let mut canvas = vec![false; canvas_width * canvas_height];
+    let mut states = Vec::new();
+
+    while let Some(frame) = decoder.read_next_frame()? {
+        let (left, top) = (frame.left as usize, frame.top as usize);
+        let frame_width = frame.width as usize;
+        let alive = alive_index(frame.palette.as_deref().unwrap_or(&global_palette));
+
+        for (i, &index) in frame.buffer.iter().enumerate() {
+            if frame.transparent == Some(index) {
+                continue; // Transparente: se conserva el píxel anterior
+            }
+            let x = left + i % frame_width;
+            let y = top + i / frame_width;
+            if x < canvas_width && y < canvas_height {
+                canvas[y * canvas_width + x] = Some(index) == alive;
+            }
+        }
+
+        // Se toma el píxel central de cada bloque scale×scale
+        let cells: Vec<CellState> = (0..width * height)
+            .map(|i| {
+                let px = (i % width) * scale + scale / 2;
+                let py = (i / width) * scale + scale / 2;
+                if canvas[py * canvas_width + px] { CellState::Alive } else { CellState::Dead }
+            })
+            .collect();
+        states.push(GameOfLife::from_cells(width, height, &cells));
+    }
+
+    if states.is_empty() {
+        return Err("el GIF no contiene frames".into());
+    }
+    Ok(states)
+}
+
+/// Compara cada frame con la generación que calcula el motor a partir del
+/// frame anterior. Devuelve el índice del primer frame que no coincide.
+pub fn first_mismatch(states: &[GameOfLife]) -> Option<usize> {
+    states.windows(2).position(|pair| {
+        let mut expected = pair[0].clone();
+        expected.next_generation();
+        expected != pair[1]
+    })
+    .map(|i| i + 1)
+}
+
+/// Índice de las células vivas: de los dos primeros colores de la paleta
+/// (muertas y vivas en los GIF exportados, en cualquier tema), el más claro
+fn alive_index(palette: &[u8]) -> Option<u8> {
+    let luminance = |rgb: &[u8]| 299 * rgb[0] as u32 + 587 * rgb[1] as u32 + 114 * rgb[2] as u32;
+    let colors: Vec<&[u8]> = palette.chunks_exact(3).collect();
+    match colors[..] {
+        [] => None,
+        [_] => Some(0),
+        [first, second, ..] => Some((luminance(second) > luminance(first)) as u8),
+    }
+}
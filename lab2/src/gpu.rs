@@ -0,0 +1,357 @@
+use std::borrow::Cow;
+use std::num::NonZeroU32;
+
+use minifb::Window;
+
+/// Colores que caben en la paleta del shader (los mismos que en un GIF)
+const PALETTE_SIZE: usize = 256;
+
+/// Dibuja un rectángulo que cubre la ventana; el fragment shader busca la
+/// celda de cada píxel en la textura del grid y la pinta con la paleta, así
+/// que escalar y colorear lo hace la GPU
+const SHADER: &str = r#"
+struct Params {
+    // ancho y alto de la textura de origen, ancho y alto de la ventana
+    size: vec4<f32>,
+    // x: 0 grid de índices, 1 píxeles ya dibujados; y: 1 si la ventana es sRGB
+    mode: vec4<f32>,
+    palette: array<vec4<f32>, 256>,
+};
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var cells: texture_2d<u32>;
+@group(0) @binding(2) var pixels: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let x = f32(index & 1u) * 4.0 - 1.0;
+    let y = f32(index >> 1u) * 4.0 - 1.0;
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let source = params.size.xy;
+    let cell = vec2<i32>(floor(position.xy / params.size.zw * source));
+    let cell = min(cell, vec2<i32>(source) - vec2<i32>(1, 1));
+    var color: vec3<f32>;
+    if (params.mode.x > 0.5) {
+        color = textureLoad(pixels, cell, 0).rgb;
+    } else {
+        color = params.palette[textureLoad(cells, cell, 0).r].rgb;
+    }
+    if (params.mode.y > 0.5) {
+        color = pow(color, vec3<f32>(2.2, 2.2, 2.2));
+    }
+    return vec4<f32>(color, 1.0);
+}
+"#;
+
+/// Qué se sube a la GPU en cada frame
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+    /// Un índice de la paleta por celda
+    Cells,
+    /// El buffer de la ventana, ya dibujado en la CPU
+    Pixels,
+}
+
+/// Renderizador con wgpu sobre la ventana de minifb. Sube el grid como una
+/// textura de índices (un byte por celda, no `scale`² píxeles) y el shader
+/// lo escala y lo colorea. minifb sigue manejando la ventana, el teclado y
+/// el mouse.
+pub struct GpuRenderer {
+    surface: wgpu::Surface,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    srgb: bool,
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    params: wgpu::Buffer,
+    /// Texturas de índices y de píxeles con su tamaño y el grupo que las
+    /// enlaza; se rehacen cuando cambia alguno de los dos tamaños
+    cells: (wgpu::Texture, (u32, u32)),
+    pixels: (wgpu::Texture, (u32, u32)),
+    bind_group: wgpu::BindGroup,
+}
+
+impl GpuRenderer {
+    /// Prepara la GPU para dibujar en `window`. Falla si no hay un
+    /// adaptador que pueda presentar en la ventana.
+    pub fn new(window: &Window) -> Result<Self, Box<dyn std::error::Error>> {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        // La superficie no sobrevive a la ventana: `Screen` guarda los dos y
+        // suelta el renderizador primero
+        let surface = unsafe { instance.create_surface(window) };
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            compatible_surface: Some(&surface),
+        }))
+        .ok_or("no hay ninguna GPU que pueda dibujar en la ventana")?;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                features: wgpu::Features::empty(),
+                limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+            },
+            None,
+        ))?;
+
+        // Los colores de la paleta ya están en sRGB: si la ventana solo
+        // acepta formatos sRGB, el shader los pasa a lineal antes
+        let formats = surface.get_supported_formats(&adapter);
+        let format = formats
+            .iter()
+            .copied()
+            .find(|format| !format.describe().srgb)
+            .or_else(|| formats.first().copied())
+            .ok_or("la ventana no acepta ningún formato de la GPU")?;
+        let (width, height) = window.get_size();
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: width.max(1) as u32,
+            height: height.max(1) as u32,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        surface.configure(&device, &config);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("grid"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+        });
+        let texture_entry = |binding, sample_type| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("grid"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1, wgpu::TextureSampleType::Uint),
+                texture_entry(2, wgpu::TextureSampleType::Float { filterable: false }),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("grid"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("grid"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState { module: &shader, entry_point: "vs_main", buffers: &[] },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("grid"),
+            size: (2 + PALETTE_SIZE as u64) * 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let cells = (create_texture(&device, 1, 1, wgpu::TextureFormat::R8Uint), (1, 1));
+        let pixels = (create_texture(&device, 1, 1, wgpu::TextureFormat::Bgra8Unorm), (1, 1));
+        let bind_group = create_bind_group(&device, &layout, &params, &cells.0, &pixels.0);
+        Ok(Self {
+            surface,
+            device,
+            queue,
+            config,
+            srgb: format.describe().srgb,
+            pipeline,
+            layout,
+            params,
+            cells,
+            pixels,
+            bind_group,
+        })
+    }
+
+    /// Dibuja un grid de `width`×`height` celdas, cada una con el color de
+    /// la paleta que indica `cells`, estirado a toda la ventana
+    pub fn draw_cells(
+        &mut self,
+        window: &Window,
+        cells: &[u8],
+        width: usize,
+        height: usize,
+        palette: &[[u8; 3]],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.upload(Source::Cells, cells, width, height, 1);
+        self.draw(window, Source::Cells, (width, height), palette)
+    }
+
+    /// Presenta un buffer de la ventana (0RGB) que ya se dibujó en la CPU,
+    /// por ejemplo con capas encima del grid
+    pub fn draw_pixels(
+        &mut self,
+        window: &Window,
+        buffer: &[u32],
+        width: usize,
+        height: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // 0RGB en little endian son los bytes B, G, R, 0: una textura BGRA
+        let bytes: Vec<u8> = buffer.iter().flat_map(|pixel| pixel.to_le_bytes()).collect();
+        self.upload(Source::Pixels, &bytes, width, height, 4);
+        self.draw(window, Source::Pixels, (width, height), &[])
+    }
+
+    /// Copia los datos a la textura de `source`, rehaciéndola si cambió de
+    /// tamaño
+    fn upload(&mut self, source: Source, data: &[u8], width: usize, height: usize, bytes_per_texel: u32) {
+        let size = (width.max(1) as u32, height.max(1) as u32);
+        let (slot, format) = match source {
+            Source::Cells => (&mut self.cells, wgpu::TextureFormat::R8Uint),
+            Source::Pixels => (&mut self.pixels, wgpu::TextureFormat::Bgra8Unorm),
+        };
+        if slot.1 != size {
+            *slot = (create_texture(&self.device, size.0, size.1, format), size);
+            self.bind_group = create_bind_group(&self.device, &self.layout, &self.params, &self.cells.0, &self.pixels.0);
+        }
+        let texture = match source {
+            Source::Cells => &self.cells.0,
+            Source::Pixels => &self.pixels.0,
+        };
+        self.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(size.0 * bytes_per_texel),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+        );
+    }
+
+    fn draw(
+        &mut self,
+        window: &Window,
+        source: Source,
+        (width, height): (usize, usize),
+        palette: &[[u8; 3]],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // La ventana se puede haber redimensionado
+        let (window_width, window_height) = window.get_size();
+        let target = (window_width.max(1) as u32, window_height.max(1) as u32);
+        if (self.config.width, self.config.height) != target {
+            (self.config.width, self.config.height) = target;
+            self.surface.configure(&self.device, &self.config);
+        }
+
+        let mut params: Vec<f32> = vec![
+            width as f32,
+            height as f32,
+            target.0 as f32,
+            target.1 as f32,
+            (source == Source::Pixels) as u8 as f32,
+            self.srgb as u8 as f32,
+            0.0,
+            0.0,
+        ];
+        for i in 0..PALETTE_SIZE {
+            let [r, g, b] = palette.get(i).copied().unwrap_or_default();
+            params.extend([r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]);
+        }
+        let bytes: Vec<u8> = params.iter().flat_map(|value| value.to_le_bytes()).collect();
+        self.queue.write_buffer(&self.params, 0, &bytes);
+
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            // Pasa al cambiar de tamaño o al minimizar: se salta el frame
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(&self.device, &self.config);
+                return Ok(());
+            }
+            Err(wgpu::SurfaceError::Timeout) => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+        let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("grid") });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("grid"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: true },
+                })],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            // Un triángulo que cubre toda la ventana
+            pass.draw(0..3, 0..1);
+        }
+        self.queue.submit(Some(encoder.finish()));
+        frame.present();
+        Ok(())
+    }
+}
+
+fn create_texture(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("grid"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+    })
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    params: &wgpu::Buffer,
+    cells: &wgpu::Texture,
+    pixels: &wgpu::Texture,
+) -> wgpu::BindGroup {
+    let cells = cells.create_view(&wgpu::TextureViewDescriptor::default());
+    let pixels = pixels.create_view(&wgpu::TextureViewDescriptor::default());
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("grid"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: params.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&cells) },
+            wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&pixels) },
+        ],
+    })
+}
@@ -0,0 +1,182 @@
+/// Espacio de color en el que se interpola entre dos paradas
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ColorSpace {
+    /// Mezcla directa de los valores RGB
+    #[default]
+    Srgb,
+    /// Mezcla en OkLab: el brillo cambia parejo y los tonos intermedios no
+    /// se ensucian (de azul a amarillo no pasa por gris)
+    Oklab,
+}
+
+/// Degradado entre colores RGB ubicados en posiciones de 0.0 a 1.0
+#[derive(Clone, PartialEq, Debug)]
+pub struct Gradient {
+    /// Paradas ordenadas por posición
+    stops: Vec<(f32, [u8; 3])>,
+    space: ColorSpace,
+}
+
+impl Gradient {
+    /// Degradado de `from` (en 0.0) a `to` (en 1.0)
+    pub fn linear(from: [u8; 3], to: [u8; 3]) -> Self {
+        Self::even(&[from, to])
+    }
+
+    /// Degradado que pasa por `colors` repartidos a la misma distancia
+    pub fn even(colors: &[[u8; 3]]) -> Self {
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        let stops = colors.iter().enumerate().map(|(i, &color)| (i as f32 / last, color)).collect();
+        Self { stops, space: ColorSpace::Srgb }
+    }
+
+    /// Agrega una parada en `position` (se recorta a 0.0..=1.0)
+    pub fn with_stop(mut self, position: f32, color: [u8; 3]) -> Self {
+        let position = position.clamp(0.0, 1.0);
+        let index = self.stops.partition_point(|&(p, _)| p <= position);
+        self.stops.insert(index, (position, color));
+        self
+    }
+
+    /// Espacio en el que se interpola entre las paradas
+    pub fn with_space(mut self, space: ColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Color en la posición `t`; fuera de 0.0..=1.0 se usa el extremo
+    pub fn sample(&self, t: f32) -> [u8; 3] {
+        let Some(&(first, first_color)) = self.stops.first() else {
+            return [0, 0, 0];
+        };
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        if t <= first {
+            return first_color;
+        }
+        let next = self.stops.partition_point(|&(p, _)| p <= t);
+        if next == self.stops.len() {
+            return self.stops[next - 1].1;
+        }
+        let ((from, a), (to, b)) = (self.stops[next - 1], self.stops[next]);
+        let t = (t - from) / (to - from);
+        match self.space {
+            ColorSpace::Srgb => std::array::from_fn(|c| to_byte(a[c] as f32 + (b[c] as f32 - a[c] as f32) * t)),
+            ColorSpace::Oklab => {
+                let (a, b) = (to_oklab(a), to_oklab(b));
+                from_oklab(std::array::from_fn(|c| a[c] + (b[c] - a[c]) * t))
+            }
+        }
+    }
+
+    /// `count` colores del degradado, del primero al último, para usar
+    /// como paleta indexada
+    pub fn palette(&self, count: usize) -> Vec<[u8; 3]> {
+        let last = count.saturating_sub(1).max(1) as f32;
+        (0..count).map(|i| self.sample(i as f32 / last)).collect()
+    }
+
+    /// Azul → verde → rojo, la rampa de siempre de los mapas de calor
+    pub fn heat() -> Self {
+        Self::even(&[[0x00, 0x00, 0xFF], [0x00, 0xFF, 0x00], [0xFF, 0x00, 0x00]])
+    }
+
+    /// Viridis (de matplotlib): violeta → verde azulado → amarillo, con el
+    /// brillo creciendo parejo
+    pub fn viridis() -> Self {
+        Self::even(&[
+            [0x44, 0x01, 0x54],
+            [0x47, 0x2C, 0x7A],
+            [0x3B, 0x51, 0x8B],
+            [0x2C, 0x71, 0x8E],
+            [0x21, 0x90, 0x8D],
+            [0x27, 0xAD, 0x81],
+            [0x5C, 0xC8, 0x63],
+            [0xAA, 0xDC, 0x32],
+            [0xFD, 0xE7, 0x25],
+        ])
+    }
+
+    /// Magma (de matplotlib): negro → violeta → naranja → crema
+    pub fn magma() -> Self {
+        Self::even(&[
+            [0x00, 0x00, 0x04],
+            [0x1C, 0x10, 0x44],
+            [0x4F, 0x12, 0x7B],
+            [0x81, 0x25, 0x81],
+            [0xB5, 0x36, 0x7A],
+            [0xE5, 0x50, 0x64],
+            [0xFB, 0x87, 0x61],
+            [0xFE, 0xC2, 0x87],
+            [0xFC, 0xFD, 0xBF],
+        ])
+    }
+}
+
+/// Mapas de colores que se pueden elegir desde la línea de comandos
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Colormap {
+    /// Azul → verde → rojo
+    Heat,
+    Viridis,
+    Magma,
+    /// Del fondo de las células muertas al color de las vivas, en OkLab
+    Cells,
+}
+
+impl Colormap {
+    pub fn gradient(self) -> Gradient {
+        match self {
+            Colormap::Heat => Gradient::heat(),
+            Colormap::Viridis => Gradient::viridis(),
+            Colormap::Magma => Gradient::magma(),
+            Colormap::Cells => Gradient::linear(crate::DEAD_RGB, crate::ALIVE_RGB).with_space(ColorSpace::Oklab),
+        }
+    }
+}
+
+/// Color con el formato de los buffers de minifb (0x00RRGGBB)
+pub fn to_u32([r, g, b]: [u8; 3]) -> u32 {
+    (r as u32) << 16 | (g as u32) << 8 | b as u32
+}
+
+fn to_byte(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+// ===== OKLAB =====
+// Conversiones de https://bottosson.github.io/posts/oklab/
+
+fn to_linear(channel: u8) -> f32 {
+    let c = channel as f32 / 255.0;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn from_linear(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let c = if c <= 0.003_130_8 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    to_byte(c * 255.0)
+}
+
+fn to_oklab(rgb: [u8; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb.map(to_linear);
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+fn from_oklab([lightness, a, b]: [f32; 3]) -> [u8; 3] {
+    let l = (lightness + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m = (lightness - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+    let s = (lightness - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+    [
+        from_linear(4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s),
+        from_linear(-1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s),
+        from_linear(-0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s),
+    ]
+}
@@ -0,0 +1,75 @@
+use crate::CellState;
+use crate::rule::Rule;
+
+/// Dónde se guardan las células de un universo de dos estados. `BitGrid`
+/// es el de `GameOfLife`; `FixedGrid` tiene el tamaño en el tipo y vive en
+/// la pila. Solo hace falta implementar el acceso a las células: la
+/// generación siguiente tiene una versión célula por célula que cualquier
+/// grid puede mejorar.
+pub trait Grid: Clone {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn is_alive(&self, x: usize, y: usize) -> bool;
+    fn set(&mut self, x: usize, y: usize, state: CellState);
+
+    fn get(&self, x: usize, y: usize) -> CellState {
+        if self.is_alive(x, y) { CellState::Alive } else { CellState::Dead }
+    }
+
+    /// Mata todas las células
+    fn clear(&mut self) {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                self.set(x, y, CellState::Dead);
+            }
+        }
+    }
+
+    /// Células vivas
+    fn population(&self) -> usize {
+        (0..self.height()).map(|y| (0..self.width()).filter(|&x| self.is_alive(x, y)).count()).sum()
+    }
+
+    /// Vecinas vivas de (`x`, `y`). En un toro los bordes se unen con los
+    /// opuestos; si no, fuera del grid todo está muerto.
+    fn neighbors(&self, x: usize, y: usize, torus: bool) -> usize {
+        let (width, height) = (self.width() as i64, self.height() as i64);
+        let mut count = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let (mut nx, mut ny) = (x as i64 + dx, y as i64 + dy);
+                if torus {
+                    nx = nx.rem_euclid(width);
+                    ny = ny.rem_euclid(height);
+                } else if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                    continue;
+                }
+                count += self.is_alive(nx as usize, ny as usize) as usize;
+            }
+        }
+        count
+    }
+
+    /// La generación siguiente con `rule`, para todas las células a la vez
+    fn step(&self, rule: &Rule, torus: bool) -> Self {
+        step_cells(self, rule, torus)
+    }
+}
+
+/// La generación siguiente de `grid` célula por célula, contando las
+/// vecinas de cada una. Es lo que hace `Grid::step` si el grid no tiene
+/// una forma más rápida.
+pub fn step_cells<G: Grid>(grid: &G, rule: &Rule, torus: bool) -> G {
+    let mut next = grid.clone();
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let n = grid.neighbors(x, y, torus);
+            let alive = if grid.is_alive(x, y) { rule.is_survival(n) } else { rule.is_birth(n) };
+            next.set(x, y, if alive { CellState::Alive } else { CellState::Dead });
+        }
+    }
+    next
+}
@@ -0,0 +1,505 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+
+use crate::cancel::CancelToken;
+use crate::engine::Engine;
+use crate::rule::Rule;
+use crate::simulation::Simulation;
+use crate::{CellState, GameOfLife};
+
+/// Nodos que se guardan antes de descartar los que ya no se usan (cada uno
+/// ocupa unos 100 bytes contando las tablas)
+const MAX_NODES: usize = 1 << 22;
+
+type NodeId = u32;
+
+/// Hojas del quadtree: una célula muerta y una viva
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// Hash multiplicativo para los números de nodo: el hash predeterminado
+/// resiste ataques que acá no importan y es varias veces más lento
+#[derive(Default)]
+struct NodeHasher(u64);
+
+impl Hasher for NodeHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_u64(byte as u64);
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write_u64(value as u64);
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = (self.0.rotate_left(5) ^ value).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+}
+
+type NodeMap<K> = HashMap<K, NodeId, BuildHasherDefault<NodeHasher>>;
+
+/// Cuadrado de 2^`level` células por lado, dividido en cuatro cuadrantes
+/// (noroeste, noreste, suroeste, sureste). Los nodos son inmutables y cada
+/// uno existe una sola vez, así que dos regiones iguales son el mismo nodo.
+#[derive(Clone, Copy)]
+struct Node {
+    level: u8,
+    children: [NodeId; 4],
+    population: u64,
+}
+
+/// HashLife, de Bill Gosper: el universo es un quadtree en el que las
+/// regiones repetidas se comparten, y lo que hace cada nodo después de 2^j
+/// generaciones se calcula una sola vez. Así se simulan universos de miles
+/// de células por lado y se saltan miles de generaciones de una vez.
+///
+/// El universo no tiene bordes: lo que sale de la región que se ve sigue
+/// existiendo y puede volver.
+pub struct HashLifeEngine {
+    rule: Rule,
+    nodes: Vec<Node>,
+    ids: NodeMap<[NodeId; 4]>,
+    /// Centro de un nodo después de 2^j generaciones, por (nodo, j)
+    results: NodeMap<(NodeId, u8)>,
+    /// Nodo vacío de cada nivel
+    empty: Vec<NodeId>,
+    /// Nodos que puede crear un salto antes de abandonarlo y partirlo en dos
+    node_limit: usize,
+    /// Si el salto en curso pasó de `node_limit`
+    overflowed: bool,
+    /// Salto más largo (2^j generaciones) que se intenta de una vez: baja
+    /// cuando un salto no entra en memoria y sube cuando entra
+    max_jump: u8,
+    root: NodeId,
+    /// Célula del universo en la esquina superior izquierda de la raíz
+    origin: (i64, i64),
+    width: usize,
+    height: usize,
+    /// Generaciones que avanza cada `step`
+    generations_per_step: u64,
+    /// Células vivas de la región que se ve, fila por fila
+    view: Vec<bool>,
+}
+
+impl HashLifeEngine {
+    /// Universo vacío que se ve en una región de `width`×`height`. Falla con
+    /// las reglas con B0, en las que el vacío infinito se enciende.
+    pub fn new(width: usize, height: usize, rule: Rule) -> Result<Self, String> {
+        if rule.is_birth(0) {
+            return Err(format!("HashLife no admite reglas con B0 ({})", rule));
+        }
+        let leaf = |population| Node { level: 0, children: [DEAD; 4], population };
+        let mut engine = Self {
+            rule,
+            nodes: vec![leaf(0), leaf(1)],
+            ids: NodeMap::default(),
+            results: NodeMap::default(),
+            empty: vec![DEAD],
+            node_limit: MAX_NODES,
+            overflowed: false,
+            max_jump: u8::MAX,
+            root: DEAD,
+            origin: (0, 0),
+            width,
+            height,
+            generations_per_step: 1,
+            view: vec![false; width * height],
+        };
+        engine.root = engine.empty(3);
+        Ok(engine)
+    }
+
+    /// Copia las células vivas y la regla de `game`
+    pub fn from_game(game: &GameOfLife) -> Result<Self, String> {
+        let mut engine = Self::new(game.width(), game.height(), game.rule())?;
+        let size = game.width().max(game.height()).max(8);
+        let level = size.next_power_of_two().trailing_zeros() as u8;
+        engine.root = engine.build(game, 0, 0, level);
+        engine.refresh_view();
+        Ok(engine)
+    }
+
+    /// El mismo motor, avanzando `generations` generaciones en cada `step`
+    /// (en cada frame de la ventana y del GIF)
+    pub fn with_generations_per_step(mut self, generations: u64) -> Self {
+        self.generations_per_step = generations.max(1);
+        self
+    }
+
+    // ===== NODOS =====
+    fn node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize]
+    }
+
+    /// El nodo con esos cuatro cuadrantes, creándolo si no existe
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let children = [nw, ne, sw, se];
+        if let Some(&id) = self.ids.get(&children) {
+            return id;
+        }
+        let node = Node {
+            level: self.node(nw).level + 1,
+            children,
+            population: children.iter().map(|&child| self.node(child).population).sum(),
+        };
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(node);
+        self.ids.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().expect("el nivel 0 siempre existe");
+            let next = self.join(e, e, e, e);
+            self.empty.push(next);
+        }
+        self.empty[level as usize]
+    }
+
+    /// Cuadrado centrado de la mitad de lado
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.node(id).children.map(|child| self.node(child).children);
+        self.join(nw[3], ne[2], sw[1], se[0])
+    }
+
+    /// Nodo de nivel `level` con las células de `game` a partir de (x, y)
+    fn build(&mut self, game: &GameOfLife, x: usize, y: usize, level: u8) -> NodeId {
+        if x >= game.width() || y >= game.height() {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return if game.is_alive(x, y) { ALIVE } else { DEAD };
+        }
+        let half = 1 << (level - 1);
+        let nw = self.build(game, x, y, level - 1);
+        let ne = self.build(game, x + half, y, level - 1);
+        let sw = self.build(game, x, y + half, level - 1);
+        let se = self.build(game, x + half, y + half, level - 1);
+        self.join(nw, ne, sw, se)
+    }
+
+    /// Duplica el lado de la raíz dejando el universo en el centro
+    fn expand(&mut self) {
+        let root = self.node(self.root);
+        let e = self.empty(root.level - 1);
+        let [nw, ne, sw, se] = root.children;
+        let nw = self.join(e, e, e, nw);
+        let ne = self.join(e, e, ne, e);
+        let sw = self.join(e, sw, e, e);
+        let se = self.join(se, e, e, e);
+        self.root = self.join(nw, ne, sw, se);
+        let half = 1i64 << (root.level - 1);
+        self.origin = (self.origin.0 - half, self.origin.1 - half);
+    }
+
+    fn size(&self) -> i64 {
+        1 << self.node(self.root).level
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        let (mut x, mut y) = (x - self.origin.0, y - self.origin.1);
+        if x < 0 || y < 0 || x >= self.size() || y >= self.size() {
+            return false;
+        }
+        let mut id = self.root;
+        let mut node = self.node(id);
+        while node.level > 0 {
+            let half = 1 << (node.level - 1);
+            let quadrant = (x >= half) as usize + 2 * (y >= half) as usize;
+            (x, y) = (x % half, y % half);
+            id = node.children[quadrant];
+            node = self.node(id);
+        }
+        id == ALIVE
+    }
+
+    /// El nodo `id` con la célula (x, y) (relativa al nodo) cambiada
+    fn set(&mut self, id: NodeId, x: i64, y: i64, alive: bool) -> NodeId {
+        let node = self.node(id);
+        if node.level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let half = 1 << (node.level - 1);
+        let quadrant = (x >= half) as usize + 2 * (y >= half) as usize;
+        let mut children = node.children;
+        children[quadrant] = self.set(children[quadrant], x % half, y % half, alive);
+        let [nw, ne, sw, se] = children;
+        self.join(nw, ne, sw, se)
+    }
+
+    // ===== EVOLUCIÓN =====
+    /// Centro del nodo `id` (de nivel k) después de 2^j generaciones, con
+    /// j ≤ k - 2: en ese tiempo nada de afuera del nodo llega al centro
+    fn advance_node(&mut self, id: NodeId, j: u8) -> NodeId {
+        // Sin lugar, el resultado se descarta: se devuelve cualquier cosa
+        if self.overflowed || self.nodes.len() > self.node_limit {
+            self.overflowed = true;
+            return DEAD;
+        }
+        let node = self.node(id);
+        if node.population == 0 {
+            return self.empty(node.level - 1);
+        }
+        if let Some(&result) = self.results.get(&(id, j)) {
+            return result;
+        }
+        let result = if node.level == 2 {
+            self.base_case(id)
+        } else {
+            // Nueve cuadrados de nivel k-1 superpuestos que cubren el nodo
+            let [a, b, c, d] = node.children;
+            let [_, a1, a2, a3] = self.node(a).children;
+            let [b0, _, b2, b3] = self.node(b).children;
+            let [c0, c1, _, c3] = self.node(c).children;
+            let [d0, d1, d2, _] = self.node(d).children;
+            let squares = [
+                a,
+                self.join(a1, b0, a3, b2),
+                b,
+                self.join(a2, a3, c0, c1),
+                self.join(a3, b2, c1, d0),
+                self.join(b2, b3, d0, d1),
+                c,
+                self.join(c1, d0, c3, d2),
+                d,
+            ];
+            // A toda velocidad (j = k-2) cada mitad del salto se hace en una
+            // de las dos etapas; si no, la primera etapa no avanza
+            let full = j == node.level - 2;
+            let mut m = [DEAD; 9];
+            for (m, &square) in m.iter_mut().zip(&squares) {
+                *m = if full { self.advance_node(square, j - 1) } else { self.centre(square) };
+            }
+            let j = if full { j - 1 } else { j };
+            let quadrants = [
+                self.join(m[0], m[1], m[3], m[4]),
+                self.join(m[1], m[2], m[4], m[5]),
+                self.join(m[3], m[4], m[6], m[7]),
+                self.join(m[4], m[5], m[7], m[8]),
+            ];
+            let [nw, ne, sw, se] = quadrants.map(|quadrant| self.advance_node(quadrant, j));
+            self.join(nw, ne, sw, se)
+        };
+        if !self.overflowed {
+            self.results.insert((id, j), result);
+        }
+        result
+    }
+
+    /// Centro de 2×2 de un nodo de 4×4 después de una generación
+    fn base_case(&mut self, id: NodeId) -> NodeId {
+        let mut cells = [[false; 4]; 4];
+        for (y, row) in cells.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                let quadrant = self.node(id).children[(x >= 2) as usize + 2 * (y >= 2) as usize];
+                *cell = self.node(quadrant).children[x % 2 + 2 * (y % 2)] == ALIVE;
+            }
+        }
+        let next = |x: usize, y: usize| {
+            let neighbors = (y - 1..=y + 1)
+                .flat_map(|ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+                .filter(|&(nx, ny)| (nx, ny) != (x, y) && cells[ny][nx])
+                .count();
+            let alive = if cells[y][x] { self.rule.is_survival(neighbors) } else { self.rule.is_birth(neighbors) };
+            if alive { ALIVE } else { DEAD }
+        };
+        let (nw, ne, sw, se) = (next(1, 1), next(2, 1), next(1, 2), next(2, 2));
+        self.join(nw, ne, sw, se)
+    }
+
+    /// Avanza 2^j generaciones: agranda la raíz hasta que el universo quede
+    /// lejos de los bordes y se queda con su centro avanzado
+    fn advance_pow2(&mut self, j: u8) {
+        if j > self.max_jump {
+            self.advance_pow2(j - 1);
+            self.advance_pow2(j - 1);
+            return;
+        }
+        let before = (self.root, self.origin);
+        loop {
+            let level = self.node(self.root).level;
+            let inner = self.centre(self.root);
+            let inner = self.centre(inner);
+            if level >= j + 3 && self.node(inner).population == self.node(self.root).population {
+                break;
+            }
+            self.expand();
+        }
+        let quarter = self.size() / 4;
+        let result = self.advance_node(self.root, j);
+        if !self.overflowed {
+            self.root = result;
+            self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+            if j == self.max_jump {
+                // El universo se puede haber calmado
+                self.max_jump = j.saturating_add(1);
+            }
+            return;
+        }
+
+        // En un universo caótico un salto largo crea demasiados nodos: se
+        // vuelve a la raíz de antes con la memoria limpia y se hacen dos
+        // saltos de la mitad
+        self.overflowed = false;
+        (self.root, self.origin) = before;
+        self.collect_garbage();
+        if j > 0 {
+            self.max_jump = j - 1;
+            self.advance_pow2(j - 1);
+            self.advance_pow2(j - 1);
+        } else {
+            // Una generación no se puede partir: se calcula sin límite
+            self.node_limit = usize::MAX;
+            self.advance_pow2(0);
+            self.node_limit = MAX_NODES.max(self.nodes.len() * 2);
+        }
+    }
+
+    /// Descarta los nodos a los que ya no llega la raíz y los resultados
+    /// guardados, para que la memoria no crezca sin límite
+    fn collect_garbage(&mut self) {
+        let mut nodes = self.nodes[..2].to_vec();
+        let mut ids = NodeMap::default();
+        let mut moved = vec![NodeId::MAX; self.nodes.len()];
+        (moved[DEAD as usize], moved[ALIVE as usize]) = (DEAD, ALIVE);
+        self.root = self.copy_node(self.root, &mut nodes, &mut ids, &mut moved);
+        (self.nodes, self.ids) = (nodes, ids);
+        self.results.clear();
+        self.empty.truncate(1);
+        // Si el universo solo ya ocupa casi todo el lugar, se le da más
+        self.node_limit = MAX_NODES.max(self.nodes.len() * 2);
+    }
+
+    fn copy_node(
+        &self,
+        id: NodeId,
+        nodes: &mut Vec<Node>,
+        ids: &mut NodeMap<[NodeId; 4]>,
+        moved: &mut [NodeId],
+    ) -> NodeId {
+        if moved[id as usize] != NodeId::MAX {
+            return moved[id as usize];
+        }
+        let mut node = self.node(id);
+        for child in &mut node.children {
+            *child = self.copy_node(*child, nodes, ids, moved);
+        }
+        let new_id = nodes.len() as NodeId;
+        nodes.push(node);
+        ids.insert(node.children, new_id);
+        moved[id as usize] = new_id;
+        new_id
+    }
+
+    /// Vuelve a leer las células de la región que se ve, saltando los nodos
+    /// vacíos
+    fn refresh_view(&mut self) {
+        self.view.fill(false);
+        let mut pending = vec![(self.root, self.origin)];
+        while let Some((id, (x, y))) = pending.pop() {
+            let node = self.node(id);
+            let size = 1i64 << node.level;
+            let outside = x >= self.width as i64 || y >= self.height as i64 || x + size <= 0 || y + size <= 0;
+            if node.population == 0 || outside {
+                continue;
+            }
+            if node.level == 0 {
+                self.view[y as usize * self.width + x as usize] = true;
+                continue;
+            }
+            let half = size / 2;
+            let [nw, ne, sw, se] = node.children;
+            pending.extend([(nw, (x, y)), (ne, (x + half, y)), (sw, (x, y + half)), (se, (x + half, y + half))]);
+        }
+    }
+}
+
+impl Engine for HashLifeEngine {
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.get(x as i64, y as i64)
+    }
+
+    fn set_cell(&mut self, x: usize, y: usize, state: CellState) {
+        let (x, y) = (x as i64, y as i64);
+        while x < self.origin.0 || y < self.origin.1 || x >= self.origin.0 + self.size() || y >= self.origin.1 + self.size() {
+            self.expand();
+        }
+        self.root = self.set(self.root, x - self.origin.0, y - self.origin.1, state == CellState::Alive);
+        if x < self.width as i64 && y < self.height as i64 {
+            self.view[y as usize * self.width + x as usize] = state == CellState::Alive;
+        }
+    }
+
+    /// Descompone el salto en potencias de 2, cada una con un solo
+    /// `advance_node` sobre la raíz
+    fn advance(&mut self, generations: u64) {
+        for j in 0..u64::BITS as u8 {
+            if generations >> j & 1 == 1 {
+                self.advance_pow2(j);
+            }
+        }
+        if self.nodes.len() > self.node_limit / 2 {
+            self.collect_garbage();
+        }
+        self.refresh_view();
+    }
+
+    /// Revisa `cancel` entre las potencias de 2 del salto
+    fn advance_cancellable(&mut self, generations: u64, cancel: &CancelToken) -> u64 {
+        let mut done = 0;
+        for j in 0..u64::BITS as u8 {
+            if generations >> j & 1 == 1 {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                self.advance_pow2(j);
+                done += 1 << j;
+            }
+        }
+        if self.nodes.len() > self.node_limit / 2 {
+            self.collect_garbage();
+        }
+        self.refresh_view();
+        done
+    }
+
+    fn population(&self) -> u64 {
+        self.node(self.root).population
+    }
+}
+
+impl Simulation for HashLifeEngine {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn step(&mut self) {
+        self.advance(self.generations_per_step);
+    }
+
+    fn palette(&self) -> Vec<[u8; 3]> {
+        vec![crate::DEAD_RGB, crate::ALIVE_RGB]
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        self.view[y * self.width + x] as u8
+    }
+
+    /// Revive la célula bajo el mouse
+    fn paint(&mut self, x: usize, y: usize) {
+        if x < self.width && y < self.height {
+            self.set_cell(x, y, CellState::Alive);
+        }
+    }
+}
@@ -0,0 +1,977 @@
+//! Motor del Juego de la Vida: el universo (`GameOfLife`), sus patrones y
+//! los demás autómatas, la exportación a GIF y las grabaciones. La ventana
+//! y los subcomandos están en `runner` y `commands`; el binario `lab2` solo
+//! interpreta la línea de comandos (función `cli`) y los llama.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rayon::prelude::*;
+
+use crate::bitgrid::BitGrid;
+use crate::geometry::{CellPos, Coordinates, GridSize};
+use crate::rle::{RlePattern, Transform};
+use crate::rule::Rule;
+use crate::streams::RngStreams;
+use crate::theme::Theme;
+
+pub mod affinity;
+pub mod agents;
+pub mod ansi;
+pub mod apng;
+pub mod autotune;
+pub mod bitgrid;
+pub mod annotations;
+pub mod breakpoints;
+pub mod brians_brain;
+pub mod camera;
+pub mod cancel;
+pub mod capture;
+pub mod channel;
+pub mod commands;
+#[cfg(feature = "cli")]
+pub mod config;
+pub mod coupled;
+pub mod demo;
+pub mod detection;
+pub mod discovery;
+pub mod engine;
+pub mod falling_sand;
+pub mod favorites;
+pub mod fixed_grid;
+pub mod font;
+pub mod geometry;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod gif_export;
+pub mod gif_import;
+pub mod gradient;
+pub mod grid;
+pub mod hashlife;
+pub mod margolus;
+pub mod methuselah;
+pub mod montage;
+pub mod options;
+pub mod overlay;
+pub mod pacing;
+pub mod parquet;
+pub mod patterns;
+pub mod png_sequence;
+pub mod profiles;
+pub mod progress;
+pub mod quantize;
+pub mod reaction_diffusion;
+pub mod recording;
+pub mod results;
+pub mod rle;
+pub mod rule;
+pub mod rule_search;
+pub mod runner;
+pub mod scenario;
+pub mod screen;
+pub mod second_order;
+pub mod showcase;
+pub mod simulation;
+pub mod snapshot;
+pub mod sparse;
+pub mod stats;
+pub mod streams;
+pub mod theme;
+pub mod totalistic;
+pub mod trails;
+pub mod turmite;
+pub mod tutorial;
+pub mod video;
+pub mod watch;
+pub mod wizard;
+
+/// Colores de las células en los GIF y en la paleta de `GameOfLife`
+pub const DEAD_RGB: [u8; 3] = [0x00, 0x11, 0x22]; // Azul oscuro
+pub const ALIVE_RGB: [u8; 3] = [0xFF, 0xFF, 0xFF]; // Blanco
+/// Probabilidad de que cada célula empiece viva en `initialize`
+pub const DEFAULT_DENSITY: f64 = 0.15;
+
+// ===== TIPOS =====
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CellState {
+    Dead,
+    Alive,
+}
+
+/// Orden en el que se actualizan las células en cada generación
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UpdateScheme {
+    /// Todas a la vez a partir de la generación anterior (Conway clásico)
+    Synchronous,
+    /// Una por una en orden aleatorio, viendo los cambios ya hechos
+    RandomSequential,
+    /// Por bloques de `n`×`n` en orden fijo; dentro de cada bloque, a la vez
+    BlockSequential(usize),
+    /// Cada célula adopta su nuevo estado con probabilidad α
+    AlphaAsynchronous(f64),
+}
+
+/// Punto del universo que queda fijo al cambiarle el tamaño
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Desplazamiento de las células al pasar de `old` a `new` células en
+    /// cada eje (negativo si el universo se achica)
+    fn offset(self, old: (usize, usize), new: (usize, usize)) -> (i64, i64) {
+        let (dx, dy) = (new.0 as i64 - old.0 as i64, new.1 as i64 - old.1 as i64);
+        let (fx, fy) = match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Center => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        };
+        (dx * fx / 2, dy * fy / 2)
+    }
+}
+
+/// Qué hacer donde se superponen dos universos al combinarlos
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum MergePolicy {
+    /// Una célula queda viva si está viva en cualquiera de los dos
+    Or,
+    /// El rectángulo del segundo universo reemplaza al primero, muertas incluidas
+    Overwrite,
+    /// Falla si una célula está viva en los dos
+    Error,
+}
+
+/// Qué hacer con las células de un patrón que caen fuera del universo al
+/// estamparlo
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Placement {
+    /// Se pierden sin avisar
+    #[default]
+    Clip,
+    /// No se estampa nada y se informa qué células quedarían fuera
+    Strict,
+    /// El universo crece hacia la derecha y hacia abajo hasta que el patrón
+    /// entra entero
+    Fit,
+}
+
+/// Qué pasa con las células que salen por un borde al trasladar el universo
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ShiftEdges {
+    /// Entran por el borde opuesto
+    Wrap,
+    /// Se pierden
+    Clip,
+}
+
+/// Qué hay más allá de los bordes del universo al contar vecinos
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Topology {
+    /// Células muertas: lo que sale por un borde desaparece
+    #[default]
+    Bounded,
+    /// El borde opuesto: el universo es un toro y las naves vuelven a entrar
+    Torus,
+}
+
+// ===== ESTRUCTURA PRINCIPAL =====
+#[derive(Clone)]
+pub struct GameOfLife {
+    grid: BitGrid,
+    width: usize,
+    height: usize,
+    update_scheme: UpdateScheme,
+    topology: Topology,
+    rule: Rule,
+    /// Células congeladas, fila por fila: conservan su estado pero sus
+    /// vecinas las siguen viendo. Vacío si no hay ninguna.
+    frozen: Vec<bool>,
+    /// Números al azar de los esquemas de actualización aleatorios, uno por
+    /// fila y generación
+    noise: RngStreams,
+    /// Generaciones aleatorias calculadas, para no repetir la secuencia
+    noise_step: u64,
+    /// Colores con los que se dibuja
+    theme: Theme,
+    /// Convención de las coordenadas de `cell_at`, `set_cell_at` y
+    /// `place_pattern_at`
+    coordinates: Coordinates,
+}
+
+/// Dos universos son iguales si tienen las mismas células, la misma regla y
+/// topología y las mismas células congeladas. Los números al azar, el tema y
+/// las coordenadas no cuentan: no cambian el estado.
+impl PartialEq for GameOfLife {
+    fn eq(&self, other: &Self) -> bool {
+        fn frozen(game: &GameOfLife) -> Option<&[bool]> {
+            game.frozen.iter().any(|&frozen| frozen).then_some(game.frozen.as_slice())
+        }
+        self.width == other.width
+            && self.height == other.height
+            && self.rule == other.rule
+            && self.topology == other.topology
+            && self.grid == other.grid
+            && frozen(self) == frozen(other)
+    }
+}
+
+// ===== IMPLEMENTACIÓN DEL JUEGO =====
+impl GameOfLife {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::new_with_topology(width, height, Topology::Bounded)
+    }
+
+    pub fn new_with_topology(width: usize, height: usize, topology: Topology) -> Self {
+        Self::new_with_rule(width, height, Rule::conway(), topology)
+    }
+
+    /// Universo vacío que evoluciona con `rule` en lugar de B3/S23
+    pub fn new_with_rule(width: usize, height: usize, rule: Rule, topology: Topology) -> Self {
+        Self {
+            grid: BitGrid::new(width, height),
+            width,
+            height,
+            update_scheme: UpdateScheme::Synchronous,
+            topology,
+            rule,
+            frozen: Vec::new(),
+            noise: RngStreams::from_entropy(),
+            noise_step: 0,
+            theme: Theme::default(),
+            coordinates: Coordinates::default(),
+        }
+    }
+
+    /// Crea un juego a partir de un estado guardado fila por fila
+    pub fn from_cells(width: usize, height: usize, cells: &[CellState]) -> Self {
+        let mut game = Self::new(width, height);
+        for (i, &state) in cells.iter().enumerate().take(width * height) {
+            game.grid.set(i % width, i / width, state);
+        }
+        game
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn set_update_scheme(&mut self, scheme: UpdateScheme) {
+        self.update_scheme = scheme;
+    }
+
+    pub fn update_scheme(&self) -> UpdateScheme {
+        self.update_scheme
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn set_coordinates(&mut self, coordinates: Coordinates) {
+        self.coordinates = coordinates;
+    }
+
+    pub fn coordinates(&self) -> Coordinates {
+        self.coordinates
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn size(&self) -> GridSize {
+        GridSize::new(self.width, self.height)
+    }
+
+    /// Si `pos` está dentro del grid
+    pub fn contains(&self, pos: CellPos) -> bool {
+        self.size().contains(pos)
+    }
+
+    /// Devuelve el estado de todas las células fila por fila
+    pub fn cells(&self) -> Vec<CellState> {
+        (0..self.width * self.height).map(|i| self.grid.get(i % self.width, i / self.width)).collect()
+    }
+
+    /// Estado de una célula; fuera del grid, muerta
+    pub fn cell(&self, x: usize, y: usize) -> CellState {
+        if x < self.width && y < self.height { self.grid.get(x, y) } else { CellState::Dead }
+    }
+
+    /// Cambia el estado de una célula (ignora posiciones inválidas)
+    pub fn set_cell(&mut self, x: usize, y: usize, state: CellState) {
+        if x < self.width && y < self.height {
+            self.grid.set(x, y, state);
+        }
+    }
+
+    /// Como `cell`, con (`x`, `y`) en la convención de `set_coordinates`
+    pub fn cell_at(&self, x: i64, y: i64) -> CellState {
+        match self.coordinates.to_cell(x, y, self.size()) {
+            Some(pos) => self.grid.get(pos.x, pos.y),
+            None => CellState::Dead,
+        }
+    }
+
+    /// Como `set_cell`, con (`x`, `y`) en la convención de `set_coordinates`
+    pub fn set_cell_at(&mut self, x: i64, y: i64, state: CellState) {
+        if let Some(pos) = self.coordinates.to_cell(x, y, self.size()) {
+            self.grid.set(pos.x, pos.y, state);
+        }
+    }
+
+    pub fn is_frozen(&self, x: usize, y: usize) -> bool {
+        !self.frozen.is_empty() && self.frozen[y * self.width + x]
+    }
+
+    /// Si hay alguna célula congelada
+    pub fn has_frozen(&self) -> bool {
+        self.frozen.iter().any(|&frozen| frozen)
+    }
+
+    /// Congela o descongela el rectángulo de `width`×`height` que empieza en
+    /// (`x`, `y`); la parte que cae fuera del grid se ignora
+    pub fn set_frozen(&mut self, x: usize, y: usize, width: usize, height: usize, frozen: bool) {
+        if self.frozen.is_empty() {
+            if !frozen {
+                return;
+            }
+            self.frozen = vec![false; self.width * self.height];
+        }
+        for cy in y..(y + height).min(self.height) {
+            for cx in x..(x + width).min(self.width) {
+                self.frozen[cy * self.width + cx] = frozen;
+            }
+        }
+    }
+
+    /// Hash estable (FNV-1a) del estado actual, usado para verificar grabaciones
+    pub fn state_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for i in 0..self.width * self.height {
+            hash ^= self.grid.is_alive(i % self.width, i / self.width) as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        hash
+    }
+
+    ///Inicializa el grid con patrones aleatorios y conocidos
+    pub fn initialize(&mut self) {
+        self.initialize_with_density(DEFAULT_DENSITY);
+    }
+
+    /// Como `initialize`, con otra probabilidad de que cada célula empiece viva
+    pub fn initialize_with_density(&mut self, density: f64) {
+        self.initialize_with_rng(density, &mut StdRng::from_entropy());
+    }
+
+    /// Como `initialize_with_density`, con las células al azar sacadas de
+    /// `rng`: con `StdRng::seed_from_u64` y la misma semilla el universo
+    /// inicial es siempre el mismo
+    pub fn initialize_with_rng(&mut self, density: f64, rng: &mut impl Rng) {
+        self.clear_grid();
+        self.add_random_cells_with(density.clamp(0.0, 1.0), rng);
+        self.add_known_patterns();
+    }
+
+    /// Como `initialize_with_density`, con las células al azar sacadas de
+    /// una secuencia por fila derivada de `seed`. Las filas se llenan en
+    /// paralelo y el universo es el mismo con cualquier cantidad de hilos.
+    pub fn initialize_with_seed(&mut self, density: f64, seed: u64) {
+        self.clear_grid();
+        self.add_random_cells_with_streams(density.clamp(0.0, 1.0), &RngStreams::new(seed));
+        self.add_known_patterns();
+    }
+
+    /// Semilla de los esquemas de actualización aleatorios (`RandomSequential`
+    /// y `AlphaAsynchronous`): con la misma semilla la evolución se repite
+    /// exacta. Sin llamarla se usa una al azar.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.noise = RngStreams::new(seed);
+        self.noise_step = 0;
+    }
+
+    ///Limpia todo el grid
+    fn clear_grid(&mut self) {
+        self.grid.clear();
+    }
+
+    ///Células aleatorias al grid
+    pub fn add_random_cells(&mut self, probability: f64) {
+        self.add_random_cells_with(probability, &mut StdRng::from_entropy());
+    }
+
+    /// Como `add_random_cells`, con los números al azar sacados de `rng`
+    pub fn add_random_cells_with(&mut self, probability: f64, rng: &mut impl Rng) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if rng.gen_bool(probability) {
+                    self.grid.set(x, y, CellState::Alive);
+                }
+            }
+        }
+    }
+
+    /// Como `add_random_cells`, con las filas llenadas en paralelo, cada una
+    /// con su propia secuencia de `streams`
+    pub fn add_random_cells_with_streams(&mut self, probability: f64, streams: &RngStreams) {
+        let rows: Vec<Vec<bool>> = (0..self.height)
+            .into_par_iter()
+            .map(|y| {
+                let mut rng = streams.stream(0, y as u64);
+                (0..self.width).map(|_| rng.gen_bool(probability)).collect()
+            })
+            .collect();
+        for (y, row) in rows.into_iter().enumerate() {
+            for (x, _) in row.into_iter().enumerate().filter(|&(_, alive)| alive) {
+                self.grid.set(x, y, CellState::Alive);
+            }
+        }
+    }
+
+    ///Patrones conocidos
+    fn add_known_patterns(&mut self) {
+        let placements = [
+            (KnownPattern::Glider, 15, 15),
+            (KnownPattern::Glider, 70, 10),
+            (KnownPattern::Block, 5, 5),
+            (KnownPattern::Block, 90, 90),
+            (KnownPattern::Blinker, 25, 25),
+            (KnownPattern::Toad, 35, 35),
+            (KnownPattern::Beacon, 45, 45),
+            (KnownPattern::Beehive, 60, 60),
+            (KnownPattern::LightweightSpaceship, 10, 50),
+            (KnownPattern::Pulsar, 50, 20),
+        ];
+        for (pattern, x, y) in placements {
+            pattern.stamp(self, x, y);
+        }
+    }
+
+    /// Avanza una generación aplicando la regla del universo según el
+    /// esquema de actualización configurado
+    pub fn next_generation(&mut self) {
+        match self.update_scheme {
+            UpdateScheme::Synchronous => self.synchronous_update(),
+            UpdateScheme::RandomSequential => self.random_sequential_update(),
+            UpdateScheme::BlockSequential(block) => self.block_sequential_update(block.max(1)),
+            UpdateScheme::AlphaAsynchronous(alpha) => self.alpha_asynchronous_update(alpha),
+        }
+    }
+
+    /// Actualización síncrona: todas las células a partir del mismo estado,
+    /// 64 a la vez con operaciones de bits
+    fn synchronous_update(&mut self) {
+        let mut new_grid = self.grid.step(&self.rule, self.topology == Topology::Torus);
+        for (i, _) in self.frozen.iter().enumerate().filter(|(_, frozen)| **frozen) {
+            let (x, y) = (i % self.width, i / self.width);
+            new_grid.set(x, y, self.grid.get(x, y));
+        }
+        self.grid = new_grid;
+    }
+
+    /// Cada célula se actualiza en el lugar, en un orden aleatorio distinto
+    /// en cada generación
+    fn random_sequential_update(&mut self) {
+        let mut order: Vec<(usize, usize)> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .collect();
+        order.shuffle(&mut self.noise.stream(self.noise_step, 0));
+        self.noise_step += 1;
+
+        for (x, y) in order {
+            let state = self.next_state(x, y);
+            self.grid.set(x, y, state);
+        }
+    }
+
+    /// Los bloques se procesan de izquierda a derecha y de arriba abajo; cada
+    /// bloque ve los cambios de los bloques anteriores
+    fn block_sequential_update(&mut self, block: usize) {
+        for block_y in (0..self.height).step_by(block) {
+            for block_x in (0..self.width).step_by(block) {
+                let ys = block_y..(block_y + block).min(self.height);
+                let xs = block_x..(block_x + block).min(self.width);
+
+                let updates: Vec<(usize, usize, CellState)> = ys
+                    .flat_map(|y| xs.clone().map(move |x| (x, y)))
+                    .map(|(x, y)| (x, y, self.next_state(x, y)))
+                    .collect();
+                for (x, y, state) in updates {
+                    self.grid.set(x, y, state);
+                }
+            }
+        }
+    }
+
+    /// Se calcula la generación síncrona, pero cada célula solo cambia con
+    /// probabilidad α. Las filas se calculan en paralelo, cada una con su
+    /// secuencia de números al azar.
+    fn alpha_asynchronous_update(&mut self, alpha: f64) {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let step = self.noise_step;
+        let updates: Vec<Vec<(usize, CellState)>> = (0..self.height)
+            .into_par_iter()
+            .map(|y| {
+                let mut rng = self.noise.stream(step, y as u64);
+                (0..self.width)
+                    .filter(|_| rng.gen_bool(alpha))
+                    .map(|x| (x, self.next_state(x, y)))
+                    .collect()
+            })
+            .collect();
+
+        let mut new_grid = self.grid.clone();
+        for (y, row) in updates.into_iter().enumerate() {
+            for (x, state) in row {
+                new_grid.set(x, y, state);
+            }
+        }
+        self.grid = new_grid;
+        self.noise_step += 1;
+    }
+
+    /// Estado de una célula en la siguiente generación; las congeladas
+    /// conservan el suyo
+    fn next_state(&self, x: usize, y: usize) -> CellState {
+        if self.is_frozen(x, y) {
+            return self.grid.get(x, y);
+        }
+        self.apply_rules(self.grid.get(x, y), self.count_live_neighbors(x, y))
+    }
+
+    /// Aplica la regla B/S del universo a una célula
+    fn apply_rules(&self, current_state: CellState, neighbors: usize) -> CellState {
+        match current_state {
+            // Una célula viva sobrevive si su cantidad de vecinos está en S
+            CellState::Alive if self.rule.is_survival(neighbors) => CellState::Alive,
+            // Una célula muerta nace si su cantidad de vecinos está en B
+            CellState::Dead if self.rule.is_birth(neighbors) => CellState::Alive,
+            // En cualquier otro caso queda (o pasa a estar) muerta
+            _ => CellState::Dead,
+        }
+    }
+
+    /// Cuenta los vecinos vivos de una célula
+    fn count_live_neighbors(&self, x: usize, y: usize) -> usize {
+        let mut count = 0;
+        
+        for dy in -1..=1i32 {
+            for dx in -1..=1i32 {
+                if dx == 0 && dy == 0 { continue; }
+                
+                let mut nx = x as i32 + dx;
+                let mut ny = y as i32 + dy;
+                if self.topology == Topology::Torus {
+                    nx = nx.rem_euclid(self.width as i32);
+                    ny = ny.rem_euclid(self.height as i32);
+                }
+                
+                if self.is_valid_position(nx, ny) && self.is_alive(nx as usize, ny as usize) {
+                    count += 1;
+                }
+            }
+        }
+        
+        count
+    }
+
+    /// Verifica si una posición es válida dentro del grid
+    fn is_valid_position(&self, x: i32, y: i32) -> bool {
+        x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32
+    }
+
+    /// Verifica si una célula está viva
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.grid.is_alive(x, y)
+    }
+
+    /// Obtiene el color de una célula para renderizado
+    pub fn get_color(&self, x: usize, y: usize) -> u32 {
+        if x < self.width && y < self.height {
+            match self.grid.get(x, y) {
+                CellState::Alive => gradient::to_u32(self.theme.alive),
+                CellState::Dead => gradient::to_u32(self.theme.dead),
+            }
+        } else {
+            gradient::to_u32(self.theme.background) // Posiciones inválidas
+        }
+    }
+
+    /// Renderiza el juego en un buffer de píxeles, `scale` píxeles por célula:
+    /// cada fila del grid se escala una vez y se copia `scale` veces
+    pub fn render(&self, buffer: &mut [u32], scale: usize) {
+        let window_width = self.width * scale;
+        if window_width == 0 {
+            return;
+        }
+        for (y, rows) in buffer.chunks_exact_mut(window_width * scale).take(self.height).enumerate() {
+            let (scanline, copies) = rows.split_at_mut(window_width);
+            for (x, cell) in scanline.chunks_exact_mut(scale).enumerate() {
+                cell.fill(self.get_color(x, y));
+            }
+            for row in copies.chunks_exact_mut(window_width) {
+                row.copy_from_slice(scanline);
+            }
+        }
+    }
+
+    /// Convierte el grid actual a datos para frame del GIF
+    pub fn to_gif_frame_data(&self) -> Vec<u8> {
+        let mut frame_data = vec![0; self.width * self.height];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = y * self.width + x;
+                frame_data[index] = match self.grid.get(x, y) {
+                    CellState::Alive => 1, // Índice del color blanco en la paleta
+                    CellState::Dead => 0,  // Índice del color azul en la paleta
+                };
+            }
+        }
+        frame_data
+    }
+}
+
+// ===== PATRONES CONOCIDOS =====
+/// Células que encendió un patrón al estamparlo, para poder quitarlo después
+#[derive(Clone, Debug, Default)]
+pub struct Stamp {
+    cells: Vec<(usize, usize)>,
+}
+
+impl Stamp {
+    /// Cuántas células encendió
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+impl GameOfLife {
+    /// Añade un patrón dado como coordenadas relativas. El `Stamp` devuelto
+    /// guarda solo las células que estaban muertas y que el patrón encendió.
+    pub fn stamp(&mut self, base_x: usize, base_y: usize, pattern: &[(usize, usize)]) -> Stamp {
+        let mut stamp = Stamp::default();
+        let size = self.size();
+        for &(dx, dy) in pattern {
+            let pos = CellPos::new(base_x + dx, base_y + dy);
+            if size.contains(pos) && !self.grid.is_alive(pos.x, pos.y) {
+                self.grid.set(pos.x, pos.y, CellState::Alive);
+                stamp.cells.push(pos.into());
+            }
+        }
+        stamp
+    }
+
+    /// Quita un patrón estampado célula por célula: apaga cada célula que
+    /// encendió y que sigue viva, como quedó al estamparlo. Las que ya se
+    /// apagaron (por la evolución o pintando encima) y las que estaban vivas
+    /// antes del patrón no se tocan, así un patrón tapado en parte se quita
+    /// solo donde sigue intacto. Devuelve cuántas células apagó.
+    pub fn remove_stamp(&mut self, stamp: Stamp) -> usize {
+        let mut removed = 0;
+        for (x, y) in stamp.cells {
+            if x < self.width && y < self.height && self.grid.is_alive(x, y) {
+                self.grid.set(x, y, CellState::Dead);
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+/// Patrones del registro (`patterns`) que se pueden elegir desde la línea
+/// de comandos
+pub enum KnownPattern {
+    Glider,
+    Block,
+    Blinker,
+    Toad,
+    Beacon,
+    Beehive,
+    LightweightSpaceship,
+    Pulsar,
+    Pentadecathlon,
+    RPentomino,
+    Acorn,
+    GosperGun,
+    SchickEngine,
+}
+
+impl KnownPattern {
+    /// Todos los patrones, en el orden de la línea de comandos
+    pub const ALL: [KnownPattern; 13] = [
+        KnownPattern::Glider,
+        KnownPattern::Block,
+        KnownPattern::Blinker,
+        KnownPattern::Toad,
+        KnownPattern::Beacon,
+        KnownPattern::Beehive,
+        KnownPattern::LightweightSpaceship,
+        KnownPattern::Pulsar,
+        KnownPattern::Pentadecathlon,
+        KnownPattern::RPentomino,
+        KnownPattern::Acorn,
+        KnownPattern::GosperGun,
+        KnownPattern::SchickEngine,
+    ];
+
+    /// Coloca el patrón cerca del borde izquierdo, a media altura, para que
+    /// las naves tengan espacio para avanzar
+    pub fn place(self, game: &mut GameOfLife) {
+        self.stamp(game, 5, game.height() / 2);
+    }
+
+    /// Nombre del patrón en el registro
+    pub fn name(self) -> &'static str {
+        match self {
+            KnownPattern::Glider => "glider",
+            KnownPattern::Block => "block",
+            KnownPattern::Blinker => "blinker",
+            KnownPattern::Toad => "toad",
+            KnownPattern::Beacon => "beacon",
+            KnownPattern::Beehive => "beehive",
+            KnownPattern::LightweightSpaceship => "lightweight-spaceship",
+            KnownPattern::Pulsar => "pulsar",
+            KnownPattern::Pentadecathlon => "pentadecathlon",
+            KnownPattern::RPentomino => "r-pentomino",
+            KnownPattern::Acorn => "acorn",
+            KnownPattern::GosperGun => "gosper-gun",
+            KnownPattern::SchickEngine => "schick-engine",
+        }
+    }
+
+    /// Las células del patrón
+    pub fn pattern(self) -> RlePattern {
+        patterns::get(self.name()).expect("cada patrón conocido está en el registro")
+    }
+
+    /// Estampa el patrón con su esquina superior izquierda en (x, y)
+    pub fn stamp(self, game: &mut GameOfLife, x: usize, y: usize) -> Stamp {
+        self.stamp_transformed(game, CellPos::new(x, y), Transform::default())
+    }
+
+    /// Como `stamp`, con la orientación `transform` y la esquina en `at`
+    pub fn stamp_transformed(self, game: &mut GameOfLife, at: CellPos, transform: Transform) -> Stamp {
+        game.add_pattern(&self.pattern(), at, transform)
+    }
+}
+
+
+// ===== TAMAÑO DEL UNIVERSO =====
+impl GameOfLife {
+    /// Copia del rectángulo de `width`×`height` que empieza en (`x`, `y`);
+    /// lo que queda fuera del universo aparece como células muertas
+    pub fn region(&self, x: i64, y: i64, width: usize, height: usize) -> GameOfLife {
+        let mut region = GameOfLife::new(width, height);
+        region.update_scheme = self.update_scheme;
+        region.topology = self.topology;
+        region.rule = self.rule;
+        region.theme = self.theme;
+        region.coordinates = self.coordinates;
+        region.noise = self.noise;
+        region.noise_step = self.noise_step;
+        for ry in 0..height {
+            let sy = y + ry as i64;
+            if sy < 0 || sy >= self.height as i64 {
+                continue;
+            }
+            for rx in 0..width {
+                let sx = x + rx as i64;
+                if sx >= 0 && sx < self.width as i64 {
+                    let (sx, sy) = (sx as usize, sy as usize);
+                    region.grid.set(rx, ry, self.grid.get(sx, sy));
+                    region.set_frozen(rx, ry, 1, 1, self.is_frozen(sx, sy));
+                }
+            }
+        }
+        region
+    }
+
+    /// Cambia el tamaño del universo dejando fijo el punto `anchor`. Las
+    /// células que quedan fuera se pierden y las nuevas nacen muertas.
+    /// Devuelve cuánto se desplazaron las coordenadas de las células.
+    pub fn resize(&mut self, new_width: usize, new_height: usize, anchor: Anchor) -> (i64, i64) {
+        let (dx, dy) = anchor.offset((self.width, self.height), (new_width, new_height));
+        *self = self.region(-dx, -dy, new_width, new_height);
+        (dx, dy)
+    }
+
+    /// Recorta el universo a la caja de las células vivas más `margin`
+    /// células de cada lado. Un universo vacío no cambia.
+    /// Devuelve cuánto se desplazaron las coordenadas de las células.
+    pub fn crop_to_live_cells(&mut self, margin: usize) -> (i64, i64) {
+        let alive = |x: usize, y: usize| self.grid.is_alive(x, y);
+        let rows: Vec<usize> = (0..self.height).filter(|&y| (0..self.width).any(|x| alive(x, y))).collect();
+        let columns: Vec<usize> = (0..self.width).filter(|&x| rows.iter().any(|&y| alive(x, y))).collect();
+        let (Some(&min_x), Some(&max_x), Some(&min_y), Some(&max_y)) =
+            (columns.first(), columns.last(), rows.first(), rows.last())
+        else {
+            return (0, 0);
+        };
+
+        let (x, y) = (min_x as i64 - margin as i64, min_y as i64 - margin as i64);
+        *self = self.region(x, y, max_x - min_x + 1 + 2 * margin, max_y - min_y + 1 + 2 * margin);
+        (-x, -y)
+    }
+
+    /// Mueve todas las células `dx` columnas a la derecha y `dy` filas hacia
+    /// abajo (negativos hacia la izquierda y arriba). Las zonas congeladas se
+    /// mueven con ellas.
+    pub fn translate(&mut self, dx: i64, dy: i64, edges: ShiftEdges) {
+        match edges {
+            ShiftEdges::Clip => *self = self.region(-dx, -dy, self.width, self.height),
+            ShiftEdges::Wrap => {
+                let (width, height) = (self.width as i64, self.height as i64);
+                let source = |i: usize, delta: i64, size: i64| (i as i64 - delta).rem_euclid(size) as usize;
+                let mut grid = BitGrid::new(self.width, self.height);
+                for y in 0..self.height {
+                    for x in 0..self.width {
+                        grid.set(x, y, self.grid.get(source(x, dx, width), source(y, dy, height)));
+                    }
+                }
+                self.grid = grid;
+                if !self.frozen.is_empty() {
+                    self.frozen = (0..self.width * self.height)
+                        .map(|i| self.is_frozen(source(i % self.width, dx, width), source(i / self.width, dy, height)))
+                        .collect();
+                }
+            }
+        }
+    }
+
+    /// Pega `other` con su esquina en (`dx`, `dy`). El universo crece si hace
+    /// falta para que entre entero; si crece hacia la izquierda o hacia
+    /// arriba, las células de `self` se desplazan lo mismo.
+    pub fn merge(&mut self, other: &GameOfLife, dx: i64, dy: i64, policy: MergePolicy) -> Result<(), Box<dyn std::error::Error>> {
+        let (min_x, min_y) = (dx.min(0), dy.min(0));
+        let width = (self.width as i64).max(dx + other.width as i64) - min_x;
+        let height = (self.height as i64).max(dy + other.height as i64) - min_y;
+        let mut merged = self.region(min_x, min_y, width as usize, height as usize);
+
+        let (x0, y0) = ((dx - min_x) as usize, (dy - min_y) as usize);
+        for y in 0..other.height {
+            for x in 0..other.width {
+                let (state, target) = (other.grid.get(x, y), (x0 + x, y0 + y));
+                match (policy, state) {
+                    (MergePolicy::Overwrite, _) => merged.grid.set(target.0, target.1, state),
+                    (_, CellState::Dead) => {}
+                    (MergePolicy::Error, _) if merged.grid.is_alive(target.0, target.1) => {
+                        return Err(format!("los universos se superponen en ({}, {})", target.0, target.1).into());
+                    }
+                    _ => merged.grid.set(target.0, target.1, CellState::Alive),
+                }
+            }
+        }
+        *self = merged;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alive(game: &GameOfLife) -> Vec<(usize, usize)> {
+        (0..game.height()).flat_map(|y| (0..game.width()).map(move |x| (x, y))).filter(|&(x, y)| game.is_alive(x, y)).collect()
+    }
+
+    #[test]
+    fn remove_stamp_restores_an_untouched_stamp() {
+        let mut game = GameOfLife::new(12, 12);
+        game.set_cell(1, 1, CellState::Alive);
+        let before = alive(&game);
+        let stamp = KnownPattern::Glider.stamp(&mut game, 3, 3);
+        assert_eq!(stamp.len(), 5);
+        assert_eq!(game.remove_stamp(stamp), 5);
+        assert_eq!(alive(&game), before);
+    }
+
+    #[test]
+    fn remove_stamp_keeps_the_overwritten_part() {
+        // Un bloque de 2×2 estampado sobre una célula que ya estaba viva
+        let mut game = GameOfLife::new(8, 8);
+        game.set_cell(2, 2, CellState::Alive);
+        let stamp = game.stamp(2, 2, &[(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(stamp.len(), 3);
+
+        // Se pinta encima: una célula del patrón se apaga y otra de al lado
+        // se enciende
+        game.set_cell(3, 2, CellState::Dead);
+        game.set_cell(4, 2, CellState::Alive);
+
+        assert_eq!(game.remove_stamp(stamp), 2);
+        assert_eq!(alive(&game), vec![(2, 2), (4, 2)]);
+    }
+
+    #[test]
+    fn rotated_gliders_travel_along_every_diagonal() {
+        let mut directions = Vec::new();
+        for transform in Transform::ALL {
+            let mut game = GameOfLife::new(32, 32);
+            let stamp = KnownPattern::Glider.stamp_transformed(&mut game, CellPos::new(14, 14), transform);
+            assert_eq!(stamp.len(), 5);
+            let cycle = crate::detection::find_cycle(&game, 8).unwrap();
+            assert_eq!(cycle.period, 4);
+            let (dx, dy) = cycle.displacement;
+            assert_eq!((dx.abs(), dy.abs()), (1, 1));
+            directions.push((dx, dy));
+        }
+        // Los 4 giros llevan el planeador por las 4 diagonales
+        let mut turns = directions[..4].to_vec();
+        turns.sort();
+        turns.dedup();
+        assert_eq!(turns.len(), 4);
+        // Sin transformación se estampa igual que con `stamp`
+        let mut plain = GameOfLife::new(32, 32);
+        KnownPattern::Glider.stamp(&mut plain, 14, 14);
+        let mut identity = GameOfLife::new(32, 32);
+        KnownPattern::Glider.stamp_transformed(&mut identity, CellPos::new(14, 14), Transform::default());
+        assert_eq!(alive(&plain), alive(&identity));
+    }
+}
@@ -1,342 +1,816 @@
-use minifb::{Key, Window, WindowOptions};
-use rand::Rng;
-use std::time::Duration;
-use gif::{Frame, Encoder, Repeat};
-use std::fs::File;
-
-// ===== CONFIGURACIÓN =====
-const WIDTH: usize = 100;
-const HEIGHT: usize = 100;
-const SCALE: usize = 8;
-const WINDOW_WIDTH: usize = WIDTH * SCALE;
-const WINDOW_HEIGHT: usize = HEIGHT * SCALE;
-const MAX_FRAMES: u32 = 200;
-const FPS: u64 = 10; // Frames por segundo
-
-// ===== TIPOS =====
-#[derive(Clone, Copy, PartialEq, Debug)]
-pub enum CellState {
-    Dead,
-    Alive,
-}
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::path::{Path, PathBuf};
+
+use lab2::config::{self, Layers};
+use lab2::coupled::{CoupledLife, CoupledRule, LayerView};
+use lab2::engine::EngineKind;
+use lab2::falling_sand::FallingSand;
+use lab2::margolus::{MargolusLife, MargolusRule};
+use lab2::reaction_diffusion::{GrayScottPreset, ReactionDiffusion};
+use lab2::results::{self, Query, ResultsDb};
+use lab2::rle::RlePattern;
+use lab2::rule_search::{SearchConfig, SearchMetric};
+use lab2::scenario::{self, Scenario};
+use lab2::second_order::{SecondOrderLife, SecondOrderRule};
+use lab2::snapshot::Snapshot;
+use lab2::totalistic::{MultiStateLife, TotalisticRule};
+use lab2::turmite::{Turmite, TurmiteWorld};
+use lab2::{GameOfLife, KnownPattern, MergePolicy};
+use lab2::favorites::Favorite;
+use lab2::commands::{
+    run_demo, run_explorer, run_export, run_favorites_list, run_import, run_methuselah_search, run_montage, run_rule_search,
+    run_show, run_showcase, run_state_list, run_tutorial,
+};
+use lab2::options::{ExportOptions, RunOptions, UniverseOptions, parse_point, parse_probability};
+use lab2::runner::{place_rle_file, run_backend, run_engine, run_player, run_simulation};
+use lab2::{detection, favorites, methuselah, wizard};
+
+// ===== LÍNEA DE COMANDOS =====
+#[derive(Parser)]
+#[command(name = "lab2", about = "Conway's Game of Life")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    universe: UniverseOptions,
+
+    #[command(flatten)]
+    run: RunOptions,
 
-// ===== ESTRUCTURA PRINCIPAL =====
-pub struct GameOfLife {
-    grid: Vec<Vec<CellState>>,
-    width: usize,
-    height: usize,
+    #[command(flatten)]
+    export: ExportOptions,
+
+    /// Guion JSON de la ejecución: keyframes de la cámara al exportar
+    /// grabaciones con play y anotaciones en la ventana y los GIF
+    #[arg(long, global = true, value_name = "ARCHIVO")]
+    scenario: Option<PathBuf>,
+
+    /// Archivo TOML con valores para las opciones (por defecto lab2.toml,
+    /// si existe). Las variables LAB2_* y la línea de comandos tienen prioridad.
+    #[arg(long, global = true, value_name = "ARCHIVO")]
+    config: Option<PathBuf>,
 }
 
-// ===== IMPLEMENTACIÓN DEL JUEGO =====
-impl GameOfLife {
-    pub fn new(width: usize, height: usize) -> Self {
-        Self {
-            grid: vec![vec![CellState::Dead; width]; height],
-            width,
-            height,
-        }
-    }
 
-    ///Inicializa el grid con patrones aleatorios y conocidos
-    pub fn initialize(&mut self) {
-        self.clear_grid();
-        self.add_random_cells(0.15); // 15% de probabilidad inicial
-        self.add_known_patterns();
-    }
+#[derive(Subcommand)]
+enum Command {
+    /// Reproduce una ejecución guardada sin volver a generar células aleatorias
+    Play {
+        /// Archivo .lab2 creado con --record
+        file: PathBuf,
 
-    ///Limpia todo el grid
-    fn clear_grid(&mut self) {
-        for row in &mut self.grid {
-            for cell in row {
-                *cell = CellState::Dead;
-            }
-        }
-    }
+        /// Reexporta la ejecución completa a un GIF
+        #[arg(long, value_name = "ARCHIVO")]
+        export: Option<PathBuf>,
 
-    ///Células aleatorias al grid
-    fn add_random_cells(&mut self, probability: f64) {
-        let mut rng = rand::thread_rng();
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if rng.gen_bool(probability) {
-                    self.grid[y][x] = CellState::Alive;
-                }
-            }
-        }
-    }
+        /// Píxeles por célula en el GIF reexportado
+        #[arg(long, default_value_t = 1)]
+        scale: usize,
+    },
+    /// Importa un GIF exportado y continúa la simulación desde su último frame
+    Import {
+        /// GIF de dos colores generado por este programa
+        file: PathBuf,
 
-    ///Patrones conocidos
-    fn add_known_patterns(&mut self) {
-        self.add_glider(15, 15);
-        self.add_glider(70, 10);
-        self.add_block(5, 5);
-        self.add_block(90, 90);
-        self.add_blinker(25, 25);
-        self.add_toad(35, 35);
-        self.add_beacon(45, 45);
-        self.add_beehive(60, 60);        
-        self.add_lightweight_spaceship(10, 50);
-        self.add_pulsar(50, 20);
-    }
+        /// Píxeles por célula en el GIF importado
+        #[arg(long, default_value_t = 1)]
+        scale: usize,
 
-    /// Avanza una generación aplicando las reglas de Conway
-    pub fn next_generation(&mut self) {
-        let mut new_grid = self.grid.clone();
-        
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let neighbors = self.count_live_neighbors(x, y);
-                new_grid[y][x] = self.apply_rules(self.grid[y][x], neighbors);
-            }
-        }
-        
-        self.grid = new_grid;
-    }
+        /// Solo verifica que el GIF coincida con la evolución del motor actual
+        #[arg(long)]
+        verify: bool,
+    },
+    /// Simula dos capas acopladas (por ejemplo presas y depredadores)
+    Coupled {
+        /// Regla que conecta las dos capas
+        #[arg(long, value_enum, default_value_t = CoupledPreset::PredatorPrey)]
+        rule: CoupledPreset,
 
-    /// Aplica las reglas de Conway a una célula
-    fn apply_rules(&self, current_state: CellState, neighbors: usize) -> CellState {
-        match (current_state, neighbors) {
-            // Una célula viva con menos de 2 vecinos muere (soledad)
-            (CellState::Alive, n) if n < 2 => CellState::Dead,
-            // Una célula viva con 2 o 3 vecinos sobrevive
-            (CellState::Alive, 2) | (CellState::Alive, 3) => CellState::Alive,
-            // Una célula viva con más de 3 vecinos muere (sobrepoblación)
-            (CellState::Alive, n) if n > 3 => CellState::Dead,
-            // Una célula muerta con exactamente 3 vecinos nace
-            (CellState::Dead, 3) => CellState::Alive,
-            // Cualquier otro caso mantiene el estado
-            (state, _) => state,
-        }
-    }
+        /// Cómo mostrar las capas
+        #[arg(long, value_enum, default_value_t = LayerView::Blend)]
+        view: LayerView,
 
-    /// Cuenta los vecinos vivos de una célula
-    fn count_live_neighbors(&self, x: usize, y: usize) -> usize {
-        let mut count = 0;
-        
-        for dy in -1..=1i32 {
-            for dx in -1..=1i32 {
-                if dx == 0 && dy == 0 { continue; }
-                
-                let nx = x as i32 + dx;
-                let ny = y as i32 + dy;
-                
-                if self.is_valid_position(nx, ny) && self.is_alive(nx as usize, ny as usize) {
-                    count += 1;
-                }
-            }
-        }
-        
-        count
-    }
+        /// Probabilidad inicial de célula viva en cada capa
+        #[arg(long, num_args = 2, default_values_t = [0.25, 0.05], value_name = "P")]
+        densities: Vec<f64>,
+    },
+    /// Reacción-difusión de Gray-Scott
+    GrayScott {
+        /// Parámetros clásicos de alimentación y eliminación
+        #[arg(long, value_enum, default_value_t = GrayScottPreset::Coral)]
+        preset: GrayScottPreset,
 
-    /// Verifica si una posición es válida dentro del grid
-    fn is_valid_position(&self, x: i32, y: i32) -> bool {
-        x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32
-    }
+        /// Reemplaza la tasa de alimentación del preset
+        #[arg(long)]
+        feed: Option<f32>,
 
-    /// Verifica si una célula está viva
-    fn is_alive(&self, x: usize, y: usize) -> bool {
-        self.grid[y][x] == CellState::Alive
-    }
+        /// Reemplaza la tasa de eliminación del preset
+        #[arg(long)]
+        kill: Option<f32>,
 
-    /// Obtiene el color de una célula para renderizado
-    pub fn get_color(&self, x: usize, y: usize) -> u32 {
-        if x < self.width && y < self.height {
-            match self.grid[y][x] {
-                CellState::Alive => 0x00FFFFFF, // Blanco
-                CellState::Dead => 0x00001122,  // Azul oscuro
-            }
-        } else {
-            0x00000000 // Negro para posiciones inválidas
-        }
-    }
+        /// Iteraciones del modelo por cada frame
+        #[arg(long, default_value_t = 10, value_name = "N")]
+        iterations: usize,
 
-    /// Renderiza el juego en un buffer de píxeles
-    pub fn render(&self, buffer: &mut Vec<u32>) {
-        for y in 0..WINDOW_HEIGHT {
-            for x in 0..WINDOW_WIDTH {
-                let grid_x = x / SCALE;
-                let grid_y = y / SCALE;
-                let color = self.get_color(grid_x, grid_y);
-                buffer[y * WINDOW_WIDTH + x] = color;
-            }
-        }
-    }
+        /// Cantidad de semillas iniciales
+        #[arg(long, default_value_t = 12, value_name = "N")]
+        seeds: usize,
+    },
+    /// Arena que cae: pinta con el mouse (1 arena, 2 agua, 3 pared, 4 fuego, 0 borrar)
+    Sand {
+        /// Empieza con el grid vacío en lugar de la escena de ejemplo
+        #[arg(long)]
+        empty: bool,
+    },
+    /// Autómata de bloques 2×2 con vecindad de Margolus (Critters, billar, Tron)
+    Margolus {
+        /// Regla predefinida
+        #[arg(long, value_enum, default_value_t = MargolusPreset::Critters)]
+        rule: MargolusPreset,
 
-    /// Convierte el grid actual a datos para frame del GIF
-    pub fn to_gif_frame_data(&self) -> Vec<u8> {
-        let mut frame_data = vec![0; WIDTH * HEIGHT];
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                let index = y * WIDTH + x;
-                frame_data[index] = match self.grid[y][x] {
-                    CellState::Alive => 1, // Índice del color blanco en la paleta
-                    CellState::Dead => 0,  // Índice del color azul en la paleta
-                };
-            }
-        }
-        frame_data
-    }
+        /// Tabla propia de 16 valores separados por comas (reemplaza --rule)
+        #[arg(long, value_parser = MargolusRule::parse)]
+        table: Option<MargolusRule>,
+
+        /// Lado del cuadrado central que se llena al azar
+        #[arg(long, default_value_t = 30, value_name = "N")]
+        size: usize,
+
+        /// Probabilidad de célula viva dentro del cuadrado inicial
+        #[arg(long, default_value_t = 0.3, value_parser = parse_probability)]
+        density: f64,
+    },
+    /// Variantes de segundo orden: cada generación depende también de la anterior
+    SecondOrder {
+        #[arg(long, value_enum, default_value_t = SecondOrderRule::Reversible)]
+        rule: SecondOrderRule,
+
+        /// Probabilidad inicial de célula viva
+        #[arg(long, default_value_t = 0.15, value_parser = parse_probability)]
+        density: f64,
+    },
+    /// Autómata totalístico de varios estados con una regla dada
+    Totalistic {
+        /// Regla en la forma estados/tabla que imprime `explore`
+        #[arg(long, value_parser = TotalisticRule::parse)]
+        rule: TotalisticRule,
+
+        /// Probabilidad inicial de célula viva
+        #[arg(long, default_value_t = 0.3, value_parser = parse_probability)]
+        density: f64,
+    },
+    /// Turmites: máquinas de estados que leen el color de su celda,
+    /// escriben otro, giran y avanzan, con la regla escrita como en Golly
+    Turmite {
+        /// Regla en la notación de Golly, por ejemplo '{{{1,2,0},{0,8,0}}}'
+        /// (la hormiga de Langton)
+        #[arg(required_unless_present = "file", value_parser = Turmite::parse)]
+        rule: Option<Turmite>,
+
+        /// Lee la regla de un archivo (las líneas con # son comentarios)
+        #[arg(long, conflicts_with = "rule", value_name = "ARCHIVO")]
+        file: Option<PathBuf>,
+
+        /// Turmites que empiezan a la vez, en una fila en el centro
+        #[arg(long, default_value_t = 1, value_name = "N")]
+        count: usize,
+
+        /// Pasos de los turmites por cada frame
+        #[arg(long, default_value_t = 1, value_name = "N")]
+        steps_per_frame: usize,
+    },
+    /// Prueba reglas totalísticas al azar y muestra las más interesantes en
+    /// una galería; un clic en una miniatura la abre en grande
+    Explore {
+        /// Estados por célula (3 o 4 dan reglas más variadas que Life)
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(2..=9))]
+        states: u8,
+
+        /// Reglas que se prueban antes de elegir las mejores
+        #[arg(long, default_value_t = 64, value_name = "N")]
+        samples: usize,
+
+        /// Probabilidad de que una entrada de la tabla lleve a un estado vivo
+        #[arg(long, default_value_t = 0.3, value_parser = parse_probability)]
+        lambda: f64,
+
+        /// Probabilidad inicial de célula viva en cada prueba
+        #[arg(long, default_value_t = 0.3, value_parser = parse_probability)]
+        density: f64,
+    },
+    /// Evoluciona reglas totalísticas con un algoritmo genético, sin ventana
+    Evolve {
+        /// Qué se quiere maximizar
+        #[arg(long, value_enum, default_value_t = SearchMetric::Interest)]
+        metric: SearchMetric,
+
+        /// Estados por célula
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(2..=9))]
+        states: u8,
+
+        /// Reglas en cada generación
+        #[arg(long, default_value_t = 32, value_name = "N")]
+        population: usize,
+
+        /// Generaciones del algoritmo genético
+        #[arg(long, default_value_t = 30, value_name = "N")]
+        generations: usize,
+
+        /// Probabilidad de mutar cada entrada de la tabla de un hijo
+        #[arg(long, default_value_t = 0.05, value_parser = parse_probability)]
+        mutation: f64,
+
+        /// Probabilidad de que una entrada nueva de la tabla lleve a un estado vivo
+        #[arg(long, default_value_t = 0.3, value_parser = parse_probability)]
+        lambda: f64,
+
+        /// Probabilidad inicial de célula viva en cada evaluación
+        #[arg(long, default_value_t = 0.3, value_parser = parse_probability)]
+        density: f64,
+
+        /// Hilos que evalúan reglas (por defecto, uno por núcleo)
+        #[arg(long, value_name = "N")]
+        workers: Option<usize>,
+
+        /// Carpeta donde se guardan las mejores reglas y un GIF de cada una
+        #[arg(long, default_value = "rule_search", value_name = "CARPETA")]
+        out: PathBuf,
+
+        /// Cuántas de las mejores reglas se guardan
+        #[arg(long, default_value_t = 5, value_name = "N")]
+        keep: usize,
+    },
+    /// Busca patrones pequeños que tarden mucho en estabilizarse (matusalenes)
+    Methuselah {
+        /// Células vivas como máximo dentro de la caja de 16×16 (de 3 a 256)
+        #[arg(
+            long,
+            default_value_t = 20,
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(methuselah::MIN_CELLS as i64..=(methuselah::BOX_SIZE * methuselah::BOX_SIZE) as i64)
+        )]
+        max_cells: u32,
+
+        /// Pasos de recocido simulado de cada cadena
+        #[arg(long, default_value_t = 300, value_name = "N")]
+        iterations: usize,
+
+        /// Cadenas de búsqueda en paralelo (por defecto, una por núcleo)
+        #[arg(long, value_name = "N")]
+        workers: Option<usize>,
+
+        /// Guarda el mejor patrón en un archivo RLE
+        #[arg(long, value_name = "ARCHIVO")]
+        out: Option<PathBuf>,
+    },
+    /// Exporta un patrón conocido o de un archivo RLE como GIF recortado a
+    /// su tamaño y con su nombre y período: los osciladores dan vueltas sin
+    /// saltos y las naves dejan una estela con su trayectoria
+    Showcase {
+        /// Patrón a mostrar
+        #[arg(long, value_enum, required_unless_present = "rle")]
+        pattern: Option<KnownPattern>,
+
+        /// Lee el patrón de un archivo .rle o .cells
+        #[arg(long, conflicts_with = "pattern", value_name = "ARCHIVO")]
+        rle: Option<PathBuf>,
+
+        /// Vueltas completas del ciclo que se graban
+        #[arg(long, default_value_t = 3, value_name = "N")]
+        periods: usize,
+
+        /// Píxeles por célula
+        #[arg(long, default_value_t = 10)]
+        scale: usize,
+
+        /// GIF de salida
+        #[arg(long, short, default_value = "showcase.gif", value_name = "ARCHIVO")]
+        output: PathBuf,
+    },
+    /// Combina varias grabaciones .lab2 o GIF exportados en un solo GIF en cuadrícula
+    Montage {
+        /// Ejecuciones a combinar, cada una con su nombre de archivo como etiqueta
+        #[arg(required = true, num_args = 1..)]
+        inputs: Vec<PathBuf>,
+
+        /// GIF de salida
+        #[arg(long, short, default_value = "montage.gif", value_name = "ARCHIVO")]
+        output: PathBuf,
+
+        /// Ejecuciones por fila (por defecto, cuadrícula cuadrada)
+        #[arg(long, value_name = "N")]
+        columns: Option<usize>,
+
+        /// Píxeles por célula en el GIF de salida
+        #[arg(long, default_value_t = 2)]
+        scale: usize,
+
+        /// Píxeles por célula de los GIF de entrada
+        #[arg(long, default_value_t = 1, value_name = "N")]
+        input_scale: usize,
+    },
+    /// Combina dos estados guardados (.snap o .snapd) en uno nuevo
+    Merge {
+        /// Primer universo; su generación es la del resultado
+        a: PathBuf,
+
+        /// Universo que se pega encima del primero
+        b: PathBuf,
+
+        /// Posición de la esquina de `b` dentro de `a`, como `x,y`
+        #[arg(long, default_value = "0,0", value_name = "X,Y", allow_hyphen_values = true, value_parser = parse_point)]
+        offset: (i64, i64),
+
+        /// Qué hacer donde los universos se superponen
+        #[arg(long, value_enum, default_value_t = MergePolicy::Or)]
+        policy: MergePolicy,
+
+        /// Estado .snap de salida
+        #[arg(long, default_value = "merged.snap", value_name = "ARCHIVO")]
+        out: PathBuf,
+    },
+    /// Calcula cuánto tarda el estado inicial en entrar en un ciclo y su período
+    Period {
+        /// Generaciones máximas de transitorio más período
+        #[arg(long, default_value_t = 100_000, value_name = "N")]
+        limit: usize,
+
+        /// Analiza un solo patrón conocido en lugar del estado inicial normal
+        #[arg(long, value_enum)]
+        pattern: Option<KnownPattern>,
+    },
+    /// Recorre escenas de muestra (cañones, púlsares, replicadores de
+    /// HighLife, Brian's Brain, Gray-Scott) de unos 10 segundos cada una;
+    /// Espacio pasa a la siguiente
+    Demo,
+    /// Tutorial guiado: colocar un planeador, correr, pausar, cambiar la
+    /// regla y exportar, paso a paso en la ventana
+    Tutorial,
+    /// Pregunta el tamaño, la regla, la densidad y el GIF de salida y
+    /// escribe un lab2.toml para empezar
+    Init {
+        /// Archivo de configuración a crear
+        #[arg(long, short, default_value = config::DEFAULT_CONFIG_FILE, value_name = "ARCHIVO")]
+        output: PathBuf,
+
+        /// Reemplaza el archivo si ya existe
+        #[arg(long)]
+        force: bool,
+    },
+    /// Herramientas para el archivo de configuración y los guiones de --scenario
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Dibuja un patrón (.rle o .cells) o un estado guardado (.snap, .snapd) en la
+    /// terminal, ajustado a su tamaño
+    Show {
+        file: PathBuf,
+    },
+    /// Exporta sin ventana el GIF de un guion que define los patrones
+    /// iniciales, las anotaciones y la cámara
+    Export {
+        /// Guion .json o .toml
+        #[arg(value_name = "GUION")]
+        file: PathBuf,
+
+        /// Vuelve a exportar cada vez que cambia el guion o alguno de sus
+        /// patrones (Ctrl+C para salir)
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Herramientas para los estados guardados con --snapshot-every
+    State {
+        #[command(subcommand)]
+        action: StateAction,
+    },
+    /// Consultas sobre los resultados guardados con --results
+    Results {
+        #[command(subcommand)]
+        action: ResultsAction,
+    },
+    /// Reglas y sopas guardadas con K en el modo descubrimiento o con
+    /// `favorites add`, que se cargan con --favorite
+    Favorites {
+        #[command(subcommand)]
+        action: FavoritesAction,
+    },
 }
 
-// ===== PATRONES CONOCIDOS =====
-impl GameOfLife {
-    /// Agrega un Glider (se mueve diagonalmente)
-    fn add_glider(&mut self, x: usize, y: usize) {
-        let pattern = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
-        self.add_pattern(x, y, &pattern);
-    }
+#[derive(Subcommand)]
+enum ResultsAction {
+    /// Muestra una consulta sobre la base de resultados
+    Query {
+        /// Base de resultados
+        #[arg(long, default_value = results::RESULTS_FILE, value_name = "ARCHIVO")]
+        db: PathBuf,
 
-    /// Agrega un Block (estructura estática)
-    fn add_block(&mut self, x: usize, y: usize) {
-        let pattern = [(0, 0), (0, 1), (1, 0), (1, 1)];
-        self.add_pattern(x, y, &pattern);
-    }
+        #[command(subcommand)]
+        query: ResultsQuery,
+    },
+}
 
-    /// Agrega un Blinker (oscilador período 2)
-    fn add_blinker(&mut self, x: usize, y: usize) {
-        let pattern = [(0, 0), (1, 0), (2, 0)];
-        self.add_pattern(x, y, &pattern);
-    }
+#[derive(Subcommand)]
+enum ResultsQuery {
+    /// Las ejecuciones que más tardaron en estabilizarse, con su semilla
+    Longest {
+        #[arg(long, default_value_t = 10, value_name = "N")]
+        limit: usize,
+    },
+    /// Población final y vida promedio para cada densidad inicial
+    ByDensity,
+    /// Cualquier consulta SQL que solo lea (SELECT) las tablas
+    /// ejecuciones, matusalenes y reglas
+    Sql {
+        statement: String,
+    },
+}
 
-    /// Agrega un Toad (oscilador período 2)
-    fn add_toad(&mut self, x: usize, y: usize) {
-        let pattern = [(1, 0), (2, 0), (3, 0), (0, 1), (1, 1), (2, 1)];
-        self.add_pattern(x, y, &pattern);
-    }
+#[derive(Subcommand)]
+enum FavoritesAction {
+    /// Muestra los favoritos guardados
+    List,
+    /// Guarda como favorito la regla, la semilla, la densidad, el tamaño y
+    /// el guion de la línea de comandos
+    Add {
+        /// Nombre para cargarlo con --favorite
+        name: String,
+    },
+}
 
-    /// Agrega un Beacon (oscilador período 2)
-    fn add_beacon(&mut self, x: usize, y: usize) {
-        let pattern = [(0, 0), (0, 1), (1, 0), (2, 3), (3, 2), (3, 3)];
-        self.add_pattern(x, y, &pattern);
-    }
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Comprueba guiones .json o .toml y archivos de configuración
+    /// (lab2.toml o el de --config) sin ejecutar nada; los errores indican
+    /// archivo:línea:columna. Sin archivos, revisa la configuración en uso.
+    Validate {
+        files: Vec<PathBuf>,
+    },
+    /// Muestra las opciones que cambió la configuración y de dónde salió
+    /// cada una (archivo, entorno o línea de comandos)
+    Show {
+        /// Muestra todas las opciones, también las predeterminadas
+        #[arg(long)]
+        resolved: bool,
+    },
+    /// Escribe el esquema JSON de los guiones (para autocompletar en el editor)
+    Schema {
+        /// Archivo de salida (por defecto, la salida estándar)
+        #[arg(long, short, value_name = "ARCHIVO")]
+        output: Option<PathBuf>,
+    },
+}
 
-    /// Agrega un Beehive (estructura estática)
-    fn add_beehive(&mut self, x: usize, y: usize) {
-        let pattern = [(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)];
-        self.add_pattern(x, y, &pattern);
-    }
+#[derive(Subcommand)]
+enum StateAction {
+    /// Muestra la generación, el tamaño y el tipo de cada estado (.snap o
+    /// .snapd) de una carpeta
+    List {
+        /// Carpeta con los estados
+        dir: PathBuf,
 
-    /// Agrega una Lightweight Spaceship (nave espacial)
-    fn add_lightweight_spaceship(&mut self, x: usize, y: usize) {
-        let pattern = [
-            (0, 0), (3, 0),
-            (4, 1),
-            (0, 2), (4, 2),
-            (1, 3), (2, 3), (3, 3), (4, 3)
-        ];
-        self.add_pattern(x, y, &pattern);
-    }
+        /// Escribe al lado de cada estado una miniatura .thumb.png
+        #[arg(long)]
+        thumbnails: bool,
+
+        /// Dibuja cada estado en la terminal con colores ANSI
+        #[arg(long)]
+        preview: bool,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum MargolusPreset {
+    Critters,
+    BilliardBall,
+    Tron,
+}
 
-    /// Agrega un Pulsar (oscilador período 3)
-    fn add_pulsar(&mut self, x: usize, y: usize) {
-        let pattern = [
-            // Cruz superior
-            (2, 0), (3, 0), (4, 0), (8, 0), (9, 0), (10, 0),
-            (0, 2), (5, 2), (7, 2), (12, 2),
-            (0, 3), (5, 3), (7, 3), (12, 3),
-            (0, 4), (5, 4), (7, 4), (12, 4),
-            (2, 5), (3, 5), (4, 5), (8, 5), (9, 5), (10, 5),
-            // Cruz inferior (espejo)
-            (2, 7), (3, 7), (4, 7), (8, 7), (9, 7), (10, 7),
-            (0, 8), (5, 8), (7, 8), (12, 8),
-            (0, 9), (5, 9), (7, 9), (12, 9),
-            (0, 10), (5, 10), (7, 10), (12, 10),
-            (2, 12), (3, 12), (4, 12), (8, 12), (9, 12), (10, 12),
-        ];
-        self.add_pattern(x, y, &pattern);
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum CoupledPreset {
+    Independent,
+    PredatorPrey,
+}
+
+// ===== FUNCIÓN PRINCIPAL =====
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let command = Cli::command();
+    let layers = Layers::resolve(&command, std::env::args_os().collect());
+    let matches = command.clone().get_matches_from(&layers.args);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if !matches!(cli.command, Some(Command::Config { .. })) && !layers.problems().is_empty() {
+        for problem in layers.problems() {
+            eprintln!("{}", problem);
+        }
+        return Err("la configuración tiene opciones inválidas (lab2 config validate la revisa sin ejecutar nada)".into());
     }
+    cli.export.apply_profile()?;
+    if let Some(name) = &cli.run.favorite {
+        let favorite = favorites::find(&cli.run.favorites, name)?;
+        println!("Favorito {}: {}", favorite.name, favorite.command_line());
+        cli.run.rule = favorite.rule()?;
+        cli.universe.seed = Some(favorite.seed);
+        cli.universe.density = favorite.density;
+        cli.universe.width = favorite.width;
+        cli.universe.height = favorite.height;
+        if cli.scenario.is_none() {
+            cli.scenario = favorite.scenario;
+        }
+    }
+    // El resultado no depende de los hilos, así que si no se pueden crear
+    // la ejecución sigue con los que rayon ya tenga
+    if let Err(e) = rayon::ThreadPoolBuilder::new().num_threads(cli.universe.threads).build_global() {
+        println!("Aviso: no se pudieron preparar {} hilos para la simulación ({}); se usan los de rayon", cli.universe.threads, e);
+    }
+    let scenario = match &cli.scenario {
+        Some(path) => Scenario::load(path)?,
+        None => Scenario::default(),
+    };
 
-    /// Helper para añadir un patrón dado como coordenadas relativas
-    fn add_pattern(&mut self, base_x: usize, base_y: usize, pattern: &[(usize, usize)]) {
-        for &(dx, dy) in pattern {
-            let x = base_x + dx;
-            let y = base_y + dy;
-            if x < self.width && y < self.height {
-                self.grid[y][x] = CellState::Alive;
+    match cli.command {
+        Some(Command::Play { file, export, scale }) => {
+            run_player(&file, export.as_deref(), scale, &cli.universe, &scenario, &cli.export)
+        }
+        Some(Command::Import { file, scale, verify }) => {
+            run_import(&file, scale, verify, &cli.universe, &cli.run, &scenario, &cli.export)
+        }
+        Some(Command::Coupled { rule, view, densities }) => {
+            let rule = match rule {
+                CoupledPreset::Independent => CoupledRule::independent(),
+                CoupledPreset::PredatorPrey => CoupledRule::predator_prey(),
+            };
+            let mut coupled = CoupledLife::new(cli.universe.width, cli.universe.height, rule, view);
+            coupled.randomize([densities[0], densities[1]]);
+            run_backend(&mut coupled, "Capas acopladas", &cli.universe, &cli.export)
+        }
+        Some(Command::GrayScott { preset, feed, kill, iterations, seeds }) => {
+            let mut params = preset.params();
+            params.feed = feed.unwrap_or(params.feed);
+            params.kill = kill.unwrap_or(params.kill);
+            let mut reaction = ReactionDiffusion::new(cli.universe.width, cli.universe.height, params, iterations);
+            if let Some(colormap) = cli.universe.colormap {
+                reaction = reaction.with_gradient(colormap.gradient());
+            }
+            reaction.seed_squares(seeds, 6);
+            run_backend(&mut reaction, "Gray-Scott", &cli.universe, &cli.export)
+        }
+        Some(Command::Sand { empty }) => {
+            let mut sand = FallingSand::new(cli.universe.width, cli.universe.height);
+            if !empty {
+                sand.add_demo_scene();
+            }
+            run_backend(&mut sand, "Arena - clic para pintar, 1-4 y 0 eligen elemento", &cli.universe, &cli.export)
+        }
+        Some(Command::Margolus { rule, table, size, density }) => {
+            let rule = table.unwrap_or_else(|| match rule {
+                MargolusPreset::Critters => MargolusRule::critters(),
+                MargolusPreset::BilliardBall => MargolusRule::billiard_ball(),
+                MargolusPreset::Tron => MargolusRule::tron(),
+            });
+            if !rule.is_reversible() {
+                println!("Aviso: la tabla no es una permutación, así que la regla no es reversible");
+            }
+            let mut margolus = MargolusLife::new(cli.universe.width, cli.universe.height, rule);
+            margolus.randomize_center(size, density);
+            run_backend(&mut margolus, "Margolus", &cli.universe, &cli.export)
+        }
+        Some(Command::SecondOrder { rule, density }) => {
+            let mut game = GameOfLife::new(cli.universe.width, cli.universe.height);
+            game.add_random_cells_with(density, &mut cli.universe.rng());
+            let mut second_order = SecondOrderLife::new(game, rule);
+            run_backend(&mut second_order, "Segundo orden", &cli.universe, &cli.export)
+        }
+        Some(Command::Totalistic { rule, density }) => {
+            let mut totalistic = MultiStateLife::new(cli.universe.width, cli.universe.height, rule);
+            totalistic.randomize(density, &mut cli.universe.rng());
+            run_backend(&mut totalistic, "Totalística", &cli.universe, &cli.export)
+        }
+        Some(Command::Turmite { rule, file, count, steps_per_frame }) => {
+            let turmite = match file {
+                Some(path) => {
+                    let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                    Turmite::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?
+                }
+                None => rule.ok_or("falta la regla del turmite")?,
+            };
+            println!("Turmite {}: {} estados, {} colores", turmite, turmite.states(), turmite.colors());
+            let mut world = TurmiteWorld::new(cli.universe.width, cli.universe.height, turmite, count.max(1))
+                .with_steps_per_frame(steps_per_frame);
+            run_backend(&mut world, "Turmite", &cli.universe, &cli.export)
+        }
+        Some(Command::Explore { states, samples, lambda, density }) => {
+            run_explorer(states, samples, lambda, density, &cli.universe, &cli.export)
+        }
+        Some(Command::Evolve {
+            metric, states, population, generations, mutation, lambda, density, workers, out, keep,
+        }) => {
+            let workers = workers.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+            let config = SearchConfig {
+                states, metric, population, generations, mutation_rate: mutation, lambda, density, workers,
+                pin_threads: cli.export.pin_threads,
+            };
+            run_rule_search(&config, &out, keep, cli.run.results.as_deref(), &cli.universe, &cli.export)
+        }
+        Some(Command::Methuselah { max_cells, iterations, workers, out }) => {
+            let (width, height) = (cli.universe.width, cli.universe.height);
+            if width < methuselah::BOX_SIZE || height < methuselah::BOX_SIZE {
+                let size = methuselah::BOX_SIZE;
+                return Err(format!("methuselah necesita un universo de al menos {}x{} (--width y --height)", size, size).into());
+            }
+            let workers = workers.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+            let config = methuselah::SearchConfig {
+                max_cells: max_cells as usize, iterations, workers, pin_threads: cli.export.pin_threads, width, height,
+            };
+            run_methuselah_search(&config, out.as_deref(), cli.run.results.as_deref())
+        }
+        Some(Command::Showcase { pattern, rle, periods, scale, output }) => {
+            let (pattern, name) = match (pattern, rle) {
+                (_, Some(path)) => {
+                    let pattern = RlePattern::load(&path)?;
+                    let stem = path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+                    let name = pattern.name.clone().unwrap_or(stem);
+                    (pattern, name)
+                }
+                (Some(pattern), None) => (pattern.pattern(), pattern.name().to_string()),
+                (None, None) => unreachable!("clap exige --pattern o --rle"),
+            };
+            run_showcase(&pattern, &name, periods, scale, &output, &cli.export)
+        }
+        Some(Command::Montage { inputs, output, columns, scale, input_scale }) => {
+            run_montage(&inputs, &output, columns, scale, input_scale, &cli.export)
+        }
+        Some(Command::Merge { a, b, offset: (dx, dy), policy, out }) => {
+            let mut merged = Snapshot::load(&a)?;
+            merged.game.merge(&Snapshot::load(&b)?.game, dx, dy, policy)?;
+            merged.save(&out)?;
+            println!("Universo de {}x{} guardado en {}", merged.game.width(), merged.game.height(), out.display());
+            Ok(())
+        }
+        Some(Command::Period { limit, pattern }) => {
+            let mut game = GameOfLife::new(cli.universe.width, cli.universe.height);
+            match pattern {
+                Some(pattern) => pattern.place(&mut game),
+                None => game.initialize_with_seed(cli.universe.density, cli.universe.seed()),
+            }
+            match detection::find_cycle(&game, limit) {
+                Some(cycle) => println!(
+                    "Transitorio de {} generaciones, luego {}",
+                    cycle.transient,
+                    cycle.describe()
+                ),
+                None => println!("No se encontró un ciclo en {} generaciones", limit),
+            }
+            Ok(())
+        }
+        Some(Command::Demo) => run_demo(&cli.universe, &cli.export),
+        Some(Command::Tutorial) => run_tutorial(&cli.universe, &cli.run, &cli.export),
+        Some(Command::Init { output, force }) => run_init(&output, force),
+        Some(Command::Config { action: ConfigAction::Validate { files } }) => run_config_validate(&files, &command, layers.path()),
+        Some(Command::State { action: StateAction::List { dir, thumbnails, preview } }) => run_state_list(&dir, thumbnails, preview),
+        Some(Command::Show { file }) => run_show(&file),
+        Some(Command::Results { action: ResultsAction::Query { db, query } }) => {
+            if !db.exists() {
+                return Err(format!("{}: no existe (se crea con --results)", db.display()).into());
+            }
+            let query = match query {
+                ResultsQuery::Longest { limit } => Query::Longest(limit),
+                ResultsQuery::ByDensity => Query::ByDensity,
+                ResultsQuery::Sql { statement } => Query::Sql(statement),
+            };
+            print!("{}", ResultsDb::open_read_only(&db)?.query(&query)?);
+            Ok(())
+        }
+        Some(Command::Favorites { action: FavoritesAction::List }) => run_favorites_list(&cli.run.favorites),
+        Some(Command::Favorites { action: FavoritesAction::Add { name } }) => {
+            let favorite = Favorite {
+                name,
+                rule: cli.run.rule.to_string(),
+                seed: cli.universe.seed(),
+                density: cli.universe.density,
+                width: cli.universe.width,
+                height: cli.universe.height,
+                scenario: cli.scenario.clone(),
+            };
+            let name = favorites::append(&cli.run.favorites, &favorite)?;
+            println!("Guardado '{}' en {}: {}", name, cli.run.favorites.display(), favorite.command_line());
+            Ok(())
+        }
+        Some(Command::Export { file, watch }) => run_export(&file, watch, &mut cli.universe, &cli.run, &cli.export),
+        Some(Command::Config { action: ConfigAction::Show { resolved } }) => {
+            for line in layers.describe(&command, &matches, resolved) {
+                println!("{}", line);
+            }
+            Ok(())
+        }
+        Some(Command::Config { action: ConfigAction::Schema { output } }) => match output {
+            Some(path) => {
+                std::fs::write(&path, scenario::SCHEMA)?;
+                println!("Esquema guardado en {}", path.display());
+                Ok(())
+            }
+            None => {
+                print!("{}", scenario::SCHEMA);
+                Ok(())
+            }
+        },
+        None => {
+            let (game, first_generation) = match &cli.run.resume {
+                Some(path) => {
+                    let mut snapshot = Snapshot::load(path)?;
+                    println!("Continuando desde la generación {}", snapshot.generation);
+                    if let Some(seed) = cli.universe.seed {
+                        snapshot.game.set_seed(seed);
+                    }
+                    (snapshot.game, snapshot.generation)
+                }
+                None => {
+                    let mut game = GameOfLife::new(cli.universe.width, cli.universe.height);
+                    let seed = cli.universe.seed();
+                    // Para que --results guarde la semilla aunque se haya elegido sola
+                    cli.universe.seed = Some(seed);
+                    match &cli.run.rle {
+                        Some(path) => place_rle_file(&mut game, path, &cli.run)?,
+                        None => game.initialize_with_seed(cli.universe.density, seed),
+                    }
+                    // La misma semilla repite también las actualizaciones al azar
+                    game.set_seed(seed);
+                    (game, 0)
+                }
+            };
+            match cli.run.engine {
+                EngineKind::Naive => run_simulation(game, first_generation, &cli.universe, &cli.run, &scenario, &cli.export),
+                EngineKind::Hashlife | EngineKind::Sparse | EngineKind::Auto => run_engine(game, &cli.universe, &cli.run, &cli.export),
             }
         }
     }
 }
 
-// ===== GENERADOR DE GIF =====
-pub struct GifGenerator {
-    encoder: Encoder<File>,
-}
-
-impl GifGenerator {
-    pub fn new(filename: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let file = File::create(filename)?;
-        let color_map = [
-            0x00, 0x11, 0x22, // Azul oscuro
-            0xFF, 0xFF, 0xFF  // Blanco 
-        ];
-        
-        let mut encoder = Encoder::new(file, WIDTH as u16, HEIGHT as u16, &color_map)?;
-        encoder.write_extension(gif::ExtensionData::Repetitions(Repeat::Infinite))?;
-        
-        Ok(Self { encoder })
+// ===== CONFIGURACIÓN INICIAL =====
+fn run_init(path: &Path, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if path.exists() && !force {
+        return Err(format!("{} ya existe (--force lo reemplaza)", path.display()).into());
     }
-
-    pub fn add_frame(&mut self, frame_data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-        let mut frame = Frame::default();
-        frame.width = WIDTH as u16;
-        frame.height = HEIGHT as u16;
-        frame.buffer = std::borrow::Cow::Borrowed(frame_data);
-        frame.delay = (100 / FPS) as u16; // Convertir FPS a centisegundos
-        
-        self.encoder.write_frame(&frame)?;
-        Ok(())
+    let text = wizard::run(&mut std::io::stdin().lock(), &mut std::io::stdout())?;
+    // Lo que escribe el asistente tiene que pasar `config validate`
+    if let Err(problems) = config::validate_text(&Cli::command(), path, &text) {
+        return Err(format!("el asistente generó una configuración inválida:\n{}", problems.join("\n")).into());
     }
+    std::fs::write(path, text)?;
+    println!("Configuración guardada en {}; `lab2 config show` muestra lo que cambia", path.display());
+    Ok(())
 }
 
-// ===== FUNCIÓN PRINCIPAL =====
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Iniciando Conway's Game of Life...");
-    
-    // Inicializar juego
-    let mut game = GameOfLife::new(WIDTH, HEIGHT);
-    game.initialize();
-    
-    // Configurar GIF
-    let mut gif_generator = GifGenerator::new("conway_game_of_life.gif")?;
-    
-    // Configurar ventana
-    let mut window = Window::new(
-        "Conway's Game of Life - Presiona ESC para salir",
-        WINDOW_WIDTH,
-        WINDOW_HEIGHT,
-        WindowOptions::default(),
-    )?;
-    
-    window.limit_update_rate(Some(Duration::from_millis(1000 / FPS)));
-    
-    let mut buffer: Vec<u32> = vec![0; WINDOW_WIDTH * WINDOW_HEIGHT];
-    let mut generation = 0;
-    
-    println!("Generando {} frames del juego...", MAX_FRAMES);
-    
-    while window.is_open() 
-        && !window.is_key_down(Key::Escape) 
-        && generation < MAX_FRAMES 
-    {
-        // Actualizar simulación
-        game.next_generation();
-        generation += 1;
-        
-        // Renderizar en ventana
-        game.render(&mut buffer);
-        window.update_with_buffer(&buffer, WINDOW_WIDTH, WINDOW_HEIGHT)?;
-        
-        // Añadir frame al GIF
-        let frame_data = game.to_gif_frame_data();
-        gif_generator.add_frame(&frame_data)?;
-        
-        // Mostrar progreso cada 20 generaciones
-        if generation % 20 == 0 {
-            println!("Generación {}/{}", generation, MAX_FRAMES);
+// ===== GUIONES =====
+/// Valida todos los archivos aunque alguno falle, para ver todos los errores juntos
+fn run_config_validate(files: &[PathBuf], command: &clap::Command, config: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let files = match (files, config) {
+        ([], Some(config)) => vec![config.to_path_buf()],
+        ([], None) => return Err(format!("no hay {} en la carpeta actual; indica los archivos a revisar", config::DEFAULT_CONFIG_FILE).into()),
+        (files, _) => files.to_vec(),
+    };
+    // El archivo de configuración es lab2.toml, el que se usa con --config o
+    // cualquier TOML sin los campos de un guion (como los de `init -o`)
+    let is_config = |path: &Path| {
+        let is_toml = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        let has_scenario_fields = || {
+            let table = std::fs::read_to_string(path).ok().and_then(|text| text.parse::<toml::Table>().ok());
+            table.is_none_or(|table| scenario::FIELDS.iter().any(|field| table.contains_key(*field)))
+        };
+        path.file_name().is_some_and(|name| name == config::DEFAULT_CONFIG_FILE)
+            || config.is_some_and(|config| config == path)
+            || (is_toml && !has_scenario_fields())
+    };
+    let mut failed = 0;
+    for path in &files {
+        if is_config(path) {
+            match config::validate_file(command, path) {
+                Ok(options) => println!("{}: configuración válida ({} opciones)", path.display(), options),
+                Err(problems) => {
+                    for problem in problems {
+                        eprintln!("{}", problem);
+                    }
+                    failed += 1;
+                }
+            }
+            continue;
+        }
+        match Scenario::validate(path) {
+            Ok(scenario) => println!(
+                "{}: válido ({} keyframes, {} anotaciones, {} patrones)",
+                path.display(),
+                scenario.camera.len(),
+                scenario.annotations.len(),
+                scenario.patterns.len()
+            ),
+            Err(e) => {
+                eprintln!("{}", e);
+                failed += 1;
+            }
         }
     }
-    
-    Ok(())
-}
\ No newline at end of file
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!("{} de {} archivos no son válidos", failed, files.len()).into()),
+    }
+}
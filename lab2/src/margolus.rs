@@ -0,0 +1,199 @@
+use rand::Rng;
+
+use crate::simulation::Simulation;
+
+// Cada bloque de 2×2 se codifica en 4 bits:
+//   bit 0 = arriba izquierda, bit 1 = arriba derecha,
+//   bit 2 = abajo izquierda,  bit 3 = abajo derecha
+const TOP_LEFT: u8 = 1;
+const TOP_RIGHT: u8 = 2;
+const BOTTOM_LEFT: u8 = 4;
+const BOTTOM_RIGHT: u8 = 8;
+
+/// Gira un bloque 180°: cada esquina pasa a la opuesta
+fn rotate_180(block: u8) -> u8 {
+    let mut rotated = 0;
+    if block & TOP_LEFT != 0 { rotated |= BOTTOM_RIGHT; }
+    if block & BOTTOM_RIGHT != 0 { rotated |= TOP_LEFT; }
+    if block & TOP_RIGHT != 0 { rotated |= BOTTOM_LEFT; }
+    if block & BOTTOM_LEFT != 0 { rotated |= TOP_RIGHT; }
+    rotated
+}
+
+/// Regla de un autómata de bloques: a qué estado pasa cada uno de los 16
+/// estados posibles de un bloque de 2×2
+#[derive(Clone, Debug, PartialEq)]
+pub struct MargolusRule {
+    table: [u8; 16],
+}
+
+impl MargolusRule {
+    /// Lee una tabla de 16 números separados por comas, por ejemplo
+    /// `0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15`. La posición es el estado del
+    /// bloque y el valor su nuevo estado, con los bits en el orden
+    /// arriba-izquierda, arriba-derecha, abajo-izquierda, abajo-derecha.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let values: Vec<u8> = text
+            .split(',')
+            .map(|v| v.trim().parse::<u8>().map_err(|_| format!("valor inválido en la tabla: '{}'", v.trim())))
+            .collect::<Result<_, _>>()?;
+        if values.len() != 16 {
+            return Err(format!("la tabla debe tener 16 valores, tiene {}", values.len()));
+        }
+        if let Some(v) = values.iter().find(|&&v| v > 15) {
+            return Err(format!("los valores de la tabla van de 0 a 15: {}", v));
+        }
+        let mut table = [0; 16];
+        table.copy_from_slice(&values);
+        Ok(Self { table })
+    }
+
+    fn from_fn(f: impl Fn(u8) -> u8) -> Self {
+        let mut table = [0; 16];
+        for (block, next) in table.iter_mut().enumerate() {
+            *next = f(block as u8);
+        }
+        Self { table }
+    }
+
+    /// Critters: los bloques con exactamente dos células no cambian; los
+    /// demás se invierten y, si tenían tres células, además giran 180°
+    pub fn critters() -> Self {
+        Self::from_fn(|block| match block.count_ones() {
+            2 => block,
+            3 => rotate_180(block ^ 0xF),
+            _ => block ^ 0xF,
+        })
+    }
+
+    /// Modelo de bolas de billar: una bola sola cruza el bloque en diagonal y
+    /// dos bolas que chocan de frente salen por la otra diagonal
+    pub fn billiard_ball() -> Self {
+        Self::from_fn(|block| match block {
+            b if b.count_ones() == 1 => rotate_180(b),
+            b if b == TOP_LEFT | BOTTOM_RIGHT => TOP_RIGHT | BOTTOM_LEFT,
+            b if b == TOP_RIGHT | BOTTOM_LEFT => TOP_LEFT | BOTTOM_RIGHT,
+            b => b,
+        })
+    }
+
+    /// Tron: los bloques vacíos o llenos se invierten; los demás no cambian
+    pub fn tron() -> Self {
+        Self::from_fn(|block| match block {
+            0 | 0xF => block ^ 0xF,
+            _ => block,
+        })
+    }
+
+    pub fn apply(&self, block: u8) -> u8 {
+        self.table[block as usize]
+    }
+
+    /// Una regla es reversible si la tabla es una permutación de los 16 estados
+    pub fn is_reversible(&self) -> bool {
+        self.inverse().is_some()
+    }
+
+    /// Regla que deshace esta, si existe
+    pub fn inverse(&self) -> Option<MargolusRule> {
+        let mut inverse = [None; 16];
+        for (block, &next) in self.table.iter().enumerate() {
+            if inverse[next as usize].replace(block as u8).is_some() {
+                return None; // Dos estados llevan al mismo: no es una permutación
+            }
+        }
+        Some(Self { table: inverse.map(|b| b.expect("una permutación cubre los 16 estados")) })
+    }
+}
+
+/// Autómata de bloques con vecindad de Margolus: el grid se divide en
+/// bloques de 2×2 y la partición se desplaza una célula en diagonal en cada
+/// paso. Los bordes son envolventes.
+pub struct MargolusLife {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+    rule: MargolusRule,
+    inverse: Option<MargolusRule>,
+    /// Paso actual; en los pasos impares los bloques empiezan en (1, 1). Puede
+    /// ser negativo si se retrocede más allá del estado inicial.
+    step: i64,
+}
+
+impl MargolusLife {
+    /// El ancho y el alto se redondean a números pares
+    pub fn new(width: usize, height: usize, rule: MargolusRule) -> Self {
+        let (width, height) = (width & !1, height & !1);
+        let inverse = rule.inverse();
+        Self { width, height, cells: vec![false; width * height], rule, inverse, step: 0 }
+    }
+
+    /// Llena un cuadrado central de `size` células con la densidad indicada
+    pub fn randomize_center(&mut self, size: usize, density: f64) {
+        let mut rng = rand::thread_rng();
+        let (x0, y0) = (self.width.saturating_sub(size) / 2, self.height.saturating_sub(size) / 2);
+        for y in y0..(y0 + size).min(self.height) {
+            for x in x0..(x0 + size).min(self.width) {
+                self.cells[y * self.width + x] = rng.gen_bool(density);
+            }
+        }
+    }
+
+    /// Aplica `table` a todos los bloques de la partición con desplazamiento `offset`
+    fn apply_partition(&mut self, offset: usize, table: impl Fn(u8) -> u8) {
+        let (w, h) = (self.width, self.height);
+        for by in (offset..h + offset).step_by(2) {
+            for bx in (offset..w + offset).step_by(2) {
+                let corners = [
+                    (by % h) * w + bx % w,
+                    (by % h) * w + (bx + 1) % w,
+                    ((by + 1) % h) * w + bx % w,
+                    ((by + 1) % h) * w + (bx + 1) % w,
+                ];
+                let block = corners
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |acc, (bit, &i)| acc | (self.cells[i] as u8) << bit);
+                let next = table(block);
+                for (bit, &i) in corners.iter().enumerate() {
+                    self.cells[i] = next & (1 << bit) != 0;
+                }
+            }
+        }
+    }
+}
+
+impl Simulation for MargolusLife {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn step(&mut self) {
+        let offset = self.step.rem_euclid(2) as usize;
+        let rule = self.rule.clone();
+        self.apply_partition(offset, |block| rule.apply(block));
+        self.step += 1;
+    }
+
+    /// Deshace el último paso aplicando la tabla inversa sobre la misma
+    /// partición, sin guardar estados anteriores
+    fn step_backward(&mut self) -> bool {
+        let Some(inverse) = self.inverse.clone() else { return false };
+        self.step -= 1;
+        let offset = self.step.rem_euclid(2) as usize;
+        self.apply_partition(offset, |block| inverse.apply(block));
+        true
+    }
+
+    fn palette(&self) -> Vec<[u8; 3]> {
+        vec![crate::DEAD_RGB, crate::ALIVE_RGB]
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        self.cells[y * self.width + x] as u8
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use rand::Rng;
+
+use crate::affinity;
+use crate::cancel::{CancelToken, Cancelled};
+use crate::detection;
+use crate::progress::{self, Progress, ProgressSink};
+use crate::rle::RlePattern;
+use crate::rule::Rule;
+use crate::{CellState, GameOfLife};
+
+/// Lado de la caja donde viven los patrones candidatos
+pub const BOX_SIZE: usize = 16;
+/// Células vivas como mínimo en un patrón candidato
+pub const MIN_CELLS: usize = 3;
+/// Generaciones máximas que se simulan por candidato
+const LONGEVITY_LIMIT: usize = 2000;
+/// Temperatura inicial del recocido, en generaciones de longevidad
+const INITIAL_TEMPERATURE: f64 = 50.0;
+/// Temperatura al final del recocido
+const FINAL_TEMPERATURE: f64 = 0.5;
+
+/// Patrón inicial dentro de la caja de `BOX_SIZE`×`BOX_SIZE`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    cells: Vec<bool>,
+}
+
+impl Pattern {
+    fn random(max_cells: usize, rng: &mut impl Rng) -> Self {
+        let mut cells = vec![false; BOX_SIZE * BOX_SIZE];
+        for _ in 0..rng.gen_range(MIN_CELLS..=max_cells.clamp(MIN_CELLS, BOX_SIZE * BOX_SIZE)) {
+            cells[rng.gen_range(0..BOX_SIZE * BOX_SIZE)] = true;
+        }
+        Self { cells }
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|&&c| c).count()
+    }
+
+    /// Vecino en el espacio de búsqueda: agrega, quita o mueve una célula sin
+    /// pasar de `max_cells`
+    fn neighbor(&self, max_cells: usize, rng: &mut impl Rng) -> Self {
+        let mut next = self.clone();
+        let i = rng.gen_range(0..next.cells.len());
+        if next.cells[i] || next.population() < max_cells {
+            next.cells[i] = !next.cells[i];
+        } else {
+            let alive: Vec<usize> = (0..next.cells.len()).filter(|&j| next.cells[j]).collect();
+            next.cells[alive[rng.gen_range(0..alive.len())]] = false;
+            next.cells[i] = true;
+        }
+        next
+    }
+
+    /// Coloca el patrón en el centro de un universo de `width`×`height`; si
+    /// la caja no entra, lo que sobra queda fuera
+    fn place(&self, width: usize, height: usize) -> GameOfLife {
+        let mut game = GameOfLife::new(width, height);
+        let (x0, y0) = (width.saturating_sub(BOX_SIZE) / 2, height.saturating_sub(BOX_SIZE) / 2);
+        for (i, _) in self.cells.iter().enumerate().filter(|(_, c)| **c) {
+            game.set_cell(x0 + i % BOX_SIZE, y0 + i / BOX_SIZE, CellState::Alive);
+        }
+        game
+    }
+
+    /// Texto RLE del patrón recortado a las células vivas
+    pub fn to_rle(&self) -> String {
+        let alive: Vec<(usize, usize)> = (0..BOX_SIZE * BOX_SIZE)
+            .filter(|&i| self.cells[i])
+            .map(|i| (i % BOX_SIZE, i / BOX_SIZE))
+            .collect();
+        let (min_x, min_y) = (alive.iter().map(|c| c.0).min().unwrap_or(0), alive.iter().map(|c| c.1).min().unwrap_or(0));
+        let cells: Vec<(usize, usize)> = alive.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+        RlePattern {
+            name: None,
+            rule: Some(Rule::conway()),
+            width: cells.iter().map(|c| c.0 + 1).max().unwrap_or(0),
+            height: cells.iter().map(|c| c.1 + 1).max().unwrap_or(0),
+            cells,
+        }
+        .to_string()
+    }
+}
+
+/// Resultado de una cadena de búsqueda
+#[derive(Clone)]
+pub struct Methuselah {
+    pub pattern: Pattern,
+    /// Generaciones hasta que la evolución entra en un ciclo
+    pub lifespan: usize,
+    /// Período del ciclo final (1 si todo queda quieto)
+    pub period: usize,
+}
+
+/// Parámetros de la búsqueda
+pub struct SearchConfig {
+    /// Entre `MIN_CELLS` y `BOX_SIZE`²
+    pub max_cells: usize,
+    /// Pasos de recocido de cada cadena
+    pub iterations: usize,
+    /// Cadenas independientes, una por hilo
+    pub workers: usize,
+    /// Fija cada cadena a un núcleo
+    pub pin_threads: bool,
+    /// Tamaño del universo donde se evalúa cada patrón; al menos `BOX_SIZE`
+    /// de lado
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Transitorio y período del ciclo final; los patrones que no se
+/// estabilizan antes del límite valen 0
+fn lifespan(pattern: &Pattern, config: &SearchConfig, cancel: &CancelToken) -> Result<(usize, usize), Cancelled> {
+    let game = pattern.place(config.width, config.height);
+    let cycle = detection::find_cycle_cancellable(&game, LONGEVITY_LIMIT, cancel)?;
+    Ok(cycle.map_or((0, 0), |cycle| (cycle.transient, cycle.period)))
+}
+
+/// Avance de una búsqueda, compartido por todas sus cadenas
+struct SharedProgress<'a> {
+    done: AtomicU64,
+    total: u64,
+    interval: u64,
+    /// Mayor longevidad encontrada por cualquier cadena
+    best: AtomicUsize,
+    sink: Mutex<&'a mut (dyn ProgressSink + Send)>,
+}
+
+impl SharedProgress<'_> {
+    /// Cuenta una iteración de una cadena e informa cada `interval`
+    fn step(&self, lifespan: usize) {
+        self.best.fetch_max(lifespan, Ordering::Relaxed);
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        if done.is_multiple_of(self.interval) || done == self.total {
+            let best = self.best.load(Ordering::Relaxed) as f64;
+            let progress = Progress::new(done, self.total).with_metric("mejor longevidad", best);
+            self.sink.lock().expect("otra cadena falló al informar").report(&progress);
+        }
+    }
+}
+
+/// Recocido simulado a partir de un patrón al azar. Si se cancela, devuelve
+/// el mejor patrón encontrado hasta ese momento.
+fn anneal(config: &SearchConfig, worker: usize, cancel: &CancelToken, shared: &SharedProgress) -> Methuselah {
+    if config.pin_threads {
+        affinity::pin_or_warn(worker);
+    }
+    let mut rng = rand::thread_rng();
+    let mut current = Pattern::random(config.max_cells, &mut rng);
+    let (mut current_lifespan, period) = lifespan(&current, config, cancel).unwrap_or((0, 0));
+    let mut best = Methuselah { pattern: current.clone(), lifespan: current_lifespan, period };
+
+    for step in 0..config.iterations {
+        let progress = step as f64 / config.iterations.max(1) as f64;
+        let temperature = INITIAL_TEMPERATURE * (FINAL_TEMPERATURE / INITIAL_TEMPERATURE).powf(progress);
+
+        let candidate = current.neighbor(config.max_cells, &mut rng);
+        let Ok((candidate_lifespan, period)) = lifespan(&candidate, config, cancel) else {
+            break;
+        };
+        let delta = candidate_lifespan as f64 - current_lifespan as f64;
+        if delta >= 0.0 || rng.gen_bool((delta / temperature).exp()) {
+            current = candidate;
+            current_lifespan = candidate_lifespan;
+            if current_lifespan > best.lifespan {
+                best = Methuselah { pattern: current.clone(), lifespan: current_lifespan, period };
+            }
+        }
+        shared.step(best.lifespan);
+    }
+    best
+}
+
+/// Corre una cadena de recocido por hilo y devuelve el mejor patrón de cada
+/// una, de mayor a menor longevidad
+pub fn search(config: &SearchConfig) -> Vec<Methuselah> {
+    search_cancellable(config, &CancelToken::new())
+}
+
+/// Como `search`; si se cancela `cancel`, cada cadena se detiene y aporta
+/// el mejor patrón que encontró hasta ese momento
+pub fn search_cancellable(config: &SearchConfig, cancel: &CancelToken) -> Vec<Methuselah> {
+    search_with(config, cancel, &mut |_: &Progress| {})
+}
+
+/// Como `search_cancellable`, informando a `sink` las iteraciones hechas
+/// entre todas las cadenas y la mayor longevidad encontrada hasta el momento
+pub fn search_with(config: &SearchConfig, cancel: &CancelToken, sink: &mut (dyn ProgressSink + Send)) -> Vec<Methuselah> {
+    let workers = config.workers.max(1);
+    let total = (config.iterations * workers) as u64;
+    let shared = SharedProgress {
+        done: AtomicU64::new(0),
+        total,
+        interval: progress::report_interval(total),
+        best: AtomicUsize::new(0),
+        sink: Mutex::new(sink),
+    };
+    let shared = &shared;
+    let mut results: Vec<Methuselah> = std::thread::scope(|scope| {
+        let chains: Vec<_> = (0..workers).map(|worker| scope.spawn(move || anneal(config, worker, cancel, shared))).collect();
+        chains.into_iter().map(|chain| chain.join().expect("la cadena de búsqueda falló")).collect()
+    });
+    results.sort_by_key(|m| std::cmp::Reverse(m.lifespan));
+    results
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use crate::font;
+use crate::recording::Recording;
+use crate::{GameOfLife, gif_import};
+
+/// Separación en píxeles entre las ejecuciones del mosaico
+const GAP: usize = 1;
+/// Alto de la barra con el nombre de cada ejecución
+const LABEL_HEIGHT: usize = font::GLYPH_HEIGHT + 2;
+/// Índices de la paleta del mosaico (después de célula muerta y viva)
+const BACKGROUND: u8 = 2;
+const LABEL_TEXT: u8 = 3;
+
+/// Una ejecución cargada de una grabación .lab2 o de un GIF exportado
+pub struct Run {
+    pub label: String,
+    pub frames: Vec<GameOfLife>,
+}
+
+impl Run {
+    /// Los GIF se importan con `gif_scale` píxeles por célula
+    pub fn load(path: &Path, gif_scale: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        let frames = match path.extension().and_then(|e| e.to_str()) {
+            Some("gif") => gif_import::import_gif(path, gif_scale)?,
+            _ => Recording::load(path)?.replay()?,
+        };
+        let label = path.file_stem().map_or_else(String::new, |s| s.to_string_lossy().into_owned());
+        Ok(Self { label, frames })
+    }
+}
+
+/// Varias ejecuciones dibujadas lado a lado en una cuadrícula, cada una con
+/// su nombre encima. Las ejecuciones más cortas se quedan en su último frame.
+pub struct Montage {
+    runs: Vec<Run>,
+    columns: usize,
+    tile_width: usize,
+    tile_height: usize,
+}
+
+impl Montage {
+    /// Sin `columns`, la cuadrícula es lo más cuadrada posible (2×2, 3×3...)
+    pub fn new(runs: Vec<Run>, columns: Option<usize>) -> Self {
+        let columns = columns.unwrap_or_else(|| (1..).find(|c| c * c >= runs.len()).unwrap_or(1)).max(1);
+        let tile_width = runs.iter().map(|r| r.frames[0].width()).max().unwrap_or(0);
+        let tile_height = runs.iter().map(|r| r.frames[0].height()).max().unwrap_or(0);
+        Self { runs, columns, tile_width, tile_height }
+    }
+
+    /// Ancho y alto del mosaico en píxeles (sin escalar)
+    pub fn size(&self) -> (usize, usize) {
+        let rows = self.runs.len().div_ceil(self.columns);
+        (
+            self.columns * (self.tile_width + GAP) + GAP,
+            rows * (self.tile_height + LABEL_HEIGHT + GAP) + GAP,
+        )
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.runs.iter().map(|r| r.frames.len()).max().unwrap_or(0)
+    }
+
+    /// Colores: célula muerta, viva, fondo y texto de las etiquetas
+    pub fn palette() -> Vec<[u8; 3]> {
+        vec![crate::DEAD_RGB, crate::ALIVE_RGB, [0x40, 0x40, 0x40], [0xFF, 0xD0, 0x40]]
+    }
+
+    /// Índices de la paleta del frame `index` del mosaico
+    pub fn frame(&self, index: usize) -> Vec<u8> {
+        let (width, height) = self.size();
+        let mut buffer = vec![BACKGROUND; width * height];
+        for (i, run) in self.runs.iter().enumerate() {
+            let x0 = GAP + i % self.columns * (self.tile_width + GAP);
+            let y0 = GAP + i / self.columns * (self.tile_height + LABEL_HEIGHT + GAP);
+            let max_chars = self.tile_width.saturating_sub(1) / (font::GLYPH_WIDTH + 1);
+            let label: String = run.label.chars().take(max_chars).collect();
+            font::draw_text(&mut buffer, width, (x0 + 1, y0 + 1), &label, LABEL_TEXT);
+
+            let state = &run.frames[index.min(run.frames.len() - 1)];
+            let cells = state.to_gif_frame_data();
+            for y in 0..state.height() {
+                let row = (y0 + LABEL_HEIGHT + y) * width + x0;
+                buffer[row..row + state.width()].copy_from_slice(&cells[y * state.width()..(y + 1) * state.width()]);
+            }
+        }
+        buffer
+    }
+}
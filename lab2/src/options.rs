@@ -0,0 +1,756 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::path::{Path, PathBuf};
+
+use crate::agents::AgentPreset;
+use crate::breakpoints::Breakpoint;
+use crate::channel::{AuxPreset, FieldRange};
+use crate::engine::EngineKind;
+use crate::gradient::Colormap;
+use crate::geometry::{Coordinates, Origin, YAxis};
+use crate::gif_export::{DedupPolicy, Downsample, ExportFormat, GifGenerator, SizePolicy};
+use crate::pacing::{DEFAULT_SPEED, Speed};
+use crate::quantize::{Quantizer, QuantizerKind};
+use crate::rle::{PatternFormat, Rotation};
+use crate::rule::Rule;
+use crate::screen::RendererKind;
+use crate::stats::{Rect, StatsFormat, Watch};
+use crate::theme::{Theme, ThemeKind};
+use crate::{Anchor, DEFAULT_DENSITY, Placement, ShiftEdges, Topology, UpdateScheme};
+use crate::favorites;
+#[cfg(feature = "cli")]
+use crate::gif_export::{parse_downsample, parse_size};
+#[cfg(feature = "cli")]
+use crate::{profiles, results, trails};
+
+/// Interpreta posiciones y desplazamientos como `10,20` o `-10,5`
+pub fn parse_point(text: &str) -> Result<(i64, i64), String> {
+    let error = || format!("se esperaban dos números como 10,20 o -10,5: {}", text);
+    let (x, y) = text.split_once(',').ok_or_else(error)?;
+    Ok((x.trim().parse().map_err(|_| error())?, y.trim().parse().map_err(|_| error())?))
+}
+
+/// Interpreta tamaños que no pueden ser 0
+pub fn parse_positive(text: &str) -> Result<usize, String> {
+    match text.trim().parse() {
+        Ok(0) | Err(_) => Err(format!("se esperaba un número mayor que 0: {}", text)),
+        Ok(n) => Ok(n),
+    }
+}
+
+/// Interpreta probabilidades: números finitos entre 0 y 1
+pub fn parse_probability(text: &str) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+        _ => Err(format!("se esperaba un número entre 0 y 1: {}", text)),
+    }
+}
+
+/// Tamaño del universo y de la ventana, y duración de la ejecución. Con la
+/// función `cli` estas opciones y las de `RunOptions` y `ExportOptions` son
+/// los argumentos de `lab2`; sin ella se arman con `Default`.
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct UniverseOptions {
+    /// Ancho del universo, en células
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 100, value_name = "N", value_parser = parse_positive))]
+    pub width: usize,
+
+    /// Alto del universo, en células
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 100, value_name = "N", value_parser = parse_positive))]
+    pub height: usize,
+
+    /// Píxeles de la ventana por célula
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 8, value_name = "N", value_parser = parse_positive))]
+    pub scale: usize,
+
+    /// Generaciones que dura la ejecución (y frames del GIF)
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 200, value_name = "N"))]
+    pub frames: u32,
+
+    /// Probabilidad de que cada célula empiece viva
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = DEFAULT_DENSITY, value_name = "P", value_parser = parse_probability))]
+    pub density: f64,
+
+    /// GIF que se graba durante la ejecución
+    #[cfg_attr(feature = "cli", arg(long, short, default_value = "conway_game_of_life.gif", value_name = "ARCHIVO"))]
+    pub output: PathBuf,
+
+    /// No abre ninguna ventana: simula lo más rápido posible y escribe el
+    /// GIF (para servidores sin pantalla)
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub headless: bool,
+
+    /// Semilla del estado inicial aleatorio: la misma semilla da siempre el
+    /// mismo universo. Sin ella se elige una al azar y se muestra.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "N"))]
+    pub seed: Option<u64>,
+
+    /// Con qué se dibuja la ventana: la CPU (minifb) o la GPU (wgpu, si
+    /// lab2 se compiló con `--features gpu`)
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = RendererKind::Cpu))]
+    pub renderer: RendererKind,
+
+    /// Hilos que calculan cada generación, repartiéndose franjas de filas
+    /// (0, lo predeterminado, = todos los núcleos). El resultado es el
+    /// mismo con cualquier cantidad; 1 calcula en serie.
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 0, value_name = "N"))]
+    pub threads: usize,
+
+    /// Colores de los mapas de calor (densidad y canal auxiliar, `heat` si no
+    /// se elige otro) y de la concentración en gray-scott
+    #[cfg_attr(feature = "cli", arg(long, value_enum, value_name = "MAPA"))]
+    pub colormap: Option<Colormap>,
+
+    /// Colores de las células, del fondo y de los mapas de calor (la tecla T
+    /// pasa al siguiente)
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = ThemeKind::Classic, value_name = "TEMA"))]
+    pub theme: ThemeKind,
+}
+
+/// Los mismos valores que `lab2` sin opciones
+impl Default for UniverseOptions {
+    fn default() -> Self {
+        Self {
+            width: 100,
+            height: 100,
+            scale: 8,
+            frames: 200,
+            density: DEFAULT_DENSITY,
+            output: PathBuf::from("conway_game_of_life.gif"),
+            headless: false,
+            seed: None,
+            renderer: RendererKind::Cpu,
+            threads: 0,
+            colormap: None,
+            theme: ThemeKind::Classic,
+        }
+    }
+}
+
+impl UniverseOptions {
+    /// --seed o una semilla nueva que se muestra para poder repetir la
+    /// ejecución
+    pub fn seed(&self) -> u64 {
+        self.seed.unwrap_or_else(|| {
+            let seed = rand::random();
+            println!("Semilla: {} (repetir con --seed {})", seed, seed);
+            seed
+        })
+    }
+
+    /// Números al azar para el estado inicial, a partir de `seed`
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed())
+    }
+
+    /// Mapa de colores de las capas de densidad y del canal auxiliar: el de
+    /// --colormap, el del tema o `heat`
+    pub fn heat_colormap(&self, theme: &Theme) -> Colormap {
+        self.colormap.or(theme.gradient).unwrap_or(Colormap::Heat)
+    }
+}
+
+/// Opciones de una simulación en vivo
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct RunOptions {
+    /// Guarda la ejecución en un archivo .lab2 para reproducirla después
+    #[cfg_attr(feature = "cli", arg(long, value_name = "ARCHIVO"))]
+    pub record: Option<PathBuf>,
+
+    /// Mueve la cámara automáticamente hacia la región más activa; el GIF
+    /// graba lo que muestra la cámara
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub auto_director: bool,
+
+    /// Células por lado que muestra la cámara del director
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 40, value_name = "N"))]
+    pub view_size: usize,
+
+    /// Orden de actualización de las células
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = UpdateOrder::Synchronous))]
+    pub update: UpdateOrder,
+
+    /// Probabilidad de actualizar cada célula con --update alpha
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 0.5))]
+    pub alpha: f64,
+
+    /// Lado de los bloques con --update block-sequential
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 10, value_name = "N"))]
+    pub block_size: usize,
+
+    /// Regla en notación B/S, por ejemplo B36/S23 (HighLife) o B3/S12345
+    #[cfg_attr(feature = "cli", arg(long, default_value = "B3/S23", value_parser = Rule::parse))]
+    pub rule: Rule,
+
+    /// Qué hay más allá de los bordes: células muertas (bounded) o el
+    /// borde opuesto (torus)
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = Topology::Bounded))]
+    pub topology: Topology,
+
+    /// Guarda el estado cada N generaciones
+    #[cfg_attr(feature = "cli", arg(long, value_name = "N"))]
+    pub snapshot_every: Option<u64>,
+
+    /// Guarda también una imagen PNG de cada estado de --snapshot-every,
+    /// con la escala de --export-scale
+    #[cfg_attr(feature = "cli", arg(long, requires = "snapshot_every"))]
+    pub snapshot_images: bool,
+
+    /// Guarda solo las células que cambiaron desde el estado anterior, con
+    /// un estado completo cada N estados
+    #[cfg_attr(feature = "cli", arg(long, value_name = "N"))]
+    pub snapshot_keyframe_every: Option<usize>,
+
+    /// Carpeta de los estados guardados con --snapshot-every
+    #[cfg_attr(feature = "cli", arg(long, default_value = "snapshots", value_name = "CARPETA"))]
+    pub snapshot_dir: PathBuf,
+
+    /// Continúa desde un estado .snap o .snapd guardado con --snapshot-every
+    #[cfg_attr(feature = "cli", arg(long, value_name = "ARCHIVO"))]
+    pub resume: Option<PathBuf>,
+
+    /// Empieza con un patrón RLE (de Golly o LifeWiki) o de texto plano
+    /// .cells en un universo vacío
+    #[cfg_attr(feature = "cli", arg(long, value_name = "ARCHIVO", conflicts_with = "resume"))]
+    pub rle: Option<PathBuf>,
+
+    /// Dónde va el patrón RLE, en las coordenadas de --origin y --y-axis
+    /// (por defecto, centrado)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "X,Y", requires = "rle", value_parser = parse_point, allow_hyphen_values = true))]
+    pub rle_at: Option<(i64, i64)>,
+
+    /// Dónde está el (0, 0) de --rle-at y de los patrones del guion: en la
+    /// esquina o en el centro del universo
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = Origin::Corner))]
+    pub origin: Origin,
+
+    /// Hacia dónde crece la y de --rle-at y de los patrones del guion
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = YAxis::Down))]
+    pub y_axis: YAxis,
+
+    /// Gira el patrón RLE en sentido horario
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = Rotation::None, requires = "rle"))]
+    pub rle_rotate: Rotation,
+
+    /// Refleja el patrón RLE horizontalmente (antes de girarlo)
+    #[cfg_attr(feature = "cli", arg(long, requires = "rle"))]
+    pub rle_flip: bool,
+
+    /// Qué hacer si el patrón de --rle o los del guion no entran enteros:
+    /// recortarlos, fallar diciendo qué células quedan fuera o agrandar el
+    /// universo
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = Placement::Clip))]
+    pub placement: Placement,
+
+    /// Carpeta donde S guarda la generación actual como .rle
+    #[cfg_attr(feature = "cli", arg(long, default_value = ".", value_name = "CARPETA"))]
+    pub rle_dir: PathBuf,
+
+    /// Formato en que S guarda la generación actual
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = PatternFormat::Rle))]
+    pub pattern_format: PatternFormat,
+
+    /// Archivo JSON donde K guarda las reglas elegidas con ?
+    #[cfg_attr(feature = "cli", arg(long, default_value = favorites::FAVORITES_FILE, value_name = "ARCHIVO"))]
+    pub favorites: PathBuf,
+
+    /// Empieza mostrando el HUD con la generación, la población y los FPS
+    /// (H lo muestra u oculta)
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub hud: bool,
+
+    /// Empieza con líneas entre las células, si la escala es de al menos 4
+    /// píxeles (G las muestra u oculta)
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub grid_lines: bool,
+
+    /// Empieza con la regla, la semilla, la densidad, el tamaño y el guion
+    /// de un favorito guardado (ver `lab2 favorites list`)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "NOMBRE"))]
+    pub favorite: Option<String>,
+
+    /// Guarda la última generación al terminar, como .rle o, si el archivo
+    /// termina en .cells, como texto plano
+    #[cfg_attr(feature = "cli", arg(long, value_name = "ARCHIVO"))]
+    pub rle_out: Option<PathBuf>,
+
+    /// Guarda con --rle-out solo este rectángulo del universo
+    #[cfg_attr(feature = "cli", arg(long, value_name = "X,Y,ANCHO,ALTO", requires = "rle_out", value_parser = Rect::parse))]
+    pub rle_out_region: Option<Rect>,
+
+    /// Dato extra por célula que se actualiza en cada generación y se
+    /// dibuja de fondo (A lo muestra u oculta): visits cuenta las
+    /// generaciones que cada célula pasó viva y diffusion es un rastro que
+    /// se difunde y se desvanece
+    #[cfg_attr(feature = "cli", arg(long, value_enum, value_name = "CANAL"))]
+    pub aux: Option<AuxPreset>,
+
+    /// Cuánto deposita cada célula viva en el campo de --aux diffusion
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 1.0, value_name = "X"))]
+    pub deposit: f32,
+
+    /// Fracción del campo que se mezcla con las celdas vecinas en cada generación
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 0.2, value_name = "X"))]
+    pub diffusion_rate: f32,
+
+    /// Fracción del campo que se pierde en cada generación
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 0.05, value_name = "X"))]
+    pub decay: f32,
+
+    /// Agentes que se mueven sobre el grid después de cada generación,
+    /// leyéndolo y escribiendo en él: langton-ant (la hormiga de Langton) o
+    /// boids (una bandada que deja células vivas detrás)
+    #[cfg_attr(feature = "cli", arg(long, value_enum, value_name = "TIPO"))]
+    pub agents: Option<AgentPreset>,
+
+    /// Cantidad de agentes de --agents
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 1, value_name = "N"))]
+    pub agent_count: usize,
+
+    /// Solo nacen células donde el canal auxiliar está en MIN..MAX (por
+    /// ejemplo 0.5.. o ..2)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "MIN..MAX", allow_hyphen_values = true, value_parser = FieldRange::parse))]
+    pub field_birth: Option<FieldRange>,
+
+    /// Solo sobreviven células donde el canal auxiliar está en MIN..MAX
+    #[cfg_attr(feature = "cli", arg(long, value_name = "MIN..MAX", allow_hyphen_values = true, value_parser = FieldRange::parse))]
+    pub field_survival: Option<FieldRange>,
+
+    /// Punto que queda fijo al agrandar el universo con Ctrl+E
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = Anchor::Center))]
+    pub expand_anchor: Anchor,
+
+    /// Qué pasa con las células que salen por un borde al trasladar el
+    /// universo (Ctrl+flechas y --director-shift)
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = ShiftEdges::Wrap))]
+    pub shift_edges: ShiftEdges,
+
+    /// Si la región elegida por el director está junto al borde, traslada
+    /// el universo para que la cámara la pueda centrar
+    #[cfg_attr(feature = "cli", arg(long, requires = "auto_director"))]
+    pub director_shift: bool,
+
+    /// Congela el rectángulo X,Y,ANCHO,ALTO: sus células no cambian, pero
+    /// sus vecinas las siguen viendo. Se puede repetir.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "X,Y,ANCHO,ALTO", value_parser = Rect::parse))]
+    pub freeze: Vec<Rect>,
+
+    /// Pausa la simulación cuando se cumple la condición:
+    /// population:N (la población cruza N), generation:N,
+    /// activity:X,Y,ANCHO,ALTO (cambia alguna célula del rectángulo) o
+    /// reach:X,Y:X,Y,ANCHO,ALTO (el objeto que está en X,Y llega al
+    /// rectángulo). Se puede repetir.
+    #[cfg_attr(feature = "cli", arg(long = "break", value_name = "CONDICIÓN", value_parser = Breakpoint::parse))]
+    pub breakpoints: Vec<Breakpoint>,
+
+    /// Muestra en la barra de estado: population, cells:X,Y,ANCHO,ALTO
+    /// (células vivas del rectángulo) o age:X,Y (generaciones que lleva
+    /// vivo el objeto que está en X,Y al empezar). Se puede repetir.
+    #[cfg_attr(feature = "cli", arg(long = "watch", value_name = "EXPRESIÓN", value_parser = Watch::parse))]
+    pub watches: Vec<Watch>,
+
+    /// Escribe la población, los nacimientos, las muertes, la entropía y la
+    /// caja de las células vivas de cada generación en un CSV (por defecto
+    /// stats.csv)
+    #[cfg_attr(feature = "cli", arg(long, value_name = "ARCHIVO", num_args = 0..=1, default_missing_value = "stats.csv"))]
+    pub stats: Option<PathBuf>,
+
+    /// Formato del archivo de --stats
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = StatsFormat::Csv))]
+    pub stats_format: StatsFormat,
+
+    /// Guarda el resultado en una base SQLite (por defecto resultados.db):
+    /// la regla, la semilla, la población final y cuándo se estabilizó, o
+    /// lo que encuentran methuselah y evolve. Se consulta con `lab2 results`.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "ARCHIVO", num_args = 0..=1, default_missing_value = results::RESULTS_FILE))]
+    pub results: Option<PathBuf>,
+
+    /// Dibuja las células que murieron en las últimas N generaciones (8 si
+    /// no se indica) con un color que se va apagando, en la ventana y en el
+    /// GIF
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            value_name = "N",
+            num_args = 0..=1,
+            default_missing_value = "8",
+            value_parser = clap::value_parser!(u32).range(1..=trails::MAX_TRAIL_LENGTH as i64)
+        )
+    )]
+    pub trails: Option<u32>,
+
+    /// Termina la ejecución (y el GIF) cuando el universo queda fijo o
+    /// repite un ciclo corto, en lugar de grabar frames iguales hasta
+    /// --frames
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub stop_on_stable: bool,
+
+    /// Motor del universo: naive (el grid de siempre, con todas las
+    /// funciones de la ventana), hashlife (universos enormes y saltos de
+    /// muchas generaciones, sin bordes) o sparse (solo las células vivas, en
+    /// un mundo sin bordes por el que se mueve la vista), o auto (mide y
+    /// elige el más rápido para el grid de siempre, y cuántas generaciones
+    /// avanzar por frame)
+    #[cfg_attr(feature = "cli", arg(long, value_enum, default_value_t = EngineKind::Naive))]
+    pub engine: EngineKind,
+
+    /// Generaciones que avanza cada frame con --engine hashlife o sparse
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 1, value_name = "N", requires = "engine"))]
+    pub generations_per_frame: u64,
+
+    /// Con --engine sparse, la vista sigue a las células vivas en lugar de
+    /// quedarse quieta (W, A, S, D la mueven a mano)
+    #[cfg_attr(feature = "cli", arg(long, requires = "engine"))]
+    pub follow: bool,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            record: None,
+            auto_director: false,
+            view_size: 40,
+            update: UpdateOrder::Synchronous,
+            alpha: 0.5,
+            block_size: 10,
+            rule: Rule::conway(),
+            topology: Topology::Bounded,
+            snapshot_every: None,
+            snapshot_images: false,
+            snapshot_keyframe_every: None,
+            snapshot_dir: PathBuf::from("snapshots"),
+            resume: None,
+            rle: None,
+            rle_at: None,
+            origin: Origin::Corner,
+            y_axis: YAxis::Down,
+            rle_rotate: Rotation::None,
+            rle_flip: false,
+            placement: Placement::Clip,
+            rle_dir: PathBuf::from("."),
+            pattern_format: PatternFormat::Rle,
+            favorites: PathBuf::from(favorites::FAVORITES_FILE),
+            hud: false,
+            grid_lines: false,
+            favorite: None,
+            rle_out: None,
+            rle_out_region: None,
+            aux: None,
+            deposit: 1.0,
+            diffusion_rate: 0.2,
+            decay: 0.05,
+            agents: None,
+            agent_count: 1,
+            field_birth: None,
+            field_survival: None,
+            expand_anchor: Anchor::Center,
+            shift_edges: ShiftEdges::Wrap,
+            director_shift: false,
+            freeze: Vec::new(),
+            breakpoints: Vec::new(),
+            watches: Vec::new(),
+            stats: None,
+            stats_format: StatsFormat::Csv,
+            results: None,
+            trails: None,
+            stop_on_stable: false,
+            engine: EngineKind::Naive,
+            generations_per_frame: 1,
+            follow: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum UpdateOrder {
+    Synchronous,
+    RandomSequential,
+    BlockSequential,
+    Alpha,
+}
+
+impl RunOptions {
+    /// Convención de --origin y --y-axis
+    pub fn coordinates(&self) -> Coordinates {
+        Coordinates::new(self.origin, self.y_axis)
+    }
+
+    /// Archivo de --stats, con la extensión del formato si todavía tiene la
+    /// de un CSV (por ejemplo, el predeterminado)
+    pub fn stats_path(&self) -> Option<PathBuf> {
+        let path = self.stats.as_ref()?;
+        Some(match path.extension() {
+            Some(ext) if ext == "csv" => path.with_extension(self.stats_format.extension()),
+            _ => path.clone(),
+        })
+    }
+
+    pub fn update_scheme(&self) -> UpdateScheme {
+        match self.update {
+            UpdateOrder::Synchronous => UpdateScheme::Synchronous,
+            UpdateOrder::RandomSequential => UpdateScheme::RandomSequential,
+            UpdateOrder::BlockSequential => UpdateScheme::BlockSequential(self.block_size),
+            UpdateOrder::Alpha => UpdateScheme::AlphaAsynchronous(self.alpha),
+        }
+    }
+}
+
+/// Opciones compartidas por todo lo que escribe un GIF
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct ExportOptions {
+    /// Perfil de exportación con nombre (quick-preview, publication o uno
+    /// definido en export_profiles.json)
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_name = "NOMBRE"))]
+    pub profile: Option<String>,
+
+    /// Píxeles por célula en el GIF (reemplaza la escala de cada modo)
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_name = "N"))]
+    pub export_scale: Option<usize>,
+
+    /// Frames que se escriben en el GIF como máximo
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_name = "N"))]
+    pub export_frames: Option<usize>,
+
+    /// Guarda solo uno de cada N frames
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_name = "N"))]
+    pub stride: Option<usize>,
+
+    /// Hilos usados para comprimir los frames del GIF
+    #[cfg_attr(feature = "cli", arg(long, global = true, default_value_t = 1, value_name = "N"))]
+    pub encoder_threads: usize,
+
+    /// Fija cada hilo de trabajo (compresión del GIF, evolve, methuselah) a
+    /// un núcleo, para que las mediciones de rendimiento sean estables
+    #[cfg_attr(feature = "cli", arg(long, global = true))]
+    pub pin_threads: bool,
+
+    /// Tamaño máximo del GIF (por ejemplo 50MB)
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_name = "TAMAÑO", value_parser = parse_size))]
+    pub max_output_size: Option<u64>,
+
+    /// Cómo ajustar la exportación al superar --max-output-size
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_enum, default_value_t = SizePolicy::Stride))]
+    pub size_policy: SizePolicy,
+
+    /// Cuantiza los colores de las simulaciones con más de 256 tonos en
+    /// lugar de usar su paleta
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_enum, value_name = "ALGORITMO"))]
+    pub quantizer: Option<QuantizerKind>,
+
+    /// Tramado Floyd-Steinberg al cuantizar, para evitar bandas
+    #[cfg_attr(feature = "cli", arg(long, global = true))]
+    pub dither: bool,
+
+    /// Calcula una paleta para cada frame al cuantizar (por defecto se usa
+    /// la del primer frame para todo el GIF)
+    #[cfg_attr(feature = "cli", arg(long, global = true))]
+    pub palette_per_frame: bool,
+
+    /// Reduce bloques de N×N células a un píxel en tonos de gris (N o 'auto')
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_name = "N", value_parser = parse_downsample))]
+    pub downsample: Option<Downsample>,
+
+    /// Qué hacer con los frames idénticos al anterior: juntarlos en uno
+    /// más largo (merge) o dejar de grabar (stop)
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_enum, value_name = "POLÍTICA"))]
+    pub dedup: Option<DedupPolicy>,
+
+    /// Recorta el GIF al ciclo cuando un frame se repite, para que dé
+    /// vueltas sin saltos (osciladores, o naves seguidas por la cámara)
+    #[cfg_attr(feature = "cli", arg(long = "loop", global = true))]
+    pub seamless_loop: bool,
+
+    /// Velocidad de la ventana y del GIF: generaciones por segundo (15gps),
+    /// frames por segundo (15fps, lo mismo) o milisegundos por
+    /// generación (66ms). Por defecto, 10 por segundo.
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_name = "VELOCIDAD", value_parser = Speed::parse))]
+    pub speed: Option<Speed>,
+
+    /// Generaciones por segundo (lo mismo que --speed Nfps)
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_name = "N", conflicts_with = "speed"))]
+    pub fps: Option<f64>,
+
+    /// Formato del archivo exportado: gif, mp4 o webm para ejecuciones
+    /// largas (necesitan ffmpeg), png-seq para una carpeta con un PNG por
+    /// generación o apng para un PNG animado con todos los colores. Una
+    /// salida .gif cambia de extensión (o, con png-seq, se vuelve el nombre
+    /// de la carpeta). Por defecto, gif.
+    #[cfg_attr(feature = "cli", arg(long, global = true, value_enum))]
+    pub format: Option<ExportFormat>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            profile: None,
+            export_scale: None,
+            export_frames: None,
+            stride: None,
+            encoder_threads: 1,
+            pin_threads: false,
+            max_output_size: None,
+            size_policy: SizePolicy::Stride,
+            quantizer: None,
+            dither: false,
+            palette_per_frame: false,
+            downsample: None,
+            dedup: None,
+            seamless_loop: false,
+            speed: None,
+            fps: None,
+            format: None,
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Completa con el perfil elegido las opciones que no se dieron en la
+    /// línea de comandos
+    #[cfg(feature = "cli")]
+    pub fn apply_profile(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        use clap::ValueEnum;
+
+        let Some(name) = &self.profile else { return Ok(()) };
+        let profile = profiles::load(name)?;
+
+        self.export_frames = self.export_frames.or(profile.frames);
+        self.export_scale = self.export_scale.or(profile.scale);
+        self.stride = self.stride.or(profile.stride);
+        self.dither |= profile.dither.unwrap_or(false);
+        if self.downsample.is_none() && let Some(text) = &profile.downsample {
+            self.downsample = Some(parse_downsample(text)?);
+        }
+        if self.quantizer.is_none() && let Some(text) = &profile.quantizer {
+            self.quantizer = Some(QuantizerKind::from_str(text, true)?);
+        }
+        if self.max_output_size.is_none() && let Some(text) = &profile.max_output_size {
+            self.max_output_size = Some(parse_size(text)?);
+        }
+        if self.format.is_none() && let Some(text) = &profile.format {
+            self.format = Some(ExportFormat::from_str(text, true)?);
+        }
+        Ok(())
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format.unwrap_or_default()
+    }
+
+    pub fn speed(&self) -> Speed {
+        self.speed
+            .or(self.fps.map(Speed::from_generations_per_second))
+            .unwrap_or(Speed::from_generations_per_second(DEFAULT_SPEED))
+    }
+
+    pub fn create_gif(
+        &self,
+        path: impl AsRef<Path>,
+        width: usize,
+        height: usize,
+        scale: usize,
+        expected_frames: usize,
+    ) -> Result<GifGenerator, Box<dyn std::error::Error>> {
+        let scale = self.export_scale.unwrap_or(scale).max(1);
+        let stride = self.stride.unwrap_or(1).max(1);
+        let mut generator = GifGenerator::new(self.format().output_path(path.as_ref()), width, height, scale)?
+            .with_format(self.format())
+            .with_threads(self.encoder_threads)
+            .with_pinned_threads(self.pin_threads)
+            .with_speed(self.speed())
+            .with_stride(stride);
+        let expected_frames = self.export_frames.map_or(expected_frames, |f| (f * stride).min(expected_frames));
+        if let Some(frames) = self.export_frames {
+            generator = generator.with_frame_limit(frames);
+        }
+        if let Some(kind) = self.quantizer {
+            let quantizer = Quantizer { kind, colors: 256, dither: self.dither };
+            generator = generator.with_quantizer(quantizer, self.palette_per_frame);
+        }
+        if let Some(downsample) = self.downsample {
+            generator = generator.with_downsample(downsample.factor_for(width, height));
+        }
+        if let Some(policy) = self.dedup {
+            generator = generator.with_dedup(policy);
+        }
+        if self.seamless_loop {
+            generator = generator.with_loop();
+        }
+        if let Some(max_bytes) = self.max_output_size {
+            generator = generator.with_size_budget(max_bytes, self.size_policy, expected_frames);
+        }
+        Ok(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_order_carries_its_parameter() {
+        let options = RunOptions { update: UpdateOrder::BlockSequential, block_size: 7, ..Default::default() };
+        assert_eq!(options.update_scheme(), UpdateScheme::BlockSequential(7));
+        let options = RunOptions { update: UpdateOrder::Alpha, alpha: 0.25, ..Default::default() };
+        assert_eq!(options.update_scheme(), UpdateScheme::AlphaAsynchronous(0.25));
+        assert_eq!(RunOptions::default().update_scheme(), UpdateScheme::Synchronous);
+    }
+
+    #[test]
+    fn stats_path_takes_the_extension_of_its_format() {
+        assert_eq!(RunOptions::default().stats_path(), None);
+        let options = RunOptions { stats: Some(PathBuf::from("stats.csv")), stats_format: StatsFormat::Parquet, ..Default::default() };
+        assert_eq!(options.stats_path(), Some(PathBuf::from("stats.parquet")));
+        let options = RunOptions { stats: Some(PathBuf::from("datos.bin")), stats_format: StatsFormat::Parquet, ..Default::default() };
+        assert_eq!(options.stats_path(), Some(PathBuf::from("datos.bin")));
+    }
+
+    #[test]
+    fn points_and_sizes_are_checked() {
+        assert_eq!(parse_point(" -10, 5"), Ok((-10, 5)));
+        assert!(parse_point("10").is_err());
+        assert_eq!(parse_positive("3"), Ok(3));
+        assert!(parse_positive("0").is_err());
+        assert_eq!(parse_probability("0.5"), Ok(0.5));
+        assert!(parse_probability("1.5").is_err());
+    }
+
+    /// `Default` tiene que dar lo mismo que `lab2` sin opciones
+    #[cfg(feature = "cli")]
+    #[test]
+    fn defaults_match_the_command_line() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Args {
+            #[command(flatten)]
+            universe: UniverseOptions,
+            #[command(flatten)]
+            run: RunOptions,
+            #[command(flatten)]
+            export: ExportOptions,
+        }
+
+        let args = Args::parse_from(["lab2"]);
+        let (universe, run, export) = (UniverseOptions::default(), RunOptions::default(), ExportOptions::default());
+        assert_eq!(
+            (args.universe.width, args.universe.height, args.universe.scale, args.universe.frames, args.universe.threads),
+            (universe.width, universe.height, universe.scale, universe.frames, universe.threads)
+        );
+        assert_eq!((args.universe.density, &args.universe.output, args.universe.seed), (universe.density, &universe.output, universe.seed));
+        assert_eq!((args.universe.renderer, args.universe.theme), (universe.renderer, universe.theme));
+
+        assert_eq!((args.run.view_size, args.run.alpha, args.run.block_size), (run.view_size, run.alpha, run.block_size));
+        assert_eq!((args.run.update, args.run.rule, args.run.topology), (run.update, run.rule, run.topology));
+        assert_eq!((&args.run.snapshot_dir, &args.run.rle_dir, &args.run.favorites), (&run.snapshot_dir, &run.rle_dir, &run.favorites));
+        assert_eq!((args.run.origin, args.run.y_axis, args.run.rle_rotate), (run.origin, run.y_axis, run.rle_rotate));
+        assert_eq!((args.run.placement, args.run.pattern_format), (run.placement, run.pattern_format));
+        assert_eq!((args.run.deposit, args.run.diffusion_rate, args.run.decay), (run.deposit, run.diffusion_rate, run.decay));
+        assert_eq!((args.run.agent_count, args.run.generations_per_frame), (run.agent_count, run.generations_per_frame));
+        assert_eq!((args.run.expand_anchor, args.run.shift_edges), (run.expand_anchor, run.shift_edges));
+        assert_eq!((args.run.stats_format, args.run.engine), (run.stats_format, run.engine));
+
+        assert_eq!((args.export.encoder_threads, args.export.size_policy), (export.encoder_threads, export.size_policy));
+    }
+}
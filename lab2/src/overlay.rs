@@ -0,0 +1,216 @@
+use crate::GameOfLife;
+use crate::agents::Agent;
+use crate::channel::AuxChannel;
+use crate::font;
+use crate::geometry::GridSize;
+use crate::gradient::{self, Colormap};
+
+/// Opacidad de la capa de densidad sobre el render normal
+const DENSITY_ALPHA: f32 = 0.4;
+/// Densidad a partir de la cual un bloque se pinta con el color más caliente
+const DENSITY_SATURATION: f32 = 0.5;
+/// Opacidad máxima del canal auxiliar detrás de las células muertas
+const AUX_ALPHA: f32 = 0.6;
+/// Tinte de las células congeladas
+const FROZEN_COLOR: u32 = 0x0040C0FF;
+const FROZEN_ALPHA: f32 = 0.35;
+/// Cuerpo y punta (hacia donde miran) de los agentes
+const AGENT_COLOR: u32 = 0x00FF4040;
+const AGENT_TIP_COLOR: u32 = 0x00FFE040;
+/// Borde de los rectángulos vigilados por puntos de interrupción
+const OUTLINE_COLOR: u32 = 0x00FFD000;
+/// Escala mínima con la que se dibujan las líneas del grid: con menos
+/// píxeles por célula taparían casi toda la célula
+pub const GRID_MIN_SCALE: usize = 4;
+/// Píxeles de pantalla por píxel de la fuente en la barra de estado
+const STATUS_SCALE: usize = 2;
+const STATUS_BACKGROUND: u32 = 0x00000000;
+const STATUS_TEXT: u32 = 0x00FFFFFF;
+/// Panel del HUD: escala de la fuente, margen y opacidad del fondo
+const HUD_SCALE: usize = 2;
+const HUD_MARGIN: usize = 4;
+const HUD_ALPHA: f32 = 0.6;
+
+/// Densidad (0.0 a 1.0) de cada bloque de `block`×`block` células, fila por fila
+pub fn block_densities(game: &GameOfLife, block: usize) -> (usize, usize, Vec<f32>) {
+    let blocks_x = game.width().div_ceil(block);
+    let blocks_y = game.height().div_ceil(block);
+    let mut live = vec![0usize; blocks_x * blocks_y];
+    let mut total = vec![0usize; blocks_x * blocks_y];
+
+    for y in 0..game.height() {
+        for x in 0..game.width() {
+            let index = (y / block) * blocks_x + x / block;
+            total[index] += 1;
+            if game.is_alive(x, y) {
+                live[index] += 1;
+            }
+        }
+    }
+
+    let densities = live.iter().zip(&total).map(|(&l, &t)| l as f32 / t as f32).collect();
+    (blocks_x, blocks_y, densities)
+}
+
+/// Pinta sobre el buffer ya renderizado una capa translúcida con la densidad
+/// de cada bloque, del primer color de `colormap` para zonas tranquilas al
+/// último para zonas agitadas (con `Heat`, de azul a rojo)
+pub fn draw_density(game: &GameOfLife, buffer: &mut [u32], block: usize, scale: usize, colormap: Colormap) {
+    let (blocks_x, _, densities) = block_densities(game, block);
+    let window_width = game.width() * scale;
+    let colors = Ramp::new(colormap);
+
+    for (i, pixel) in buffer.iter_mut().enumerate() {
+        let (x, y) = (i % window_width / scale, i / window_width / scale);
+        let density = densities[(y / block) * blocks_x + x / block];
+        *pixel = blend(*pixel, colors.at(density / DENSITY_SATURATION), DENSITY_ALPHA);
+    }
+}
+
+/// Pinta el canal auxiliar como fondo de las células muertas, con los mismos
+/// colores que el mapa de densidad; las vivas no cambian
+pub fn draw_aux(game: &GameOfLife, channel: &AuxChannel, buffer: &mut [u32], scale: usize, colormap: Colormap) {
+    if (channel.width(), channel.height()) != (game.width(), game.height()) {
+        return;
+    }
+    let levels = channel.levels();
+    let window_width = game.width() * scale;
+    let colors = Ramp::new(colormap);
+    for (i, pixel) in buffer.iter_mut().enumerate() {
+        let (x, y) = (i % window_width / scale, i / window_width / scale);
+        let level = levels[y * game.width() + x];
+        if level > 0.0 && !game.is_alive(x, y) {
+            *pixel = blend(*pixel, colors.at(level), AUX_ALPHA * level.sqrt());
+        }
+    }
+}
+
+/// Dibuja cada agente como un cuadrado rojo con una punta amarilla hacia
+/// donde mira (con escalas chicas, solo el cuadrado)
+pub fn draw_agents(buffer: &mut [u32], window_width: usize, agents: &[Agent], scale: usize) {
+    let window_height = buffer.len() / window_width.max(1);
+    let mut fill = |left: usize, top: usize, size: usize, color: u32| {
+        for py in top..(top + size).min(window_height) {
+            for px in left..(left + size).min(window_width) {
+                buffer[py * window_width + px] = color;
+            }
+        }
+    };
+    for agent in agents {
+        let (left, top) = (agent.x * scale, agent.y * scale);
+        if scale < 4 {
+            fill(left, top, scale, AGENT_COLOR);
+            continue;
+        }
+        let (margin, tip) = (scale / 4, (scale / 4).max(1));
+        fill(left + margin, top + margin, scale - 2 * margin, AGENT_COLOR);
+        let (dx, dy) = agent.heading.delta();
+        let center = (scale - tip) / 2;
+        let offset = |d: i64| match d {
+            -1 => margin,
+            1 => scale - margin - tip,
+            _ => center,
+        };
+        fill(left + offset(dx), top + offset(dy), tip, AGENT_TIP_COLOR);
+    }
+}
+
+/// Tiñe de celeste las células congeladas
+pub fn draw_frozen(game: &GameOfLife, buffer: &mut [u32], scale: usize) {
+    let window_width = game.width() * scale;
+    for (i, pixel) in buffer.iter_mut().enumerate() {
+        if game.is_frozen(i % window_width / scale, i / window_width / scale) {
+            *pixel = blend(*pixel, FROZEN_COLOR, FROZEN_ALPHA);
+        }
+    }
+}
+
+/// Dibuja el borde del rectángulo (x, y, ancho, alto), en células
+pub fn draw_outline(buffer: &mut [u32], window_width: usize, rect: (usize, usize, usize, usize), scale: usize) {
+    let (x, y, width, height) = rect;
+    let window_height = buffer.len() / window_width;
+    let (left, top) = (x * scale, y * scale);
+    let (right, bottom) = (((x + width) * scale).min(window_width), ((y + height) * scale).min(window_height));
+    if left >= right || top >= bottom {
+        return;
+    }
+    let (right, bottom) = (right - 1, bottom - 1);
+    for px in left..=right {
+        buffer[top * window_width + px] = OUTLINE_COLOR;
+        buffer[bottom * window_width + px] = OUTLINE_COLOR;
+    }
+    for py in top..=bottom {
+        buffer[py * window_width + left] = OUTLINE_COLOR;
+        buffer[py * window_width + right] = OUTLINE_COLOR;
+    }
+}
+
+/// Líneas de un píxel entre las células de un universo de `size` dibujado
+/// desde la esquina, sobre la primera columna y fila de píxeles de cada
+/// célula. No dibuja nada con una escala menor que `GRID_MIN_SCALE`.
+pub fn draw_grid_lines(buffer: &mut [u32], window_width: usize, size: GridSize, scale: usize, color: u32) {
+    if scale < GRID_MIN_SCALE {
+        return;
+    }
+    let width = (size.width * scale).min(window_width);
+    for (py, row) in buffer.chunks_mut(window_width).take(size.height * scale).enumerate() {
+        if py > 0 && py.is_multiple_of(scale) {
+            row[..width].fill(color);
+        } else {
+            for px in (scale..width).step_by(scale) {
+                row[px] = color;
+            }
+        }
+    }
+}
+
+/// Barra con `text` sobre el borde inferior de la ventana
+pub fn draw_status(buffer: &mut [u32], window_width: usize, text: &str) {
+    let height = (font::GLYPH_HEIGHT + 2) * STATUS_SCALE;
+    let top = (buffer.len() / window_width).saturating_sub(height);
+    buffer[top * window_width..].fill(STATUS_BACKGROUND);
+    font::draw_text_scaled(buffer, window_width, (STATUS_SCALE, top + STATUS_SCALE), text, STATUS_TEXT, STATUS_SCALE);
+}
+
+/// Panel arriba a la izquierda con una fila de texto por cada elemento de
+/// `lines`, sobre un fondo oscuro semitransparente para que se lea encima
+/// de las células
+pub fn draw_hud(buffer: &mut [u32], window_width: usize, lines: &[String]) {
+    let window_height = buffer.len() / window_width.max(1);
+    let line_height = (font::GLYPH_HEIGHT + 2) * HUD_SCALE;
+    let columns = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let width = (columns * (font::GLYPH_WIDTH + 1) * HUD_SCALE + 2 * HUD_MARGIN).min(window_width);
+    let height = (lines.len() * line_height + 2 * HUD_MARGIN).min(window_height);
+    for y in 0..height {
+        for pixel in &mut buffer[y * window_width..y * window_width + width] {
+            *pixel = blend(*pixel, STATUS_BACKGROUND, HUD_ALPHA);
+        }
+    }
+    for (i, line) in lines.iter().enumerate() {
+        let origin = (HUD_MARGIN, HUD_MARGIN + i * line_height + HUD_SCALE);
+        font::draw_text_scaled(buffer, window_width, origin, line, STATUS_TEXT, HUD_SCALE);
+    }
+}
+
+/// Mapa de colores muestreado una vez por frame, para no interpolar en cada píxel
+struct Ramp(Vec<u32>);
+
+impl Ramp {
+    fn new(colormap: Colormap) -> Self {
+        Self(colormap.gradient().palette(256).into_iter().map(gradient::to_u32).collect())
+    }
+
+    /// Color para t entre 0.0 y 1.0
+    fn at(&self, t: f32) -> u32 {
+        self.0[(t.clamp(0.0, 1.0) * 255.0) as usize]
+    }
+}
+
+fn blend(base: u32, over: u32, alpha: f32) -> u32 {
+    let channel = |shift: u32| {
+        let a = ((base >> shift) & 0xFF) as f32;
+        let b = ((over >> shift) & 0xFF) as f32;
+        ((a * (1.0 - alpha) + b * alpha) as u32) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
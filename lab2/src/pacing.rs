@@ -0,0 +1,111 @@
+use std::fmt;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Velocidad de la ventana y de los GIF si no se elige otra, en
+/// generaciones por segundo
+pub const DEFAULT_SPEED: f64 = 10.0;
+/// Velocidad mínima y máxima que se puede elegir, en generaciones por segundo
+const MIN_SPEED: f64 = 0.1;
+const MAX_SPEED: f64 = 1000.0;
+/// Demora mínima de un frame del GIF en centésimas: los navegadores muestran
+/// las demoras de 0 y 1 como si fueran de 10
+const MIN_GIF_DELAY: u64 = 2;
+
+/// Velocidad de la simulación. Cada frame de la ventana muestra una
+/// generación, así que frames por segundo y generaciones por segundo son lo
+/// mismo; los milisegundos por generación son la inversa.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Speed {
+    generations_per_second: f64,
+}
+
+impl Speed {
+    pub fn from_generations_per_second(generations_per_second: f64) -> Self {
+        Self { generations_per_second: generations_per_second.clamp(MIN_SPEED, MAX_SPEED) }
+    }
+
+    /// Interpreta `15gps` (o `15gen/s`), `15fps` o `66ms` (o `66ms/gen`).
+    /// Un número sin unidad son generaciones por segundo.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+        let (number, unit) = text.split_at(split);
+        let number: f64 = number.parse().map_err(|_| format!("velocidad inválida: {}", text))?;
+        if number <= 0.0 {
+            return Err(format!("la velocidad tiene que ser positiva: {}", text));
+        }
+        match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "gps" | "gen/s" | "fps" => Ok(Self::from_generations_per_second(number)),
+            "ms" | "ms/gen" => Ok(Self::from_generations_per_second(1000.0 / number)),
+            other => Err(format!("unidad de velocidad desconocida: {} (usar gps, fps o ms)", other)),
+        }
+    }
+
+    /// Tiempo entre una generación y la siguiente
+    pub fn generation_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.generations_per_second)
+    }
+
+    /// El doble de rápido (`factor` 2.0) o la mitad (0.5)
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self::from_generations_per_second(self.generations_per_second * factor)
+    }
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} gen/s ({:.0} ms/gen)", self.generations_per_second, 1000.0 / self.generations_per_second)
+    }
+}
+
+/// Reparte las demoras de los frames de un GIF, que solo pueden ser
+/// centésimas de segundo enteras, acumulando el error: a 15 frames por
+/// segundo alterna demoras de 7 y 6 centésimas en lugar de usar siempre 6, y
+/// la duración total se mantiene a menos de media centésima de la exacta
+#[derive(Clone, Debug, Default)]
+pub struct DelayClock {
+    /// Duración exacta de los frames hasta ahora, en segundos
+    exact: f64,
+    /// Suma de las demoras ya asignadas, en centésimas
+    written: u64,
+}
+
+impl DelayClock {
+    /// Demora en centésimas del siguiente frame, que dura `seconds`
+    pub fn next_delay(&mut self, seconds: f64) -> u16 {
+        self.exact += seconds;
+        let target = (self.exact * 100.0).round() as u64;
+        let delay = target.saturating_sub(self.written).clamp(MIN_GIF_DELAY, u16::MAX as u64);
+        self.written += delay;
+        delay as u16
+    }
+}
+
+/// Frames por segundo que se muestran de verdad, contando los frames del
+/// último segundo
+#[derive(Clone, Debug, Default)]
+pub struct FpsMeter {
+    frames: VecDeque<Instant>,
+}
+
+impl FpsMeter {
+    /// Registra un frame mostrado ahora
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        while self.frames.front().is_some_and(|&t| now.duration_since(t) > Duration::from_secs(1)) {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(now);
+    }
+
+    /// Frames por segundo, a partir de los del último segundo
+    pub fn fps(&self) -> f64 {
+        match (self.frames.front(), self.frames.back()) {
+            (Some(first), Some(last)) if self.frames.len() > 1 => {
+                (self.frames.len() - 1) as f64 / last.duration_since(*first).as_secs_f64().max(1e-6)
+            }
+            _ => 0.0,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use ::parquet::basic::{Repetition, Type as PhysicalType};
+use ::parquet::data_type::{DoubleType, Int64Type};
+use ::parquet::errors::ParquetError;
+use ::parquet::file::properties::WriterProperties;
+use ::parquet::file::writer::SerializedFileWriter;
+use ::parquet::schema::types::Type;
+
+/// Valores de una columna, todos presentes
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnData {
+    Int64(Vec<i64>),
+    Double(Vec<f64>),
+}
+
+impl ColumnData {
+    fn len(&self) -> usize {
+        match self {
+            ColumnData::Int64(values) => values.len(),
+            ColumnData::Double(values) => values.len(),
+        }
+    }
+
+    fn physical_type(&self) -> PhysicalType {
+        match self {
+            ColumnData::Int64(_) => PhysicalType::INT64,
+            ColumnData::Double(_) => PhysicalType::DOUBLE,
+        }
+    }
+}
+
+/// Escribe en `path` una tabla Parquet con las columnas `columns` (nombre y
+/// valores, todas del mismo largo), con el crate `parquet`: un solo grupo
+/// de filas, sin compresión y sin valores nulos, que pandas, polars o
+/// DuckDB leen con los tipos de cada columna.
+pub fn write(path: &Path, columns: &[(&str, ColumnData)]) -> io::Result<()> {
+    let rows = columns.first().map_or(0, |(_, data)| data.len());
+    if columns.iter().any(|(_, data)| data.len() != rows) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "las columnas tienen distinto largo"));
+    }
+    write_columns(File::create(path)?, columns).map_err(io::Error::other)
+}
+
+fn write_columns(file: File, columns: &[(&str, ColumnData)]) -> Result<(), ParquetError> {
+    let fields = columns
+        .iter()
+        .map(|(name, data)| {
+            Type::primitive_type_builder(name, data.physical_type())
+                .with_repetition(Repetition::REQUIRED)
+                .build()
+                .map(Arc::new)
+        })
+        .collect::<Result<_, _>>()?;
+    let schema = Type::group_type_builder("schema").with_fields(fields).build()?;
+
+    let mut writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(WriterProperties::builder().build()))?;
+    let mut row_group = writer.next_row_group()?;
+    for (_, data) in columns {
+        let Some(mut column) = row_group.next_column()? else { break };
+        match data {
+            ColumnData::Int64(values) => column.typed::<Int64Type>().write_batch(values, None, None)?,
+            ColumnData::Double(values) => column.typed::<DoubleType>().write_batch(values, None, None)?,
+        };
+        column.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::parquet::file::reader::{FileReader, SerializedFileReader};
+    use ::parquet::record::Field;
+
+    #[test]
+    fn round_trip_keeps_rows_and_schema() {
+        let path = std::env::temp_dir().join(format!("lab2-{}.parquet", std::process::id()));
+        let columns = [
+            ("generation", ColumnData::Int64(vec![1, 2, 3])),
+            ("entropy", ColumnData::Double(vec![0.5, 0.25, 0.0])),
+            ("population", ColumnData::Int64(vec![5, -1, i64::MAX])),
+        ];
+        write(&path, &columns).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..4], b"PAR1");
+        assert_eq!(&bytes[bytes.len() - 4..], b"PAR1");
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 3);
+        let schema = metadata.schema_descr();
+        let names: Vec<&str> = schema.columns().iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["generation", "entropy", "population"]);
+        let types: Vec<PhysicalType> = schema.columns().iter().map(|c| c.physical_type()).collect();
+        assert_eq!(types, [PhysicalType::INT64, PhysicalType::DOUBLE, PhysicalType::INT64]);
+
+        let rows: Vec<Vec<Field>> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().get_column_iter().map(|(_, field)| field.clone()).collect())
+            .collect();
+        assert_eq!(rows[1], [Field::Long(2), Field::Double(0.25), Field::Long(-1)]);
+        assert_eq!(rows[2][2], Field::Long(i64::MAX));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn columns_must_have_the_same_length() {
+        let path = std::env::temp_dir().join(format!("lab2-uneven-{}.parquet", std::process::id()));
+        let columns = [("a", ColumnData::Int64(vec![1, 2])), ("b", ColumnData::Double(vec![1.0]))];
+        assert_eq!(write(&path, &columns).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+}
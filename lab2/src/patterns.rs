@@ -0,0 +1,45 @@
+use crate::rle::RlePattern;
+
+/// Patrones incluidos en el programa, por nombre, en formato RLE (los de
+/// LifeWiki, sin la cabecera). Agregar uno es agregar una línea.
+const REGISTRY: &[(&str, &str)] = &[
+    // Naves y estructuras pequeñas
+    ("glider", "#N Glider\nbo$2bo$3o!"),
+    ("block", "#N Block\n2o$2o!"),
+    ("blinker", "#N Blinker\n3o!"),
+    ("toad", "#N Toad\nb3o$3o!"),
+    ("beacon", "#N Beacon\n2o$o$3bo$2b2o!"),
+    ("beehive", "#N Beehive\nb2o$o2bo$b2o!"),
+    ("lightweight-spaceship", "#N Lightweight spaceship\no2bo$4bo$o3bo$b4o!"),
+    (
+        "pulsar",
+        "#N Pulsar\n2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    ),
+    ("pentadecathlon", "#N Pentadecathlon\n2bo4bo$2ob4ob2o$2bo4bo!"),
+    // Matusalenes: pocas células que tardan miles de generaciones en calmarse
+    ("r-pentomino", "#N R-pentomino\nb2o$2o$bo!"),
+    ("acorn", "#N Acorn\nbo$3bo$2o2b3o!"),
+    // Cañones y motores
+    (
+        "gosper-gun",
+        "#N Gosper glider gun\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    ),
+    (
+        "schick-engine",
+        "#N Schick engine\nbo2bo$o$o3bo$4o9b2o$6b3o5b2o$6b2ob2o6b3o$6b3o5b2o$4o9b2o$o3bo$o$bo2bo!",
+    ),
+];
+
+/// El patrón llamado `name` (por ejemplo `"gosper-gun"`), o `None` si no
+/// hay ninguno con ese nombre
+pub fn get(name: &str) -> Option<RlePattern> {
+    REGISTRY
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, rle)| RlePattern::parse(rle).expect("los patrones incluidos son RLE válidos"))
+}
+
+/// Nombres de todos los patrones incluidos, en el orden del registro
+pub fn names() -> impl Iterator<Item = &'static str> {
+    REGISTRY.iter().map(|(name, _)| *name)
+}
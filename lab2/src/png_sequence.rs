@@ -0,0 +1,48 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// Carpeta con una imagen PNG por frame, con la generación en el nombre:
+/// `gen_00042.png`. Sirve para armar videos con otras herramientas o para
+/// elegir frames sueltos.
+pub struct PngSequence {
+    dir: PathBuf,
+    written: usize,
+}
+
+impl PngSequence {
+    /// Crea la carpeta si no existe
+    pub fn new(dir: &Path) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        Ok(Self { dir: dir.to_path_buf(), written: 0 })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Imágenes escritas hasta ahora
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Escribe el frame de la generación `generation`: `pixels` son índices
+    /// de `palette` (3 bytes por color), `width` por fila
+    pub fn write_frame(
+        &mut self,
+        generation: usize,
+        width: usize,
+        height: usize,
+        palette: &[u8],
+        pixels: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.dir.join(format!("gen_{:05}.png", generation));
+        let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width as u32, height as u32);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(palette);
+        encoder.write_header()?.write_image_data(pixels)?;
+        self.written += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// Archivo opcional con perfiles de exportación propios
+pub const PROFILES_FILE: &str = "export_profiles.json";
+
+/// Conjunto de opciones de exportación con nombre. Los campos vacíos no
+/// cambian nada y las opciones dadas en la línea de comandos tienen
+/// prioridad sobre el perfil.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportProfile {
+    /// Frames que se escriben como máximo
+    pub frames: Option<usize>,
+    /// Píxeles por célula en el GIF
+    pub scale: Option<usize>,
+    /// Guardar uno de cada N frames
+    pub stride: Option<usize>,
+    /// Igual que `--downsample` (un número o "auto")
+    pub downsample: Option<String>,
+    /// Igual que `--quantizer` ("median-cut" o "neu-quant")
+    pub quantizer: Option<String>,
+    pub dither: Option<bool>,
+    /// Igual que `--max-output-size`, por ejemplo "20MB"
+    pub max_output_size: Option<String>,
+    /// Igual que `--format` ("gif", "mp4", "webm", "png-seq" o "apng")
+    pub format: Option<String>,
+}
+
+/// Perfiles incluidos en el programa
+fn builtin() -> HashMap<String, ExportProfile> {
+    HashMap::from([
+        (
+            "quick-preview".to_string(),
+            ExportProfile { frames: Some(50), scale: Some(4), stride: Some(2), ..ExportProfile::default() },
+        ),
+        (
+            "publication".to_string(),
+            ExportProfile {
+                scale: Some(1),
+                stride: Some(1),
+                quantizer: Some("neu-quant".to_string()),
+                dither: Some(true),
+                format: Some("apng".to_string()),
+                ..ExportProfile::default()
+            },
+        ),
+    ])
+}
+
+/// Busca un perfil por nombre, primero en `PROFILES_FILE` (si existe) y
+/// después entre los incluidos
+pub fn load(name: &str) -> Result<ExportProfile, Box<dyn std::error::Error>> {
+    let mut profiles = builtin();
+    match std::fs::read_to_string(PROFILES_FILE) {
+        Ok(text) => {
+            let custom: HashMap<String, ExportProfile> = serde_json::from_str(&text)
+                .map_err(|e| format!("{} no es válido: {}", PROFILES_FILE, e))?;
+            profiles.extend(custom);
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    profiles.remove(name).ok_or_else(|| {
+        let mut names: Vec<String> = profiles.into_keys().collect();
+        names.sort();
+        format!("no existe el perfil '{}' (disponibles: {})", name, names.join(", ")).into()
+    })
+}
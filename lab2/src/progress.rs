@@ -0,0 +1,55 @@
+/// Veces que una operación informa su avance de principio a fin
+pub const REPORTS: u64 = 100;
+
+/// Avance de una operación larga: cuánto lleva de cuánto, más algunas
+/// medidas del momento (por ejemplo la población o los bytes escritos)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress {
+    pub done: u64,
+    pub total: u64,
+    /// Nombre y valor de cada medida
+    pub metrics: Vec<(&'static str, f64)>,
+}
+
+impl Progress {
+    pub fn new(done: u64, total: u64) -> Self {
+        Self { done, total, metrics: Vec::new() }
+    }
+
+    pub fn with_metric(mut self, name: &'static str, value: f64) -> Self {
+        self.metrics.push((name, value));
+        self
+    }
+
+    /// Parte completada, de 0 a 1 (1 si no había nada que hacer)
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 { 1.0 } else { (self.done as f64 / self.total as f64).min(1.0) }
+    }
+
+    pub fn percent(&self) -> f64 {
+        self.fraction() * 100.0
+    }
+
+    /// Valor de la medida `name`, si la operación la informa
+    pub fn metric(&self, name: &str) -> Option<f64> {
+        self.metrics.iter().find(|(n, _)| *n == name).map(|&(_, value)| value)
+    }
+}
+
+/// Recibe el avance de una operación, por ejemplo para mostrar una barra de
+/// progreso. Cualquier closure `FnMut(&Progress)` sirve.
+pub trait ProgressSink {
+    fn report(&mut self, progress: &Progress);
+}
+
+impl<F: FnMut(&Progress)> ProgressSink for F {
+    fn report(&mut self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+/// Cada cuántas unidades de `total` conviene informar para hacerlo unas
+/// `REPORTS` veces
+pub fn report_interval(total: u64) -> u64 {
+    total.div_ceil(REPORTS).max(1)
+}
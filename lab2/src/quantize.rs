@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+/// Algoritmo que elige la paleta de 256 colores de un frame con más colores
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum QuantizerKind {
+    /// Divide el cubo RGB en cajas por la mediana del canal más amplio
+    MedianCut,
+    /// Red neuronal de Kohonen (NeuQuant), más lenta pero con mejores degradados
+    NeuQuant,
+}
+
+/// Reduce frames RGB a índices de una paleta de como máximo `colors` colores
+#[derive(Clone, Copy, Debug)]
+pub struct Quantizer {
+    pub kind: QuantizerKind,
+    pub colors: usize,
+    /// Difunde el error de color a las células vecinas (Floyd-Steinberg)
+    pub dither: bool,
+}
+
+impl Quantizer {
+    /// Paleta para los píxeles dados. Si ya tienen pocos colores se usan tal
+    /// cual, sin pérdida.
+    pub fn palette(&self, pixels: &[[u8; 3]]) -> Vec<[u8; 3]> {
+        let mut distinct: Vec<[u8; 3]> = pixels.to_vec();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if distinct.len() <= self.colors {
+            return distinct;
+        }
+
+        match self.kind {
+            QuantizerKind::MedianCut => median_cut(distinct, self.colors),
+            QuantizerKind::NeuQuant => {
+                let rgba: Vec<u8> = pixels.iter().flat_map(|&[r, g, b]| [r, g, b, 0xFF]).collect();
+                let quant = color_quant::NeuQuant::new(10, self.colors, &rgba);
+                quant.color_map_rgb().chunks(3).map(|c| [c[0], c[1], c[2]]).collect()
+            }
+        }
+    }
+
+    /// Índice de la paleta de cada píxel de una imagen de `width` de ancho
+    pub fn map(&self, pixels: &[[u8; 3]], width: usize, palette: &[[u8; 3]]) -> Vec<u8> {
+        let mut cache = HashMap::new();
+        let mut nearest = |color: [u8; 3]| *cache.entry(color).or_insert_with(|| nearest_index(palette, color));
+        if !self.dither {
+            return pixels.iter().map(|&p| nearest(p)).collect();
+        }
+
+        // Floyd-Steinberg: el error de cada píxel se reparte a la derecha y abajo
+        let mut error = vec![[0.0f32; 3]; pixels.len()];
+        let mut indices = Vec::with_capacity(pixels.len());
+        for (i, pixel) in pixels.iter().enumerate() {
+            let wanted: [f32; 3] = std::array::from_fn(|c| (pixel[c] as f32 + error[i][c]).clamp(0.0, 255.0));
+            let index = nearest(wanted.map(|v| v.round() as u8));
+            indices.push(index);
+
+            let chosen = palette[index as usize];
+            let (x, y) = ((i % width) as i64, (i / width) as i64);
+            for (dx, dy, weight) in [(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)] {
+                let (nx, ny) = (x + dx, y + dy);
+                let target = (ny * width as i64 + nx) as usize;
+                if nx >= 0 && nx < width as i64 && target < pixels.len() {
+                    for c in 0..3 {
+                        error[target][c] += (wanted[c] - chosen[c] as f32) * weight / 16.0;
+                    }
+                }
+            }
+        }
+        indices
+    }
+}
+
+fn nearest_index(palette: &[[u8; 3]], color: [u8; 3]) -> u8 {
+    let distance = |p: &[u8; 3]| (0..3).map(|c| (p[c] as i32 - color[c] as i32).pow(2)).sum::<i32>();
+    (0..palette.len()).min_by_key(|&i| distance(&palette[i])).unwrap_or(0) as u8
+}
+
+/// Corte por la mediana: parte repetidamente la caja con el canal más amplio
+/// y usa el promedio de cada caja como color
+fn median_cut(colors: Vec<[u8; 3]>, count: usize) -> Vec<[u8; 3]> {
+    let range = |b: &[[u8; 3]], c: usize| {
+        let (min, max) = b.iter().fold((255, 0), |(lo, hi), p| (p[c].min(lo), p[c].max(hi)));
+        max - min
+    };
+    let widest = |b: &[[u8; 3]]| (0..3).max_by_key(|&c| range(b, c)).unwrap_or(0);
+
+    let mut boxes = vec![colors];
+    while boxes.len() < count {
+        let Some((i, _)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .max_by_key(|(_, b)| range(b, widest(b)) as usize * b.len())
+        else {
+            break;
+        };
+        let mut split = boxes.swap_remove(i);
+        let channel = widest(&split);
+        split.sort_unstable_by_key(|p| p[channel]);
+        let upper = split.split_off(split.len() / 2);
+        boxes.push(split);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .map(|b| std::array::from_fn(|c| (b.iter().map(|p| p[c] as usize).sum::<usize>() / b.len()) as u8))
+        .collect()
+}
@@ -0,0 +1,164 @@
+use rand::Rng;
+
+use crate::gradient::Gradient;
+use crate::simulation::Simulation;
+
+/// Concentración de V que se dibuja con el color más claro
+const V_DISPLAY_MAX: f32 = 0.4;
+/// Color de los frentes de reacción en la exportación con `--quantizer`
+const FRONT_RGB: [u8; 3] = [0xFF, 0x80, 0x20];
+
+/// Parámetros del modelo de Gray-Scott
+#[derive(Clone, Copy, Debug)]
+pub struct GrayScottParams {
+    /// Difusión de U
+    pub du: f32,
+    /// Difusión de V
+    pub dv: f32,
+    /// Tasa de alimentación de U
+    pub feed: f32,
+    /// Tasa de eliminación de V
+    pub kill: f32,
+}
+
+/// Combinaciones clásicas de alimentación y eliminación
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum GrayScottPreset {
+    Coral,
+    Mitosis,
+    Solitons,
+    Worms,
+    Maze,
+}
+
+impl GrayScottPreset {
+    pub fn params(self) -> GrayScottParams {
+        let (feed, kill) = match self {
+            GrayScottPreset::Coral => (0.0545, 0.062),
+            GrayScottPreset::Mitosis => (0.0367, 0.0649),
+            GrayScottPreset::Solitons => (0.030, 0.062),
+            GrayScottPreset::Worms => (0.078, 0.061),
+            GrayScottPreset::Maze => (0.029, 0.057),
+        };
+        GrayScottParams { du: 1.0, dv: 0.5, feed, kill }
+    }
+}
+
+/// Reacción-difusión de dos sustancias sobre un toro. Cada paso de la
+/// simulación aplica varias iteraciones del modelo porque los patrones
+/// cambian muy despacio.
+pub struct ReactionDiffusion {
+    width: usize,
+    height: usize,
+    u: Vec<f32>,
+    v: Vec<f32>,
+    params: GrayScottParams,
+    iterations_per_step: usize,
+    /// Colores según la concentración de V, de 0 a `V_DISPLAY_MAX`
+    gradient: Gradient,
+}
+
+impl ReactionDiffusion {
+    pub fn new(width: usize, height: usize, params: GrayScottParams, iterations_per_step: usize) -> Self {
+        Self {
+            width,
+            height,
+            u: vec![1.0; width * height],
+            v: vec![0.0; width * height],
+            params,
+            iterations_per_step: iterations_per_step.max(1),
+            gradient: Gradient::linear(crate::DEAD_RGB, crate::ALIVE_RGB),
+        }
+    }
+
+    /// Reemplaza el degradado del fondo azul oscuro hasta blanco
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = gradient;
+        self
+    }
+
+    /// Siembra `count` cuadrados pequeños de V en posiciones aleatorias
+    pub fn seed_squares(&mut self, count: usize, size: usize) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..count {
+            let cx = rng.gen_range(0..self.width);
+            let cy = rng.gen_range(0..self.height);
+            for dy in 0..size {
+                for dx in 0..size {
+                    let i = ((cy + dy) % self.height) * self.width + (cx + dx) % self.width;
+                    self.u[i] = 0.5;
+                    self.v[i] = 0.25 + rng.gen_range(0.0..0.05);
+                }
+            }
+        }
+    }
+
+    /// Laplaciano 3×3 (vecinos directos 0.2, diagonales 0.05) con bordes envolventes
+    fn laplacian(&self, field: &[f32], x: usize, y: usize) -> f32 {
+        let (w, h) = (self.width, self.height);
+        let (left, right) = ((x + w - 1) % w, (x + 1) % w);
+        let (up, down) = ((y + h - 1) % h, (y + 1) % h);
+        let at = |x: usize, y: usize| field[y * w + x];
+
+        -at(x, y)
+            + 0.2 * (at(left, y) + at(right, y) + at(x, up) + at(x, down))
+            + 0.05 * (at(left, up) + at(right, up) + at(left, down) + at(right, down))
+    }
+
+    fn iterate(&mut self) {
+        let GrayScottParams { du, dv, feed, kill } = self.params;
+        let mut next_u = self.u.clone();
+        let mut next_v = self.v.clone();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = y * self.width + x;
+                let (u, v) = (self.u[i], self.v[i]);
+                let reaction = u * v * v;
+                next_u[i] = (u + du * self.laplacian(&self.u, x, y) - reaction + feed * (1.0 - u)).clamp(0.0, 1.0);
+                next_v[i] = (v + dv * self.laplacian(&self.v, x, y) + reaction - (kill + feed) * v).clamp(0.0, 1.0);
+            }
+        }
+
+        self.u = next_u;
+        self.v = next_v;
+    }
+}
+
+impl Simulation for ReactionDiffusion {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn step(&mut self) {
+        for _ in 0..self.iterations_per_step {
+            self.iterate();
+        }
+    }
+
+    /// 256 tonos del degradado (por defecto, del fondo azul oscuro hasta blanco)
+    fn palette(&self) -> Vec<[u8; 3]> {
+        self.gradient.palette(256)
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        let v = self.v[y * self.width + x] / V_DISPLAY_MAX;
+        (v.clamp(0.0, 1.0) * 255.0) as u8
+    }
+
+    /// Degradado continuo según `v`, teñido de naranja donde se consumió `u`
+    /// (los frentes de reacción). Tiene miles de tonos, así que para
+    /// exportarlo hace falta `--quantizer`.
+    fn color(&self, x: usize, y: usize) -> [u8; 3] {
+        let i = y * self.width + x;
+        let v = (self.v[i] / V_DISPLAY_MAX).clamp(0.0, 1.0);
+        let heat = (1.0 - self.u[i]).clamp(0.0, 1.0) * 0.5;
+        let base = self.gradient.sample(v);
+        std::array::from_fn(|c| (base[c] as f32 * (1.0 - heat) + FRONT_RGB[c] as f32 * heat) as u8)
+    }
+}
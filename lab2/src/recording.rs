@@ -0,0 +1,189 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::{CellState, GameOfLife};
+
+// ===== FORMATO .lab2 =====
+// Todos los enteros se guardan en little-endian:
+//   magic (8 bytes) | versión u32 | ancho u32 | alto u32
+//   estado inicial empaquetado en bits (fila por fila)
+//   cantidad de eventos u32 | eventos (generación u32, x u32, y u32, estado u8)
+//   cantidad de frames u32 | hash u64 de cada generación
+const MAGIC: &[u8; 8] = b"LAB2REC\0";
+const VERSION: u32 = 1;
+
+/// Cambio manual de una célula aplicado antes de avanzar desde `generation`
+#[derive(Clone, Copy, Debug)]
+pub struct CellEvent {
+    pub generation: u32,
+    pub x: usize,
+    pub y: usize,
+    pub state: CellState,
+}
+
+/// Ejecución guardada: estado inicial, eventos y hash de cada generación
+pub struct Recording {
+    pub width: usize,
+    pub height: usize,
+    initial: Vec<CellState>,
+    events: Vec<CellEvent>,
+    frame_hashes: Vec<u64>,
+}
+
+impl Recording {
+    /// Comienza una grabación a partir del estado actual del juego
+    pub fn start(game: &GameOfLife) -> Self {
+        Self {
+            width: game.width(),
+            height: game.height(),
+            initial: game.cells(),
+            events: Vec::new(),
+            frame_hashes: Vec::new(),
+        }
+    }
+
+    /// Registra la generación que se acaba de calcular
+    pub fn record_frame(&mut self, game: &GameOfLife) {
+        self.frame_hashes.push(game.state_hash());
+    }
+
+    /// Registra un cambio manual de una célula, hecho después de la última
+    /// generación grabada y antes de calcular la siguiente
+    pub fn record_event(&mut self, x: usize, y: usize, state: CellState) {
+        let generation = self.frame_hashes.len() as u32;
+        self.events.push(CellEvent { generation, x, y, state });
+    }
+
+    /// Número de generaciones grabadas
+    pub fn frame_count(&self) -> usize {
+        self.frame_hashes.len()
+    }
+
+    /// Reconstruye todas las generaciones (la 0 es el estado inicial),
+    /// verificando que coincidan con los hashes guardados
+    pub fn replay(&self) -> Result<Vec<GameOfLife>, Box<dyn std::error::Error>> {
+        let mut game = GameOfLife::from_cells(self.width, self.height, &self.initial);
+        let mut states = Vec::with_capacity(self.frame_hashes.len() + 1);
+        states.push(game.clone());
+
+        for (generation, &expected) in self.frame_hashes.iter().enumerate() {
+            for event in self.events.iter().filter(|e| e.generation as usize == generation) {
+                game.set_cell(event.x, event.y, event.state);
+            }
+            game.next_generation();
+
+            if game.state_hash() != expected {
+                return Err(invalid_data(format!(
+                    "la generación {} no coincide con la grabación",
+                    generation + 1
+                ))
+                .into());
+            }
+            states.push(game.clone());
+        }
+
+        Ok(states)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(MAGIC)?;
+        write_u32(&mut out, VERSION)?;
+        write_u32(&mut out, self.width as u32)?;
+        write_u32(&mut out, self.height as u32)?;
+
+        out.write_all(&pack_cells(&self.initial))?;
+
+        write_u32(&mut out, self.events.len() as u32)?;
+        for event in &self.events {
+            write_u32(&mut out, event.generation)?;
+            write_u32(&mut out, event.x as u32)?;
+            write_u32(&mut out, event.y as u32)?;
+            out.write_all(&[(event.state == CellState::Alive) as u8])?;
+        }
+
+        write_u32(&mut out, self.frame_hashes.len() as u32)?;
+        for hash in &self.frame_hashes {
+            out.write_all(&hash.to_le_bytes())?;
+        }
+        out.flush()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut input = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("no es un archivo de grabación .lab2".to_string()));
+        }
+        let version = read_u32(&mut input)?;
+        if version != VERSION {
+            return Err(invalid_data(format!("versión de grabación no soportada: {}", version)));
+        }
+
+        let width = read_u32(&mut input)? as usize;
+        let height = read_u32(&mut input)? as usize;
+        let initial = read_cells(&mut input, width * height)?;
+
+        let event_count = read_u32(&mut input)?;
+        let mut events = Vec::with_capacity(event_count as usize);
+        for _ in 0..event_count {
+            let generation = read_u32(&mut input)?;
+            let x = read_u32(&mut input)? as usize;
+            let y = read_u32(&mut input)? as usize;
+            let mut state = [0u8; 1];
+            input.read_exact(&mut state)?;
+            let state = if state[0] == 1 { CellState::Alive } else { CellState::Dead };
+            events.push(CellEvent { generation, x, y, state });
+        }
+
+        let frame_count = read_u32(&mut input)?;
+        let mut frame_hashes = Vec::with_capacity(frame_count as usize);
+        for _ in 0..frame_count {
+            let mut bytes = [0u8; 8];
+            input.read_exact(&mut bytes)?;
+            frame_hashes.push(u64::from_le_bytes(bytes));
+        }
+
+        Ok(Self { width, height, initial, events, frame_hashes })
+    }
+}
+
+/// Empaqueta las células en bits, 8 por byte, empezando por el bit más bajo
+pub fn pack_cells(cells: &[CellState]) -> Vec<u8> {
+    let mut packed = vec![0u8; cells.len().div_ceil(8)];
+    for (i, cell) in cells.iter().enumerate() {
+        if *cell == CellState::Alive {
+            packed[i / 8] |= 1 << (i % 8);
+        }
+    }
+    packed
+}
+
+/// Lee `count` células empaquetadas con `pack_cells`
+pub fn read_cells(input: &mut impl Read, count: usize) -> io::Result<Vec<CellState>> {
+    let mut packed = vec![0u8; count.div_ceil(8)];
+    input.read_exact(&mut packed)?;
+    Ok((0..count)
+        .map(|i| match (packed[i / 8] >> (i % 8)) & 1 {
+            1 => CellState::Alive,
+            _ => CellState::Dead,
+        })
+        .collect())
+}
+
+pub fn write_u32(out: &mut impl Write, value: u32) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+pub fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
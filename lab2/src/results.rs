@@ -0,0 +1,269 @@
+use std::path::{Path, PathBuf};
+
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags, params};
+
+use crate::detection::Stable;
+
+/// Base de datos donde se guardan los resultados si no se elige otra
+pub const RESULTS_FILE: &str = "resultados.db";
+
+/// Tablas de la base. Las semillas van como texto porque SQLite guarda los
+/// enteros con signo y una semilla de 64 bits no siempre entra.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS ejecuciones (
+    id INTEGER PRIMARY KEY,
+    fecha TEXT NOT NULL DEFAULT (datetime('now')),
+    regla TEXT NOT NULL,
+    semilla TEXT,
+    densidad REAL NOT NULL,
+    ancho INTEGER NOT NULL,
+    alto INTEGER NOT NULL,
+    generaciones INTEGER NOT NULL,
+    poblacion INTEGER NOT NULL,
+    vida INTEGER NOT NULL,
+    estable INTEGER NOT NULL,
+    periodo INTEGER
+);
+CREATE TABLE IF NOT EXISTS matusalenes (
+    id INTEGER PRIMARY KEY,
+    fecha TEXT NOT NULL DEFAULT (datetime('now')),
+    celulas INTEGER NOT NULL,
+    vida INTEGER NOT NULL,
+    periodo INTEGER NOT NULL,
+    rle TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS reglas (
+    id INTEGER PRIMARY KEY,
+    fecha TEXT NOT NULL DEFAULT (datetime('now')),
+    estados INTEGER NOT NULL,
+    puntaje REAL NOT NULL,
+    regla TEXT NOT NULL
+);
+";
+
+/// Resultado de una ejecución, una fila de `ejecuciones`
+#[derive(Clone, Debug)]
+pub struct RunResult {
+    /// Regla en notación B/S
+    pub rule: String,
+    /// `None` si el universo no salió de una sopa al azar
+    pub seed: Option<u64>,
+    pub density: f64,
+    pub width: usize,
+    pub height: usize,
+    /// Generaciones que se simularon
+    pub generations: u64,
+    /// Células vivas al terminar
+    pub population: u64,
+    /// Ciclo en el que quedó, si se detectó uno
+    pub stable: Option<Stable>,
+}
+
+impl RunResult {
+    /// Generaciones hasta quedar fijo o en un ciclo; si no se estabilizó,
+    /// todas las simuladas
+    pub fn lifespan(&self) -> u64 {
+        self.stable.map_or(self.generations, |stable| stable.since)
+    }
+}
+
+/// Consultas preparadas de `lab2 results query`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    /// Las `n` ejecuciones que más tardaron en estabilizarse
+    Longest(usize),
+    /// Población final y vida promedio para cada densidad inicial
+    ByDensity,
+    /// Una consulta SQL que solo lee (un SELECT); las que modifican la
+    /// base se rechazan
+    Sql(String),
+}
+
+impl Query {
+    fn sql(&self) -> &str {
+        match self {
+            Query::Longest(_) => {
+                "SELECT semilla, regla, densidad, ancho || 'x' || alto AS tamano, vida, estable, periodo \
+                 FROM ejecuciones ORDER BY vida DESC LIMIT ?1"
+            }
+            Query::ByDensity => {
+                "SELECT densidad, COUNT(*) AS ejecuciones, ROUND(AVG(poblacion), 1) AS poblacion_media, \
+                 ROUND(AVG(vida), 1) AS vida_media, ROUND(AVG(estable), 2) AS estables \
+                 FROM ejecuciones GROUP BY densidad ORDER BY densidad"
+            }
+            Query::Sql(statement) => statement,
+        }
+    }
+}
+
+/// Resultados de experimentos en una base SQLite, para que muchas
+/// ejecuciones y búsquedas no terminen en un montón de CSV sueltos. SQLite
+/// va incluido en el programa (con rusqlite), así que no hace falta
+/// instalar nada.
+pub struct ResultsDb {
+    path: PathBuf,
+    connection: Connection,
+}
+
+impl ResultsDb {
+    /// Abre la base en `path`, creándola y sus tablas si no existen
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let connection = Connection::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { path: path.to_path_buf(), connection })
+    }
+
+    /// Abre una base que ya existe solo para leerla, como hace
+    /// `results query`: ninguna consulta puede modificarla
+    pub fn open_read_only(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Self { path: path.to_path_buf(), connection })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record_run(&self, run: &RunResult) -> Result<(), Box<dyn std::error::Error>> {
+        self.connection.execute(
+            "INSERT INTO ejecuciones (regla, semilla, densidad, ancho, alto, generaciones, poblacion, vida, estable, periodo) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                run.rule,
+                run.seed.map(|seed| seed.to_string()),
+                run.density,
+                run.width as i64,
+                run.height as i64,
+                run.generations as i64,
+                run.population as i64,
+                run.lifespan() as i64,
+                run.stable.is_some(),
+                run.stable.map(|stable| stable.period as i64),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Un patrón encontrado por la búsqueda de matusalenes
+    pub fn record_methuselah(&self, cells: usize, lifespan: usize, period: usize, rle: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.connection.execute(
+            "INSERT INTO matusalenes (celulas, vida, periodo, rle) VALUES (?1, ?2, ?3, ?4)",
+            params![cells as i64, lifespan as i64, period as i64, rle],
+        )?;
+        Ok(())
+    }
+
+    /// Una regla elegida por la búsqueda evolutiva, con su puntaje
+    pub fn record_rule(&self, states: u8, fitness: f64, rule: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.connection.execute(
+            "INSERT INTO reglas (estados, puntaje, regla) VALUES (?1, ?2, ?3)",
+            params![states, fitness, rule],
+        )?;
+        Ok(())
+    }
+
+    /// Resultado de `query` como una tabla de texto con encabezados
+    pub fn query(&self, query: &Query) -> Result<String, Box<dyn std::error::Error>> {
+        let mut statement = self.connection.prepare(query.sql())?;
+        if !statement.readonly() {
+            return Err("results query solo acepta consultas que leen la base (SELECT)".into());
+        }
+        let columns: Vec<String> = statement.column_names().into_iter().map(str::to_string).collect();
+        let mut rows = match query {
+            Query::Longest(n) => statement.query([*n as i64])?,
+            _ => statement.query([])?,
+        };
+        let mut table = Vec::new();
+        while let Some(row) = rows.next()? {
+            let cells = (0..columns.len()).map(|i| row.get_ref(i).map(show_value)).collect::<Result<_, _>>()?;
+            table.push(cells);
+        }
+        Ok(format_table(&columns, &table))
+    }
+}
+
+/// Un valor de SQLite como texto; NULL queda vacío
+fn show_value(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(n) => n.to_string(),
+        ValueRef::Real(x) => x.to_string(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned(),
+        ValueRef::Blob(bytes) => format!("({} bytes)", bytes.len()),
+    }
+}
+
+/// Filas en columnas alineadas a la izquierda, con los encabezados
+/// subrayados (como `sqlite3 -header -column`)
+fn format_table(columns: &[String], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| rows.iter().map(|row| row[i].chars().count()).chain([columns[i].chars().count()]).max().unwrap_or(0))
+        .collect();
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells.iter().zip(&widths).map(|(cell, &width)| format!("{:<width$}", cell)).collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+    let mut text = line(columns);
+    text += &line(&widths.iter().map(|&width| "-".repeat(width)).collect::<Vec<_>>());
+    for row in rows {
+        text += &line(row);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("lab2-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn run(rule: &str, seed: Option<u64>, generations: u64) -> RunResult {
+        RunResult { rule: rule.to_string(), seed, density: 0.3, width: 10, height: 8, generations, population: 4, stable: None }
+    }
+
+    #[test]
+    fn values_are_stored_as_given() {
+        let path = temp_db("values");
+        let db = ResultsDb::open(&path).unwrap();
+        // Una comilla en el texto no rompe la consulta y la semilla no pierde dígitos
+        db.record_run(&run("B3/S23'); DROP TABLE ejecuciones; --", Some(u64::MAX), 50)).unwrap();
+        db.record_run(&run("B36/S23", None, 80)).unwrap();
+        db.record_methuselah(5, 1103, 2, "bo$2bo$3o!").unwrap();
+        // Abrir otra vez no repite ni borra nada
+        let db = ResultsDb::open(&path).unwrap();
+
+        let table = db.query(&Query::Longest(1)).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("semilla"));
+        assert!(lines[2].contains("B36/S23") && lines[2].contains("10x8"));
+
+        let seeds = db.query(&Query::Sql("SELECT semilla, regla FROM ejecuciones WHERE semilla IS NOT NULL".into())).unwrap();
+        assert!(seeds.contains(&u64::MAX.to_string()));
+        assert!(seeds.contains("B3/S23'); DROP TABLE ejecuciones; --"));
+        let count = db.query(&Query::Sql("SELECT COUNT(*) FROM matusalenes".into())).unwrap();
+        assert_eq!(count.lines().nth(2), Some("1"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn free_sql_can_only_read() {
+        let path = temp_db("read-only");
+        ResultsDb::open(&path).unwrap().record_run(&run("B3/S23", Some(1), 10)).unwrap();
+
+        let db = ResultsDb::open_read_only(&path).unwrap();
+        assert!(db.query(&Query::Sql("DELETE FROM ejecuciones".into())).is_err());
+        assert!(db.query(&Query::Sql("DROP TABLE ejecuciones".into())).is_err());
+        // Tampoco como segunda sentencia: solo se ejecuta la primera
+        let _ = db.query(&Query::Sql("SELECT 1; DELETE FROM ejecuciones".into()));
+        assert!(db.query(&Query::ByDensity).unwrap().contains("0.3"));
+        assert_eq!(db.query(&Query::Longest(10)).unwrap().lines().count(), 3);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,505 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::geometry::{CellPos, GridSize};
+use crate::rule::Rule;
+use crate::{Anchor, CellState, GameOfLife, Placement, Stamp};
+
+/// Ancho máximo de las líneas del cuerpo al escribir un RLE
+const LINE_WIDTH: usize = 70;
+
+/// Lado más grande que se acepta en un RLE, con o sin cabecera
+const MAX_PATTERN_SIDE: usize = 1 << 16;
+
+/// Células vivas más que se aceptan en un RLE, para que una corrida enorme
+/// no agote la memoria
+const MAX_PATTERN_CELLS: usize = 1 << 24;
+
+/// Rotación en sentido horario al estampar un patrón
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Rotation {
+    #[default]
+    #[cfg_attr(feature = "cli", value(name = "0"))]
+    None,
+    #[cfg_attr(feature = "cli", value(name = "90"))]
+    Quarter,
+    #[cfg_attr(feature = "cli", value(name = "180"))]
+    Half,
+    #[cfg_attr(feature = "cli", value(name = "270"))]
+    ThreeQuarters,
+}
+
+impl Rotation {
+    pub fn degrees(self) -> u32 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Quarter => 90,
+            Rotation::Half => 180,
+            Rotation::ThreeQuarters => 270,
+        }
+    }
+}
+
+/// Orientación de un patrón al estamparlo: reflejado horizontalmente (si
+/// `flip`) y luego girado. Las 4 rotaciones por 2 reflejos dan las 8
+/// direcciones en que puede viajar una nave como el planeador.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Transform {
+    pub rotation: Rotation,
+    pub flip: bool,
+}
+
+impl Transform {
+    /// Las 8 orientaciones, empezando por la original
+    pub const ALL: [Transform; 8] = [
+        Transform::new(Rotation::None, false),
+        Transform::new(Rotation::Quarter, false),
+        Transform::new(Rotation::Half, false),
+        Transform::new(Rotation::ThreeQuarters, false),
+        Transform::new(Rotation::None, true),
+        Transform::new(Rotation::Quarter, true),
+        Transform::new(Rotation::Half, true),
+        Transform::new(Rotation::ThreeQuarters, true),
+    ];
+
+    pub const fn new(rotation: Rotation, flip: bool) -> Self {
+        Self { rotation, flip }
+    }
+
+    /// La orientación que sigue en `ALL`, volviendo a la original después
+    /// de la última
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&t| t == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}°", self.rotation.degrees())?;
+        if self.flip {
+            write!(f, " reflejado")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formato de texto de un patrón
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum PatternFormat {
+    /// Corridas de células (`bo$2bo$3o!`), el formato de Golly
+    #[default]
+    Rle,
+    /// Texto plano de LifeWiki: una línea por fila con `.` y `O`
+    Cells,
+}
+
+impl PatternFormat {
+    /// `.cells` es texto plano; cualquier otra extensión, RLE
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("cells") => PatternFormat::Cells,
+            _ => PatternFormat::Rle,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            PatternFormat::Rle => "rle",
+            PatternFormat::Cells => "cells",
+        }
+    }
+
+    pub fn parse(self, text: &str) -> Result<RlePattern, String> {
+        match self {
+            PatternFormat::Rle => RlePattern::parse(text),
+            PatternFormat::Cells => RlePattern::parse_cells(text),
+        }
+    }
+
+    pub fn write(self, pattern: &RlePattern) -> String {
+        match self {
+            PatternFormat::Rle => pattern.to_string(),
+            PatternFormat::Cells => pattern.to_cells(),
+        }
+    }
+}
+
+/// Patrón leído de un archivo RLE (el formato de Golly y LifeWiki) o de
+/// texto plano .cells
+#[derive(Clone, Debug, PartialEq)]
+pub struct RlePattern {
+    /// Nombre de la línea `#N`, si la hay
+    pub name: Option<String>,
+    /// Regla de la cabecera, si la hay
+    pub rule: Option<Rule>,
+    pub width: usize,
+    pub height: usize,
+    /// Células vivas, relativas a la esquina superior izquierda
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl RlePattern {
+    /// Lee un texto RLE:
+    ///
+    /// ```text
+    /// #N Glider
+    /// x = 3, y = 3, rule = B3/S23
+    /// bob$2bo$3o!
+    /// ```
+    ///
+    /// Las líneas `#` son comentarios (salvo `#N`, el nombre); `b` es una
+    /// célula muerta, `o` una viva, `$` termina una fila y `!` el patrón.
+    /// Las corridas no pueden pasarse del ancho y alto de la cabecera.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut name = None;
+        let mut header = None;
+        let mut body = String::new();
+        for line in text.lines().map(str::trim) {
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(n) = comment.strip_prefix('N') {
+                    name = Some(n.trim().to_string());
+                }
+            } else if header.is_none() && line.starts_with('x') {
+                header = Some(line);
+            } else {
+                body.push_str(line);
+            }
+        }
+
+        let mut rule = None;
+        let (mut width, mut height) = (0, 0);
+        if let Some(header) = header {
+            for field in header.split(',') {
+                let (key, value) = field.split_once('=').ok_or_else(|| format!("cabecera RLE inválida: {}", header))?;
+                let value = value.trim();
+                match key.trim() {
+                    "x" => width = value.parse().map_err(|_| format!("ancho inválido en la cabecera: {}", value))?,
+                    "y" => height = value.parse().map_err(|_| format!("alto inválido en la cabecera: {}", value))?,
+                    "rule" => rule = Some(Rule::parse(value)?),
+                    _ => {}
+                }
+            }
+            if width > MAX_PATTERN_SIDE || height > MAX_PATTERN_SIDE {
+                return Err(format!("el patrón de {}x{} es demasiado grande (máximo {} por lado)", width, height, MAX_PATTERN_SIDE));
+            }
+        }
+
+        // Sin cabecera, el patrón manda hasta el tamaño máximo
+        let max_x = if width > 0 { width } else { MAX_PATTERN_SIDE };
+        let max_y = if height > 0 { height } else { MAX_PATTERN_SIDE };
+        let advance = |position: usize, run: usize, max: usize| {
+            position.checked_add(run).filter(|&end| end <= max)
+        };
+
+        let mut cells = Vec::new();
+        let (mut x, mut y) = (0, 0);
+        let mut count = String::new();
+        for c in body.chars() {
+            if c.is_ascii_digit() {
+                count.push(c);
+                continue;
+            }
+            let run: usize = if count.is_empty() { 1 } else { count.parse().map_err(|_| format!("corrida inválida: {}", count))? };
+            count.clear();
+            let past_width = || format!("la corrida de {} en la fila {} se pasa del ancho {}", run, y, max_x);
+            match c {
+                'b' | '.' => x = advance(x, run, max_x).ok_or_else(past_width)?,
+                'o' => {
+                    let end = advance(x, run, max_x).ok_or_else(past_width)?;
+                    if y >= max_y {
+                        return Err(format!("la fila {} se pasa del alto {}", y, max_y));
+                    }
+                    if cells.len() + run > MAX_PATTERN_CELLS {
+                        return Err(format!("el patrón tiene más de {} células vivas", MAX_PATTERN_CELLS));
+                    }
+                    cells.extend((x..end).map(|cx| (cx, y)));
+                    x = end;
+                }
+                '$' => {
+                    y = advance(y, run, max_y).ok_or_else(|| format!("la corrida de {} filas se pasa del alto {}", run, max_y))?;
+                    x = 0;
+                }
+                '!' => break,
+                c if c.is_whitespace() => {}
+                c => return Err(format!("símbolo desconocido en el RLE: '{}'", c)),
+            }
+        }
+
+        // Sin cabecera, el tamaño sale de las células
+        width = cells.iter().map(|&(x, _)| x + 1).fold(width, usize::max);
+        height = cells.iter().map(|&(_, y)| y + 1).fold(height, usize::max);
+        Ok(Self { name, rule, width, height, cells })
+    }
+
+    /// Lee un texto plano .cells:
+    ///
+    /// ```text
+    /// !Name: Glider
+    /// .O
+    /// ..O
+    /// OOO
+    /// ```
+    ///
+    /// Las líneas `!` son comentarios (salvo `!Name:`, el nombre, y
+    /// `!Rule:`, la regla); `.` es una célula muerta y `O` una viva. Las
+    /// filas pueden terminar antes del ancho del patrón y una línea vacía es
+    /// una fila de células muertas.
+    pub fn parse_cells(text: &str) -> Result<Self, String> {
+        let (mut name, mut rule) = (None, None);
+        let mut cells = Vec::new();
+        let (mut width, mut height) = (0, 0);
+        let mut y = 0;
+        for line in text.lines().map(str::trim_end) {
+            if let Some(comment) = line.strip_prefix('!') {
+                if let Some(n) = comment.strip_prefix("Name:") {
+                    name = Some(n.trim().to_string());
+                } else if let Some(r) = comment.strip_prefix("Rule:") {
+                    rule = Some(Rule::parse(r.trim())?);
+                }
+                continue;
+            }
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '.' => {}
+                    'O' | 'o' | '*' => cells.push((x, y)),
+                    c => return Err(format!("símbolo desconocido en el .cells: '{}'", c)),
+                }
+            }
+            // Las líneas vacías del final no son parte del patrón
+            if !line.is_empty() {
+                width = width.max(line.chars().count());
+                height = y + 1;
+            }
+            y += 1;
+        }
+        Ok(Self { name, rule, width, height, cells })
+    }
+
+    /// Caja que ocupa el patrón
+    pub fn size(&self) -> GridSize {
+        GridSize::new(self.width, self.height)
+    }
+
+    /// Lee un patrón de un archivo, en el formato que indica su extensión.
+    /// Los errores incluyen la ruta.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        PatternFormat::from_path(path).parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// El patrón como texto plano .cells, con el nombre y la regla como
+    /// comentarios. Las filas terminan en su última célula viva.
+    pub fn to_cells(&self) -> String {
+        let mut text = String::new();
+        if let Some(name) = &self.name {
+            text.push_str(&format!("!Name: {}\n", name));
+        }
+        if let Some(rule) = self.rule {
+            text.push_str(&format!("!Rule: {}\n", rule));
+        }
+        let mut rows = vec![Vec::new(); self.height];
+        for &(x, y) in &self.cells {
+            rows[y].push(x);
+        }
+        for row in &mut rows {
+            row.sort_unstable();
+            // Una fila vacía se escribe como un punto para que se vea
+            let mut line = vec!['.'; row.last().map_or(1, |&x| x + 1)];
+            for &x in row.iter() {
+                line[x] = 'O';
+            }
+            text.extend(line);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Las células vivas de `game`, recortadas a la caja que ocupan, con la
+    /// regla del universo
+    pub fn from_game(game: &GameOfLife) -> Self {
+        let cells = game.cells();
+        let alive: Vec<(usize, usize)> = (0..game.height())
+            .flat_map(|y| (0..game.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| cells[y * game.width() + x] == CellState::Alive)
+            .collect();
+        let min_x = alive.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = alive.first().map_or(0, |&(_, y)| y);
+        let cells: Vec<(usize, usize)> = alive.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+        let width = cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+        Self { name: None, rule: Some(game.rule()), width, height, cells }
+    }
+
+    /// El patrón con la orientación `transform`
+    pub fn transformed(&self, transform: Transform) -> Self {
+        let Transform { rotation, flip } = transform;
+        let (w, h) = (self.width, self.height);
+        let cells = self
+            .cells
+            .iter()
+            .map(|&(x, y)| if flip { (w - 1 - x, y) } else { (x, y) })
+            .map(|(x, y)| match rotation {
+                Rotation::None => (x, y),
+                Rotation::Quarter => (h - 1 - y, x),
+                Rotation::Half => (w - 1 - x, h - 1 - y),
+                Rotation::ThreeQuarters => (y, w - 1 - x),
+            })
+            .collect();
+        let (width, height) = match rotation {
+            Rotation::None | Rotation::Half => (w, h),
+            Rotation::Quarter | Rotation::ThreeQuarters => (h, w),
+        };
+        Self { cells, width, height, ..self.clone() }
+    }
+
+    /// Estampa el patrón con su esquina superior izquierda en (`x`, `y`);
+    /// lo que cae fuera del universo se pierde
+    pub fn stamp(&self, game: &mut GameOfLife, x: usize, y: usize) -> Stamp {
+        game.stamp(x, y, &self.cells)
+    }
+}
+
+impl fmt::Display for RlePattern {
+    /// Escribe el patrón en formato RLE, con líneas de hasta 70 caracteres
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = &self.name {
+            writeln!(f, "#N {}", name)?;
+        }
+        writeln!(f, "x = {}, y = {}, rule = {}", self.width, self.height, self.rule.unwrap_or_default())?;
+        if self.cells.is_empty() {
+            return writeln!(f, "!");
+        }
+
+        let last_row = self.cells.iter().map(|&(_, y)| y).max().unwrap_or(0);
+        let mut rows = vec![Vec::new(); last_row + 1];
+        for &(x, y) in &self.cells {
+            rows[y].push(x);
+        }
+        // Cada corrida es (cantidad, símbolo); las filas vacías se acumulan en '$'
+        let mut runs: Vec<(usize, char)> = Vec::new();
+        let mut push = |count: usize, symbol: char| match runs.last_mut() {
+            Some((n, s)) if *s == symbol => *n += count,
+            _ => runs.push((count, symbol)),
+        };
+        for (y, row) in rows.iter_mut().enumerate() {
+            row.sort_unstable();
+            let mut x = 0;
+            for &cx in row.iter() {
+                if cx > x {
+                    push(cx - x, 'b');
+                }
+                push(1, 'o');
+                x = cx + 1;
+            }
+            push(1, if y == last_row { '!' } else { '$' });
+        }
+
+        let mut line = String::new();
+        for (count, symbol) in runs {
+            let token = if count > 1 { format!("{}{}", count, symbol) } else { symbol.to_string() };
+            if line.len() + token.len() > LINE_WIDTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+impl GameOfLife {
+    /// Las células vivas en formato RLE, recortadas a la caja que ocupan
+    pub fn to_rle(&self) -> String {
+        RlePattern::from_game(self).to_string()
+    }
+
+    /// Las células vivas como texto plano .cells, recortadas a la caja que
+    /// ocupan
+    pub fn to_cells(&self) -> String {
+        RlePattern::from_game(self).to_cells()
+    }
+
+    /// Estampa `pattern` con la orientación `transform` y su esquina
+    /// superior izquierda (ya orientado) en `at`. Lo que cae fuera del
+    /// universo se pierde; `try_add_pattern` avisa en lugar de recortar.
+    pub fn add_pattern(&mut self, pattern: &RlePattern, at: CellPos, transform: Transform) -> Stamp {
+        pattern.transformed(transform).stamp(self, at.x, at.y)
+    }
+
+    /// Como `add_pattern`, pero si alguna célula quedaría fuera del universo
+    /// no estampa nada y el error dice cuáles
+    pub fn try_add_pattern(&mut self, pattern: &RlePattern, at: CellPos, transform: Transform) -> Result<Stamp, String> {
+        self.place_pattern(pattern, at, transform, Placement::Strict)
+    }
+
+    /// Estampa `pattern` como `add_pattern`, haciendo con las células que no
+    /// entran lo que diga `placement`: recortarlas, fallar o agrandar el
+    /// universo (que conserva sus células en el mismo lugar)
+    pub fn place_pattern(&mut self, pattern: &RlePattern, at: CellPos, transform: Transform, placement: Placement) -> Result<Stamp, String> {
+        let pattern = pattern.transformed(transform);
+        let size = self.size();
+        let outside: Vec<CellPos> = pattern
+            .cells
+            .iter()
+            .map(|&(dx, dy)| CellPos::new(at.x + dx, at.y + dy))
+            .filter(|&pos| !size.contains(pos))
+            .collect();
+        match placement {
+            Placement::Clip => {}
+            Placement::Strict if !outside.is_empty() => {
+                return Err(clipped_cells_message(&outside, pattern.cells.len(), size));
+            }
+            Placement::Strict => {}
+            Placement::Fit => {
+                let width = size.width.max(at.x + pattern.width);
+                let height = size.height.max(at.y + pattern.height);
+                if (width, height) != (size.width, size.height) {
+                    self.resize(width, height, Anchor::TopLeft);
+                }
+            }
+        }
+        Ok(pattern.stamp(self, at.x, at.y))
+    }
+
+    /// Como `place_pattern`, con el patrón en el punto (`x`, `y`) de la
+    /// convención de `set_coordinates` (ver `Coordinates::pattern_corner`)
+    pub fn place_pattern_at(
+        &mut self,
+        pattern: &RlePattern,
+        (x, y): (i64, i64),
+        transform: Transform,
+        placement: Placement,
+    ) -> Result<Stamp, String> {
+        let pattern = pattern.transformed(transform);
+        let corner = self
+            .coordinates()
+            .pattern_corner(x, y, pattern.size(), self.size())
+            .ok_or_else(|| format!("el patrón en ({}, {}) empieza fuera del universo de {}", x, y, self.size()))?;
+        self.place_pattern(&pattern, corner, Transform::default(), placement)
+    }
+
+    /// Estampa un patrón RLE con su esquina superior izquierda en (`x`, `y`)
+    pub fn place_rle(&mut self, x: usize, y: usize, rle: &str) -> Result<Stamp, String> {
+        Ok(RlePattern::parse(rle)?.stamp(self, x, y))
+    }
+}
+
+/// Cuántas células de un patrón de `total` quedarían fuera de un universo
+/// de `size` y las primeras de ellas
+fn clipped_cells_message(outside: &[CellPos], total: usize, size: GridSize) -> String {
+    const SHOWN: usize = 5;
+    let mut cells: Vec<String> = outside.iter().take(SHOWN).map(|pos| format!("({}, {})", pos.x, pos.y)).collect();
+    if outside.len() > SHOWN {
+        cells.push("...".to_string());
+    }
+    format!(
+        "el patrón quedaría recortado: {} de sus {} células caen fuera del universo de {}: {}",
+        outside.len(),
+        total,
+        size,
+        cells.join(", ")
+    )
+}
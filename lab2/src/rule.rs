@@ -0,0 +1,86 @@
+use std::fmt;
+
+use rand::Rng;
+
+/// Probabilidad de cada cantidad de vecinos en una regla al azar
+const RANDOM_RULE_DENSITY: f64 = 0.3;
+
+/// Regla de tipo Life en notación B/S: cuántos vecinos vivos hacen nacer a
+/// una célula muerta (B) y cuántos mantienen viva a una viva (S)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl Rule {
+    /// B3/S23, el Juego de la Vida de Conway
+    pub fn conway() -> Self {
+        Self::parse("B3/S23").expect("B3/S23 es una regla válida")
+    }
+
+    /// Lee una regla como `B36/S23`; también acepta la forma `23/36`
+    /// (supervivencia/nacimiento) y minúsculas
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let error = || format!("se esperaba una regla como B36/S23: {}", text);
+        let (first, second) = text.trim().split_once('/').ok_or_else(error)?;
+        let (first, second) = (first.trim().to_ascii_uppercase(), second.trim().to_ascii_uppercase());
+        let (birth, survival) = match (first.strip_prefix('B'), second.strip_prefix('S')) {
+            (Some(birth), Some(survival)) => (birth.to_string(), survival.to_string()),
+            (None, None) if !first.starts_with('S') => (second, first),
+            _ => return Err(error()),
+        };
+
+        let counts = |digits: &str| -> Result<[bool; 9], String> {
+            let mut counts = [false; 9];
+            for c in digits.chars() {
+                match c.to_digit(10) {
+                    Some(n) if n <= 8 => counts[n as usize] = true,
+                    _ => return Err(format!("cantidad de vecinos inválida en la regla: '{}'", c)),
+                }
+            }
+            Ok(counts)
+        };
+        Ok(Self { birth: counts(&birth)?, survival: counts(&survival)? })
+    }
+
+    /// Regla al azar, sin B0 ni B1 (con ellas el universo se llena o
+    /// explota enseguida) y con al menos una cantidad de nacimiento
+    pub fn random(rng: &mut impl Rng) -> Self {
+        loop {
+            let mut birth = [false; 9];
+            for born in &mut birth[2..] {
+                *born = rng.gen_bool(RANDOM_RULE_DENSITY);
+            }
+            let survival = std::array::from_fn(|_| rng.gen_bool(RANDOM_RULE_DENSITY));
+            if birth.contains(&true) {
+                return Self { birth, survival };
+            }
+        }
+    }
+
+    /// Si una célula muerta con `neighbors` vecinos vivos nace
+    pub fn is_birth(&self, neighbors: usize) -> bool {
+        self.birth.get(neighbors).copied().unwrap_or(false)
+    }
+
+    /// Si una célula viva con `neighbors` vecinos vivos sobrevive
+    pub fn is_survival(&self, neighbors: usize) -> bool {
+        self.survival.get(neighbors).copied().unwrap_or(false)
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::conway()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = |counts: &[bool; 9]| -> String {
+            (0..9).filter(|&n| counts[n]).map(|n| char::from(b'0' + n as u8)).collect()
+        };
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
@@ -0,0 +1,241 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::affinity;
+use crate::cancel::CancelToken;
+use crate::simulation::Simulation;
+use crate::totalistic::{self, MultiStateLife, TotalisticRule, TRIAL_SIZE};
+
+/// Generaciones máximas al medir la longevidad
+const LONGEVITY_LIMIT: usize = 500;
+/// Condiciones iniciales distintas con las que se evalúa cada regla
+const TRIALS_PER_RULE: usize = 2;
+/// Participantes de cada torneo al elegir padres
+const TOURNAMENT_SIZE: usize = 3;
+/// Mejores reglas que pasan sin cambios a la siguiente generación
+const ELITE: usize = 2;
+/// Generaciones que corre la sopa antes de buscar planeadores
+const GLIDER_WARMUP: usize = 100;
+/// Células vivas máximas de un planeador
+const GLIDER_MAX_CELLS: usize = 12;
+/// Lado máximo de la caja de un planeador
+const GLIDER_MAX_SIDE: usize = 6;
+/// Período máximo con el que se prueba si un grupo se desplaza
+const GLIDER_MAX_PERIOD: usize = 8;
+
+/// Qué se quiere maximizar al evolucionar reglas
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SearchMetric {
+    /// El puntaje del explorador: entropía y actividad moderada
+    Interest,
+    /// Generaciones hasta que el universo queda fijo o oscilando con
+    /// período 2. Las reglas que no se estabilizan antes del límite valen 0,
+    /// porque casi siempre son ruido.
+    Longevity,
+    /// Planeadores que quedan en la sopa: grupos pequeños que, solos en un
+    /// universo vacío, vuelven a su forma desplazados en pocas generaciones
+    Gliders,
+}
+
+/// Parámetros del algoritmo genético
+pub struct SearchConfig {
+    pub states: u8,
+    pub metric: SearchMetric,
+    pub population: usize,
+    pub generations: usize,
+    /// Probabilidad de cambiar cada entrada de la tabla de un hijo
+    pub mutation_rate: f64,
+    pub lambda: f64,
+    pub density: f64,
+    pub workers: usize,
+    /// Fija cada hilo de evaluación a un núcleo
+    pub pin_threads: bool,
+}
+
+/// Una regla y su puntaje según la métrica elegida
+#[derive(Clone)]
+pub struct Candidate {
+    pub rule: TotalisticRule,
+    pub fitness: f64,
+}
+
+/// Generaciones que tarda en estabilizarse un universo al azar, o 0 si no
+/// se estabiliza antes de `LONGEVITY_LIMIT`
+fn longevity(rule: &TotalisticRule, density: f64, rng: &mut impl Rng) -> f64 {
+    let mut simulation = MultiStateLife::new(TRIAL_SIZE, TRIAL_SIZE, rule.clone());
+    simulation.randomize(density, rng);
+
+    let mut two_back = Vec::new();
+    for step in 1..=LONGEVITY_LIMIT {
+        let before = simulation.cells().to_vec();
+        simulation.step();
+        if simulation.cells() == before || simulation.cells() == two_back {
+            return step as f64;
+        }
+        two_back = before;
+    }
+    0.0
+}
+
+/// Células vivas como (x, y, estado)
+type Shape = Vec<(usize, usize, u8)>;
+
+/// Las células relativas a la esquina de su caja, ordenadas; junto con esa
+/// esquina
+fn normalize(mut cells: Shape) -> (Shape, (usize, usize)) {
+    let min_x = cells.iter().map(|c| c.0).min().unwrap_or(0);
+    let min_y = cells.iter().map(|c| c.1).min().unwrap_or(0);
+    cells.iter_mut().for_each(|c| *c = (c.0 - min_x, c.1 - min_y, c.2));
+    cells.sort_unstable();
+    (cells, (min_x, min_y))
+}
+
+/// Si `shape` (normalizada), sola en un universo vacío, vuelve a la misma
+/// forma en otra posición antes de `GLIDER_MAX_PERIOD` generaciones. Nada se
+/// mueve más de una célula por generación, así que el margen alcanza para
+/// que no toque los bordes.
+fn is_glider(rule: &TotalisticRule, shape: &[(usize, usize, u8)]) -> bool {
+    let margin = GLIDER_MAX_PERIOD + 1;
+    let side = GLIDER_MAX_SIDE + 2 * margin;
+    let mut simulation = MultiStateLife::new(side, side, rule.clone());
+    shape.iter().for_each(|&(x, y, state)| simulation.set_cell(x + margin, y + margin, state));
+
+    for _ in 0..GLIDER_MAX_PERIOD {
+        simulation.step();
+        let alive: Vec<_> = (0..side * side)
+            .filter(|&i| simulation.cells()[i] != 0)
+            .map(|i| (i % side, i / side, simulation.cells()[i]))
+            .collect();
+        if alive.is_empty() || alive.len() > GLIDER_MAX_CELLS * 4 {
+            return false;
+        }
+        let (cells, corner) = normalize(alive);
+        if cells == shape {
+            return corner != (margin, margin);
+        }
+    }
+    false
+}
+
+/// Grupos (vecindad de 8) de células vivas de una sopa ya asentada que son
+/// planeadores. Los que tocan el borde no cuentan: el borde muerto los
+/// deforma.
+fn gliders(rule: &TotalisticRule, density: f64, rng: &mut impl Rng) -> f64 {
+    let mut simulation = MultiStateLife::new(TRIAL_SIZE, TRIAL_SIZE, rule.clone());
+    simulation.randomize(density, rng);
+    for _ in 0..GLIDER_WARMUP {
+        simulation.step();
+    }
+
+    let cells = simulation.cells();
+    let mut seen = vec![false; cells.len()];
+    let mut count = 0;
+    for start in 0..cells.len() {
+        if cells[start] == 0 || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let (mut group, mut pending) = (Vec::new(), vec![start]);
+        while let Some(i) = pending.pop() {
+            let (x, y) = (i % TRIAL_SIZE, i / TRIAL_SIZE);
+            group.push((x, y, cells[i]));
+            for ny in y.saturating_sub(1)..=(y + 1).min(TRIAL_SIZE - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(TRIAL_SIZE - 1) {
+                    let j = ny * TRIAL_SIZE + nx;
+                    if cells[j] != 0 && !seen[j] {
+                        seen[j] = true;
+                        pending.push(j);
+                    }
+                }
+            }
+        }
+        let touches_border = group.iter().any(|&(x, y, _)| x == 0 || y == 0 || x == TRIAL_SIZE - 1 || y == TRIAL_SIZE - 1);
+        if touches_border || group.len() > GLIDER_MAX_CELLS {
+            continue;
+        }
+        let (shape, _) = normalize(group);
+        let fits = shape.iter().all(|&(x, y, _)| x < GLIDER_MAX_SIDE && y < GLIDER_MAX_SIDE);
+        if fits && is_glider(rule, &shape) {
+            count += 1;
+        }
+    }
+    count as f64
+}
+
+fn evaluate(rule: &TotalisticRule, config: &SearchConfig, rng: &mut impl Rng) -> f64 {
+    let total: f64 = (0..TRIALS_PER_RULE)
+        .map(|_| match config.metric {
+            SearchMetric::Interest => totalistic::run_trial(rule.clone(), config.density, rng).score,
+            SearchMetric::Longevity => longevity(rule, config.density, rng),
+            SearchMetric::Gliders => gliders(rule, config.density, rng),
+        })
+        .sum();
+    total / TRIALS_PER_RULE as f64
+}
+
+/// Evalúa las reglas repartiéndolas entre `workers` hilos
+fn evaluate_all(rules: Vec<TotalisticRule>, config: &SearchConfig) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = rules.into_iter().map(|rule| Candidate { rule, fitness: 0.0 }).collect();
+    let chunk_size = candidates.len().div_ceil(config.workers.max(1)).max(1);
+    std::thread::scope(|scope| {
+        for (worker, chunk) in candidates.chunks_mut(chunk_size).enumerate() {
+            scope.spawn(move || {
+                if config.pin_threads {
+                    affinity::pin_or_warn(worker);
+                }
+                let mut rng = rand::thread_rng();
+                for candidate in chunk {
+                    candidate.fitness = evaluate(&candidate.rule, config, &mut rng);
+                }
+            });
+        }
+    });
+    candidates.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    candidates
+}
+
+/// Elige el mejor de unos pocos candidatos al azar
+fn tournament<'a>(candidates: &'a [Candidate], rng: &mut impl Rng) -> &'a Candidate {
+    candidates
+        .choose_multiple(rng, TOURNAMENT_SIZE)
+        .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+        .expect("la población no está vacía")
+}
+
+/// Evoluciona una población de reglas totalísticas y devuelve la última
+/// generación ordenada de mayor a menor puntaje. `on_generation` recibe el
+/// número de generación y la población evaluada.
+pub fn evolve(config: &SearchConfig, on_generation: impl FnMut(usize, &[Candidate])) -> Vec<Candidate> {
+    evolve_cancellable(config, &CancelToken::new(), on_generation)
+}
+
+/// Como `evolve`, revisando `cancel` entre generaciones. Si se cancela,
+/// devuelve la última población evaluada entera.
+pub fn evolve_cancellable(
+    config: &SearchConfig,
+    cancel: &CancelToken,
+    mut on_generation: impl FnMut(usize, &[Candidate]),
+) -> Vec<Candidate> {
+    let mut rng = rand::thread_rng();
+    let population = config.population.max(ELITE + 1);
+    let initial = (0..population).map(|_| TotalisticRule::random(config.states, config.lambda, &mut rng)).collect();
+    let mut candidates = evaluate_all(initial, config);
+    on_generation(0, &candidates);
+
+    for generation in 1..=config.generations {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let mut children: Vec<TotalisticRule> = candidates.iter().take(ELITE).map(|c| c.rule.clone()).collect();
+        while children.len() < population {
+            let (a, b) = (tournament(&candidates, &mut rng), tournament(&candidates, &mut rng));
+            let mut child = a.rule.crossover(&b.rule, &mut rng);
+            child.mutate(config.mutation_rate, config.lambda, &mut rng);
+            children.push(child);
+        }
+        candidates = evaluate_all(children, config);
+        on_generation(generation, &candidates);
+    }
+    candidates
+}
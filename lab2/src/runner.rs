@@ -0,0 +1,1153 @@
+use minifb::{Key, KeyRepeat, MouseButton};
+use std::path::Path;
+
+use crate::breakpoints::{Breakpoint, Condition};
+use crate::camera::{AutoDirector, Camera, ZoomView};
+use crate::channel::{Diffusion, FieldRule};
+use crate::autotune::AutoEngine;
+use crate::engine::{Engine, EngineKind};
+use crate::hashlife::HashLifeEngine;
+use crate::gradient;
+use crate::geometry::{CellPos, GridSize, PixelPos};
+use crate::gif_export::ExportFormat;
+use crate::pacing::{FpsMeter, Speed};
+use crate::recording::Recording;
+use crate::results::{ResultsDb, RunResult};
+use crate::rle::{PatternFormat, RlePattern, Transform};
+use crate::rule::Rule;
+use crate::scenario::Scenario;
+use crate::screen::Screen;
+use crate::snapshot::Snapshotter;
+use crate::sparse::{SparseLife, Viewport};
+use crate::stats::{GenerationStats, Rect, StatsLog, Watch};
+use crate::simulation::{self, Simulation};
+use crate::theme::Theme;
+use crate::trails::Trails;
+use crate::{CellState, GameOfLife, KnownPattern, Placement, Stamp, Topology, UpdateScheme};
+use crate::annotations::{self, ANNOTATION_INDEX, ANNOTATION_RGB};
+use crate::detection::StabilityDetector;
+use crate::discovery::Discovery;
+use crate::options::{ExportOptions, RunOptions, UniverseOptions};
+use crate::{detection, favorites, overlay};
+
+// ===== CONFIGURACIÓN =====
+const DENSITY_BLOCK: usize = 10; // Células por lado de cada bloque del mapa de densidad
+const DIRECTOR_BLOCK: usize = 10; // Células por lado de las regiones que evalúa el director
+const DIRECTOR_INTERVAL: usize = 15; // Generaciones entre cada cambio de objetivo
+const EXPAND_FACTOR: usize = 2; // Ctrl+E multiplica el ancho y el alto del universo por este factor
+const ZOOM_STEP: f32 = 1.25; // Cuánto acerca cada paso de la rueda del mouse o Ctrl+=
+const PAN_STEP: f32 = 0.125; // Parte de la ventana que mueve cada flecha
+const CROP_MARGIN: usize = 5; // Células muertas que deja Ctrl+R alrededor de las vivas
+const SHIFT_STEP: i64 = 1; // Células que se mueve el universo con cada Ctrl+flecha
+const FREEZE_BRUSH: usize = 3; // Lado del pincel con el que se pintan zonas congeladas
+const WATCH_SIZE: usize = 10; // Lado del rectángulo que vigila W+clic
+const ANNOTATION_TEXT_SCALE: usize = 2; // Píxeles de pantalla por píxel de la fuente en las anotaciones
+const STABLE_MAX_PERIOD: usize = 30; // Período más largo que cuenta como ciclo corto para --stop-on-stable
+
+
+/// Estampa el patrón de `path` según --rle-at, --rle-rotate y --rle-flip
+pub fn place_rle_file(game: &mut GameOfLife, path: &Path, options: &RunOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let pattern = RlePattern::load(path)?.transformed(Transform::new(options.rle_rotate, options.rle_flip));
+    if options.placement == Placement::Clip && !game.size().fits(pattern.size()) {
+        println!("Aviso: el patrón mide {} y no entra entero en el universo de {}", pattern.size(), game.size());
+    }
+    if let Some(rule) = pattern.rule
+        && rule != options.rule
+    {
+        println!("Aviso: el patrón es para la regla {} y se usa {} (ver --rule)", rule, options.rule);
+    }
+
+    game.set_coordinates(options.coordinates());
+    let size = game.size();
+    let (x, y) = options.rle_at.unwrap_or_else(|| {
+        let corner = CellPos::new(
+            game.width().saturating_sub(pattern.width) / 2,
+            game.height().saturating_sub(pattern.height) / 2,
+        );
+        game.coordinates().pattern_point(corner, pattern.size(), size)
+    });
+    let placed = game
+        .place_pattern_at(&pattern, (x, y), Transform::default(), options.placement)
+        .map_err(|e| format!("{}: {}", path.display(), e))?
+        .len();
+    println!("{}: {} células en ({}, {})", pattern.name.as_deref().unwrap_or(&path.display().to_string()), placed, x, y);
+    if game.size() != size {
+        println!("El universo creció a {} para que el patrón entre entero", game.size());
+    }
+    Ok(())
+}
+
+/// Corre el universo con HashLife, avanzando --generations-per-frame
+/// generaciones por frame. Solo tiene la ventana básica de `run_backend`.
+/// Ejecuta `game` con uno de los motores que no son el grid de siempre
+pub fn run_engine(
+    mut game: GameOfLife,
+    universe: &UniverseOptions,
+    options: &RunOptions,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let name = match options.engine {
+        EngineKind::Naive => unreachable!("el motor naive usa run_simulation"),
+        EngineKind::Hashlife => "hashlife",
+        EngineKind::Sparse => "sparse",
+        EngineKind::Auto => "auto",
+    };
+    let unsupported = options.record.is_some()
+        || options.auto_director
+        || options.aux.is_some()
+        || options.agents.is_some()
+        || !options.freeze.is_empty()
+        || !options.breakpoints.is_empty()
+        || !options.watches.is_empty()
+        || options.stats.is_some()
+        || options.results.is_some()
+        || options.trails.is_some()
+        || options.stop_on_stable;
+    if unsupported {
+        return Err(format!("--engine {} no funciona con --record, --auto-director, --aux, --agents, --freeze, --break, --watch, --stats, --results, --trails ni --stop-on-stable", name).into());
+    }
+    if options.update_scheme() != UpdateScheme::Synchronous {
+        return Err(format!("--engine {} solo funciona con --update synchronous", name).into());
+    }
+    if options.topology != Topology::Bounded && options.engine != EngineKind::Auto {
+        // Su universo no tiene bordes: lo que sale de la vista sigue existiendo
+        return Err(format!("--engine {} no funciona con --topology torus", name).into());
+    }
+    if options.follow && options.engine != EngineKind::Sparse {
+        return Err("--follow solo funciona con --engine sparse".into());
+    }
+    game.set_rule(options.rule);
+    if options.engine == EngineKind::Auto {
+        game.set_topology(options.topology);
+        // Cada frame dura lo que dice --speed: el motor elige cuántas
+        // generaciones entran en ese tiempo
+        let mut engine = AutoEngine::new(game, export_options.speed().generation_interval())?;
+        run_backend(&mut engine, "Motor automático", universe, export_options)?;
+        println!("Población final: {}", engine.population());
+        return Ok(());
+    }
+    let generations = options.generations_per_frame;
+    let (mut engine, title): (Box<dyn Engine>, _) = match options.engine {
+        EngineKind::Sparse => {
+            let viewport = Viewport::new(game.width(), game.height()).with_follow(options.follow);
+            let sparse = SparseLife::from_game(&game)?.with_viewport(viewport).with_generations_per_step(generations);
+            (Box::new(sparse), "Mundo sin bordes - W, A, S, D mueven la vista")
+        }
+        _ => (Box::new(HashLifeEngine::from_game(&game)?.with_generations_per_step(generations)), "HashLife"),
+    };
+    println!("{}: {} generaciones por frame", title, options.generations_per_frame.max(1));
+    run_backend(engine.as_mut(), title, universe, export_options)?;
+    println!("Población final: {}", engine.population());
+    Ok(())
+}
+
+/// `first_generation` es la generación de `game` cuando la ejecución
+/// continúa desde un estado guardado
+pub fn run_simulation(
+    mut game: GameOfLife,
+    first_generation: u64,
+    universe: &UniverseOptions,
+    options: &RunOptions,
+    scenario: &Scenario,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Iniciando Conway's Game of Life...");
+    game.set_update_scheme(options.update_scheme());
+    game.set_topology(options.topology);
+    game.set_rule(options.rule);
+    let mut theme = universe.theme;
+    game.set_theme(theme.theme());
+    if options.record.is_some() && game.update_scheme() != UpdateScheme::Synchronous {
+        // El reproductor recalcula las generaciones con la actualización síncrona
+        return Err("--record solo funciona con --update synchronous".into());
+    }
+    if options.record.is_some() && options.topology != Topology::Bounded {
+        // y con bordes muertos
+        return Err("--record solo funciona con --topology bounded".into());
+    }
+    if options.record.is_some() && options.rule != Rule::conway() {
+        // y con B3/S23
+        return Err("--record solo funciona con --rule B3/S23".into());
+    }
+    if options.record.is_some() && options.director_shift {
+        // El reproductor no sabe que el universo se trasladó
+        return Err("--record no funciona con --director-shift".into());
+    }
+    if options.aux.is_none() && (options.field_birth.is_some() || options.field_survival.is_some()) {
+        return Err("--field-birth y --field-survival necesitan un canal --aux".into());
+    }
+    if options.record.is_some() && (options.field_birth.is_some() || options.field_survival.is_some()) {
+        // Ni el campo del que dependen los nacimientos
+        return Err("--record no funciona con --field-birth ni --field-survival".into());
+    }
+    if options.record.is_some() && options.agents.is_some() {
+        // Ni lo que escriben los agentes
+        return Err("--record no funciona con --agents".into());
+    }
+    if options.record.is_some() && !options.freeze.is_empty() {
+        // Ni qué células están congeladas
+        return Err("--record no funciona con --freeze".into());
+    }
+    check_annotations(scenario, export_options)?;
+    if options.auto_director && !scenario.annotations.is_empty() {
+        return Err("las anotaciones no funcionan con --auto-director".into());
+    }
+    if options.trails.is_some() && options.auto_director {
+        return Err("--trails no funciona con --auto-director".into());
+    }
+    if options.trails.is_some() && export_options.downsample.is_some() {
+        // Los bloques reducidos se colorean por cantidad de células vivas
+        return Err("--trails no funciona con --downsample".into());
+    }
+    for rect in &options.freeze {
+        game.set_frozen(rect.x, rect.y, rect.width, rect.height, true);
+    }
+    
+    let mut recording = options.record.as_ref().map(|_| Recording::start(&game));
+    let results = options.results.as_deref().map(ResultsDb::open).transpose()?;
+    let mut last_stable = None;
+    let stats_path = options.stats_path();
+    let mut stats_log = match &stats_path {
+        Some(path) => {
+            let mut log = StatsLog::create(path, options.stats_format)?;
+            log.write(&GenerationStats::initial(first_generation, &game))?;
+            Some(log)
+        }
+        None => None,
+    };
+    let mut snapshotter = match options.snapshot_every {
+        Some(every) => {
+            let mut snapshotter = Snapshotter::new(every, &options.snapshot_dir)?;
+            if options.snapshot_images {
+                snapshotter = snapshotter.with_images(export_options.export_scale.unwrap_or(1));
+            }
+            Some(match options.snapshot_keyframe_every {
+                Some(keyframe_every) => snapshotter.with_deltas(keyframe_every),
+                None => snapshotter,
+            })
+        }
+        None => None,
+    };
+    // Con agentes o un canal auxiliar, las células quietas no significan que
+    // nada cambie
+    let mut stability = (options.agents.is_none() && options.aux.is_none()).then(|| {
+        let mut detector = StabilityDetector::new(STABLE_MAX_PERIOD);
+        detector.observe(&game, first_generation);
+        detector
+    });
+
+    // Con el director, la ventana y el GIF muestran solo la vista de la cámara
+    let mut director = options.auto_director.then(|| {
+        let camera = Camera::new(&game, options.view_size, options.view_size);
+        (camera, AutoDirector::new(&game, DIRECTOR_BLOCK, DIRECTOR_INTERVAL))
+    });
+    let GridSize { width: gif_width, height: gif_height } = match &director {
+        Some((camera, _)) => camera.view_size(),
+        None => game.size(),
+    };
+    
+    // Configurar GIF
+    let mut trails = options.trails.map(|length| Trails::new(&game, first_generation, length));
+    // Los rastros van en la paleta del GIF después de los demás colores
+    let mut gif_palette = gif_palette(game.theme(), scenario);
+    let trail_index = gif_palette.len() as u8;
+    if let Some(trails) = &trails {
+        gif_palette.extend(trails.colors(game.theme()));
+    }
+    let mut gif_generator = export_options
+        .create_gif(&universe.output, gif_width, gif_height, 1, universe.frames as usize)?
+        .with_palette(gif_palette);
+    
+    // Configurar ventana
+    let mut scale = universe.scale;
+    let mut speed = export_options.speed();
+    let (mut window_width, mut window_height) = (game.width() * scale, game.height() * scale);
+    let mut window = open_life_window(window_width, window_height, speed, universe)?;
+    let mut buffer: Vec<u32> = vec![0; window_width * window_height];
+    let mut generation = 0;
+    let mut show_density = false;
+    let diffusion = Diffusion { deposit: options.deposit, rate: options.diffusion_rate, decay: options.decay };
+    let mut aux = options.aux.map(|preset| preset.channel(game.width(), game.height(), diffusion));
+    let mut agents = options.agents.map(|preset| {
+        let seed = universe.seed.unwrap_or_else(rand::random);
+        preset.layer(options.agent_count, game.width(), game.height(), seed)
+    });
+    let field_rule = FieldRule {
+        birth: options.field_birth.unwrap_or_default(),
+        survival: options.field_survival.unwrap_or_default(),
+    };
+    // Con --loop, la detección de períodos dice dónde empieza el ciclo y
+    // cuánto dura, y el GIF graba exactamente una vuelta. Los agentes y un
+    // campo con condiciones cambian la evolución, así que en ese caso el
+    // generador busca la repetición en los frames.
+    if export_options.seamless_loop
+        && agents.is_none()
+        && (aux.is_none() || field_rule.is_unconditional())
+        && let Some(cycle) = detection::find_cycle(&game, universe.frames as usize)
+    {
+        // El primer frame muestra la generación 1, y cualquier tramo de
+        // `period` generaciones desde el transitorio es una vuelta completa
+        let start = cycle.transient.max(1);
+        println!("El universo entra en un ciclo de {} generaciones en la generación {}: el GIF graba solo esa vuelta", cycle.period, first_generation + start as u64);
+        gif_generator = gif_generator.with_cycle(start - 1, cycle.period);
+    }
+    let mut show_aux = true;
+    let mut show_hud = options.hud;
+    let mut show_grid_lines = options.grid_lines;
+    let mut view = ZoomView::new(GridSize::new(window_width, window_height), scale);
+    let mut last_window_size = window.get_size();
+    // Último píxel del arrastre con el botón derecho, que mueve la vista
+    let mut pan_from: Option<PixelPos> = None;
+    let mut fps = FpsMeter::default();
+    // Esquina del GIF en coordenadas del universo: si el universo cambia de
+    // tamaño, el GIF sigue grabando la misma zona
+    let mut gif_origin = (0i64, 0i64);
+    let mut selected_pattern = KnownPattern::Glider;
+    let mut orientation = Transform::default();
+    let mut discovery = Discovery::new();
+    // Patrones estampados con P, del más viejo al más nuevo
+    let mut stamps: Vec<Stamp> = Vec::new();
+    let mut breakpoints = options.breakpoints.clone();
+    let mut watches = options.watches.clone();
+    let mut state = RunState::Running;
+    // Última célula pintada con el mouse y el estado con el que se pinta
+    let mut brush: Option<(CellPos, CellState)> = None;
+    let annotation_color = gradient::to_u32(ANNOTATION_RGB);
+    
+    println!("Generando {} frames del juego...", universe.frames);
+    // La ejecución termina después de guardar el frame de esta generación
+    let mut last_frame = false;
+    
+    while window.is_open() 
+        && !window.is_key_down(Key::Escape) 
+        && generation < universe.frames 
+    {
+        // Espacio pausa o reanuda y N avanza una generación en pausa; un
+        // punto de interrupción también pausa
+        if window.is_key_pressed(Key::Space, KeyRepeat::No) {
+            state = state.toggled();
+        }
+        if window.is_key_pressed(Key::N, KeyRepeat::Yes) && state == RunState::Paused {
+            state = RunState::Stepping;
+        }
+        if let Some(new_speed) = speed_keys(&window, speed) {
+            speed = new_speed;
+            window.limit_update_rate(Some(speed.generation_interval()));
+            gif_generator.set_speed(speed);
+            println!("Velocidad: {}", speed);
+        }
+
+        // Actualizar simulación
+        let advanced = state != RunState::Paused;
+        if advanced {
+            // Lo que se pinta entre generaciones no cuenta como nacimientos
+            let before = stats_log.is_some().then(|| game.cells());
+            match aux.as_ref() {
+                Some(field) if !field_rule.is_unconditional() => game.next_generation_in_field(field, &field_rule),
+                _ => game.next_generation(),
+            }
+            generation += 1;
+            if let (Some(log), Some(before)) = (stats_log.as_mut(), before) {
+                log.write(&GenerationStats::between(first_generation + generation as u64, &before, &game))?;
+            }
+            let stable = stability.as_mut().and_then(|detector| detector.observe(&game, first_generation + generation as u64));
+            if let Some(stable) = stable {
+                last_stable = Some(stable);
+                println!("El universo quedó {}", stable.describe());
+                if options.stop_on_stable {
+                    println!("Ya no va a cambiar: la ejecución termina en la generación {}", first_generation + generation as u64);
+                    last_frame = true;
+                }
+            }
+            if let Some(agents) = agents.as_mut() {
+                agents.step(&mut game);
+            }
+            if let Some(aux) = aux.as_mut() {
+                aux.step(&game);
+            }
+            if let Some(recording) = recording.as_mut() {
+                recording.record_frame(&game);
+            }
+            if let Some(trails) = trails.as_mut() {
+                trails.observe(&game, first_generation + generation as u64);
+            }
+            if let Some(snapshotter) = snapshotter.as_mut() {
+                snapshotter.observe(&game, first_generation + generation as u64)?;
+            }
+            for watch in &mut watches {
+                watch.update(&game, first_generation + generation as u64);
+            }
+            for breakpoint in &mut breakpoints {
+                if breakpoint.check(&game, first_generation + generation as u64) {
+                    println!("Pausa en la generación {}: {} (Espacio para seguir, N para avanzar una)", first_generation + generation as u64, breakpoint);
+                    state = RunState::Paused;
+                }
+            }
+            if state == RunState::Stepping {
+                println!("Generación {}", first_generation + generation as u64);
+                state = RunState::Paused;
+            }
+            // Sin ventana nadie puede reanudar: la ejecución termina en la pausa
+            if state == RunState::Paused && window.is_headless() {
+                println!("Sin ventana, la ejecución termina aquí");
+                last_frame = true;
+            }
+        }
+        
+        let ctrl = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+
+        // Ctrl+flechas traslada todas las células para recentrar un patrón
+        if ctrl {
+            let shifts = [(Key::Left, (-1, 0)), (Key::Right, (1, 0)), (Key::Up, (0, -1)), (Key::Down, (0, 1))];
+            for (key, (dx, dy)) in shifts {
+                if !window.is_key_pressed(key, KeyRepeat::Yes) {
+                    continue;
+                }
+                if recording.is_some() {
+                    println!("No se puede trasladar el universo con --record");
+                } else {
+                    game.translate(dx * SHIFT_STEP, dy * SHIFT_STEP, options.shift_edges);
+                    stamps.clear();
+                    if let Some((_, auto_director)) = director.as_mut() {
+                        auto_director.rebase(&game);
+                    }
+                }
+            }
+        }
+
+        // Ctrl+E agranda el universo y Ctrl+R lo recorta a las células vivas
+        let resize = if !ctrl {
+            None
+        } else if window.is_key_pressed(Key::E, KeyRepeat::No) {
+            Some(true)
+        } else if window.is_key_pressed(Key::R, KeyRepeat::No) {
+            Some(false)
+        } else {
+            None
+        };
+        if let Some(expand) = resize {
+            if recording.is_some() || director.is_some() {
+                println!("No se puede cambiar el tamaño del universo con --record ni con --auto-director");
+            } else {
+                let (dx, dy) = if expand {
+                    let (width, height) = (game.width() * EXPAND_FACTOR, game.height() * EXPAND_FACTOR);
+                    game.resize(width, height, options.expand_anchor)
+                } else {
+                    game.crop_to_live_cells(CROP_MARGIN)
+                };
+                gif_origin = (gif_origin.0 + dx, gif_origin.1 + dy);
+                stamps.clear();
+                println!("Universo de {}x{}", game.width(), game.height());
+
+                // La ventana conserva más o menos su tamaño: cambia la escala
+                let base = universe.scale;
+                scale = (base * gif_width / game.width()).min(base * gif_height / game.height()).clamp(1, base);
+                (window_width, window_height) = (game.width() * scale, game.height() * scale);
+                buffer = vec![0; window_width * window_height];
+                view = ZoomView::new(GridSize::new(window_width, window_height), scale);
+                window.invalidate();
+                if window.get_size() != (window_width, window_height) {
+                    window = open_life_window(window_width, window_height, speed, universe)?;
+                }
+                last_window_size = window.get_size();
+            }
+        }
+
+        // Si se cambió el tamaño de la ventana, la escala se ajusta para que
+        // el universo entre en ella
+        if let Some(fitted) = window_resized(&window, &mut last_window_size, game.size()) {
+            scale = fitted;
+            (window_width, window_height) = (game.width() * scale, game.height() * scale);
+            buffer = vec![0; window_width * window_height];
+            view.resize(GridSize::new(window_width, window_height), scale);
+            window.invalidate();
+        }
+
+        // La rueda del mouse y Ctrl+=/Ctrl+- acercan o alejan la vista,
+        // las flechas y arrastrar con el botón derecho la mueven y Ctrl+0
+        // vuelve a mostrar el universo entero
+        if director.is_none() {
+            if let Some((_, wheel)) = window.get_scroll_wheel()
+                && wheel != 0.0
+            {
+                let pixel = window.mouse_pixel().unwrap_or(view.center());
+                view.zoom_by(ZOOM_STEP.powf(wheel.signum()), pixel);
+            }
+            if ctrl {
+                if [Key::Equal, Key::NumPadPlus].iter().any(|&k| window.is_key_pressed(k, KeyRepeat::Yes)) {
+                    view.zoom_by(ZOOM_STEP, view.center());
+                }
+                if [Key::Minus, Key::NumPadMinus].iter().any(|&k| window.is_key_pressed(k, KeyRepeat::Yes)) {
+                    view.zoom_by(1.0 / ZOOM_STEP, view.center());
+                }
+                if window.is_key_pressed(Key::Key0, KeyRepeat::No) {
+                    view.reset();
+                }
+            } else {
+                let step = window_width.min(window_height) as f32 * PAN_STEP;
+                for (key, dx, dy) in [(Key::Left, -step, 0.0), (Key::Right, step, 0.0), (Key::Up, 0.0, -step), (Key::Down, 0.0, step)] {
+                    if window.is_key_pressed(key, KeyRepeat::Yes) {
+                        view.pan(dx, dy);
+                    }
+                }
+            }
+            let dragging = window.get_mouse_down(MouseButton::Right) && !window.is_key_down(Key::F);
+            match (dragging, window.mouse_pixel()) {
+                (true, Some(pixel)) => {
+                    if let Some(from) = pan_from {
+                        view.pan(from.x - pixel.x, from.y - pixel.y);
+                    }
+                    pan_from = Some(pixel);
+                }
+                _ => pan_from = None,
+            }
+        }
+
+        // Con F apretada, el botón izquierdo congela células y el derecho las
+        // descongela
+        if window.is_key_down(Key::F) && recording.is_none() && director.is_none() {
+            let buttons = [(MouseButton::Left, true), (MouseButton::Right, false)];
+            for (button, frozen) in buttons {
+                if window.get_mouse_down(button)
+                    && let Some(cell) = window.mouse_pixel().and_then(|p| view.to_cell(p, game.size()))
+                {
+                    let half = FREEZE_BRUSH / 2;
+                    let corner = cell.saturating_sub(half, half);
+                    game.set_frozen(corner.x, corner.y, FREEZE_BRUSH, FREEZE_BRUSH, frozen);
+                }
+            }
+        }
+
+        // W+clic vigila el rectángulo alrededor del mouse: pausa cuando
+        // empieza a haber actividad en él
+        if window.is_key_down(Key::W)
+            && director.is_none()
+            && window.get_mouse_down(MouseButton::Left)
+            && let Some(cell) = window.mouse_pixel().and_then(|p| view.to_cell(p, game.size()))
+        {
+            let corner = cell.saturating_sub(WATCH_SIZE / 2, WATCH_SIZE / 2);
+            let rect = Rect { x: corner.x, y: corner.y, width: WATCH_SIZE, height: WATCH_SIZE };
+            if !breakpoints.iter().any(|b| b.area() == Some(rect)) {
+                println!("Vigilando {}", rect);
+                breakpoints.push(Breakpoint::new(Condition::Activity(rect)));
+            }
+        }
+
+        // Clic o arrastrar pinta células, en pausa o corriendo: si la primera
+        // célula estaba muerta revive todas las que toca, y si estaba viva las
+        // mata. Con --record los cambios quedan en la grabación.
+        let painting = !ctrl && !window.is_key_down(Key::F) && !window.is_key_down(Key::W) && director.is_none();
+        match window.mouse_pixel().and_then(|p| view.to_cell(p, game.size())) {
+            Some(cell) if painting && window.get_mouse_down(MouseButton::Left) => {
+                let (from, state) = *brush.get_or_insert_with(|| match game.cell(cell.x, cell.y) {
+                    CellState::Alive => (cell, CellState::Dead),
+                    CellState::Dead => (cell, CellState::Alive),
+                });
+                paint_line(&mut game, recording.as_mut(), from, cell, state);
+                brush = Some((cell, state));
+            }
+            _ => brush = None,
+        }
+
+        // Tab elige un patrón, O lo gira o refleja, P lo estampa bajo el
+        // mouse, Shift+P reemplaza el último estampado y Retroceso lo quita
+        if recording.is_none() && director.is_none() {
+            if window.is_key_pressed(Key::Tab, KeyRepeat::No) {
+                let patterns = KnownPattern::ALL;
+                let index = patterns.iter().position(|p| *p == selected_pattern).unwrap_or(0);
+                selected_pattern = patterns[(index + 1) % patterns.len()];
+                println!("Patrón: {}", selected_pattern.name());
+            }
+            if window.is_key_pressed(Key::O, KeyRepeat::No) {
+                orientation = orientation.next();
+                println!("Orientación: {}", orientation);
+            }
+            let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+            if window.is_key_pressed(Key::P, KeyRepeat::No)
+                && let Some(cell) = window.mouse_pixel().and_then(|p| view.to_cell(p, game.size()))
+            {
+                if shift && let Some(last) = stamps.pop() {
+                    remove_stamp(&mut game, last);
+                }
+                stamps.push(selected_pattern.stamp_transformed(&mut game, cell, orientation));
+            }
+            if window.is_key_pressed(Key::Backspace, KeyRepeat::No)
+                && let Some(last) = stamps.pop()
+            {
+                remove_stamp(&mut game, last);
+            }
+        }
+
+        // ? salta a una regla al azar con una sopa nueva; K la guarda en los
+        // favoritos y X la descarta y prueba otra
+        if recording.is_none() && director.is_none() {
+            let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+            let reroll = (shift && window.is_key_pressed(Key::Slash, KeyRepeat::No))
+                || (discovery.current().is_some() && window.is_key_pressed(Key::X, KeyRepeat::No));
+            if reroll {
+                let found = discovery.reroll(&mut game, universe.density);
+                println!("Regla {} con la semilla {} (K la guarda, X prueba otra)", found.rule, found.seed);
+            }
+            if window.is_key_pressed(Key::K, KeyRepeat::No) {
+                match discovery.current() {
+                    Some(found) => {
+                        let name = favorites::append(&options.favorites, found)?;
+                        println!("Guardada como '{}' en {}: {}", name, options.favorites.display(), found.command_line());
+                    }
+                    None => println!("Primero ? elige una regla al azar"),
+                }
+            }
+        }
+
+        // S guarda la generación actual como RLE, para abrirla en Golly
+        if !ctrl && window.is_key_pressed(Key::S, KeyRepeat::No) {
+            std::fs::create_dir_all(&options.rle_dir)?;
+            let format = options.pattern_format;
+            let name = format!("generacion_{}.{}", first_generation + generation as u64, format.extension());
+            let path = options.rle_dir.join(name);
+            std::fs::write(&path, format.write(&RlePattern::from_game(&game)))?;
+            println!("Generación guardada en {}", path.display());
+        }
+
+        // Renderizar en ventana (D muestra u oculta el mapa de densidad)
+        if window.is_key_pressed(Key::D, KeyRepeat::No) {
+            show_density = !show_density;
+        }
+        if window.is_key_pressed(Key::A, KeyRepeat::No) {
+            show_aux = !show_aux;
+        }
+        if window.is_key_pressed(Key::H, KeyRepeat::No) {
+            show_hud = !show_hud;
+        }
+        if window.is_key_pressed(Key::G, KeyRepeat::No) {
+            show_grid_lines = !show_grid_lines;
+        }
+        // T pasa al tema siguiente; el GIF conserva el del principio porque
+        // su paleta es una sola para todos los frames
+        if window.is_key_pressed(Key::T, KeyRepeat::No) {
+            theme = theme.next();
+            game.set_theme(theme.theme());
+            println!("Tema: {}", theme.name());
+        }
+        // Sin nada encima del grid, la pantalla lo dibuja sola: la GPU lo
+        // escala y lo colorea, y minifb solo reescribe las células que cambiaron
+        let mut plain_frame = false;
+        let frame_data = match director.as_mut() {
+            Some((camera, auto_director)) => {
+                if advanced && let Some((x, y)) = auto_director.observe(&game) {
+                    camera.look_at(x, y);
+                    let (dx, dy) = camera.out_of_reach();
+                    if options.director_shift && (dx, dy) != (0, 0) {
+                        game.translate(-dx, -dy, options.shift_edges);
+                        camera.shift(-dx as f32, -dy as f32);
+                        auto_director.rebase(&game);
+                    }
+                }
+                camera.update();
+                camera.render(&game, &mut buffer, window_width, window_height);
+                camera.to_gif_frame_data(&game)
+            }
+            None => {
+                let current = first_generation + generation as u64;
+                let layered = (show_aux && aux.is_some())
+                    || game.has_frozen()
+                    || breakpoints.iter().any(|b| b.area().is_some())
+                    || show_density
+                    || show_hud
+                    || trails.is_some()
+                    || (show_grid_lines && scale >= overlay::GRID_MIN_SCALE)
+                    || agents.is_some()
+                    || annotations::visible(&scenario.annotations, current, gif_origin).next().is_some()
+                    || !watches.is_empty();
+                // Con zoom o desplazada, la ventana muestra solo las células
+                let zoomed = !view.is_identity();
+                plain_frame = !layered && !zoomed;
+                let grid_line_color = gradient::to_u32(game.theme().grid_line());
+                if zoomed {
+                    view.render(&game, &mut buffer);
+                    if show_grid_lines {
+                        view.draw_grid_lines(&mut buffer, game.size(), grid_line_color);
+                    }
+                } else if layered {
+                    simulation::render(&game, &mut buffer, scale);
+                    if let Some(trails) = &trails {
+                        trails.render(&mut buffer, window_width, scale, game.theme());
+                    }
+                    if show_grid_lines {
+                        overlay::draw_grid_lines(&mut buffer, window_width, game.size(), scale, grid_line_color);
+                    }
+                    if show_aux && let Some(aux) = &aux {
+                        overlay::draw_aux(&game, aux, &mut buffer, scale, universe.heat_colormap(game.theme()));
+                    }
+                    overlay::draw_frozen(&game, &mut buffer, scale);
+                    for rect in breakpoints.iter().filter_map(Breakpoint::area) {
+                        overlay::draw_outline(&mut buffer, window_width, (rect.x, rect.y, rect.width, rect.height), scale);
+                    }
+                    if show_density {
+                        overlay::draw_density(&game, &mut buffer, DENSITY_BLOCK, scale, universe.heat_colormap(game.theme()));
+                    }
+                    if let Some(agents) = &agents {
+                        overlay::draw_agents(&mut buffer, window_width, agents.agents(), scale);
+                    }
+                    for annotation in annotations::visible(&scenario.annotations, current, gif_origin) {
+                        annotation.draw(&mut buffer, window_width, scale, ANNOTATION_TEXT_SCALE, annotation_color);
+                    }
+                }
+                let mut frame_data = if gif_origin == (0, 0) && (game.width(), game.height()) == (gif_width, gif_height) {
+                    game.to_gif_frame_data()
+                } else {
+                    game.region(gif_origin.0, gif_origin.1, gif_width, gif_height).to_gif_frame_data()
+                };
+                if let Some(trails) = &trails {
+                    trails.mark_frame(&mut frame_data, GridSize::new(gif_width, gif_height), gif_origin, trail_index);
+                }
+                // El GIF sigue grabando la zona original, así que ahí las
+                // anotaciones no se mueven
+                for annotation in annotations::visible(&scenario.annotations, current, (0, 0)) {
+                    annotation.draw(&mut frame_data, gif_width, 1, 1, ANNOTATION_INDEX);
+                }
+                frame_data
+            }
+        };
+        if plain_frame {
+            window.update_with_simulation(&game, &mut buffer, scale)?;
+        } else {
+            if !watches.is_empty() {
+                let status: Vec<String> = watches.iter().map(Watch::to_string).collect();
+                overlay::draw_status(&mut buffer, window_width, &status.join("   "));
+            }
+            if show_hud {
+                let lines = [
+                    format!("generación {}", first_generation + generation as u64),
+                    format!("vivas {}", game.population()),
+                    format!("fps {:.0}", fps.fps()),
+                ];
+                overlay::draw_hud(&mut buffer, window_width, &lines);
+            }
+            window.update_with_buffer(&buffer, window_width, window_height)?;
+        }
+        fps.tick();
+        
+        // En pausa no pasó ninguna generación, así que no hay frame nuevo
+        if !advanced {
+            continue;
+        }
+
+        // Añadir frame al GIF
+        gif_generator.add_frame(&frame_data)?;
+        
+        // Mostrar progreso cada 20 generaciones
+        if generation % 20 == 0 {
+            println!("Generación {}/{}", generation, universe.frames);
+        }
+        if last_frame {
+            break;
+        }
+    }
+    gif_generator.finish()?;
+
+    if let Some(path) = &options.rle_out {
+        let pattern = match options.rle_out_region {
+            Some(rect) => RlePattern::from_game(&game.region(rect.x as i64, rect.y as i64, rect.width, rect.height)),
+            None => RlePattern::from_game(&game),
+        };
+        std::fs::write(path, PatternFormat::from_path(path).write(&pattern))?;
+        println!("Última generación guardada en {}", path.display());
+    }
+    if let (Some(path), Some(recording)) = (&options.record, recording) {
+        recording.save(path)?;
+        println!("Ejecución guardada en {}", path.display());
+    }
+    if let (Some(path), Some(log)) = (&stats_path, stats_log) {
+        log.finish()?;
+        println!("Estadísticas guardadas en {}", path.display());
+    }
+    if let Some(db) = &results {
+        db.record_run(&RunResult {
+            rule: game.rule().to_string(),
+            seed: universe.seed.filter(|_| options.rle.is_none() && options.resume.is_none()),
+            density: universe.density,
+            width: game.width(),
+            height: game.height(),
+            generations: first_generation + generation as u64,
+            population: game.population(),
+            stable: last_stable,
+        })?;
+        println!("Resultado agregado a {}", db.path().display());
+    }
+    
+    Ok(())
+}
+
+/// Quita un patrón estampado y avisa si parte de él ya había cambiado
+fn remove_stamp(game: &mut GameOfLife, stamp: Stamp) {
+    let stamped = stamp.len();
+    let removed = game.remove_stamp(stamp);
+    if removed < stamped {
+        println!("Se quitaron {} de las {} células del patrón: las demás ya habían cambiado", removed, stamped);
+    }
+}
+
+/// Pinta con `state` las células de la línea de `from` a `to` (para que
+/// arrastrar rápido no deje huecos) y registra los cambios en la grabación
+fn paint_line(game: &mut GameOfLife, mut recording: Option<&mut Recording>, from: CellPos, to: CellPos, state: CellState) {
+    let (dx, dy) = (to.x as i64 - from.x as i64, to.y as i64 - from.y as i64);
+    let steps = dx.abs().max(dy.abs());
+    for i in 0..=steps {
+        let t = if steps == 0 { 0.0 } else { i as f64 / steps as f64 };
+        let x = (from.x as f64 + dx as f64 * t).round() as usize;
+        let y = (from.y as f64 + dy as f64 * t).round() as usize;
+        if !game.contains(CellPos::new(x, y)) || game.cell(x, y) == state || game.is_frozen(x, y) {
+            continue;
+        }
+        game.set_cell(x, y, state);
+        if let Some(recording) = recording.as_deref_mut() {
+            recording.record_event(x, y, state);
+        }
+    }
+}
+
+/// Estado del bucle de la simulación en vivo
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum RunState {
+    /// Avanza una generación por vuelta, a la velocidad elegida
+    Running,
+    /// No avanza; la ventana sigue respondiendo
+    Paused,
+    /// En pausa, pero avanza una sola generación en esta vuelta (N)
+    Stepping,
+}
+
+impl RunState {
+    /// Espacio: reanuda si está en pausa y pausa si está corriendo
+    fn toggled(self) -> Self {
+        match self {
+            RunState::Running => RunState::Paused,
+            RunState::Paused | RunState::Stepping => RunState::Running,
+        }
+    }
+}
+
+/// Las anotaciones se dibujan en los GIF con un tercer color de la paleta,
+/// que la reducción por bloques no conserva
+pub(crate) fn check_annotations(scenario: &Scenario, export_options: &ExportOptions) -> Result<(), Box<dyn std::error::Error>> {
+    if !scenario.annotations.is_empty() && export_options.downsample.is_some() {
+        return Err("las anotaciones no funcionan con --downsample".into());
+    }
+    Ok(())
+}
+
+/// Paleta del GIF: la del tema y, si el guion tiene anotaciones, su color
+pub(crate) fn gif_palette(theme: &Theme, scenario: &Scenario) -> Vec<[u8; 3]> {
+    let mut palette = theme.palette();
+    if !scenario.annotations.is_empty() {
+        palette.push(ANNOTATION_RGB);
+    }
+    palette
+}
+
+fn open_life_window(width: usize, height: usize, speed: Speed, universe: &UniverseOptions) -> Result<Screen, Box<dyn std::error::Error>> {
+    let mut window = Screen::open("Conway's Game of Life - Presiona ESC para salir", width, height, universe.headless)?
+        .with_renderer(universe.renderer);
+    window.limit_update_rate(Some(speed.generation_interval()));
+    Ok(window)
+}
+
+/// Si el usuario cambió el tamaño de la ventana desde `last`, la escala
+/// con la que un universo de `grid` entra en ella (al menos 1). El buffer
+/// se dibuja arriba a la izquierda y lo que sobra de la ventana queda vacío.
+fn window_resized(window: &Screen, last: &mut (usize, usize), grid: GridSize) -> Option<usize> {
+    let size = window.get_size();
+    if size == *last || window.is_headless() {
+        return None;
+    }
+    *last = size;
+    Some((size.0 / grid.width.max(1)).min(size.1 / grid.height.max(1)).max(1))
+}
+
+/// `+` duplica la velocidad y `-` la reduce a la mitad. Devuelve la nueva
+/// velocidad si cambió.
+pub(crate) fn speed_keys(window: &Screen, speed: Speed) -> Option<Speed> {
+    // Con Ctrl, + y - son el zoom
+    if window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl) {
+        return None;
+    }
+    let faster = [Key::Equal, Key::NumPadPlus].iter().any(|&k| window.is_key_pressed(k, KeyRepeat::No));
+    let slower = [Key::Minus, Key::NumPadMinus].iter().any(|&k| window.is_key_pressed(k, KeyRepeat::No));
+    match (faster, slower) {
+        (true, false) => Some(speed.scaled(2.0)),
+        (false, true) => Some(speed.scaled(0.5)),
+        _ => None,
+    }
+}
+
+/// Bucle de ventana y exportación compartido por los modos de simulación
+/// que no son el Juego de la Vida clásico
+pub fn run_backend(
+    simulation: &mut dyn Simulation,
+    title: &str,
+    universe: &UniverseOptions,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut scale = universe.scale;
+    let window_width = simulation.width() * scale;
+    let window_height = simulation.height() * scale;
+
+    let mut gif_generator = export_options
+        .create_gif(&universe.output, simulation.width(), simulation.height(), 1, universe.frames as usize)?
+        .with_palette(simulation.palette());
+
+    let mut window = Screen::open(&format!("{} - Presiona ESC para salir", title), window_width, window_height, universe.headless)?
+        .with_renderer(universe.renderer);
+    window.limit_update_rate(Some(export_options.speed().generation_interval()));
+
+    let mut buffer: Vec<u32> = vec![0; window_width * window_height];
+    let mut generation = 0;
+    let mut speed = export_options.speed();
+    let mut last_window_size = window.get_size();
+
+    while window.is_open() && !window.is_key_down(Key::Escape) && generation < universe.frames {
+        let size = GridSize::new(simulation.width(), simulation.height());
+        if let Some(fitted) = window_resized(&window, &mut last_window_size, size) {
+            scale = fitted;
+            let (width, height) = size.to_pixels(scale);
+            buffer = vec![0; width * height];
+            window.invalidate();
+        }
+        if let Some(new_speed) = speed_keys(&window, speed) {
+            speed = new_speed;
+            window.limit_update_rate(Some(speed.generation_interval()));
+            gif_generator.set_speed(speed);
+            println!("Velocidad: {}", speed);
+        }
+        for key in window.get_keys_pressed(KeyRepeat::No) {
+            if let Some(digit) = digit_key(key) {
+                simulation.select_brush(digit);
+            }
+        }
+        for key in window.get_keys_pressed(KeyRepeat::Yes) {
+            let step = (simulation.width().max(simulation.height()) / 8).max(1) as i64;
+            match key {
+                Key::W => simulation.pan(0, -step),
+                Key::A => simulation.pan(-step, 0),
+                Key::S => simulation.pan(0, step),
+                Key::D => simulation.pan(step, 0),
+                _ => {}
+            }
+        }
+        if window.get_mouse_down(MouseButton::Left)
+            && let Some(cell) = window.mouse_pixel().and_then(|p| p.to_cell(scale))
+        {
+            simulation.paint(cell.x, cell.y);
+        }
+
+        // Mantener ← retrocede en el tiempo si la regla es reversible
+        if !(window.is_key_down(Key::Left) && simulation.step_backward()) {
+            simulation.step();
+        }
+        generation += 1;
+
+        window.update_with_simulation(simulation, &mut buffer, scale)?;
+        // El PNG animado guarda los colores exactos sin cuantizarlos
+        let true_color = export_options.format() == ExportFormat::Apng && export_options.downsample.is_none();
+        if export_options.quantizer.is_some() || true_color {
+            gif_generator.add_rgb_frame(&simulation::to_rgb_frame_data(simulation))?;
+        } else {
+            gif_generator.add_frame(&simulation::to_gif_frame_data(simulation))?;
+        }
+
+        if generation % 20 == 0 {
+            println!("Generación {}/{}", generation, universe.frames);
+        }
+    }
+    gif_generator.finish()?;
+
+    Ok(())
+}
+
+/// Número de una tecla de la fila superior del teclado
+fn digit_key(key: Key) -> Option<u8> {
+    let digits = [
+        Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4,
+        Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+    ];
+    digits.iter().position(|&k| k == key).map(|d| d as u8)
+}
+
+// ===== REPRODUCTOR DE GRABACIONES =====
+/// Muestra una ejecución guardada. Controles: Espacio pausa, ←/→ una
+/// generación, ↑/↓ diez generaciones, Inicio/Fin saltan a los extremos,
+/// +/- cambian la velocidad, D muestra el mapa de densidad
+pub fn run_player(
+    path: &Path,
+    export: Option<&Path>,
+    scale: usize,
+    universe: &UniverseOptions,
+    scenario: &Scenario,
+    export_options: &ExportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let recording = Recording::load(path)?;
+    let mut states = recording.replay()?;
+    let mut theme = universe.theme;
+    for state in &mut states {
+        state.set_theme(theme.theme());
+    }
+    check_annotations(scenario, export_options)?;
+    let last = states.len() - 1;
+    println!("Grabación cargada: {} generaciones", recording.frame_count());
+
+    if let Some(export) = export {
+        let mut gif_generator =
+            export_options.create_gif(export, recording.width, recording.height, scale.max(1), last)?;
+        // Con keyframes, cada frame muestra lo que ve la cámara del guion
+        let camera_path = scenario.camera_path()?;
+        if camera_path.is_some() && !scenario.annotations.is_empty() {
+            return Err("las anotaciones no funcionan con keyframes de cámara".into());
+        }
+        gif_generator = gif_generator.with_palette(gif_palette(&theme.theme(), scenario));
+        for (generation, state) in states.iter().enumerate().skip(1) {
+            let mut frame_data = match &camera_path {
+                Some(path) => path.frame_data(state, generation as u64, recording.width, recording.height),
+                None => state.to_gif_frame_data(),
+            };
+            for annotation in annotations::visible(&scenario.annotations, generation as u64, (0, 0)) {
+                annotation.draw(&mut frame_data, recording.width, 1, 1, ANNOTATION_INDEX);
+            }
+            gif_generator.add_frame(&frame_data)?;
+        }
+        gif_generator.finish()?;
+        println!("GIF exportado en {}", export.display());
+    }
+    if universe.headless {
+        return Ok(());
+    }
+
+    let window_width = recording.width * universe.scale;
+    let window_height = recording.height * universe.scale;
+    let mut window = Screen::open(
+        "Reproducción - Espacio pausa, flechas para navegar, +/- velocidad, ESC para salir",
+        window_width,
+        window_height,
+        false,
+    )?;
+    let mut speed = export_options.speed();
+    window.limit_update_rate(Some(speed.generation_interval()));
+
+    let mut buffer: Vec<u32> = vec![0; window_width * window_height];
+    let annotation_color = gradient::to_u32(ANNOTATION_RGB);
+    let mut current = 0;
+    let mut paused = false;
+    let mut show_density = false;
+
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        if let Some(new_speed) = speed_keys(&window, speed) {
+            speed = new_speed;
+            window.limit_update_rate(Some(speed.generation_interval()));
+            println!("Velocidad: {}", speed);
+        }
+        for key in window.get_keys_pressed(KeyRepeat::Yes) {
+            match key {
+                Key::Space => paused = !paused,
+                Key::Right => { paused = true; current = (current + 1).min(last); }
+                Key::Left => { paused = true; current = current.saturating_sub(1); }
+                Key::Up => { paused = true; current = (current + 10).min(last); }
+                Key::Down => { paused = true; current = current.saturating_sub(10); }
+                Key::Home => { paused = true; current = 0; }
+                Key::End => { paused = true; current = last; }
+                Key::D => show_density = !show_density,
+                Key::T => {
+                    theme = theme.next();
+                    for state in &mut states {
+                        state.set_theme(theme.theme());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        states[current].render(&mut buffer, universe.scale);
+        if show_density {
+            overlay::draw_density(&states[current], &mut buffer, DENSITY_BLOCK, universe.scale, universe.heat_colormap(states[current].theme()));
+        }
+        for annotation in annotations::visible(&scenario.annotations, current as u64, (0, 0)) {
+            annotation.draw(&mut buffer, window_width, universe.scale, ANNOTATION_TEXT_SCALE, annotation_color);
+        }
+        window.update_with_buffer(&buffer, window_width, window_height)?;
+
+        if !paused && current < last {
+            current += 1;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotations::Annotation;
+    use crate::gif_export::Downsample;
+
+    /// Frames que tiene el GIF de `path`
+    fn frame_count(path: &Path) -> usize {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(std::fs::File::open(path).unwrap()).unwrap();
+        std::iter::from_fn(|| decoder.read_next_frame().unwrap().map(|_| ())).count()
+    }
+
+    /// Universo sin ventana de 16×16 que escribe su GIF en `output`
+    fn headless(output: &Path, frames: u32) -> UniverseOptions {
+        UniverseOptions { width: 16, height: 16, frames, output: output.to_path_buf(), headless: true, seed: Some(1), ..Default::default() }
+    }
+
+    #[test]
+    fn space_pauses_and_resumes() {
+        assert_eq!(RunState::Running.toggled(), RunState::Paused);
+        assert_eq!(RunState::Paused.toggled(), RunState::Running);
+        assert_eq!(RunState::Stepping.toggled(), RunState::Running);
+    }
+
+    #[test]
+    fn digit_keys_are_their_numbers() {
+        assert_eq!(digit_key(Key::Key0), Some(0));
+        assert_eq!(digit_key(Key::Key7), Some(7));
+        assert_eq!(digit_key(Key::A), None);
+    }
+
+    #[test]
+    fn annotations_add_a_color_and_refuse_downsampling() {
+        let theme = Theme::default();
+        let mut scenario = Scenario::default();
+        assert_eq!(gif_palette(&theme, &scenario), theme.palette());
+        let mut export_options = ExportOptions { downsample: Some(Downsample::Factor(2)), ..Default::default() };
+        assert!(check_annotations(&scenario, &export_options).is_ok());
+
+        scenario.annotations.push(Annotation { generation: 0, duration: 10, x: 3, y: 3, text: "aquí".to_string() });
+        assert_eq!(gif_palette(&theme, &scenario).last(), Some(&ANNOTATION_RGB));
+        assert!(check_annotations(&scenario, &export_options).is_err());
+        export_options.downsample = None;
+        assert!(check_annotations(&scenario, &export_options).is_ok());
+    }
+
+    #[test]
+    fn headless_simulation_records_every_frame() {
+        let path = std::env::temp_dir().join(format!("lab2-runner-{}.gif", std::process::id()));
+        let mut game = GameOfLife::new(16, 16);
+        KnownPattern::Glider.stamp(&mut game, 2, 2);
+        run_simulation(game, 0, &headless(&path, 12), &RunOptions::default(), &Scenario::default(), &ExportOptions::default()).unwrap();
+        assert_eq!(frame_count(&path), 12);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn headless_loop_keeps_one_period() {
+        let path = std::env::temp_dir().join(format!("lab2-runner-loop-{}.gif", std::process::id()));
+        let mut game = GameOfLife::new(16, 16);
+        KnownPattern::Blinker.stamp(&mut game, 6, 6);
+        let export_options = ExportOptions { seamless_loop: true, ..Default::default() };
+        run_simulation(game, 0, &headless(&path, 20), &RunOptions::default(), &Scenario::default(), &export_options).unwrap();
+        assert_eq!(frame_count(&path), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::annotations::Annotation;
+use crate::camera::{CameraPath, Keyframe};
+
+/// Esquema JSON del guion, para que los editores autocompleten los campos
+pub const SCHEMA: &str = include_str!("../scenario.schema.json");
+
+/// Campos de primer nivel de un guion
+pub const FIELDS: [&str; 4] = ["$schema", "camera", "annotations", "patterns"];
+
+/// Guion de una ejecución, escrito en JSON o TOML (según la extensión del
+/// archivo). Todos los campos son opcionales.
+///
+/// ```json
+/// {
+///   "patterns": [
+///     { "file": "gosper.rle", "x": 10, "y": 10 }
+///   ],
+///   "camera": [
+///     { "generation": 0, "x": 50, "y": 50 },
+///     { "generation": 80, "x": 20, "y": 30, "zoom": 4, "easing": "ease-in-out" }
+///   ],
+///   "annotations": [
+///     { "generation": 150, "duration": 40, "x": 42, "y": 17, "text": "choque de gliders" }
+///   ]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct Scenario {
+    /// Ruta o URL del esquema, que los editores usan para encontrarlo
+    #[serde(rename = "$schema")]
+    pub schema: Option<String>,
+    /// Keyframes de la cámara al exportar grabaciones
+    pub camera: Vec<Keyframe>,
+    /// Textos que aparecen en la ventana y en los GIF en ciertas generaciones
+    pub annotations: Vec<Annotation>,
+    /// Patrones con los que empieza el universo al exportar el guion con
+    /// `lab2 export`
+    pub patterns: Vec<PatternPlacement>,
+}
+
+/// Patrón (.rle o .cells) que se estampa en el universo vacío
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternPlacement {
+    /// Archivo .rle o .cells; una ruta relativa parte de la carpeta del guion
+    pub file: PathBuf,
+    /// Dónde va el patrón, en células: su esquina superior izquierda, o la
+    /// que diga la convención de `--origin` y `--y-axis`
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Scenario {
+    /// Lee el guion. Los errores de formato indican `archivo:línea:columna`.
+    /// Las rutas relativas de los patrones se resuelven desde la carpeta
+    /// del guion.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let is_toml = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        let parsed = if is_toml { parse_toml(&text) } else { parse_json(&text) };
+        let mut scenario = parsed.map_err(|(line, column, message)| {
+            format!("{}:{}:{}: {}", path.display(), line, column, message)
+        })?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for pattern in &mut scenario.patterns {
+            pattern.file = dir.join(&pattern.file);
+        }
+        Ok(scenario)
+    }
+
+    /// Lee el guion y además comprueba lo que el formato no puede expresar
+    /// (por ejemplo, que el acercamiento sea positivo o que existan los
+    /// archivos de los patrones)
+    pub fn validate(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let scenario = Self::load(path)?;
+        scenario.camera_path().map_err(|e| format!("{}: camera: {}", path.display(), e))?;
+        if let Some(missing) = scenario.patterns.iter().find(|pattern| !pattern.file.is_file()) {
+            return Err(format!("{}: patterns: no existe {}", path.display(), missing.file.display()).into());
+        }
+        Ok(scenario)
+    }
+
+    /// Recorrido de la cámara, si el guion define keyframes
+    pub fn camera_path(&self) -> Result<Option<CameraPath>, String> {
+        if self.camera.is_empty() {
+            return Ok(None);
+        }
+        CameraPath::new(self.camera.clone()).map(Some)
+    }
+
+    /// El guion y los archivos de patrones que usa, los que hay que vigilar
+    /// para saber si la exportación cambió
+    pub fn inputs(&self, path: &Path) -> Vec<PathBuf> {
+        std::iter::once(path.to_path_buf()).chain(self.patterns.iter().map(|pattern| pattern.file.clone())).collect()
+    }
+}
+
+/// Error de lectura: línea, columna (desde 1) y mensaje
+type ParseError = (usize, usize, String);
+
+fn parse_json(text: &str) -> Result<Scenario, ParseError> {
+    serde_json::from_str(text).map_err(|e| {
+        // El mensaje de serde_json ya termina con la posición, que se muestra aparte
+        let message = e.to_string();
+        let message = message.split(" at line ").next().unwrap_or(&message).to_string();
+        (e.line(), e.column(), message)
+    })
+}
+
+fn parse_toml(text: &str) -> Result<Scenario, ParseError> {
+    toml::from_str(text).map_err(|e| {
+        let (line, column) = line_column(text, e.span().map_or(0, |span| span.start));
+        (line, column, e.message().trim().to_string())
+    })
+}
+
+/// Línea y columna (desde 1) del byte `offset` de `text`
+pub(crate) fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+    (line, column)
+}
@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, ScaleMode, Window, WindowOptions};
+
+use crate::geometry::PixelPos;
+use crate::simulation::{PartialRender, Simulation};
+
+/// Con qué se dibujan los frames en la ventana
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum RendererKind {
+    /// minifb: cada píxel se calcula en la CPU
+    #[default]
+    Cpu,
+    /// wgpu: el grid se sube como textura y la GPU lo escala y colorea
+    /// (hay que compilar con `--features gpu`; si falla, se usa minifb)
+    Gpu,
+}
+
+/// Ventana de minifb, o ninguna en modo sin ventana (`--headless`): entonces
+/// no hay teclas ni mouse, los frames se descartan y la simulación corre
+/// sin esperar entre generaciones
+pub struct Screen {
+    // Va antes que la ventana para soltarse primero: su superficie dibuja
+    // sobre ella
+    #[cfg(feature = "gpu")]
+    gpu: Option<crate::gpu::GpuRenderer>,
+    window: Option<Window>,
+    size: (usize, usize),
+    /// Lo que se dibujó en el buffer con `update_with_simulation`, para
+    /// reescribir solo lo que cambió
+    partial: PartialRender,
+}
+
+impl Screen {
+    /// Abre la ventana, salvo con `headless`
+    pub fn open(title: &str, width: usize, height: usize, headless: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let window = match headless {
+            true => None,
+            // Al agrandar la ventana el buffer queda arriba a la izquierda, sin
+            // estirarse, así la posición del mouse sigue siendo la del buffer
+            false => Some(Window::new(
+                title,
+                width,
+                height,
+                WindowOptions { resize: true, scale_mode: ScaleMode::UpperLeft, ..WindowOptions::default() },
+            )?),
+        };
+        Ok(Self {
+            #[cfg(feature = "gpu")]
+            gpu: None,
+            window,
+            size: (width, height),
+            partial: PartialRender::new(),
+        })
+    }
+
+    /// La misma pantalla, dibujando con `kind`. Si la GPU no está disponible
+    /// avisa y sigue con minifb.
+    pub fn with_renderer(self, kind: RendererKind) -> Self {
+        if kind == RendererKind::Cpu || self.window.is_none() {
+            return self;
+        }
+        self.open_gpu()
+    }
+
+    #[cfg(feature = "gpu")]
+    fn open_gpu(mut self) -> Self {
+        match crate::gpu::GpuRenderer::new(self.window.as_ref().expect("la ventana está abierta")) {
+            Ok(gpu) => self.gpu = Some(gpu),
+            Err(error) => eprintln!("No se pudo usar la GPU ({}); se dibuja con minifb", error),
+        }
+        self
+    }
+
+    #[cfg(not(feature = "gpu"))]
+    fn open_gpu(self) -> Self {
+        eprintln!("lab2 se compiló sin la función gpu (cargo build --features gpu); se dibuja con minifb");
+        self
+    }
+
+    /// Si los frames se dibujan con la GPU
+    pub fn uses_gpu(&self) -> bool {
+        #[cfg(feature = "gpu")]
+        {
+            self.gpu.is_some()
+        }
+        #[cfg(not(feature = "gpu"))]
+        {
+            false
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.window.is_none()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.window.as_ref().is_none_or(Window::is_open)
+    }
+
+    pub fn get_size(&self) -> (usize, usize) {
+        self.window.as_ref().map_or(self.size, Window::get_size)
+    }
+
+    pub fn limit_update_rate(&mut self, interval: Option<Duration>) {
+        if let Some(window) = self.window.as_mut() {
+            window.limit_update_rate(interval);
+        }
+    }
+
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.window.as_ref().is_some_and(|w| w.is_key_down(key))
+    }
+
+    pub fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        self.window.as_ref().is_some_and(|w| w.is_key_pressed(key, repeat))
+    }
+
+    pub fn get_keys_pressed(&self, repeat: KeyRepeat) -> Vec<Key> {
+        self.window.as_ref().map_or_else(Vec::new, |w| w.get_keys_pressed(repeat))
+    }
+
+    pub fn get_mouse_down(&self, button: MouseButton) -> bool {
+        self.window.as_ref().is_some_and(|w| w.get_mouse_down(button))
+    }
+
+    pub fn get_mouse_pos(&self, mode: MouseMode) -> Option<(f32, f32)> {
+        self.window.as_ref().and_then(|w| w.get_mouse_pos(mode))
+    }
+
+    /// Giro de la rueda del mouse desde el frame anterior, si hubo
+    pub fn get_scroll_wheel(&self) -> Option<(f32, f32)> {
+        self.window.as_ref().and_then(|w| w.get_scroll_wheel())
+    }
+
+    /// Posición del mouse en la ventana, si está dentro de ella
+    pub fn mouse_pixel(&self) -> Option<PixelPos> {
+        self.get_mouse_pos(MouseMode::Discard).map(PixelPos::from)
+    }
+
+    /// El próximo `update_with_simulation` dibuja el buffer entero (por
+    /// ejemplo si se reemplazó el buffer)
+    pub fn invalidate(&mut self) {
+        self.partial.invalidate();
+    }
+
+    /// Muestra un buffer ya dibujado (0RGB) de `width`×`height` píxeles
+    pub fn update_with_buffer(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Box<dyn std::error::Error>> {
+        // Quien dibujó el buffer pudo haber pintado encima del grid
+        self.partial.invalidate();
+        self.present(buffer, width, height)
+    }
+
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Box<dyn std::error::Error>> {
+        self.size = (width, height);
+        let Some(window) = self.window.as_mut() else { return Ok(()) };
+        #[cfg(feature = "gpu")]
+        if let Some(gpu) = self.gpu.as_mut() {
+            gpu.draw_pixels(window, buffer, width, height)?;
+            window.update();
+            return Ok(());
+        }
+        window.update_with_buffer(buffer, width, height)?;
+        Ok(())
+    }
+
+    /// Muestra la simulación con `scale` píxeles por celda. Con la GPU solo
+    /// se sube un índice de la paleta por celda; con minifb se dibujan en
+    /// `buffer`, que tiene el tamaño de la ventana, solo las celdas que
+    /// cambiaron desde la última llamada.
+    pub fn update_with_simulation(
+        &mut self,
+        simulation: &dyn Simulation,
+        buffer: &mut [u32],
+        scale: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (width, height) = (simulation.width() * scale, simulation.height() * scale);
+        #[cfg(feature = "gpu")]
+        if let (Some(gpu), Some(window)) = (self.gpu.as_mut(), self.window.as_mut()) {
+            self.size = (width, height);
+            let cells = crate::simulation::to_gif_frame_data(simulation);
+            gpu.draw_cells(window, &cells, simulation.width(), simulation.height(), &simulation.palette())?;
+            window.update();
+            return Ok(());
+        }
+        if self.window.is_some() {
+            self.partial.render(simulation, buffer, scale);
+        }
+        self.present(buffer, width, height)
+    }
+}
@@ -0,0 +1,100 @@
+use crate::simulation::Simulation;
+use crate::{CellState, GameOfLife};
+
+/// Reglas de segundo orden: el siguiente estado depende de la generación
+/// actual y de la anterior
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SecondOrderRule {
+    /// Conway combinado con XOR sobre la generación anterior (Fredkin). Es
+    /// reversible: con dos generaciones se puede calcular la anterior.
+    Reversible,
+    /// Una célula solo aplica las reglas de Conway si no cambió en la
+    /// generación anterior, así que los cambios recientes tienen inercia
+    Memory,
+}
+
+impl SecondOrderRule {
+    /// Siguiente estado de una célula a partir de su estado actual, el
+    /// anterior y sus vecinos vivos en la generación actual
+    pub fn next_state(self, game: &GameOfLife, current: CellState, previous: CellState, neighbors: usize) -> CellState {
+        let conway = game.apply_rules(current, neighbors);
+        match self {
+            SecondOrderRule::Reversible if conway == previous => CellState::Dead,
+            SecondOrderRule::Reversible => CellState::Alive,
+            SecondOrderRule::Memory if current == previous => conway,
+            SecondOrderRule::Memory => current,
+        }
+    }
+}
+
+/// Juego de la Vida que conserva la generación anterior
+pub struct SecondOrderLife {
+    current: GameOfLife,
+    previous: GameOfLife,
+    rule: SecondOrderRule,
+}
+
+impl SecondOrderLife {
+    /// Empieza con `current` y una generación anterior vacía
+    pub fn new(current: GameOfLife, rule: SecondOrderRule) -> Self {
+        let previous = GameOfLife::new(current.width(), current.height());
+        Self { current, previous, rule }
+    }
+
+    /// Calcula la generación que sigue a `current` cuando antes estaba `previous`
+    fn advance(&self, current: &GameOfLife, previous: &GameOfLife) -> GameOfLife {
+        let mut next = current.clone();
+        for y in 0..current.height() {
+            for x in 0..current.width() {
+                let neighbors = current.count_live_neighbors(x, y);
+                let state = self.rule.next_state(current, current.grid.get(x, y), previous.grid.get(x, y), neighbors);
+                next.set_cell(x, y, state);
+            }
+        }
+        next
+    }
+}
+
+impl Simulation for SecondOrderLife {
+    fn width(&self) -> usize {
+        self.current.width()
+    }
+
+    fn height(&self) -> usize {
+        self.current.height()
+    }
+
+    fn step(&mut self) {
+        let next = self.advance(&self.current, &self.previous);
+        self.previous = std::mem::replace(&mut self.current, next);
+    }
+
+    /// Con la regla reversible, la generación anterior a (anterior, actual)
+    /// es conway(anterior) XOR actual, que es la misma fórmula con los
+    /// papeles invertidos
+    fn step_backward(&mut self) -> bool {
+        if self.rule != SecondOrderRule::Reversible {
+            return false;
+        }
+        let before = self.advance(&self.previous, &self.current);
+        self.current = std::mem::replace(&mut self.previous, before);
+        true
+    }
+
+    fn palette(&self) -> Vec<[u8; 3]> {
+        vec![
+            crate::DEAD_RGB,
+            crate::ALIVE_RGB,
+            [0x30, 0x50, 0x80], // Viva solo en la generación anterior
+        ]
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        match (self.current.is_alive(x, y), self.previous.is_alive(x, y)) {
+            (true, _) => 1,
+            (false, true) => 2,
+            (false, false) => 0,
+        }
+    }
+}
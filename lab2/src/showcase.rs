@@ -0,0 +1,230 @@
+use crate::detection::{self, Cycle};
+use crate::rle::RlePattern;
+use crate::stats::Tracker;
+use crate::{GameOfLife, font};
+
+/// Células libres alrededor del patrón en el primer universo de prueba
+const MIN_FIELD_MARGIN: usize = 16;
+/// Generaciones máximas de transitorio más período
+const CYCLE_LIMIT: usize = 1000;
+/// Células libres alrededor de la caja que ocupa el patrón en todas sus fases
+const MARGIN: usize = 1;
+/// Píxeles libres alrededor del texto de la etiqueta
+const PADDING: usize = 4;
+/// Índices de la paleta (después de célula muerta y viva)
+const LABEL_TEXT: u8 = 2;
+const TRAIL: u8 = 3;
+
+/// Un patrón listo para exportarse, recortado a la caja que ocupa en todos
+/// sus frames y con una etiqueta arriba. Un oscilador es un ciclo que se
+/// repite; una nave son todas las generaciones de su recorrido, con la
+/// trayectoria de su centroide dibujada detrás.
+pub struct Showcase {
+    frames: Vec<GameOfLife>,
+    /// Veces que se repiten los frames (1 para las naves)
+    repeat: usize,
+    period: usize,
+    /// Centroide de la nave en cada frame, en células del recorte
+    trail: Vec<(f32, f32)>,
+    lines: Vec<String>,
+    scale: usize,
+    text_scale: usize,
+}
+
+impl Showcase {
+    /// Prueba `pattern` (con su regla, si la trae) en un universo a su
+    /// medida: su caja más un margen de células libres. Como nada avanza más
+    /// de una célula por generación, el borde no lo afecta mientras el
+    /// transitorio más el período no lleguen al margen; si llegan, se
+    /// agranda el margen y se vuelve a probar. El recorrido de una nave en
+    /// las `periods` vueltas se agrega después, al grabarla. Un patrón que
+    /// no se estabiliza solo (un cañón) sigue tocando el borde con el margen
+    /// agrandado: se muestra con el primer ciclo que se encontró, con el
+    /// borde frenando lo que emite.
+    pub fn from_pattern(pattern: &RlePattern, name: &str, periods: usize, scale: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut margin = MIN_FIELD_MARGIN;
+        let mut bordered = None;
+        loop {
+            let mut game = GameOfLife::new(pattern.width + 2 * margin, pattern.height + 2 * margin);
+            if let Some(rule) = pattern.rule {
+                game.set_rule(rule);
+            }
+            pattern.stamp(&mut game, margin, margin);
+            let cycle = detection::find_cycle(&game, CYCLE_LIMIT);
+            margin = match cycle {
+                Some(cycle) if cycle.transient + cycle.period < margin => {
+                    return Self::with_cycle(&game, cycle, name, periods, scale);
+                }
+                // Con un margen de más de `CYCLE_LIMIT` el borde ya no alcanza
+                // a tocar ningún ciclo que se pueda encontrar
+                Some(_) if bordered.is_some() => break,
+                _ if margin > CYCLE_LIMIT => break,
+                Some(cycle) => {
+                    bordered = Some((game, cycle));
+                    cycle.transient + cycle.period + 1
+                }
+                None => (margin * 4).min(CYCLE_LIMIT + 1),
+            };
+        }
+        let (game, cycle) = bordered.ok_or_else(|| format!("{} no entra en un ciclo en {} generaciones", name, CYCLE_LIMIT))?;
+        Self::with_cycle(&game, cycle, name, periods, scale)
+    }
+
+    /// Analiza el patrón de `game`, que tiene que estar en un universo con
+    /// espacio de sobra a su alrededor (como el de `from_pattern`), y
+    /// prepara `periods` vueltas de su ciclo. Cada célula ocupa
+    /// `scale`×`scale` píxeles.
+    pub fn new(game: &GameOfLife, name: &str, periods: usize, scale: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        let cycle = detection::find_cycle(game, CYCLE_LIMIT)
+            .ok_or_else(|| format!("{} no entra en un ciclo en {} generaciones", name, CYCLE_LIMIT))?;
+        Self::with_cycle(game, cycle, name, periods, scale)
+    }
+
+    fn with_cycle(game: &GameOfLife, cycle: Cycle, name: &str, periods: usize, scale: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        if cycle.population == 0 {
+            return Err(format!("{} se extingue", name).into());
+        }
+        let is_ship = cycle.displacement != (0, 0);
+
+        let mut state = game.clone();
+        for _ in 0..cycle.transient {
+            state.next_generation();
+        }
+        if is_ship {
+            // Espacio extra hacia donde avanza la nave
+            let (dx, dy) = (cycle.displacement.0 * periods as i64, cycle.displacement.1 * periods as i64);
+            let (width, height) = (state.width() + dx.unsigned_abs() as usize, state.height() + dy.unsigned_abs() as usize);
+            state = state.region(dx.min(0), dy.min(0), width, height);
+        }
+
+        // Una nave se mide una generación más, para que la velocidad abarque
+        // vueltas completas del ciclo
+        let generations = if is_ship { cycle.period * periods + 1 } else { cycle.period };
+        let mut frames = Vec::with_capacity(generations);
+        for _ in 0..generations {
+            frames.push(state.clone());
+            state.next_generation();
+        }
+
+        let mut trail = Vec::new();
+        if is_ship {
+            let (x0, y0, x1, y1) = live_bounds(&frames[0]).ok_or("el patrón no tiene células vivas")?;
+            let mut tracker = Tracker::new(((x0 + x1) / 2, (y0 + y1) / 2));
+            for frame in &frames {
+                tracker.update(frame);
+                trail.push(tracker.centroid(frame).ok_or("se perdió la nave al seguirla")?);
+            }
+        }
+
+        let mut lines = vec![name.to_string(), cycle.describe()];
+        if let (Some(first), Some(last)) = (trail.first(), trail.last()) {
+            let elapsed = (trail.len() - 1) as f32;
+            let speed = ((last.0 - first.0).abs().max((last.1 - first.1).abs())) / elapsed;
+            lines.push(format!("velocidad medida {:.3}c", speed));
+            frames.pop();
+            trail.pop();
+        }
+
+        // Caja que contiene todos los frames, para que el recorte no corte ninguno
+        let (min_x, min_y, max_x, max_y) = frames
+            .iter()
+            .filter_map(live_bounds)
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+            .ok_or("el patrón no tiene células vivas")?;
+        let (x, y) = (min_x as i64 - MARGIN as i64, min_y as i64 - MARGIN as i64);
+        let (width, height) = (max_x - min_x + 1 + 2 * MARGIN, max_y - min_y + 1 + 2 * MARGIN);
+        let frames = frames.iter().map(|frame| frame.region(x, y, width, height)).collect();
+        let trail = trail.into_iter().map(|(cx, cy)| (cx - x as f32, cy - y as f32)).collect();
+
+        // El texto se agranda con la escala, pero nunca más que el ancho de las células
+        let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let text_scale = (1..=(scale / 4).max(1))
+            .rev()
+            .find(|&s| longest * (font::GLYPH_WIDTH + 1) * s <= width * scale)
+            .unwrap_or(1);
+        let repeat = if is_ship { 1 } else { periods };
+        Ok(Self { frames, repeat, period: cycle.period, trail, lines, scale, text_scale })
+    }
+
+    /// Generaciones de una vuelta completa del ciclo
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len() * self.repeat
+    }
+
+    fn label_height(&self) -> usize {
+        self.lines.len() * (font::GLYPH_HEIGHT + 1) * self.text_scale + PADDING
+    }
+
+    fn text_width(&self) -> usize {
+        let longest = self.lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        longest * (font::GLYPH_WIDTH + 1) * self.text_scale + 2 * PADDING
+    }
+
+    /// Ancho y alto del frame en píxeles. Si el texto no cabe sobre las
+    /// células, el frame se ensancha y las células quedan centradas.
+    pub fn size(&self) -> (usize, usize) {
+        let frame = &self.frames[0];
+        (
+            (frame.width() * self.scale).max(self.text_width()),
+            frame.height() * self.scale + self.label_height(),
+        )
+    }
+
+    /// Colores: célula muerta, viva, texto de la etiqueta y trayectoria
+    pub fn palette() -> Vec<[u8; 3]> {
+        vec![crate::DEAD_RGB, crate::ALIVE_RGB, [0xFF, 0xD0, 0x40], [0x70, 0x30, 0x50]]
+    }
+
+    /// Índices de la paleta del frame `index` (los osciladores dan vueltas
+    /// al ciclo)
+    pub fn frame(&self, index: usize) -> Vec<u8> {
+        let (width, height) = self.size();
+        let mut buffer = vec![0; width * height];
+        for (i, line) in self.lines.iter().enumerate() {
+            let y = PADDING + i * (font::GLYPH_HEIGHT + 1) * self.text_scale;
+            font::draw_text_scaled(&mut buffer, width, (PADDING, y), line, LABEL_TEXT, self.text_scale);
+        }
+
+        let index = index % self.frames.len();
+        let frame = &self.frames[index];
+        let cells = frame.to_gif_frame_data();
+        let x0 = (width - frame.width() * self.scale) / 2;
+        let y0 = self.label_height();
+        for py in 0..frame.height() * self.scale {
+            for px in 0..frame.width() * self.scale {
+                buffer[(y0 + py) * width + x0 + px] = cells[py / self.scale * frame.width() + px / self.scale];
+            }
+        }
+
+        // Trayectoria hasta este frame, solo sobre las células muertas
+        let to_pixels = |(cx, cy): (f32, f32)| (x0 as f32 + cx * self.scale as f32, y0 as f32 + cy * self.scale as f32);
+        let brush = (self.scale / 4).max(1);
+        for pair in self.trail[..(index + 1).min(self.trail.len())].windows(2) {
+            let ((ax, ay), (bx, by)) = (to_pixels(pair[0]), to_pixels(pair[1]));
+            let steps = (bx - ax).abs().max((by - ay).abs()).ceil().max(1.0) as usize;
+            for step in 0..=steps {
+                let t = step as f32 / steps as f32;
+                let (px, py) = ((ax + (bx - ax) * t) as usize, (ay + (by - ay) * t) as usize);
+                for y in py.saturating_sub(brush / 2)..(py + brush.div_ceil(2)).min(height) {
+                    for x in px.saturating_sub(brush / 2)..(px + brush.div_ceil(2)).min(width) {
+                        if buffer[y * width + x] == 0 {
+                            buffer[y * width + x] = TRAIL;
+                        }
+                    }
+                }
+            }
+        }
+        buffer
+    }
+}
+
+/// Columnas y filas extremas con células vivas: (x mínima, y mínima, x máxima, y máxima)
+fn live_bounds(game: &GameOfLife) -> Option<(usize, usize, usize, usize)> {
+    let rows: Vec<usize> = (0..game.height()).filter(|&y| (0..game.width()).any(|x| game.is_alive(x, y))).collect();
+    let columns: Vec<usize> = (0..game.width()).filter(|&x| rows.iter().any(|&y| game.is_alive(x, y))).collect();
+    Some((*columns.first()?, *rows.first()?, *columns.last()?, *rows.last()?))
+}
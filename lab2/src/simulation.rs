@@ -0,0 +1,156 @@
+use crate::GameOfLife;
+use crate::gradient;
+
+/// Cualquier autómata que se pueda mostrar en la ventana y exportar a GIF.
+/// Cada celda se dibuja con un color de la paleta, así que el mismo código de
+/// renderizado y exportación sirve para todos los modos de simulación.
+pub trait Simulation {
+    /// Ancho en celdas de la imagen que produce la simulación
+    fn width(&self) -> usize;
+    /// Alto en celdas de la imagen que produce la simulación
+    fn height(&self) -> usize;
+    /// Avanza un paso de tiempo
+    fn step(&mut self);
+    /// Retrocede un paso exacto en reglas reversibles. Devuelve `false` si la
+    /// simulación no puede retroceder.
+    fn step_backward(&mut self) -> bool {
+        false
+    }
+    /// Colores RGB que usan las celdas (como máximo 256)
+    fn palette(&self) -> Vec<[u8; 3]>;
+    /// Índice de la paleta con el que se dibuja la celda (x, y)
+    fn palette_index(&self, x: usize, y: usize) -> u8;
+    /// Color exacto de la celda (x, y). Por defecto es el de la paleta; las
+    /// simulaciones con tonos continuos lo reemplazan y al exportar con
+    /// `--quantizer` se usa este color en lugar de la paleta.
+    fn color(&self, x: usize, y: usize) -> [u8; 3] {
+        self.palette()[self.palette_index(x, y) as usize]
+    }
+    /// Pinta con la herramienta actual sobre la celda (x, y) al hacer clic
+    fn paint(&mut self, _x: usize, _y: usize) {}
+    /// Cambia la herramienta de pintura con una tecla numérica
+    fn select_brush(&mut self, _digit: u8) {}
+    /// Mueve la vista `dx`, `dy` celdas en las simulaciones cuyo mundo es
+    /// más grande que lo que se muestra
+    fn pan(&mut self, _dx: i64, _dy: i64) {}
+}
+
+impl Simulation for GameOfLife {
+    fn width(&self) -> usize {
+        self.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height()
+    }
+
+    fn step(&mut self) {
+        self.next_generation();
+    }
+
+    fn palette(&self) -> Vec<[u8; 3]> {
+        self.theme().palette()
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        self.grid.is_alive(x, y) as u8
+    }
+}
+
+/// Dibuja la simulación en el buffer de la ventana, `scale` píxeles por celda
+pub fn render(simulation: &dyn Simulation, buffer: &mut [u32], scale: usize) {
+    render_at(simulation, buffer, simulation.width() * scale, (0, 0), scale);
+}
+
+/// Dibuja la simulación en un rectángulo de un buffer más grande que empieza
+/// en el píxel `origin`; `buffer_width` es el ancho del buffer en píxeles.
+/// Cada fila de celdas se pinta una vez en una línea ya escalada que después
+/// se copia `scale` veces, sin dividir ni buscar el color de cada píxel.
+pub fn render_at(simulation: &dyn Simulation, buffer: &mut [u32], buffer_width: usize, origin: (usize, usize), scale: usize) {
+    let colors: Vec<u32> = simulation.palette().into_iter().map(gradient::to_u32).collect();
+    let width = simulation.width() * scale;
+    if width == 0 {
+        return;
+    }
+
+    let mut scanline = vec![0; width];
+    for y in 0..simulation.height() {
+        for (x, cell) in scanline.chunks_exact_mut(scale).enumerate() {
+            cell.fill(colors[simulation.palette_index(x, y) as usize]);
+        }
+        for py in y * scale..(y + 1) * scale {
+            let row = (origin.1 + py) * buffer_width + origin.0;
+            buffer[row..row + width].copy_from_slice(&scanline);
+        }
+    }
+}
+
+/// Dibuja la simulación en el buffer de la ventana reescribiendo solo los
+/// bloques de `scale`×`scale` píxeles de las celdas que cambiaron desde el
+/// frame anterior. Supone que nadie más escribió en el buffer: después de
+/// dibujar algo encima hay que llamar a `invalidate`.
+#[derive(Default)]
+pub struct PartialRender {
+    /// Índice de la paleta de cada celda en el último frame; vacío si el
+    /// próximo frame se dibuja entero
+    previous: Vec<u8>,
+    palette: Vec<[u8; 3]>,
+    /// Ancho y alto en celdas y escala del último frame
+    size: (usize, usize, usize),
+}
+
+impl PartialRender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// El próximo frame se dibuja entero
+    pub fn invalidate(&mut self) {
+        self.previous.clear();
+    }
+
+    /// Dibuja como `render` y devuelve cuántas celdas reescribió
+    pub fn render(&mut self, simulation: &dyn Simulation, buffer: &mut [u32], scale: usize) -> usize {
+        let current = to_gif_frame_data(simulation);
+        let palette = simulation.palette();
+        let size = (simulation.width(), simulation.height(), scale);
+        if self.previous.len() != current.len() || self.size != size || self.palette != palette {
+            render(simulation, buffer, scale);
+            (self.previous, self.palette, self.size) = (current, palette, size);
+            return self.previous.len();
+        }
+
+        let colors: Vec<u32> = palette.iter().copied().map(gradient::to_u32).collect();
+        let (width, window_width) = (size.0, size.0 * scale);
+        let mut changed = 0;
+        for (i, (&index, previous)) in current.iter().zip(self.previous.iter_mut()).enumerate() {
+            if index == *previous {
+                continue;
+            }
+            *previous = index;
+            changed += 1;
+            let (px, py) = (i % width * scale, i / width * scale);
+            for row in py..py + scale {
+                let start = row * window_width + px;
+                buffer[start..start + scale].fill(colors[index as usize]);
+            }
+        }
+        changed
+    }
+}
+
+/// Índices de la paleta de todas las celdas, listos para un frame del GIF
+pub fn to_gif_frame_data(simulation: &dyn Simulation) -> Vec<u8> {
+    let (width, height) = (simulation.width(), simulation.height());
+    (0..width * height)
+        .map(|i| simulation.palette_index(i % width, i / width))
+        .collect()
+}
+
+/// Color RGB de todas las celdas, para exportar con un cuantizador
+pub fn to_rgb_frame_data(simulation: &dyn Simulation) -> Vec<[u8; 3]> {
+    let (width, height) = (simulation.width(), simulation.height());
+    (0..width * height)
+        .map(|i| simulation.color(i % width, i / width))
+        .collect()
+}
@@ -0,0 +1,340 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::capture::ImageBuffer;
+use crate::gradient::Gradient;
+use crate::overlay;
+use crate::recording::{self, invalid_data, read_u32, write_u32};
+use crate::{CellState, GameOfLife};
+
+// ===== FORMATO .snap =====
+// Todos los enteros se guardan en little-endian:
+//   magic (8 bytes) | versión u32 | generación u64 | ancho u32 | alto u32
+//   células empaquetadas en bits (fila por fila)
+const MAGIC: &[u8; 8] = b"LAB2SNP\0";
+const VERSION: u32 = 1;
+
+// ===== FORMATO .snapd (diferencia) =====
+//   magic (8 bytes) | versión u32 | generación u64 | generación base u64
+//   ancho u32 | alto u32 | cantidad de cambios u32
+//   distancia desde el cambio anterior de cada célula que cambió (varint)
+// El estado es el de la generación base con esas células invertidas. La base
+// se busca en la misma carpeta, como .snap o .snapd.
+const DELTA_MAGIC: &[u8; 8] = b"LAB2SND\0";
+
+/// Estado completo del universo en una generación
+pub struct Snapshot {
+    pub generation: u64,
+    pub game: GameOfLife,
+}
+
+impl Snapshot {
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(MAGIC)?;
+        write_u32(&mut out, VERSION)?;
+        out.write_all(&self.generation.to_le_bytes())?;
+        write_u32(&mut out, self.game.width() as u32)?;
+        write_u32(&mut out, self.game.height() as u32)?;
+        out.write_all(&recording::pack_cells(&self.game.cells()))?;
+        out.flush()
+    }
+
+    /// Guarda solo las células que cambiaron respecto de `base`
+    pub fn save_delta(&self, path: &Path, base: &Snapshot) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(DELTA_MAGIC)?;
+        write_u32(&mut out, VERSION)?;
+        out.write_all(&self.generation.to_le_bytes())?;
+        out.write_all(&base.generation.to_le_bytes())?;
+        write_u32(&mut out, self.game.width() as u32)?;
+        write_u32(&mut out, self.game.height() as u32)?;
+
+        let (cells, base_cells) = (self.game.cells(), base.game.cells());
+        let changed: Vec<usize> = (0..cells.len()).filter(|&i| cells[i] != base_cells[i]).collect();
+        write_u32(&mut out, changed.len() as u32)?;
+        let mut previous = 0;
+        for index in changed {
+            write_varint(&mut out, (index - previous) as u64)?;
+            previous = index;
+        }
+        out.flush()
+    }
+
+    /// Carga un estado completo (.snap) o una diferencia (.snapd), aplicando
+    /// las diferencias desde el último estado completo
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut input = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic == DELTA_MAGIC {
+            return Self::load_delta(path, &mut input);
+        }
+        if &magic != MAGIC {
+            return Err(invalid_data("no es un archivo de estado .snap".to_string()));
+        }
+        let version = read_u32(&mut input)?;
+        if version != VERSION {
+            return Err(invalid_data(format!("versión de estado no soportada: {}", version)));
+        }
+
+        let mut generation = [0u8; 8];
+        input.read_exact(&mut generation)?;
+        let width = read_u32(&mut input)? as usize;
+        let height = read_u32(&mut input)? as usize;
+        let cells = recording::read_cells(&mut input, width * height)?;
+        Ok(Self { generation: u64::from_le_bytes(generation), game: GameOfLife::from_cells(width, height, &cells) })
+    }
+
+    fn load_delta(path: &Path, input: &mut impl Read) -> io::Result<Self> {
+        let version = read_u32(input)?;
+        if version != VERSION {
+            return Err(invalid_data(format!("versión de estado no soportada: {}", version)));
+        }
+        let generation = read_u64(input)?;
+        let base_generation = read_u64(input)?;
+        let width = read_u32(input)? as usize;
+        let height = read_u32(input)? as usize;
+
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let stem = dir.join(file_stem(base_generation));
+        let base_path = [stem.with_extension("snap"), stem.with_extension("snapd")]
+            .into_iter()
+            .find(|p| p.exists())
+            .ok_or_else(|| invalid_data(format!("falta el estado base de la generación {}", base_generation)))?;
+        let mut cells = Self::load(&base_path)?.game.cells();
+        if cells.len() != width * height {
+            return Err(invalid_data("el estado base tiene otro tamaño".to_string()));
+        }
+
+        let mut index = 0;
+        for _ in 0..read_u32(input)? {
+            index += read_varint(input)? as usize;
+            let cell = cells.get_mut(index).ok_or_else(|| invalid_data("cambio fuera del grid".to_string()))?;
+            *cell = match cell {
+                CellState::Alive => CellState::Dead,
+                CellState::Dead => CellState::Alive,
+            };
+        }
+        Ok(Self { generation, game: GameOfLife::from_cells(width, height, &cells) })
+    }
+}
+
+// ===== LISTADO =====
+/// Lo que dice la cabecera de un estado guardado, sin leer las células
+#[derive(Clone, Debug)]
+pub struct SnapshotInfo {
+    pub path: PathBuf,
+    pub generation: u64,
+    pub width: usize,
+    pub height: usize,
+    /// Generación del estado del que es diferencia (solo en .snapd)
+    pub base_generation: Option<u64>,
+    /// Tamaño del archivo en bytes
+    pub bytes: u64,
+}
+
+impl SnapshotInfo {
+    /// Lee la cabecera de un .snap o .snapd
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let bytes = std::fs::metadata(path)?.len();
+        let mut input = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        let delta = match &magic {
+            MAGIC => false,
+            DELTA_MAGIC => true,
+            _ => return Err(invalid_data("no es un archivo de estado .snap".to_string())),
+        };
+        let version = read_u32(&mut input)?;
+        if version != VERSION {
+            return Err(invalid_data(format!("versión de estado no soportada: {}", version)));
+        }
+        let generation = read_u64(&mut input)?;
+        let base_generation = if delta { Some(read_u64(&mut input)?) } else { None };
+        let width = read_u32(&mut input)? as usize;
+        let height = read_u32(&mut input)? as usize;
+        Ok(Self { path: path.to_path_buf(), generation, width, height, base_generation, bytes })
+    }
+}
+
+/// Archivo de estado que no se pudo leer y por qué
+pub type Unreadable = (PathBuf, io::Error);
+
+/// Cabeceras de los estados (.snap y .snapd) de `dir`, ordenados por
+/// generación. Los archivos que no se pueden leer se devuelven aparte con
+/// su error.
+pub fn list(dir: &Path) -> io::Result<(Vec<SnapshotInfo>, Vec<Unreadable>)> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<_>>()?;
+    paths.retain(|p| p.extension().is_some_and(|ext| ext == "snap" || ext == "snapd"));
+    paths.sort();
+
+    let (mut states, mut failed) = (Vec::new(), Vec::new());
+    for path in paths {
+        match SnapshotInfo::read(&path) {
+            Ok(info) => states.push(info),
+            Err(e) => failed.push((path, e)),
+        }
+    }
+    states.sort_by_key(|info| info.generation);
+    Ok((states, failed))
+}
+
+/// Bloques de células que entran en `max_side`×`max_side` (como mínimo 1×1)
+fn thumbnail_block(game: &GameOfLife, max_side: usize) -> usize {
+    game.width().max(game.height()).div_ceil(max_side.max(1)).max(1)
+}
+
+/// Imagen chica del universo: cada píxel es un bloque de células, más
+/// claro cuantas más vivas tiene. Ningún lado pasa de `max_side` píxeles.
+pub fn write_thumbnail(game: &GameOfLife, path: &Path, max_side: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let (blocks_x, blocks_y, densities) = overlay::block_densities(game, thumbnail_block(game, max_side));
+    let palette = Gradient::linear(crate::DEAD_RGB, crate::ALIVE_RGB).palette(256);
+    ImageBuffer::from_fn(blocks_x, blocks_y, |x, y| palette[(densities[y * blocks_x + x] * 255.0).round() as usize])
+        .save_png(path)
+}
+
+/// Nombre de archivo (sin extensión) del estado de una generación
+fn file_stem(generation: u64) -> String {
+    format!("gen_{:08}", generation)
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Entero en LEB128: 7 bits por byte, el bit alto indica que sigue otro byte
+fn write_varint(out: &mut impl Write, mut value: u64) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            return out.write_all(&[byte]);
+        }
+        out.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint(input: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8; 1];
+        input.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7F) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint demasiado largo".to_string()))
+}
+
+/// Guarda el estado (.snap) cada `every` generaciones en `dir`, con la
+/// generación en el nombre: `gen_00000120.snap`. Con `with_images` guarda
+/// también una imagen (.png) de cada uno.
+pub struct Snapshotter {
+    every: u64,
+    dir: PathBuf,
+    /// Píxeles por célula de las imágenes, si se guardan
+    image_scale: Option<usize>,
+    /// Con diferencias, cada cuántos estados se guarda uno completo
+    keyframe_every: Option<usize>,
+    previous: Option<Snapshot>,
+    since_keyframe: usize,
+}
+
+impl Snapshotter {
+    pub fn new(every: u64, dir: &Path) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        Ok(Self {
+            every: every.max(1),
+            dir: dir.to_path_buf(),
+            image_scale: None,
+            keyframe_every: None,
+            previous: None,
+            since_keyframe: 0,
+        })
+    }
+
+    /// Guarda al lado de cada estado una imagen PNG con `scale` píxeles por
+    /// célula
+    pub fn with_images(mut self, scale: usize) -> Self {
+        self.image_scale = Some(scale.max(1));
+        self
+    }
+
+    /// Guarda diferencias (.snapd) respecto del estado anterior y un estado
+    /// completo cada `keyframe_every` estados
+    pub fn with_deltas(mut self, keyframe_every: usize) -> Self {
+        self.keyframe_every = Some(keyframe_every.max(1));
+        self
+    }
+
+    /// Llamar después de calcular cada generación
+    pub fn observe(&mut self, game: &GameOfLife, generation: u64) -> Result<(), Box<dyn std::error::Error>> {
+        if !generation.is_multiple_of(self.every) {
+            return Ok(());
+        }
+        let stem = self.dir.join(file_stem(generation));
+        let snapshot = Snapshot { generation, game: game.clone() };
+        match (&self.previous, self.keyframe_every) {
+            // Si el universo cambió de tamaño hace falta un estado completo
+            (Some(previous), Some(keyframe_every))
+                if self.since_keyframe < keyframe_every
+                    && (previous.game.width(), previous.game.height()) == (game.width(), game.height()) =>
+            {
+                snapshot.save_delta(&stem.with_extension("snapd"), previous)?;
+                self.since_keyframe += 1;
+            }
+            _ => {
+                snapshot.save(&stem.with_extension("snap"))?;
+                self.since_keyframe = 1;
+            }
+        }
+        if let Some(scale) = self.image_scale {
+            write_png(game, &stem.with_extension("png"), scale)?;
+        }
+        if self.keyframe_every.is_some() {
+            self.previous = Some(snapshot);
+        }
+        Ok(())
+    }
+}
+
+/// Imagen PNG con los colores de célula muerta y viva, `scale` píxeles por célula
+fn write_png(game: &GameOfLife, path: &Path, scale: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let color = |x: usize, y: usize| match game.is_alive(x / scale, y / scale) {
+        true => crate::ALIVE_RGB,
+        false => crate::DEAD_RGB,
+    };
+    ImageBuffer::from_fn(game.width() * scale, game.height() * scale, color).save_png(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thumbnail_fits_in_max_side() {
+        let path = std::env::temp_dir().join(format!("lab2-thumbnail-{}.png", std::process::id()));
+        let mut game = GameOfLife::new(200, 100);
+        for x in 0..4 {
+            game.set_cell(x, 0, CellState::Alive);
+        }
+        write_thumbnail(&game, &path, 64).unwrap();
+        let thumbnail = image::open(&path).unwrap().to_rgb8();
+        std::fs::remove_file(&path).unwrap();
+
+        // Bloques de 4×4 células: 50×25 píxeles
+        assert_eq!(thumbnail.dimensions(), (50, 25));
+        // El primer bloque tiene 4 de 16 células vivas y el resto, ninguna
+        let palette = Gradient::linear(crate::DEAD_RGB, crate::ALIVE_RGB).palette(256);
+        assert_eq!(thumbnail.get_pixel(0, 0).0, palette[64]);
+        assert_eq!(thumbnail.get_pixel(1, 0).0, crate::DEAD_RGB);
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::engine::Engine;
+use crate::rule::Rule;
+use crate::simulation::Simulation;
+use crate::{CellState, GameOfLife};
+
+/// Coordenadas de una célula en el mundo sin bordes
+pub type Cell = (i64, i64);
+
+// ===== VISTA =====
+/// Región del mundo que se dibuja en la ventana y en el GIF: `width`×`height`
+/// células a partir de la esquina superior izquierda (`x`, `y`)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Viewport {
+    x: i64,
+    y: i64,
+    width: usize,
+    height: usize,
+    /// Se recentra en las células vivas después de cada paso
+    follow: bool,
+}
+
+impl Viewport {
+    /// Vista de `width`×`height` células con la esquina en (0, 0)
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { x: 0, y: 0, width, height, follow: false }
+    }
+
+    pub fn with_origin(mut self, x: i64, y: i64) -> Self {
+        (self.x, self.y) = (x, y);
+        self
+    }
+
+    /// Con `follow` la vista se centra en la caja que encierra a las células
+    /// vivas después de cada paso, así que lo que viaja no se sale de ella
+    pub fn with_follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    pub fn origin(&self) -> Cell {
+        (self.x, self.y)
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Célula del mundo que se ve en (x, y) de la vista
+    pub fn to_world(&self, x: usize, y: usize) -> Cell {
+        (self.x + x as i64, self.y + y as i64)
+    }
+
+    /// Mueve la vista; moverla a mano deja de seguir a las células
+    pub fn pan(&mut self, dx: i64, dy: i64) {
+        self.x += dx;
+        self.y += dy;
+        self.follow = false;
+    }
+
+    /// Pone la célula (x, y) en el centro de la vista
+    pub fn center_on(&mut self, x: i64, y: i64) {
+        self.x = x - self.width as i64 / 2;
+        self.y = y - self.height as i64 / 2;
+    }
+
+    /// Recentra la vista si sigue a las células
+    fn track(&mut self, cells: &HashSet<Cell>) {
+        if self.follow
+            && let Some(((left, top), (right, bottom))) = bounding_box(cells)
+        {
+            self.center_on(left + (right - left) / 2, top + (bottom - top) / 2);
+        }
+    }
+}
+
+/// Esquinas superior izquierda e inferior derecha (incluidas) de la caja
+/// que encierra a `cells`, o `None` si no hay ninguna
+pub fn bounding_box(cells: &HashSet<Cell>) -> Option<(Cell, Cell)> {
+    let mut iter = cells.iter();
+    let &first = iter.next()?;
+    Some(iter.fold((first, first), |((left, top), (right, bottom)), &(x, y)| {
+        ((left.min(x), top.min(y)), (right.max(x), bottom.max(y)))
+    }))
+}
+
+// ===== MOTOR =====
+/// Juego de la Vida sin bordes que guarda solo las coordenadas de las
+/// células vivas: los planeadores viajan para siempre y la memoria depende
+/// de la población, no del tamaño del mundo. Cada generación cuesta lo
+/// mismo que la población, así que conviene para patrones dispersos; uno
+/// denso va más rápido con el grid de siempre.
+pub struct SparseLife {
+    cells: HashSet<Cell>,
+    rule: Rule,
+    viewport: Viewport,
+    generations_per_step: u64,
+    /// Ancho y alto del mundo si tiene bordes; fuera de él todo está muerto
+    bounds: Option<(i64, i64)>,
+}
+
+impl SparseLife {
+    /// Mundo vacío con `rule`, mostrado por `viewport`. No admite las reglas
+    /// con B0, en las que el vacío infinito se enciende.
+    pub fn new(rule: Rule, viewport: Viewport) -> Result<Self, String> {
+        if rule.is_birth(0) {
+            return Err(format!("el motor disperso no admite reglas con B0 ({})", rule));
+        }
+        Ok(Self { cells: HashSet::new(), rule, viewport, generations_per_step: 1, bounds: None })
+    }
+
+    /// Las células vivas y la regla de `game`, con una vista del mismo
+    /// tamaño que su grid
+    pub fn from_game(game: &GameOfLife) -> Result<Self, String> {
+        let mut sparse = Self::new(game.rule(), Viewport::new(game.width(), game.height()))?;
+        for y in 0..game.height() {
+            for x in 0..game.width() {
+                if game.is_alive(x, y) {
+                    sparse.cells.insert((x as i64, y as i64));
+                }
+            }
+        }
+        Ok(sparse)
+    }
+
+    /// El mismo motor, avanzando `generations` generaciones en cada `step`
+    pub fn with_generations_per_step(mut self, generations: u64) -> Self {
+        self.generations_per_step = generations.max(1);
+        self
+    }
+
+    /// Limita el mundo a `width`×`height` células desde (0, 0), como el grid
+    /// de siempre: lo que sale de ahí muere
+    pub fn with_bounds(mut self, width: usize, height: usize) -> Self {
+        self.bounds = Some((width as i64, height as i64));
+        self.cells.retain(|&(x, y)| x >= 0 && y >= 0 && x < width as i64 && y < height as i64);
+        self
+    }
+
+    pub fn with_viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = viewport;
+        self.viewport.track(&self.cells);
+        self
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    pub fn cells(&self) -> &HashSet<Cell> {
+        &self.cells
+    }
+
+    pub fn is_alive_at(&self, cell: Cell) -> bool {
+        self.cells.contains(&cell)
+    }
+
+    pub fn set_at(&mut self, cell: Cell, state: CellState) {
+        match state {
+            CellState::Alive => self.cells.insert(cell),
+            CellState::Dead => self.cells.remove(&cell),
+        };
+    }
+
+    /// Cuenta los vecinos vivos de cada célula que tiene alguno (y de las
+    /// vivas aunque no tengan) y se queda con las que siguen o nacen
+    fn next_generation(&mut self) {
+        let mut counts: HashMap<Cell, u8> = HashMap::with_capacity(self.cells.len() * 4);
+        for &(x, y) in &self.cells {
+            counts.entry((x, y)).or_insert(0);
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx, dy) != (0, 0) {
+                        *counts.entry((x + dx, y + dy)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        self.cells = counts
+            .into_iter()
+            .filter(|&((x, y), _)| self.bounds.is_none_or(|(width, height)| x >= 0 && y >= 0 && x < width && y < height))
+            .filter(|&(cell, count)| {
+                let count = count as usize;
+                if self.cells.contains(&cell) { self.rule.is_survival(count) } else { self.rule.is_birth(count) }
+            })
+            .map(|(cell, _)| cell)
+            .collect();
+    }
+}
+
+impl Engine for SparseLife {
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.is_alive_at(self.viewport.to_world(x, y))
+    }
+
+    fn set_cell(&mut self, x: usize, y: usize, state: CellState) {
+        self.set_at(self.viewport.to_world(x, y), state);
+    }
+
+    fn advance(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.next_generation();
+        }
+        self.viewport.track(&self.cells);
+    }
+
+    fn population(&self) -> u64 {
+        self.cells.len() as u64
+    }
+}
+
+impl Simulation for SparseLife {
+    fn width(&self) -> usize {
+        self.viewport.width
+    }
+
+    fn height(&self) -> usize {
+        self.viewport.height
+    }
+
+    fn step(&mut self) {
+        self.advance(self.generations_per_step);
+    }
+
+    fn palette(&self) -> Vec<[u8; 3]> {
+        vec![crate::DEAD_RGB, crate::ALIVE_RGB]
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        Engine::is_alive(self, x, y) as u8
+    }
+
+    /// Revive la célula bajo el mouse
+    fn paint(&mut self, x: usize, y: usize) {
+        self.set_cell(x, y, CellState::Alive);
+    }
+
+    fn pan(&mut self, dx: i64, dy: i64) {
+        self.viewport.pan(dx, dy);
+    }
+}
@@ -0,0 +1,372 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::parquet::{self, ColumnData};
+
+use crate::{CellState, GameOfLife};
+
+/// Distancia máxima (en células) entre el punto dado y el objeto a seguir
+const TRACK_SEARCH_RADIUS: usize = 3;
+/// Margen alrededor de la caja del objeto donde se lo busca en la siguiente generación
+const TRACK_MARGIN: usize = 2;
+
+/// Rectángulo de células
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    /// Interpreta `X,Y,ANCHO,ALTO`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let values: Result<Vec<usize>, _> = text.split(',').map(|v| v.trim().parse()).collect();
+        match values.as_deref() {
+            Ok(&[x, y, width, height]) => Ok(Rect { x, y, width, height }),
+            _ => Err(format!("se esperaba un rectángulo como 10,10,20,5: {}", text)),
+        }
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// Estado de las células del rectángulo que caen dentro del grid
+    pub fn cells(&self, game: &GameOfLife) -> Vec<bool> {
+        (self.y..(self.y + self.height).min(game.height()))
+            .flat_map(|y| (self.x..(self.x + self.width).min(game.width())).map(move |x| (x, y)))
+            .map(|(x, y)| game.is_alive(x, y))
+            .collect()
+    }
+}
+
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)
+    }
+}
+/// Células vivas del universo
+pub fn population(game: &GameOfLife) -> usize {
+    game.cells().iter().filter(|&&c| c == CellState::Alive).count()
+}
+
+/// Población de una generación, cuántas células nacieron y murieron para
+/// llegar a ella, su entropía y la caja que encierra a las vivas
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub population: usize,
+    pub births: usize,
+    pub deaths: usize,
+    /// Entropía de Shannon, en bits, de los bloques de 2×2 células: 0 si
+    /// todos los bloques son iguales, hasta 4 si las 16 combinaciones
+    /// aparecen por igual
+    pub entropy: f64,
+    /// Caja de las células vivas; `None` si no hay ninguna
+    pub bounds: Option<Rect>,
+}
+
+impl GenerationStats {
+    /// Estadísticas de `after`, la generación `generation`, comparada con
+    /// las células de la anterior (`before`, de `GameOfLife::cells`)
+    pub fn between(generation: u64, before: &[CellState], after: &GameOfLife) -> Self {
+        let mut stats = Self { generation, ..Self::measure(after) };
+        for (old, new) in before.iter().zip(after.cells()) {
+            match (*old, new) {
+                (CellState::Dead, CellState::Alive) => stats.births += 1,
+                (CellState::Alive, CellState::Dead) => stats.deaths += 1,
+                _ => {}
+            }
+        }
+        stats
+    }
+
+    /// La generación inicial: todas sus células están desde el principio
+    pub fn initial(generation: u64, game: &GameOfLife) -> Self {
+        Self { generation, ..Self::measure(game) }
+    }
+
+    /// Población, entropía y caja de `game`
+    fn measure(game: &GameOfLife) -> Self {
+        let (width, height) = (game.width(), game.height());
+        let mut stats = Self::default();
+        let mut corners: Option<(usize, usize, usize, usize)> = None;
+        for y in 0..height {
+            for x in (0..width).filter(|&x| game.is_alive(x, y)) {
+                stats.population += 1;
+                let (x0, y0, x1, y1) = corners.get_or_insert((x, y, x, y));
+                (*x0, *y0, *x1, *y1) = ((*x0).min(x), (*y0).min(y), (*x1).max(x), (*y1).max(y));
+            }
+        }
+        stats.bounds = corners.map(|(x0, y0, x1, y1)| Rect { x: x0, y: y0, width: x1 - x0 + 1, height: y1 - y0 + 1 });
+
+        let mut blocks = [0usize; 16];
+        for y in (0..height / 2 * 2).step_by(2) {
+            for x in (0..width / 2 * 2).step_by(2) {
+                let code = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                    .iter()
+                    .enumerate()
+                    .fold(0, |code, (bit, &(dx, dy))| code | (game.is_alive(x + dx, y + dy) as usize) << bit);
+                blocks[code] += 1;
+            }
+        }
+        let total = blocks.iter().sum::<usize>().max(1) as f64;
+        stats.entropy = blocks.iter().filter(|&&n| n > 0).map(|&n| n as f64 / total).map(|p| -p * p.log2()).sum();
+        stats
+    }
+}
+
+/// Formato del archivo de `--stats`
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum StatsFormat {
+    /// Texto separado por comas, una fila por generación
+    #[default]
+    Csv,
+    /// Tabla Parquet en columnas, para pandas o polars
+    Parquet,
+}
+
+impl StatsFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            StatsFormat::Csv => "csv",
+            StatsFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// Columnas de las estadísticas, en el orden en que se escriben
+const STATS_COLUMNS: [&str; 9] =
+    ["generacion", "poblacion", "nacimientos", "muertes", "entropia", "caja_x", "caja_y", "caja_ancho", "caja_alto"];
+
+/// Archivo con una fila de `GenerationStats` por generación, para graficar
+/// la población con cualquier planilla o con pandas. Sin células vivas la
+/// caja queda vacía en el CSV y en 0 en Parquet.
+pub struct StatsLog {
+    sink: StatsSink,
+}
+
+enum StatsSink {
+    /// Cada fila se escribe apenas llega
+    Csv(BufWriter<File>),
+    /// Parquet guarda por columnas, así que las filas se juntan hasta el final
+    Parquet(std::path::PathBuf, Vec<GenerationStats>),
+}
+
+impl StatsLog {
+    /// Crea el archivo y, en CSV, escribe la cabecera
+    pub fn create(path: &Path, format: StatsFormat) -> std::io::Result<Self> {
+        let sink = match format {
+            StatsFormat::Csv => {
+                let mut writer = BufWriter::new(File::create(path)?);
+                writeln!(writer, "{}", STATS_COLUMNS.join(","))?;
+                StatsSink::Csv(writer)
+            }
+            StatsFormat::Parquet => {
+                // Falla ahora, y no al terminar, si no se puede escribir
+                File::create(path)?;
+                StatsSink::Parquet(path.to_path_buf(), Vec::new())
+            }
+        };
+        Ok(Self { sink })
+    }
+
+    pub fn write(&mut self, stats: &GenerationStats) -> std::io::Result<()> {
+        match &mut self.sink {
+            StatsSink::Csv(writer) => {
+                let bounds = stats.bounds.map_or(",,,".to_string(), |rect| rect.to_string());
+                writeln!(
+                    writer,
+                    "{},{},{},{},{:.6},{}",
+                    stats.generation, stats.population, stats.births, stats.deaths, stats.entropy, bounds
+                )
+            }
+            StatsSink::Parquet(_, rows) => {
+                rows.push(*stats);
+                Ok(())
+            }
+        }
+    }
+
+    /// Termina de escribir el archivo
+    pub fn finish(self) -> std::io::Result<()> {
+        match self.sink {
+            StatsSink::Csv(mut writer) => writer.flush(),
+            StatsSink::Parquet(path, rows) => {
+                let int = |value: fn(&GenerationStats) -> usize| ColumnData::Int64(rows.iter().map(|s| value(s) as i64).collect());
+                let columns = [
+                    ColumnData::Int64(rows.iter().map(|s| s.generation as i64).collect()),
+                    int(|s| s.population),
+                    int(|s| s.births),
+                    int(|s| s.deaths),
+                    ColumnData::Double(rows.iter().map(|s| s.entropy).collect()),
+                    int_bounds(&rows, |r| r.x),
+                    int_bounds(&rows, |r| r.y),
+                    int_bounds(&rows, |r| r.width),
+                    int_bounds(&rows, |r| r.height),
+                ];
+                let named: Vec<(&str, ColumnData)> = STATS_COLUMNS.into_iter().zip(columns).collect();
+                parquet::write(&path, &named)
+            }
+        }
+    }
+}
+
+/// Un lado de la caja de cada fila, 0 si la generación no tiene células
+fn int_bounds(rows: &[GenerationStats], value: fn(&Rect) -> usize) -> ColumnData {
+    ColumnData::Int64(rows.iter().map(|s| s.bounds.as_ref().map_or(0, value) as i64).collect())
+}
+
+/// Sigue un objeto (grupo de células vivas conectadas) de generación en
+/// generación buscándolo cerca de donde estaba
+#[derive(Clone)]
+pub struct Tracker {
+    from: (usize, usize),
+    /// Caja (x mínima, y mínima, x máxima, y máxima) del objeto
+    bounds: Option<(usize, usize, usize, usize)>,
+    started: bool,
+}
+
+impl Tracker {
+    /// Seguirá el objeto que esté en `from` (o cerca) la primera vez que se actualice
+    pub fn new(from: (usize, usize)) -> Self {
+        Self { from, bounds: None, started: false }
+    }
+
+    /// Busca el objeto en la generación actual y devuelve su centro, o
+    /// `None` si se perdió (una vez perdido no se vuelve a buscar)
+    pub fn update(&mut self, game: &GameOfLife) -> Option<(usize, usize)> {
+        self.bounds = match (self.started, self.bounds) {
+            (false, _) => find_object(game, self.from),
+            (true, Some(bounds)) => follow_object(game, bounds),
+            (true, None) => None,
+        };
+        self.started = true;
+        self.bounds.map(|(x0, y0, x1, y1)| ((x0 + x1) / 2, (y0 + y1) / 2))
+    }
+
+    /// Promedio de las posiciones de las células vivas del objeto en la
+    /// última generación actualizada (el centro de la célula es x + 0.5)
+    pub fn centroid(&self, game: &GameOfLife) -> Option<(f32, f32)> {
+        let (x0, y0, x1, y1) = self.bounds?;
+        let cells: Vec<(usize, usize)> = (y0..=y1)
+            .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+            .filter(|&(x, y)| game.is_alive(x, y))
+            .collect();
+        let n = cells.len() as f32;
+        let (sum_x, sum_y) = cells.iter().fold((0.0, 0.0), |(sx, sy), &(x, y)| (sx + x as f32, sy + y as f32));
+        Some((sum_x / n + 0.5, sum_y / n + 0.5))
+    }
+}
+
+/// Caja del objeto (grupo de células vivas conectadas) más cercano a `from`
+fn find_object(game: &GameOfLife, from: (usize, usize)) -> Option<(usize, usize, usize, usize)> {
+    let (fx, fy) = from;
+    let r = TRACK_SEARCH_RADIUS;
+    let seed = (fy.saturating_sub(r)..=(fy + r).min(game.height() - 1))
+        .flat_map(|y| (fx.saturating_sub(r)..=(fx + r).min(game.width() - 1)).map(move |x| (x, y)))
+        .filter(|&(x, y)| game.is_alive(x, y))
+        .min_by_key(|&(x, y)| x.abs_diff(fx).max(y.abs_diff(fy)))?;
+    flood_bounds(game, vec![seed])
+}
+
+/// Caja del objeto en la generación actual, buscándolo cerca de su caja anterior
+fn follow_object(game: &GameOfLife, bounds: (usize, usize, usize, usize)) -> Option<(usize, usize, usize, usize)> {
+    let (x0, y0, x1, y1) = bounds;
+    let m = TRACK_MARGIN;
+    let seeds = (y0.saturating_sub(m)..=(y1 + m).min(game.height() - 1))
+        .flat_map(|y| (x0.saturating_sub(m)..=(x1 + m).min(game.width() - 1)).map(move |x| (x, y)))
+        .filter(|&(x, y)| game.is_alive(x, y))
+        .collect();
+    flood_bounds(game, seeds)
+}
+
+/// Caja de todas las células vivas conectadas (incluidas las diagonales) a
+/// alguna de `seeds`, o `None` si no hay ninguna
+fn flood_bounds(game: &GameOfLife, seeds: Vec<(usize, usize)>) -> Option<(usize, usize, usize, usize)> {
+    let mut visited = vec![false; game.width() * game.height()];
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    let mut pending = seeds;
+    while let Some((x, y)) = pending.pop() {
+        if std::mem::replace(&mut visited[y * game.width() + x], true) {
+            continue;
+        }
+        bounds = Some(match bounds {
+            None => (x, y, x, y),
+            Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+        });
+        for ny in y.saturating_sub(1)..=(y + 1).min(game.height() - 1) {
+            for nx in x.saturating_sub(1)..=(x + 1).min(game.width() - 1) {
+                if game.is_alive(nx, ny) && !visited[ny * game.width() + nx] {
+                    pending.push((nx, ny));
+                }
+            }
+        }
+    }
+    bounds
+}
+
+/// Expresión que se muestra en la barra de estado y se actualiza en cada
+/// generación
+#[derive(Clone)]
+pub struct Watch {
+    kind: WatchKind,
+    value: Option<u64>,
+}
+
+#[derive(Clone)]
+enum WatchKind {
+    Population,
+    LiveCells(Rect),
+    /// Generaciones desde que se marcó el objeto que estaba en `from`
+    Age { from: (usize, usize), tracker: Tracker, tagged_at: Option<u64> },
+}
+
+impl Watch {
+    /// Interpreta `population`, `cells:X,Y,ANCHO,ALTO` o `age:X,Y`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let error = || format!("expresión inválida: {}", text);
+        let (kind, value) = text.split_once(':').unwrap_or((text, ""));
+        let kind = match kind.trim() {
+            "population" => WatchKind::Population,
+            "cells" => WatchKind::LiveCells(Rect::parse(value).map_err(|_| error())?),
+            "age" => {
+                let (x, y) = value.split_once(',').ok_or_else(error)?;
+                let from = (x.trim().parse().map_err(|_| error())?, y.trim().parse().map_err(|_| error())?);
+                WatchKind::Age { from, tracker: Tracker::new(from), tagged_at: None }
+            }
+            _ => return Err(error()),
+        };
+        Ok(Self { kind, value: None })
+    }
+
+    pub fn update(&mut self, game: &GameOfLife, generation: u64) {
+        self.value = match &mut self.kind {
+            WatchKind::Population => Some(population(game) as u64),
+            WatchKind::LiveCells(rect) => Some(rect.cells(game).iter().filter(|&&alive| alive).count() as u64),
+            WatchKind::Age { tracker, tagged_at, .. } => {
+                let tagged_at = *tagged_at.get_or_insert(generation);
+                tracker.update(game).map(|_| generation - tagged_at)
+            }
+        };
+    }
+}
+
+impl fmt::Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            WatchKind::Population => write!(f, "población")?,
+            WatchKind::LiveCells(rect) => write!(f, "vivas {}", rect)?,
+            WatchKind::Age { from: (x, y), .. } => write!(f, "edad {},{}", x, y)?,
+        }
+        match self.value {
+            Some(value) => write!(f, ": {}", value),
+            None => write!(f, ": -"),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use rand::RngCore;
+
+/// Incremento de SplitMix64 (la parte fraccionaria de φ en 64 bits)
+const GOLDEN: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Números al azar independientes para cada región (una fila, un bloque)
+/// derivados de una semilla maestra. Cada número es una función de la
+/// semilla, el paso, la región y su posición en la secuencia, sin estado
+/// compartido entre regiones: el resultado es idéntico bit a bit sin
+/// importar cuántos hilos procesen las regiones ni en qué orden.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RngStreams {
+    seed: u64,
+}
+
+impl RngStreams {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// Con una semilla al azar
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Secuencia de la región `region` en el paso `step` (por ejemplo, la
+    /// generación). Pedir la misma dos veces da los mismos números.
+    pub fn stream(&self, step: u64, region: u64) -> Stream {
+        Stream { key: mix(mix(mix(self.seed) ^ step) ^ region), counter: 0 }
+    }
+}
+
+/// Secuencia de una región: el número `i` es una mezcla de la clave y de
+/// `i` (SplitMix64 usado como generador basado en contador)
+#[derive(Clone, Debug)]
+pub struct Stream {
+    key: u64,
+    counter: u64,
+}
+
+impl RngCore for Stream {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.counter += 1;
+        mix(self.key.wrapping_add(self.counter.wrapping_mul(GOLDEN)))
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Mezcla final de SplitMix64: cambia en promedio la mitad de los bits de
+/// la salida por cada bit de la entrada
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,93 @@
+use crate::gradient::{Colormap, Gradient};
+use crate::{ALIVE_RGB, DEAD_RGB};
+
+/// Qué tanto se acercan las líneas del grid al color de las vivas
+const GRID_LINE_MIX: f32 = 0.25;
+
+/// Colores con los que se dibuja un `GameOfLife` en la ventana y en el GIF
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Theme {
+    pub alive: [u8; 3],
+    pub dead: [u8; 3],
+    /// Lo que queda fuera del universo (bordes de la ventana, zoom alejado)
+    pub background: [u8; 3],
+    /// Mapa de calor que va con el tema, si `--colormap` no elige otro
+    pub gradient: Option<Colormap>,
+}
+
+impl Theme {
+    /// Paleta de los GIF y de `Simulation`: índice 0 muerta, 1 viva
+    pub fn palette(&self) -> Vec<[u8; 3]> {
+        vec![self.dead, self.alive]
+    }
+
+    /// Color de las líneas del grid: a un cuarto del camino de las muertas a
+    /// las vivas, para que se vean sin competir con las células
+    pub fn grid_line(&self) -> [u8; 3] {
+        Gradient::linear(self.dead, self.alive).sample(GRID_LINE_MIX)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        ThemeKind::Classic.theme()
+    }
+}
+
+/// Temas que se eligen con `--theme` y se recorren con la tecla T
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ThemeKind {
+    /// Blanco sobre azul oscuro
+    #[default]
+    Classic,
+    /// Ámbar de terminal antigua
+    Amber,
+    /// Verde fósforo sobre negro
+    Matrix,
+    /// Blanco sobre negro puro, con el borde en gris
+    HighContrast,
+}
+
+impl ThemeKind {
+    pub const ALL: [ThemeKind; 4] = [ThemeKind::Classic, ThemeKind::Amber, ThemeKind::Matrix, ThemeKind::HighContrast];
+
+    pub fn theme(self) -> Theme {
+        match self {
+            ThemeKind::Classic => Theme { alive: ALIVE_RGB, dead: DEAD_RGB, background: [0x00, 0x00, 0x00], gradient: None },
+            ThemeKind::Amber => Theme {
+                alive: [0xFF, 0xB0, 0x00],
+                dead: [0x1A, 0x0E, 0x00],
+                background: [0x0A, 0x05, 0x00],
+                gradient: Some(Colormap::Magma),
+            },
+            ThemeKind::Matrix => Theme {
+                alive: [0x00, 0xFF, 0x41],
+                dead: [0x00, 0x0D, 0x02],
+                background: [0x00, 0x00, 0x00],
+                gradient: Some(Colormap::Viridis),
+            },
+            ThemeKind::HighContrast => Theme {
+                alive: [0xFF, 0xFF, 0xFF],
+                dead: [0x00, 0x00, 0x00],
+                background: [0x55, 0x55, 0x55],
+                gradient: None,
+            },
+        }
+    }
+
+    /// El tema siguiente, volviendo al primero después del último
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&kind| kind == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeKind::Classic => "classic",
+            ThemeKind::Amber => "amber",
+            ThemeKind::Matrix => "matrix",
+            ThemeKind::HighContrast => "high-contrast",
+        }
+    }
+}
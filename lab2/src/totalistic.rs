@@ -0,0 +1,254 @@
+use std::fmt;
+
+use rand::Rng;
+
+use crate::simulation::Simulation;
+
+/// Regla totalística de varios estados: el siguiente estado depende del
+/// estado actual y de la suma de los estados de los 8 vecinos
+#[derive(Clone, Debug, PartialEq)]
+pub struct TotalisticRule {
+    states: u8,
+    /// `table[estado * (suma máxima + 1) + suma]`
+    table: Vec<u8>,
+}
+
+impl TotalisticRule {
+    /// Mayor suma posible de los 8 vecinos
+    fn max_sum(states: u8) -> usize {
+        8 * (states as usize - 1)
+    }
+
+    /// Regla aleatoria. `lambda` es la probabilidad de que una entrada de la
+    /// tabla lleve a un estado vivo: valores bajos tienden a universos
+    /// muertos y valores altos a ruido
+    pub fn random(states: u8, lambda: f64, rng: &mut impl Rng) -> Self {
+        let states = states.clamp(2, 9);
+        let len = states as usize * (Self::max_sum(states) + 1);
+        let table = (0..len)
+            .map(|_| if rng.gen_bool(lambda) { rng.gen_range(1..states) } else { 0 })
+            .collect();
+        Self { states, table }
+    }
+
+    /// Lee una regla escrita como `estados/tabla`, por ejemplo `3/0120...`,
+    /// con un dígito por entrada de la tabla (la misma forma que imprime
+    /// `to_string`)
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (states, digits) = text.split_once('/').ok_or("se esperaba 'estados/tabla'")?;
+        let states: u8 = states.trim().parse().map_err(|_| format!("número de estados inválido: {}", states))?;
+        if !(2..=9).contains(&states) {
+            return Err(format!("el número de estados va de 2 a 9: {}", states));
+        }
+
+        let table: Vec<u8> = digits
+            .trim()
+            .chars()
+            .map(|c| match c.to_digit(10) {
+                Some(d) if (d as u8) < states => Ok(d as u8),
+                _ => Err(format!("entrada inválida en la tabla: '{}'", c)),
+            })
+            .collect::<Result<_, _>>()?;
+        let expected = states as usize * (Self::max_sum(states) + 1);
+        if table.len() != expected {
+            return Err(format!("una regla de {} estados necesita {} entradas, hay {}", states, expected, table.len()));
+        }
+        Ok(Self { states, table })
+    }
+
+    /// Cruce uniforme: cada entrada de la tabla sale de uno de los dos padres
+    pub fn crossover(&self, other: &TotalisticRule, rng: &mut impl Rng) -> Self {
+        let table = self
+            .table
+            .iter()
+            .zip(&other.table)
+            .map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b })
+            .collect();
+        Self { states: self.states, table }
+    }
+
+    /// Cambia cada entrada de la tabla con probabilidad `rate`, usando el
+    /// mismo `lambda` que `random`
+    pub fn mutate(&mut self, rate: f64, lambda: f64, rng: &mut impl Rng) {
+        for entry in &mut self.table {
+            if rng.gen_bool(rate) {
+                *entry = if rng.gen_bool(lambda) { rng.gen_range(1..self.states) } else { 0 };
+            }
+        }
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    pub fn next_state(&self, current: u8, neighbor_sum: usize) -> u8 {
+        self.table[current as usize * (Self::max_sum(self.states) + 1) + neighbor_sum]
+    }
+}
+
+impl fmt::Display for TotalisticRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/", self.states)?;
+        self.table.iter().try_for_each(|d| write!(f, "{}", d))
+    }
+}
+
+/// Autómata totalístico de varios estados con bordes muertos
+#[derive(Clone)]
+pub struct MultiStateLife {
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
+    rule: TotalisticRule,
+}
+
+impl MultiStateLife {
+    pub fn new(width: usize, height: usize, rule: TotalisticRule) -> Self {
+        Self { width, height, cells: vec![0; width * height], rule }
+    }
+
+    /// Cada celda queda viva (en un estado al azar) con la probabilidad dada
+    pub fn randomize(&mut self, density: f64, rng: &mut impl Rng) {
+        let states = self.rule.states();
+        for cell in &mut self.cells {
+            *cell = if rng.gen_bool(density) { rng.gen_range(1..states) } else { 0 };
+        }
+    }
+
+    pub fn rule(&self) -> &TotalisticRule {
+        &self.rule
+    }
+
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, state: u8) {
+        self.cells[y * self.width + x] = state;
+    }
+
+    fn neighbor_sum(&self, x: usize, y: usize) -> usize {
+        let mut sum = 0;
+        for dy in -1..=1i32 {
+            for dx in -1..=1i32 {
+                if dx == 0 && dy == 0 { continue; }
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if nx >= 0 && ny >= 0 && (nx as usize) < self.width && (ny as usize) < self.height {
+                    sum += self.cells[ny as usize * self.width + nx as usize] as usize;
+                }
+            }
+        }
+        sum
+    }
+}
+
+impl Simulation for MultiStateLife {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn step(&mut self) {
+        let next = (0..self.cells.len())
+            .map(|i| {
+                let (x, y) = (i % self.width, i / self.width);
+                self.rule.next_state(self.cells[i], self.neighbor_sum(x, y))
+            })
+            .collect();
+        self.cells = next;
+    }
+
+    /// Fondo oscuro y luego colores cada vez más claros para los estados altos
+    fn palette(&self) -> Vec<[u8; 3]> {
+        let colors = [
+            [0x30, 0x90, 0xF0],
+            [0xF0, 0xA0, 0x30],
+            [0xFF, 0xFF, 0xFF],
+            [0x50, 0xE0, 0x70],
+            [0xE0, 0x40, 0x60],
+            [0xB0, 0x60, 0xF0],
+            [0xF0, 0xF0, 0x40],
+            [0x40, 0xE0, 0xE0],
+        ];
+        std::iter::once(crate::DEAD_RGB)
+            .chain(colors.iter().copied().take(self.rule.states() as usize - 1))
+            .collect()
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        self.cells[y * self.width + x]
+    }
+}
+
+// ===== EXPLORADOR DE REGLAS =====
+/// Tamaño del universo con el que se prueba cada regla
+pub const TRIAL_SIZE: usize = 48;
+/// Generaciones de cada prueba
+const TRIAL_STEPS: usize = 60;
+
+/// Entropía (en bits) de la distribución de estados
+fn entropy(cells: &[u8], states: u8) -> f64 {
+    let mut counts = vec![0usize; states as usize];
+    cells.iter().for_each(|&c| counts[c as usize] += 1);
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / cells.len() as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Resultado de probar una regla
+pub struct Trial {
+    pub score: f64,
+    /// Universo al final de la prueba, listo para seguir animándose
+    pub simulation: MultiStateLife,
+}
+
+/// Simula una regla unas pocas generaciones y la califica. Las reglas más
+/// interesantes terminan con una mezcla de estados (entropía alta), siguen
+/// cambiando sin volverse ruido (actividad moderada) y organizan el desorden
+/// inicial (la entropía baja un poco respecto al inicio).
+pub fn run_trial(rule: TotalisticRule, density: f64, rng: &mut impl Rng) -> Trial {
+    let states = rule.states();
+    let mut simulation = MultiStateLife::new(TRIAL_SIZE, TRIAL_SIZE, rule);
+    simulation.randomize(density, rng);
+    let initial_entropy = entropy(simulation.cells(), states);
+
+    let mut changes = 0usize;
+    for step in 0..TRIAL_STEPS {
+        let before = simulation.cells().to_vec();
+        simulation.step();
+        if step >= TRIAL_STEPS / 2 {
+            changes += before.iter().zip(simulation.cells()).filter(|(a, b)| a != b).count();
+        }
+    }
+
+    let cells = TRIAL_SIZE * TRIAL_SIZE;
+    let activity = changes as f64 / (cells * (TRIAL_STEPS - TRIAL_STEPS / 2)) as f64;
+    let final_entropy = entropy(simulation.cells(), states);
+
+    // La actividad ideal está alrededor del 25%: ni congelado ni ruido
+    let activity_term = (activity * (0.5 - activity) * 16.0).max(0.0);
+    let organization = (initial_entropy - final_entropy).max(0.0);
+    let score = final_entropy / (states as f64).log2() * activity_term * (1.0 + organization);
+
+    Trial { score, simulation }
+}
+
+/// Prueba `samples` reglas aleatorias y devuelve las `keep` mejores,
+/// ordenadas de mayor a menor puntaje
+pub fn explore(states: u8, samples: usize, keep: usize, lambda: f64, density: f64) -> Vec<Trial> {
+    let mut rng = rand::thread_rng();
+    let mut trials: Vec<Trial> = (0..samples)
+        .map(|_| run_trial(TotalisticRule::random(states, lambda, &mut rng), density, &mut rng))
+        .collect();
+    trials.sort_by(|a, b| b.score.total_cmp(&a.score));
+    trials.truncate(keep);
+    trials
+}
@@ -0,0 +1,100 @@
+use crate::{CellState, GameOfLife};
+use crate::geometry::{CellPos, GridSize};
+use crate::gradient::{self, ColorSpace, Gradient};
+use crate::theme::Theme;
+
+/// Rastro más largo: cada generación del rastro es un color más en la
+/// paleta del GIF, que tiene 256
+pub const MAX_TRAIL_LENGTH: u32 = 64;
+
+/// Marca de una célula que no estuvo viva desde que se empezó a observar
+const NEVER: u64 = u64::MAX;
+
+/// Rastros de las células que murieron hace poco: se dibujan con el color
+/// de las vivas cada vez más apagado, así las naves dejan una estela
+pub struct Trails {
+    length: u32,
+    size: GridSize,
+    /// Última generación en la que estuvo viva cada célula, fila por fila
+    last_alive: Vec<u64>,
+    generation: u64,
+}
+
+impl Trails {
+    /// Rastros de `length` generaciones, empezando por el estado actual de
+    /// `game` en la generación `generation`
+    pub fn new(game: &GameOfLife, generation: u64, length: u32) -> Self {
+        let mut trails = Self { length: length.clamp(1, MAX_TRAIL_LENGTH), size: GridSize::default(), last_alive: Vec::new(), generation };
+        trails.observe(game, generation);
+        trails
+    }
+
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// Anota las células vivas de `game` en la generación `generation`. Si
+    /// el universo cambió de tamaño, los rastros empiezan de nuevo.
+    pub fn observe(&mut self, game: &GameOfLife, generation: u64) {
+        if game.size() != self.size {
+            self.size = game.size();
+            self.last_alive = vec![NEVER; self.size.area()];
+        }
+        self.generation = generation;
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                if game.cell(x, y) == CellState::Alive {
+                    self.last_alive[self.size.index(CellPos::new(x, y))] = generation;
+                }
+            }
+        }
+    }
+
+    /// Hace cuántas generaciones murió la célula `pos`, si está muerta y su
+    /// rastro todavía se ve (de 1 a `length`)
+    pub fn age(&self, pos: CellPos) -> Option<u32> {
+        if !self.size.contains(pos) {
+            return None;
+        }
+        let last = self.last_alive[self.size.index(pos)];
+        let age = self.generation.checked_sub(last)?;
+        (last != NEVER && (1..=self.length as u64).contains(&age)).then_some(age as u32)
+    }
+
+    /// Un color por edad del rastro, de la más reciente a la más vieja: del
+    /// color de las vivas al de las muertas, mezclados en OkLab
+    pub fn colors(&self, theme: &Theme) -> Vec<[u8; 3]> {
+        let fade = Gradient::linear(theme.alive, theme.dead).with_space(ColorSpace::Oklab);
+        (1..=self.length).map(|age| fade.sample(age as f32 / (self.length + 1) as f32)).collect()
+    }
+
+    /// Pinta los rastros sobre un frame ya dibujado desde la esquina con
+    /// `scale` píxeles por célula
+    pub fn render(&self, buffer: &mut [u32], window_width: usize, scale: usize, theme: &Theme) {
+        let colors: Vec<u32> = self.colors(theme).into_iter().map(gradient::to_u32).collect();
+        let width = (self.size.width * scale).min(window_width);
+        for (py, row) in buffer.chunks_mut(window_width).take(self.size.height * scale).enumerate() {
+            for (x, cell) in row[..width].chunks_mut(scale).enumerate() {
+                if let Some(age) = self.age(CellPos::new(x, py / scale)) {
+                    cell.fill(colors[age as usize - 1]);
+                }
+            }
+        }
+    }
+
+    /// Cambia a los índices de los rastros las células muertas de un frame
+    /// del GIF de `frame_size` cuya esquina es la célula `origin` del
+    /// universo. Los colores de `colors` van en la paleta desde
+    /// `first_index`.
+    pub fn mark_frame(&self, frame: &mut [u8], frame_size: GridSize, origin: (i64, i64), first_index: u8) {
+        for (i, index) in frame.iter_mut().enumerate() {
+            let (x, y) = ((i % frame_size.width) as i64 + origin.0, (i / frame_size.width) as i64 + origin.1);
+            if *index != 0 || x < 0 || y < 0 {
+                continue;
+            }
+            if let Some(age) = self.age(CellPos::new(x as usize, y as usize)) {
+                *index = first_index + age as u8 - 1;
+            }
+        }
+    }
+}
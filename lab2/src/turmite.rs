@@ -0,0 +1,317 @@
+use std::fmt;
+
+use rand::RngCore;
+
+use crate::agents::{Agent, AgentLayer, Behavior, Cells, Heading};
+use crate::simulation::Simulation;
+
+/// Color de los turmites en la ventana y el GIF
+const TURMITE_RGB: [u8; 3] = [0xFF, 0x40, 0x40];
+
+/// Qué hace un turmite en un estado al ver un color
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    /// Color que escribe en la celda
+    pub write: u8,
+    /// Cuartos de vuelta en sentido horario: 0, 1 (derecha), 2 (media
+    /// vuelta) o 3 (izquierda)
+    pub turn: i32,
+    pub next_state: u32,
+}
+
+/// Turmite: una máquina de estados que, según su estado y el color de la
+/// celda en la que está, escribe un color, gira y avanza. Se escribe como en
+/// Golly, `{{{escribe, giro, siguiente}, ...}, ...}`: una lista por estado
+/// con una terna por color, donde el giro es 1 (seguir derecho), 2
+/// (derecha), 4 (media vuelta) u 8 (izquierda). La hormiga de Langton es
+/// `{{{1,2,0},{0,8,0}}}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Turmite {
+    colors: u8,
+    /// `table[estado][color]`
+    table: Vec<Vec<Transition>>,
+}
+
+/// Número o lista de la notación de Golly
+enum Node {
+    Number(u32),
+    List(Vec<Node>),
+}
+
+impl Turmite {
+    /// Lee la notación de Golly. Los espacios, los saltos de línea y las
+    /// líneas que empiezan con `#` se ignoran.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text: String = text
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(str::chars)
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let mut chars = text.chars().peekable();
+        let root = parse_node(&mut chars)?;
+        if let Some(c) = chars.next() {
+            return Err(format!("texto de más después de la regla: '{}'", c));
+        }
+
+        let Node::List(states) = root else {
+            return Err("la regla tiene que empezar con '{'".to_string());
+        };
+        if states.is_empty() {
+            return Err("la regla no tiene estados".to_string());
+        }
+        let state_count = states.len();
+        let mut table = Vec::with_capacity(state_count);
+        for (state, colors) in states.iter().enumerate() {
+            let Node::List(colors) = colors else {
+                return Err(format!("el estado {} tiene que ser una lista de ternas", state));
+            };
+            let row = colors
+                .iter()
+                .enumerate()
+                .map(|(color, triple)| parse_transition(triple, state, color, state_count))
+                .collect::<Result<Vec<_>, _>>()?;
+            table.push(row);
+        }
+
+        let colors = table[0].len();
+        if !(2..=255).contains(&colors) {
+            return Err(format!("un turmite usa de 2 a 255 colores, hay {}", colors));
+        }
+        if let Some(state) = table.iter().position(|row| row.len() != colors) {
+            return Err(format!("el estado {} tiene {} colores y el estado 0 tiene {}", state, table[state].len(), colors));
+        }
+        if let Some(t) = table.iter().flatten().find(|t| t.write as usize >= colors) {
+            return Err(format!("el color {} no existe: la regla usa {} colores", t.write, colors));
+        }
+        Ok(Self { colors: colors as u8, table })
+    }
+
+    /// Cantidad de colores de la regla (el 0 es el fondo)
+    pub fn colors(&self) -> u8 {
+        self.colors
+    }
+
+    pub fn states(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn transition(&self, state: u32, color: u8) -> Transition {
+        self.table[state as usize][color as usize]
+    }
+}
+
+fn parse_node(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Node, String> {
+    match chars.peek() {
+        Some('{') => {
+            chars.next();
+            let mut items = Vec::new();
+            if chars.peek() == Some(&'}') {
+                chars.next();
+                return Ok(Node::List(items));
+            }
+            loop {
+                items.push(parse_node(chars)?);
+                match chars.next() {
+                    Some(',') => {}
+                    Some('}') => return Ok(Node::List(items)),
+                    Some(c) => return Err(format!("se esperaba ',' o '}}': '{}'", c)),
+                    None => return Err("falta cerrar una '{'".to_string()),
+                }
+            }
+        }
+        Some(c) if c.is_ascii_digit() => {
+            let mut digits = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                digits.push(c);
+                chars.next();
+            }
+            digits.parse().map(Node::Number).map_err(|_| format!("número inválido: {}", digits))
+        }
+        Some(c) => Err(format!("símbolo inesperado en la regla: '{}'", c)),
+        None => Err("la regla está incompleta".to_string()),
+    }
+}
+
+fn parse_transition(node: &Node, state: usize, color: usize, state_count: usize) -> Result<Transition, String> {
+    let error = || format!("el estado {} y el color {} necesitan una terna {{escribe, giro, siguiente}}", state, color);
+    let Node::List(items) = node else { return Err(error()) };
+    let [Node::Number(write), Node::Number(turn), Node::Number(next_state)] = items.as_slice() else {
+        return Err(error());
+    };
+    let turn = match turn {
+        1 => 0,
+        2 => 1,
+        4 => 2,
+        8 => 3,
+        _ => return Err(format!("giro inválido {} (1 derecho, 2 derecha, 4 media vuelta, 8 izquierda)", turn)),
+    };
+    if *next_state as usize >= state_count {
+        return Err(format!("el estado {} no existe: la regla tiene {} estados", next_state, state_count));
+    }
+    let write = u8::try_from(*write).map_err(|_| format!("color inválido: {}", write))?;
+    Ok(Transition { write, turn, next_state: *next_state })
+}
+
+impl fmt::Display for Turmite {
+    /// La misma notación que lee `parse`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let states: Vec<String> = self
+            .table
+            .iter()
+            .map(|row| {
+                let colors: Vec<String> = row
+                    .iter()
+                    .map(|t| format!("{{{},{},{}}}", t.write, 1 << t.turn, t.next_state))
+                    .collect();
+                format!("{{{}}}", colors.join(","))
+            })
+            .collect();
+        write!(f, "{{{}}}", states.join(","))
+    }
+}
+
+impl Behavior for Turmite {
+    fn act(&self, agent: &mut Agent, _others: &[Agent], cells: &mut dyn Cells, _rng: &mut dyn RngCore) {
+        let color = cells.get(agent.x, agent.y).min(self.colors - 1);
+        let transition = self.transition(agent.state, color);
+        cells.set(agent.x, agent.y, transition.write);
+        agent.heading = agent.heading.turned(transition.turn);
+        agent.state = transition.next_state;
+        agent.forward(cells.width(), cells.height());
+    }
+}
+
+// ===== MUNDO DE LOS TURMITES =====
+/// Grid de colores sobre el que se mueven los turmites, con los bordes
+/// unidos a los opuestos
+pub struct TurmiteWorld {
+    width: usize,
+    height: usize,
+    colors: u8,
+    cells: ColorGrid,
+    layer: AgentLayer,
+    /// Pasos de los turmites por cada frame
+    steps_per_frame: usize,
+    /// Celdas ocupadas por algún turmite, para dibujarlos
+    occupied: Vec<bool>,
+}
+
+/// Colores de las celdas, fila por fila
+struct ColorGrid {
+    width: usize,
+    height: usize,
+    colors: Vec<u8>,
+}
+
+impl Cells for ColorGrid {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, x: usize, y: usize) -> u8 {
+        self.colors[y * self.width + x]
+    }
+
+    fn set(&mut self, x: usize, y: usize, color: u8) {
+        self.colors[y * self.width + x] = color;
+    }
+}
+
+impl TurmiteWorld {
+    /// `count` turmites con la regla `turmite` en un grid vacío: el primero
+    /// en el centro mirando al norte y los demás repartidos en una fila
+    pub fn new(width: usize, height: usize, turmite: Turmite, count: usize) -> Self {
+        let spacing = (width / count.max(1)).max(1);
+        let agents = (0..count)
+            .map(|i| {
+                let offset = (i as i64 + 1) / 2 * if i % 2 == 0 { 1 } else { -1 };
+                let x = (width as i64 / 2 + offset * spacing as i64).rem_euclid(width as i64) as usize;
+                Agent::new(x, height / 2, Heading::North)
+            })
+            .collect();
+        let colors = turmite.colors();
+        let mut world = Self {
+            width,
+            height,
+            colors,
+            cells: ColorGrid { width, height, colors: vec![0; width * height] },
+            layer: AgentLayer::new(agents, Box::new(turmite), 0),
+            steps_per_frame: 1,
+            occupied: vec![false; width * height],
+        };
+        world.update_occupied();
+        world
+    }
+
+    /// El mismo mundo, con `steps` pasos de los turmites por frame (muchos
+    /// turmites tardan miles de pasos en mostrar su forma)
+    pub fn with_steps_per_frame(mut self, steps: usize) -> Self {
+        self.steps_per_frame = steps.max(1);
+        self
+    }
+
+    pub fn agents(&self) -> &[Agent] {
+        self.layer.agents()
+    }
+
+    fn update_occupied(&mut self) {
+        self.occupied.fill(false);
+        for agent in self.layer.agents() {
+            self.occupied[agent.y * self.width + agent.x] = true;
+        }
+    }
+}
+
+impl Simulation for TurmiteWorld {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn step(&mut self) {
+        for _ in 0..self.steps_per_frame {
+            self.layer.step(&mut self.cells);
+        }
+        self.update_occupied();
+    }
+
+    /// El fondo, un tono por color y al final el de los turmites
+    fn palette(&self) -> Vec<[u8; 3]> {
+        let hues = (1..self.colors).map(|i| {
+            let hue = (i - 1) as f32 / (self.colors - 1).max(1) as f32;
+            hue_to_rgb(hue)
+        });
+        std::iter::once(crate::DEAD_RGB).chain(hues).chain(std::iter::once(TURMITE_RGB)).collect()
+    }
+
+    fn palette_index(&self, x: usize, y: usize) -> u8 {
+        let i = y * self.width + x;
+        if self.occupied[i] { self.colors } else { self.cells.colors[i] }
+    }
+}
+
+/// Color saturado para un tono entre 0.0 y 1.0, del blanco al amarillo, verde
+/// y azul, para que con dos colores la celda pintada sea blanca como en Life
+fn hue_to_rgb(hue: f32) -> [u8; 3] {
+    if hue == 0.0 {
+        return crate::ALIVE_RGB;
+    }
+    let h = hue * 5.0;
+    let x = (1.0 - (h % 2.0 - 1.0).abs()) * 255.0;
+    let (r, g, b) = match h as u32 {
+        0 => (255.0, x, 0.0),
+        1 => (x, 255.0, 0.0),
+        2 => (0.0, 255.0, x),
+        3 => (0.0, x, 255.0),
+        _ => (x, 0.0, 255.0),
+    };
+    [r as u8, g as u8, b as u8]
+}
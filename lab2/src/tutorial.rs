@@ -0,0 +1,93 @@
+use crate::KnownPattern;
+
+/// Algo que hizo el usuario en la ventana del tutorial
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    /// Estampó un patrón y quedó al menos una célula dentro del universo
+    Stamped(KnownPattern),
+    /// Puso a correr la simulación
+    Resumed,
+    /// La pausó
+    Paused,
+    /// Cambió la regla
+    RuleChanged,
+    /// Guardó la generación actual en un archivo
+    Exported,
+}
+
+/// Un paso de `lab2 tutorial`: lo que se le pide al usuario y la acción
+/// que lo completa
+pub struct Step {
+    pub title: &'static str,
+    /// Instrucción que se muestra abajo de la ventana
+    pub prompt: &'static str,
+    pub goal: Action,
+}
+
+/// Los pasos del tutorial, en orden
+pub const STEPS: [Step; 5] = [
+    Step {
+        title: "Colocar un planeador",
+        prompt: "apunta con el mouse y presiona P para estampar un planeador",
+        goal: Action::Stamped(KnownPattern::Glider),
+    },
+    Step {
+        title: "Correr",
+        prompt: "presiona Espacio para que el universo empiece a evolucionar",
+        goal: Action::Resumed,
+    },
+    Step {
+        title: "Pausar",
+        prompt: "presiona Espacio otra vez para pausarlo",
+        goal: Action::Paused,
+    },
+    Step {
+        title: "Cambiar la regla",
+        prompt: "presiona ? (Shift+/) para probar una regla al azar",
+        goal: Action::RuleChanged,
+    },
+    Step {
+        title: "Exportar",
+        prompt: "presiona S para guardar la generación actual como RLE",
+        goal: Action::Exported,
+    },
+];
+
+/// Avance por los pasos: cada uno se completa solo cuando el usuario hace
+/// de verdad lo que pide, no al pasar el tiempo
+#[derive(Clone, Debug, Default)]
+pub struct Tutorial {
+    step: usize,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// El paso que falta completar, o `None` si ya terminó
+    pub fn current(&self) -> Option<&'static Step> {
+        STEPS.get(self.step)
+    }
+
+    /// Número del paso actual, contando desde 1
+    pub fn position(&self) -> usize {
+        (self.step + 1).min(STEPS.len())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= STEPS.len()
+    }
+
+    /// Anota lo que hizo el usuario. Devuelve `true` si con eso completó el
+    /// paso actual; las demás acciones no cuentan.
+    pub fn observe(&mut self, action: Action) -> bool {
+        match self.current() {
+            Some(step) if step.goal == action => {
+                self.step += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use crate::gif_export::ExportFormat;
+
+/// Frames por segundo máximos del video: con más generaciones por segundo
+/// algunas no llegan a verse
+pub const MAX_VIDEO_FPS: u32 = 60;
+
+/// Video que se escribe pasando frames RGB crudos a un proceso de ffmpeg.
+/// El video tiene una cantidad fija de frames por segundo: cada frame que
+/// se agrega se repite tantas veces como haga falta para durar lo que dice
+/// su demora, así que los frames juntados por la deduplicación o los cambios
+/// de velocidad se ven igual que en el GIF.
+pub struct VideoEncoder {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    fps: u32,
+    /// Segundos que duran los frames agregados
+    played: f64,
+    /// Frames escritos en el video
+    emitted: u64,
+}
+
+impl VideoEncoder {
+    /// Empieza a escribir en `path` un video de `width`×`height` píxeles. Un
+    /// lado impar se completa con una columna o fila negra porque los
+    /// códecs trabajan con bloques de 2×2.
+    pub fn new(path: &Path, width: usize, height: usize, fps: u32, format: ExportFormat) -> Result<Self, Box<dyn std::error::Error>> {
+        let fps = fps.clamp(1, MAX_VIDEO_FPS);
+        let spawned = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgb24"])
+            .args(["-s", &format!("{}x{}", width, height), "-r", &fps.to_string(), "-i", "-"])
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2", "-pix_fmt", "yuv420p"])
+            .args(format.codec_args())
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(format!("no se encontró ffmpeg: instálalo para exportar --format {}", format.extension()).into());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let stdin = child.stdin.take();
+        Ok(Self { child, stdin, fps, played: 0.0, emitted: 0 })
+    }
+
+    /// Agrega un frame (3 bytes por píxel, fila por fila) que dura `seconds`
+    pub fn write_frame(&mut self, rgb: &[u8], seconds: f64) -> io::Result<()> {
+        self.played += seconds;
+        let stdin = self.stdin.as_mut().expect("la entrada de ffmpeg se cierra en finish");
+        while (self.emitted as f64) < self.played * self.fps as f64 - 1e-6 {
+            stdin.write_all(rgb).map_err(|e| io::Error::new(e.kind(), format!("ffmpeg dejó de recibir frames: {}", e)))?;
+            self.emitted += 1;
+        }
+        Ok(())
+    }
+
+    /// Segundos de video escritos
+    pub fn duration(&self) -> f64 {
+        self.emitted as f64 / self.fps as f64
+    }
+
+    /// Cierra la entrada de ffmpeg y espera a que termine el archivo
+    pub fn finish(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        drop(self.stdin.take());
+        let status = self.child.wait()?;
+        if !status.success() {
+            return Err(format!("ffmpeg terminó con error ({})", status).into());
+        }
+        Ok(())
+    }
+}
+
+/// Si el video no se termina con `finish`, igual se cierra ffmpeg para que
+/// el archivo quede completo con lo escrito hasta ahora
+impl Drop for VideoEncoder {
+    fn drop(&mut self) {
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
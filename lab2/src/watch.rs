@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Cada cuánto se revisan las fechas de modificación
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Tiempo sin cambios que se espera antes de avisar: un editor suele
+/// escribir un archivo en varios pasos
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Vigila un conjunto de archivos comparando su fecha de modificación cada
+/// `POLL_INTERVAL`. Un archivo que desaparece o aparece también cuenta
+/// como cambio.
+pub struct Watcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Watcher {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        let mut watcher = Self { files: Vec::new() };
+        watcher.set_files(files);
+        watcher
+    }
+
+    /// Reemplaza los archivos vigilados, tomando su estado actual como el
+    /// de partida
+    pub fn set_files(&mut self, files: Vec<PathBuf>) {
+        self.files = files.into_iter().map(|path| (path.clone(), modified(&path))).collect();
+    }
+
+    /// Espera a que cambie algún archivo y a que después pasen `DEBOUNCE`
+    /// sin más cambios. Devuelve los que cambiaron.
+    pub fn wait(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            changed.extend(self.poll());
+            if !changed.is_empty() {
+                break;
+            }
+        }
+        loop {
+            thread::sleep(DEBOUNCE);
+            let more = self.poll();
+            if more.is_empty() {
+                break;
+            }
+            changed.extend(more);
+        }
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+
+    /// Archivos cuya fecha cambió desde la última revisión
+    fn poll(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, last) in &mut self.files {
+            let now = modified(path);
+            if now != *last {
+                *last = now;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,91 @@
+use std::io::{self, BufRead, Write};
+
+use crate::pacing::Speed;
+use crate::rule::Rule;
+
+/// Hace una pregunta hasta recibir una respuesta válida. Una línea vacía (o
+/// el final de la entrada) elige `default`.
+fn ask<T>(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    default: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> io::Result<(T, String)> {
+    loop {
+        write!(output, "{} [{}]: ", question, default)?;
+        output.flush()?;
+        let mut line = String::new();
+        let read = input.read_line(&mut line)?;
+        let answer = match line.trim() {
+            "" => default,
+            answer => answer,
+        };
+        match parse(answer) {
+            Ok(value) => return Ok((value, answer.to_string())),
+            Err(e) if read == 0 => return Err(io::Error::new(io::ErrorKind::InvalidInput, e)),
+            Err(e) => writeln!(output, "  {}", e)?,
+        }
+    }
+}
+
+fn positive(text: &str) -> Result<usize, String> {
+    match text.parse() {
+        Ok(0) | Err(_) => Err(format!("se esperaba un número mayor que 0: {}", text)),
+        Ok(n) => Ok(n),
+    }
+}
+
+/// Pregunta los parámetros más comunes y devuelve un `lab2.toml` con ellos,
+/// comentado para que sea fácil de editar después
+pub fn run(input: &mut impl BufRead, output: &mut impl Write) -> io::Result<String> {
+    writeln!(output, "Configuración inicial de lab2 (Enter acepta el valor entre corchetes)")?;
+    let (width, _) = ask(input, output, "Ancho del universo, en células", "100", positive)?;
+    let (height, _) = ask(input, output, "Alto del universo, en células", "100", positive)?;
+    let (rule, _) = ask(input, output, "Regla (B3/S23 es la de Conway, B36/S23 HighLife)", "B3/S23", Rule::parse)?;
+    let (torus, _) = ask(input, output, "¿Unir los bordes opuestos, como un toro? (s/n)", "n", |text| {
+        match text.to_lowercase().as_str() {
+            "s" | "si" | "sí" | "y" | "yes" => Ok(true),
+            "n" | "no" => Ok(false),
+            _ => Err("responder s o n".to_string()),
+        }
+    })?;
+    let (density, _) = ask(input, output, "Probabilidad de que cada célula empiece viva", "0.15", |text| {
+        match text.parse::<f64>() {
+            Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+            _ => Err(format!("se esperaba un número entre 0 y 1: {}", text)),
+        }
+    })?;
+    let (frames, _) = ask(input, output, "Generaciones que dura la ejecución", "200", positive)?;
+    let (_, speed) = ask(input, output, "Velocidad (15gps, 66ms, ...)", "10gps", Speed::parse)?;
+    let (gif, _) = ask(input, output, "GIF de salida", "conway_game_of_life.gif", |text| Ok(text.to_string()))?;
+    let (export_scale, _) = ask(input, output, "Píxeles por célula en el GIF", "1", positive)?;
+
+    Ok(format!(
+        "# Configuración de lab2, creada con `lab2 init`. Las claves son las\n\
+         # opciones de la línea de comandos (ver `lab2 --help`); las variables\n\
+         # LAB2_* y la línea de comandos tienen prioridad sobre este archivo.\n\
+         \n\
+         # Universo\n\
+         width = {}\n\
+         height = {}\n\
+         rule = \"{}\"\n\
+         topology = \"{}\"\n\
+         density = {}\n\
+         \n\
+         # Ejecución y GIF\n\
+         frames = {}\n\
+         speed = \"{}\"\n\
+         output = \"{}\"\n\
+         export_scale = {}\n",
+        width,
+        height,
+        rule,
+        if torus { "torus" } else { "bounded" },
+        density,
+        frames,
+        speed,
+        gif.replace('\\', "\\\\").replace('"', "\\\""),
+        export_scale,
+    ))
+}
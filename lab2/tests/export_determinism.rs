@@ -0,0 +1,103 @@
+//! Exporta escenarios fijos y compara el SHA-256 de cada GIF con el guardado
+//! en `tests/data/export_hashes.txt`, así cualquier cambio en el motor, la
+//! paleta o el codificador que altere los bytes exportados hace fallar el
+//! test. Si el cambio es intencional, los hashes se regeneran con
+//!
+//! ```text
+//! LAB2_UPDATE_HASHES=1 cargo test --test export_determinism
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use lab2::gif_export::GifGenerator;
+use lab2::rule::Rule;
+use lab2::{GameOfLife, KnownPattern, Topology};
+use sha2::{Digest, Sha256};
+
+const HASHES: &str = "tests/data/export_hashes.txt";
+const SIZE: usize = 64;
+const GENERATIONS: usize = 60;
+const SCALE: usize = 4;
+
+/// Universo con un patrón de cada tipo en posiciones fijas (sin células
+/// aleatorias, para que el resultado no dependa de la semilla)
+fn fixed_universe(rule: Rule, topology: Topology) -> GameOfLife {
+    let mut game = GameOfLife::new_with_rule(SIZE, SIZE, rule, topology);
+    KnownPattern::Glider.stamp(&mut game, 2, 2);
+    KnownPattern::LightweightSpaceship.stamp(&mut game, 4, 40);
+    KnownPattern::Pulsar.stamp(&mut game, 40, 8);
+    KnownPattern::Toad.stamp(&mut game, 30, 50);
+    KnownPattern::Beacon.stamp(&mut game, 50, 50);
+    KnownPattern::Block.stamp(&mut game, 20, 30);
+    game
+}
+
+/// Ajustes del GIF de un escenario
+type Configure = fn(GifGenerator) -> GifGenerator;
+
+/// Escenarios: nombre, universo inicial y ajustes del GIF
+fn scenarios() -> Vec<(&'static str, GameOfLife, Configure)> {
+    let conway = || fixed_universe(Rule::conway(), Topology::Bounded);
+    vec![
+        ("conway", conway(), |gif| gif),
+        ("conway-threads", conway(), |gif| gif.with_threads(4)),
+        ("conway-downsample", conway(), |gif| gif.with_downsample(2)),
+        ("conway-stride", conway(), |gif| gif.with_stride(3)),
+        ("highlife-torus", fixed_universe(Rule::parse("B36/S23").unwrap(), Topology::Torus), |gif| gif),
+    ]
+}
+
+fn export(name: &str, mut game: GameOfLife, configure: Configure, dir: &Path) -> String {
+    let path = dir.join(format!("{}.gif", name));
+    let mut gif = configure(GifGenerator::new(&path, game.width(), game.height(), SCALE).unwrap());
+    for _ in 0..GENERATIONS {
+        gif.add_frame(&game.to_gif_frame_data()).unwrap();
+        game.next_generation();
+    }
+    gif.finish().unwrap();
+
+    let digest = Sha256::digest(std::fs::read(&path).unwrap());
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn load_hashes() -> BTreeMap<String, String> {
+    std::fs::read_to_string(HASHES)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(name, hash)| (name.to_string(), hash.trim().to_string()))
+        .collect()
+}
+
+#[test]
+fn exported_gifs_match_stored_hashes() {
+    let dir: PathBuf = std::env::temp_dir().join(format!("lab2-determinism-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let actual: BTreeMap<String, String> = scenarios()
+        .into_iter()
+        .map(|(name, game, configure)| (name.to_string(), export(name, game, configure, &dir)))
+        .collect();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // Los lotes de varios hilos se escriben en orden: el GIF no cambia
+    assert_eq!(actual["conway"], actual["conway-threads"], "el GIF depende de la cantidad de hilos");
+
+    if std::env::var_os("LAB2_UPDATE_HASHES").is_some() {
+        let text: String = actual.iter().map(|(name, hash)| format!("{} {}\n", name, hash)).collect();
+        std::fs::write(HASHES, text).unwrap();
+        return;
+    }
+
+    let expected = load_hashes();
+    let mismatches: Vec<String> = actual
+        .iter()
+        .filter(|(name, hash)| expected.get(*name) != Some(hash))
+        .map(|(name, hash)| format!("{}: esperado {}, obtenido {}", name, expected.get(name).map_or("-", |h| h), hash))
+        .collect();
+    assert!(
+        mismatches.is_empty(),
+        "los GIF exportados cambiaron (LAB2_UPDATE_HASHES=1 regenera los hashes si es intencional):\n{}",
+        mismatches.join("\n")
+    );
+}
@@ -0,0 +1,126 @@
+//! Compara la generación siguiente de cada implementación de las células
+//! con `grid::step_cells`, la versión célula por célula: el `BitGrid` (de a
+//! 64 células por palabra), el `FixedGrid`, HashLife y el motor disperso
+//! tienen que dar exactamente las mismas células.
+
+use lab2::bitgrid::BitGrid;
+use lab2::engine::Engine;
+use lab2::fixed_grid::FixedGrid;
+use lab2::grid::{self, Grid};
+use lab2::hashlife::HashLifeEngine;
+use lab2::rule::Rule;
+use lab2::sparse::{SparseLife, Viewport};
+use lab2::CellState;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const GENERATIONS: usize = 12;
+const RULES: [&str; 4] = ["B3/S23", "B36/S23", "B2/S", "B3678/S34678"];
+
+fn rules() -> impl Iterator<Item = Rule> {
+    RULES.iter().map(|rule| Rule::parse(rule).unwrap())
+}
+
+/// Llena `grid` al azar con la semilla `seed`, solo dentro del rectángulo
+/// (`x`, `y`, `width`, `height`)
+fn soup<G: Grid>(grid: &mut G, seed: u64, (x, y, width, height): (usize, usize, usize, usize)) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for cy in y..y + height {
+        for cx in x..x + width {
+            if rng.gen_bool(0.4) {
+                grid.set(cx, cy, CellState::Alive);
+            }
+        }
+    }
+}
+
+/// La primera célula en la que difieren dos grids del mismo tamaño
+fn first_difference(a: &impl Grid, b: &impl Grid) -> Option<(usize, usize)> {
+    (0..a.height()).flat_map(|y| (0..a.width()).map(move |x| (x, y))).find(|&(x, y)| a.is_alive(x, y) != b.is_alive(x, y))
+}
+
+#[test]
+fn bitgrid_matches_step_cells() {
+    // Anchos que no son múltiplos de 64, justo 64 y más de una palabra
+    let sizes = [(1, 1), (5, 3), (63, 10), (64, 64), (65, 7), (130, 33)];
+    for rule in rules() {
+        for (seed, &(width, height)) in sizes.iter().enumerate() {
+            for torus in [false, true] {
+                let mut grid = BitGrid::new(width, height);
+                soup(&mut grid, seed as u64, (0, 0, width, height));
+                let mut expected = grid.clone();
+                for generation in 1..=GENERATIONS {
+                    grid = grid.step(&rule, torus);
+                    expected = grid::step_cells(&expected, &rule, torus);
+                    assert_eq!(
+                        first_difference(&grid, &expected),
+                        None,
+                        "BitGrid {}x{} con {} (toro: {}) difiere en la generación {}",
+                        width, height, rule, torus, generation
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn fixed_grid_matches_step_cells_and_bitgrid() {
+    for rule in rules() {
+        for torus in [false, true] {
+            let mut fixed = FixedGrid::<17, 9>::new();
+            soup(&mut fixed, 7, (0, 0, 17, 9));
+            let mut bits = BitGrid::new(17, 9);
+            for (y, row) in fixed.rows().iter().enumerate() {
+                for (x, &alive) in row.iter().enumerate() {
+                    bits.set(x, y, if alive { CellState::Alive } else { CellState::Dead });
+                }
+            }
+            for generation in 1..=GENERATIONS {
+                let expected = grid::step_cells(&fixed, &rule, torus);
+                fixed = fixed.step(&rule, torus);
+                bits = bits.step(&rule, torus);
+                assert_eq!(fixed, expected, "FixedGrid con {} (toro: {}) difiere en la generación {}", rule, torus, generation);
+                assert_eq!(
+                    first_difference(&fixed, &bits),
+                    None,
+                    "FixedGrid y BitGrid con {} (toro: {}) difieren en la generación {}",
+                    rule, torus, generation
+                );
+            }
+        }
+    }
+}
+
+/// HashLife y el motor disperso no tienen bordes: la sopa empieza lejos de
+/// ellos, así en `GENERATIONS` generaciones no llega a tocarlos
+#[test]
+fn engines_match_step_cells() {
+    const SIZE: usize = 64;
+    const SOUP: (usize, usize, usize, usize) = (24, 24, 16, 16);
+    // Las reglas con B2 crecen hasta dos células por generación
+    for rule in rules().filter(|rule| !rule.is_birth(2)) {
+        let mut expected = BitGrid::new(SIZE, SIZE);
+        soup(&mut expected, 11, SOUP);
+        let mut hashlife = HashLifeEngine::new(SIZE, SIZE, rule).unwrap();
+        let mut sparse = SparseLife::new(rule, Viewport::new(SIZE, SIZE)).unwrap();
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let state = expected.get(x, y);
+                hashlife.set_cell(x, y, state);
+                sparse.set_cell(x, y, state);
+            }
+        }
+        for generation in 1..=GENERATIONS {
+            expected = grid::step_cells(&expected, &rule, false);
+            hashlife.advance(1);
+            sparse.advance(1);
+            for (name, engine) in [("HashLife", &hashlife as &dyn Engine), ("disperso", &sparse as &dyn Engine)] {
+                let difference = (0..SIZE)
+                    .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+                    .find(|&(x, y)| engine.is_alive(x, y) != expected.is_alive(x, y));
+                assert_eq!(difference, None, "el motor {} con {} difiere en la generación {}", name, rule, generation);
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Corre `lab2 init` con respuestas fijas y comprueba que `lab2 config
+//! validate` acepte el archivo que escribe, y que el programa lo use sin
+//! errores.
+
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+const LAB2: &str = env!("CARGO_BIN_EXE_lab2");
+
+/// Corre `lab2` en `dir` con `args`, escribiendo `input` en su entrada
+fn lab2(dir: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(LAB2)
+        .args(args)
+        .current_dir(dir)
+        .env_remove("LAB2_CONFIG")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn assert_success(output: &Output, what: &str) {
+    assert!(
+        output.status.success(),
+        "{} falló:\n{}{}",
+        what,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn init_writes_a_config_that_validates() {
+    let dir = std::env::temp_dir().join(format!("lab2-init-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // Todas las respuestas predeterminadas, y otras escritas a mano
+    let answers = [("lab2.toml", "\n".repeat(10)), ("otro.toml", "40\n30\nB36/S23\ns\n1\n50\n66ms\nsalida \"1\".gif\n2\n".to_string())];
+    for (file, input) in &answers {
+        assert_success(&lab2(&dir, &["init", "-o", file], input), &format!("init -o {}", file));
+        assert_success(&lab2(&dir, &["config", "validate", file], ""), &format!("config validate {}", file));
+        let run = ["--config", file, "--headless", "--frames", "1", "-o", "prueba.gif"];
+        assert_success(&lab2(&dir, &run, ""), &format!("--config {}", file));
+    }
+    // Sin archivos, `config validate` revisa el lab2.toml de la carpeta
+    assert_success(&lab2(&dir, &["config", "validate"], ""), "config validate");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}